@@ -1,3 +1,21 @@
+use std::process::Command;
+
 fn main() {
-    tauri_build::build()
+    // Short commit hash for get_app_info; release builds from a source
+    // tarball have no git checkout and report "unknown".
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=LOCKPILOT_BUILD_HASH={hash}");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+
+    // The core-only build for the integration tests doesn't link Tauri,
+    // which expects its own build script output.
+    if std::env::var_os("CARGO_FEATURE_APP").is_some() {
+        tauri_build::build()
+    }
 }
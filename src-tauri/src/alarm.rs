@@ -0,0 +1,190 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+#[cfg(feature = "app")]
+use std::fs;
+
+use crate::{applescript_string, dialogs::DialogQueue, media, osascript_output, system, USER_CANCELED_ERROR};
+
+const SOUNDS_DIR: &str = "/System/Library/Sounds";
+const AFPLAY: &str = "/usr/bin/afplay";
+/// How long one ring lasts before the alarm gives up, so an alarm nobody
+/// hears doesn't play all day.
+const RING_LIMIT: Duration = Duration::from_secs(10 * 60);
+/// Snooze is no longer offered after this many.
+const MAX_SNOOZES: u32 = 6;
+pub const MAX_SNOOZE_MINUTES: u32 = 60;
+/// How often the sound loop checks whether it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How often a snoozed alarm checks that its timer still exists.
+const SNOOZE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn default_snooze_minutes() -> u32 {
+    9
+}
+
+/// Names of the system alert sounds, e.g. `Glass`, for the alarm's `sound`.
+#[cfg(feature = "app")]
+pub fn sounds() -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(SOUNDS_DIR).map_err(|err| format!("Failed to list {SOUNDS_DIR}: {err}"))?;
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "aiff"))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+fn sound_path(name: &str) -> PathBuf {
+    Path::new(SOUNDS_DIR).join(format!("{name}.aiff"))
+}
+
+pub fn check_sound(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains('/') || !sound_path(name).is_file() {
+        return Err(format!("Unknown alarm sound: {name}"));
+    }
+    Ok(())
+}
+
+enum Ring {
+    Stopped,
+    Snoozed,
+    GaveUp,
+}
+
+/// Rings until someone clicks Stop (or presses Return): wakes the display,
+/// raises the volume to `volume_percent` and plays `sound` over and over
+/// while the alarm dialog is open. Snooze (or Escape) silences it for
+/// `snooze_minutes`, then it rings again. Stops early once `still_wanted`
+/// returns `false` (the timer was cancelled).
+pub fn ring(
+    dialogs: &DialogQueue,
+    message: Option<&str>,
+    sound: &str,
+    volume_percent: u8,
+    snooze_minutes: u32,
+    still_wanted: impl Fn() -> bool,
+) -> Result<Option<String>, String> {
+    let message = message.filter(|message| !message.trim().is_empty()).unwrap_or("Alarm");
+    let mut snoozes = 0;
+    loop {
+        match ring_once(dialogs, message, sound, volume_percent, snoozes < MAX_SNOOZES)? {
+            Ring::Stopped => {
+                return Ok(Some(match snoozes {
+                    0 => "Alarm stopped".to_string(),
+                    _ => format!("Alarm stopped after {snoozes} snooze(s)"),
+                }))
+            }
+            Ring::GaveUp => {
+                return Ok(Some(format!(
+                    "Alarm rang for {} min without being stopped",
+                    RING_LIMIT.as_secs() / 60
+                )))
+            }
+            Ring::Snoozed => snoozes += 1,
+        }
+
+        let until = Instant::now() + Duration::from_secs(u64::from(snooze_minutes) * 60);
+        while Instant::now() < until {
+            thread::sleep(SNOOZE_POLL_INTERVAL.min(until.saturating_duration_since(Instant::now())));
+            if !still_wanted() {
+                return Ok(Some(format!("Alarm cancelled while snoozed ({snoozes} snooze(s))")));
+            }
+        }
+    }
+}
+
+fn ring_once(
+    dialogs: &DialogQueue,
+    message: &str,
+    sound: &str,
+    volume_percent: u8,
+    can_snooze: bool,
+) -> Result<Ring, String> {
+    // Held for the ring: turns the display on and keeps it on.
+    let _awake = system::StayAwake::with_display_on(RING_LIMIT + Duration::from_secs(60))?;
+    let previous_volume = media::raise_volume(volume_percent)?;
+    let player = SoundLoop::start(sound_path(sound));
+
+    let buttons = if can_snooze {
+        "buttons {\"Snooze\", \"Stop\"} default button \"Stop\" cancel button \"Snooze\""
+    } else {
+        "buttons {\"Stop\"} default button \"Stop\""
+    };
+    let script = format!(
+        "display dialog {} with title \"LockPilot\" with icon caution {buttons} giving up after {}",
+        applescript_string(message),
+        RING_LIMIT.as_secs()
+    );
+    let result = dialogs.exclusive(|| osascript_output(&script));
+    drop(player);
+    if let Err(err) = media::restore_volume(previous_volume) {
+        eprintln!("{err}");
+    }
+
+    match result {
+        Ok(output) if output.contains("gave up:true") => Ok(Ring::GaveUp),
+        Ok(_) => Ok(Ring::Stopped),
+        Err(err) if err.contains(USER_CANCELED_ERROR) => Ok(Ring::Snoozed),
+        Err(err) => Err(format!("Alarm could not be shown: {}", err.trim())),
+    }
+}
+
+/// Plays a sound file back to back until dropped.
+struct SoundLoop {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SoundLoop {
+    fn start(path: PathBuf) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                let Ok(mut child) = Command::new(AFPLAY).arg(&path).spawn() else {
+                    eprintln!("Failed to run afplay for {}", path.display());
+                    return;
+                };
+                loop {
+                    if stopped.load(Ordering::SeqCst) {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return;
+                    }
+                    match child.try_wait() {
+                        Ok(None) => thread::sleep(POLL_INTERVAL),
+                        Ok(Some(status)) if status.success() => break,
+                        // Played once and failed: don't respawn it in a tight loop.
+                        _ => {
+                            eprintln!("afplay could not play {}", path.display());
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for SoundLoop {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
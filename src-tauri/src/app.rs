@@ -0,0 +1,1652 @@
+use tauri::{Emitter, Manager, State};
+
+use super::*;
+use kiosk::ReadOnlyLaunch;
+
+impl Frontend for tauri::AppHandle {
+    fn emit(&self, event: &str, payload: serde_json::Value) {
+        let _ = Emitter::emit(self, event, payload);
+    }
+
+    fn set_badge_count(&self, count: Option<i64>) -> bool {
+        self.get_webview_window("main")
+            .is_some_and(|window| window.set_badge_count(count).is_ok())
+    }
+}
+
+#[tauri::command]
+fn list_timers(state: State<'_, TimerStore>) -> CommandResult<Vec<TimerView>> {
+    Ok(state.list_views()?)
+}
+
+#[tauri::command]
+fn cancel_timer(id: String, state: State<'_, TimerStore>) -> CommandResult<bool> {
+    Ok(state.cancel(&id, &AuditOrigin::Ui)?)
+}
+
+#[tauri::command]
+fn create_timer(request: CreateTimerRequest, state: State<'_, TimerStore>) -> CommandResult<CreatedTimerView> {
+    state
+        .policy
+        .check_timer(
+            &request.action,
+            request.escalation.as_ref().map(|escalation| &escalation.action),
+            request.hooks.as_ref(),
+        )
+        .map_err(CommandError::policy)?;
+    if let Some(issue) = state.validate(&request)?.errors.into_iter().next() {
+        return Err(CommandError::validation(issue.message).with_context(issue.field));
+    }
+
+    let (_, format) = parse_target_time(&request.target_time)?;
+    let info = state.create(request, &AuditOrigin::Ui)?;
+    Ok(CreatedTimerView::new(info, format, Utc::now()))
+}
+
+/// Creates several timers at once, e.g. a weekly schedule. If any request
+/// is invalid nothing is created; the error's context is `<index>.<field>`.
+#[tauri::command]
+fn create_timers(requests: Vec<CreateTimerRequest>, state: State<'_, TimerStore>) -> CommandResult<Vec<TimerInfo>> {
+    for (index, request) in requests.iter().enumerate() {
+        state
+            .policy
+            .check_timer(
+                &request.action,
+                request.escalation.as_ref().map(|escalation| &escalation.action),
+                request.hooks.as_ref(),
+            )
+            .map_err(|err| CommandError::policy(err).with_context(index.to_string()))?;
+        if let Some(issue) = state.validate(request)?.errors.into_iter().next() {
+            return Err(CommandError::validation(issue.message).with_context(format!("{index}.{}", issue.field)));
+        }
+    }
+
+    Ok(state.create_many(requests, &AuditOrigin::Ui)?)
+}
+
+/// Parses a quick-entry phrase such as "lock at 10pm every weekday" and
+/// creates the timer in one call. Parse and validation problems come back
+/// as diagnostics rather than an `Err`.
+#[tauri::command]
+fn create_timer_from_text(text: String, state: State<'_, TimerStore>) -> CommandResult<TextTimerResult> {
+    let (request, mut diagnostics) = quick_entry::parse(&text, Local::now());
+    let timer = match request {
+        Some(request) => match state.create(request, &AuditOrigin::Ui) {
+            Ok(info) => Some(TimerView::new(info, Utc::now())),
+            Err(err) => {
+                diagnostics.push(Diagnostic::error(err, None));
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(TextTimerResult { timer, diagnostics })
+}
+
+/// Current Automation and Accessibility grants, without prompting.
+#[tauri::command]
+fn get_permission_status() -> CommandResult<PermissionStatus> {
+    Ok(PermissionStatus {
+        automation: system::automation_permission(false),
+        accessibility: system::accessibility_permission(),
+    })
+}
+
+/// Asks for the permissions up front instead of on the first firing.
+/// Automation gets the system consent prompt; Accessibility has no prompt,
+/// so its Settings pane is opened when it isn't granted yet.
+#[tauri::command]
+fn request_permissions() -> CommandResult<PermissionStatus> {
+    let automation = system::automation_permission(true);
+    if automation == PermissionState::Denied {
+        system::open_privacy_settings(Permission::Automation)?;
+    }
+    let accessibility = system::accessibility_permission();
+    if accessibility == PermissionState::Denied {
+        system::open_privacy_settings(Permission::Accessibility)?;
+    }
+
+    Ok(PermissionStatus {
+        automation,
+        accessibility,
+    })
+}
+
+/// Version, build and install details for the updater UI and diagnostics.
+#[tauri::command]
+fn get_app_info(app: tauri::AppHandle, state: State<'_, TimerStore>) -> CommandResult<AppInfo> {
+    let version = app.package_info().version.to_string();
+    let storage_dir = state
+        .storage_path
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    Ok(app_info::collect(&version, &storage_dir))
+}
+
+/// Crash reports from earlier runs, newest first, so the UI can offer to
+/// send them.
+#[tauri::command]
+fn list_crash_reports(state: State<'_, CrashReports>) -> CommandResult<Vec<CrashReport>> {
+    state.list().map_err(CommandError::storage)
+}
+
+#[tauri::command]
+fn delete_crash_report(id: String, state: State<'_, CrashReports>) -> CommandResult<bool> {
+    state.delete(&id).map_err(CommandError::storage)
+}
+
+/// Opens a prefilled GitHub issue for the report in the browser, then
+/// deletes the local copy. Nothing is uploaded unless the user submits it.
+#[tauri::command]
+fn send_crash_report(id: String, state: State<'_, CrashReports>) -> CommandResult<String> {
+    let report = state
+        .get(&id)
+        .map_err(|err| CommandError::not_found(err).with_context("id"))?;
+    let url = crash::issue_url(&report)?;
+    exec::spawn(Command::new("/usr/bin/open").arg(url.as_str()))
+        .map_err(|err| CommandError::io("Failed to open browser", err))?;
+    state.delete(&id).map_err(CommandError::storage)?;
+    Ok(url.to_string())
+}
+
+/// Dry-runs every action type so a fresh install can be checked before a
+/// 2am shutdown depends on it. Nothing is locked, slept or shut down.
+#[tauri::command]
+fn run_self_test(state: State<'_, TimerStore>) -> CommandResult<SelfTestReport> {
+    let settings = state.settings.snapshot()?;
+    Ok(self_test::run(&state.plugins, settings.lock_compatibility))
+}
+
+/// Diagnostics for the UI's health panel. Slow-ish: it shells out to
+/// osascript and makes one network request.
+#[tauri::command]
+fn health_check(state: State<'_, TimerStore>) -> CommandResult<HealthReport> {
+    Ok(health::run(&state))
+}
+
+/// Cheap summary for menubar titles, widgets and other pollers.
+#[tauri::command]
+fn get_dashboard_state(state: State<'_, TimerStore>) -> CommandResult<DashboardState> {
+    Ok(state.dashboard_state()?)
+}
+
+/// Fire times a recurrence would produce from `start`, including `start`
+/// itself; `start` takes any format `targetTime` does. A start in the past
+/// is first moved to its next future run.
+/// With `jitter_minutes`, each run may fire up to that much either side of
+/// the time listed.
+#[tauri::command]
+fn preview_occurrences(
+    recurrence: RecurrenceConfig,
+    start: String,
+    count: usize,
+    jitter_minutes: Option<u32>,
+    state: State<'_, TimerStore>,
+) -> CommandResult<OccurrencePreview> {
+    validate_recurrence(Some(&recurrence))
+        .map_err(|err| CommandError::validation(err).with_context("recurrence"))?;
+    let jitter_minutes = jitter_minutes.filter(|minutes| *minutes > 0);
+    validate_jitter(jitter_minutes, Some(&recurrence))
+        .map_err(|err| CommandError::validation(err).with_context("jitterMinutes"))?;
+    let (start, _) = parse_target_time(&start).map_err(|err| CommandError::validation(err).with_context("start"))?;
+    let count = count.clamp(1, MAX_PREVIEW_OCCURRENCES);
+
+    let now = Utc::now();
+    let first = if start > now {
+        start
+    } else {
+        next_run_after(start, &recurrence, now)
+            .ok_or_else(|| CommandError::validation("Recurrence has no future runs").with_context("recurrence"))?
+    };
+
+    let holiday_region = state
+        .settings
+        .snapshot()?
+        .holiday_region
+        .filter(|_| recurrence.skip_holidays);
+    if let Some(region) = holiday_region.as_deref() {
+        // Surfaces an unreachable dataset here instead of silently keeping
+        // every run, and warms the cache for the lookups below, which only
+        // read it. Next year's holidays may not be published yet.
+        let year = first.with_timezone(&Local).year();
+        state.holidays.holidays(region, year).map_err(CommandError::network)?;
+        state.holidays.prefetch(region, &[year + 1]);
+    }
+
+    let mut preview = OccurrencePreview {
+        occurrences: Vec::with_capacity(count),
+        jitter_minutes,
+        holiday_region,
+        skipped: Vec::new(),
+    };
+    let mut candidate = Some(first);
+    // Bounded in case a region has long runs of holidays.
+    for _ in 0..count * 4 {
+        let Some(at) = candidate else {
+            break;
+        };
+        match state.holiday_skip(&recurrence, at) {
+            Some(holiday) => preview.skipped.push(SkippedOccurrence {
+                at,
+                holiday: holiday.name,
+            }),
+            None => preview.occurrences.push(at),
+        }
+        if preview.occurrences.len() == count {
+            break;
+        }
+        candidate = compute_next_run(at, &recurrence);
+    }
+    Ok(preview)
+}
+
+#[tauri::command]
+fn get_holiday_region(state: State<'_, SettingsStore>) -> CommandResult<Option<String>> {
+    Ok(state.snapshot()?.holiday_region)
+}
+
+/// Sets the country whose public holidays `skipHolidays` recurrences skip,
+/// fetching this year's holidays first so a bad code or no network is
+/// reported now rather than at fire time. `None` clears it.
+#[tauri::command]
+fn set_holiday_region(region: Option<String>, state: State<'_, TimerStore>) -> CommandResult<Option<String>> {
+    let region = region
+        .filter(|region| !region.trim().is_empty())
+        .map(|region| holidays::normalize_region(&region))
+        .transpose()
+        .map_err(|err| CommandError::validation(err).with_context("region"))?;
+    if let Some(region) = region.as_deref() {
+        state
+            .holidays
+            .holidays(region, Local::now().year())
+            .map_err(|err| CommandError::network(err).with_context("region"))?;
+    }
+
+    let settings = state
+        .settings
+        .update(|settings| settings.holiday_region = region)
+        .map_err(CommandError::storage)?;
+    Ok(settings.holiday_region)
+}
+
+/// Subscribes to an iCalendar feed and syncs it straight away, so a feed
+/// that can't be fetched or parsed shows up as `lastError` right here.
+#[tauri::command]
+fn subscribe_ics(
+    url: String,
+    mapping: IcsMapping,
+    subscriptions: State<'_, IcsSubscriptions>,
+    store: State<'_, TimerStore>,
+) -> CommandResult<IcsSubscription> {
+    let url = ics::normalize_url(&url).map_err(|err| CommandError::validation(err).with_context("url"))?;
+    if matches!(mapping.action, TimerAction::Popup | TimerAction::Push | TimerAction::Email)
+        && mapping.message.as_deref().is_some_and(|message| message.trim().is_empty())
+    {
+        return Err(CommandError::validation("Message must not be empty").with_context("mapping.message"));
+    }
+    let subscription = subscriptions.add(url, mapping).map_err(CommandError::storage)?;
+    Ok(subscriptions.sync(&subscription.id, &store)?)
+}
+
+#[tauri::command]
+fn list_ics_subscriptions(subscriptions: State<'_, IcsSubscriptions>) -> CommandResult<Vec<IcsSubscription>> {
+    Ok(subscriptions.list()?)
+}
+
+#[tauri::command]
+fn unsubscribe_ics(
+    id: String,
+    subscriptions: State<'_, IcsSubscriptions>,
+    store: State<'_, TimerStore>,
+) -> CommandResult<bool> {
+    if !subscriptions.remove(&id, &store).map_err(CommandError::storage)? {
+        return Err(CommandError::not_found(format!("Subscription not found: {id}")).with_context("id"));
+    }
+    Ok(true)
+}
+
+/// Schedules a window in which the listed apps are quit or hidden whenever
+/// they are open.
+#[tauri::command]
+fn create_focus_session(
+    request: CreateFocusSessionRequest,
+    sessions: State<'_, FocusSessions>,
+    store: State<'_, TimerStore>,
+) -> CommandResult<FocusSession> {
+    let CreateFocusSessionRequest {
+        label,
+        starts_at,
+        ends_at,
+        blocked_apps,
+        mode,
+        block_websites,
+    } = request;
+    let now = Utc::now();
+    let starts_at = match starts_at.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => {
+            parse_target_time(value)
+                .map_err(|err| CommandError::validation(err).with_context("startsAt"))?
+                .0
+        }
+        None => now,
+    }
+    .max(now);
+    let (ends_at, _) = parse_target_time(&ends_at).map_err(|err| CommandError::validation(err).with_context("endsAt"))?;
+    if ends_at <= starts_at {
+        return Err(
+            CommandError::validation("The session must end in the future, after it starts").with_context("endsAt")
+        );
+    }
+    if ends_at - starts_at > ChronoDuration::hours(focus::MAX_SESSION_HOURS) {
+        return Err(CommandError::validation(format!(
+            "A focus session can last at most {} hours",
+            focus::MAX_SESSION_HOURS
+        ))
+        .with_context("endsAt"));
+    }
+    let blocked_apps =
+        focus::normalize_apps(blocked_apps).map_err(|err| CommandError::validation(err).with_context("blockedApps"))?;
+    if block_websites && !helper::is_installed() {
+        return Err(CommandError::validation(WEBSITE_BLOCKING_NEEDS_HELPER).with_context("blockWebsites"));
+    }
+    if blocked_apps.is_empty() && !block_websites {
+        return Err(CommandError::validation("Add at least one app to block").with_context("blockedApps"));
+    }
+
+    let session = sessions
+        .add(label, starts_at, ends_at, blocked_apps, mode, block_websites)
+        .map_err(CommandError::storage)?;
+    store.emit("focus-sessions-changed", ());
+    Ok(session)
+}
+
+/// Domains blocked during focus sessions and quiet hours that block
+/// websites.
+#[tauri::command]
+fn list_blocked_domains(state: State<'_, SettingsStore>) -> CommandResult<Vec<String>> {
+    Ok(state.snapshot()?.blocked_domains)
+}
+
+/// Replaces the blocked domains; a block in place picks up the new list
+/// within a few seconds.
+#[tauri::command]
+fn set_blocked_domains(domains: Vec<String>, state: State<'_, SettingsStore>) -> CommandResult<Vec<String>> {
+    let domains =
+        hosts::normalize_domains(domains).map_err(|err| CommandError::validation(err).with_context("domains"))?;
+    let settings = state
+        .update(|settings| settings.blocked_domains = domains)
+        .map_err(CommandError::storage)?;
+    Ok(settings.blocked_domains)
+}
+
+#[tauri::command]
+fn list_focus_sessions(sessions: State<'_, FocusSessions>) -> CommandResult<Vec<FocusSession>> {
+    Ok(sessions.list()?)
+}
+
+/// Ends a running session early or drops a scheduled one.
+#[tauri::command]
+fn cancel_focus_session(
+    id: String,
+    sessions: State<'_, FocusSessions>,
+    store: State<'_, TimerStore>,
+) -> CommandResult<bool> {
+    if sessions.cancel(&id).map_err(CommandError::storage)?.is_none() {
+        return Err(CommandError::not_found(format!("Focus session not found: {id}")).with_context("id"));
+    }
+    store.emit("focus-sessions-changed", ());
+    Ok(true)
+}
+
+/// Dry run of `create_timer` for live form validation; nothing is created.
+#[tauri::command]
+fn validate_timer(request: CreateTimerRequest, state: State<'_, TimerStore>) -> CommandResult<TimerValidation> {
+    Ok(state.validate(&request)?)
+}
+
+#[tauri::command]
+fn snooze_timer(id: String, minutes: u32, state: State<'_, TimerStore>) -> CommandResult<TimerView> {
+    let Some(timer) = state.get(&id)? else {
+        return Err(CommandError::not_found(format!("Timer not found: {id}")).with_context(id));
+    };
+    if timer.managed.is_some() {
+        return Err(CommandError::policy(MANAGED_TIMER_SNOOZE).with_context(id));
+    }
+
+    check_snooze_minutes(minutes).map_err(|err| CommandError::validation(err).with_context("minutes"))?;
+    let info = state
+        .snooze(&id, minutes, &AuditOrigin::Ui)
+        .map_err(CommandError::storage)?;
+    Ok(TimerView::new(info, Utc::now()))
+}
+
+/// Moves the next run of every matching timer by `minutes` (negative is
+/// earlier), e.g. "push everything back an hour tonight". Recurring timers
+/// return to their usual schedule afterwards.
+#[tauri::command]
+fn shift_timers(
+    minutes: i64,
+    filter: Option<TimerFilter>,
+    state: State<'_, TimerStore>,
+) -> CommandResult<Vec<ShiftedTimer>> {
+    let filter = filter.unwrap_or_default();
+    let before = filter
+        .before
+        .as_deref()
+        .map(parse_target_time)
+        .transpose()
+        .map_err(|err| CommandError::validation(err).with_context("filter.before"))?
+        .map(|(before, _)| before);
+    let filter = ShiftFilter {
+        actions: filter.actions,
+        profile: filter.profile,
+        before,
+    };
+
+    state
+        .shift(minutes, &filter, &AuditOrigin::Ui)
+        .map_err(|err| CommandError::validation(err).with_context("minutes"))
+}
+
+/// Same as a button pressed on a `LOCKPILOT_TIMER` notification, which
+/// `notifications` routes to `TimerStore::notification_action` directly.
+#[tauri::command]
+fn handle_notification_action(
+    category: String,
+    action: String,
+    timer_id: String,
+    state: State<'_, TimerStore>,
+) -> CommandResult<bool> {
+    state.notification_action(&category, &action, &timer_id)
+}
+
+#[tauri::command]
+fn list_plugins(state: State<'_, TimerStore>) -> CommandResult<PluginCatalog> {
+    Ok(PluginCatalog {
+        directory: state.plugins.dir().display().to_string(),
+        plugins: state.plugins.discover(),
+    })
+}
+
+/// VPN service names for the VPN action.
+#[tauri::command]
+fn list_vpn_services() -> CommandResult<Vec<String>> {
+    vpn::services().map_err(|err| CommandError::new(ErrorCode::Internal, err))
+}
+
+/// System sound names for the Alarm action.
+#[tauri::command]
+fn list_alarm_sounds() -> CommandResult<Vec<String>> {
+    alarm::sounds().map_err(|err| CommandError::new(ErrorCode::Internal, err))
+}
+
+/// Shortcut names for the Run Shortcut action.
+#[tauri::command]
+fn list_shortcuts() -> CommandResult<Vec<String>> {
+    shortcuts::names().map_err(|err| CommandError::new(ErrorCode::Internal, err))
+}
+
+#[tauri::command]
+fn get_history(limit: Option<usize>, state: State<'_, HistoryStore>) -> CommandResult<Vec<HistoryEntry>> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "Failed to lock history".to_string())?;
+
+    let limit = limit.unwrap_or(locked.len());
+    Ok(locked.iter().rev().take(limit).cloned().collect())
+}
+
+/// Run counts per local weekday and hour, for a GitHub-style heatmap of
+/// when actions actually happen. `range` defaults to the last 30 days.
+#[tauri::command]
+fn get_activity_heatmap(range: Option<HeatmapRange>, state: State<'_, HistoryStore>) -> CommandResult<ActivityHeatmap> {
+    Ok(state.heatmap(range.unwrap_or_default())?)
+}
+
+#[tauri::command]
+fn get_quiet_hours(state: State<'_, SettingsStore>) -> CommandResult<Option<QuietHours>> {
+    Ok(state.snapshot()?.quiet_hours)
+}
+
+#[tauri::command]
+fn get_working_hours(state: State<'_, SettingsStore>) -> CommandResult<Option<WorkingHours>> {
+    Ok(state.snapshot()?.working_hours)
+}
+
+/// Sets the global window in which timer actions may run. Timers can
+/// override it with their own `workingHours` or opt out entirely.
+#[tauri::command]
+fn set_working_hours(
+    working_hours: Option<WorkingHours>,
+    state: State<'_, SettingsStore>,
+) -> CommandResult<Option<WorkingHours>> {
+    if working_hours.as_ref().is_some_and(|hours| hours.start == hours.end) {
+        return Err(CommandError::validation("Working hours start and end must differ").with_context("end"));
+    }
+
+    let settings = state
+        .update(|settings| settings.working_hours = working_hours)
+        .map_err(CommandError::storage)?;
+    Ok(settings.working_hours)
+}
+
+#[tauri::command]
+fn set_quiet_hours(
+    quiet_hours: Option<QuietHours>,
+    state: State<'_, SettingsStore>,
+) -> CommandResult<Option<QuietHours>> {
+    if let Some(quiet) = quiet_hours.as_ref() {
+        if quiet.start == quiet.end {
+            return Err(CommandError::validation("Quiet hours start and end must differ").with_context("end"));
+        }
+        if quiet.block_websites && !helper::is_installed() {
+            return Err(CommandError::validation(WEBSITE_BLOCKING_NEEDS_HELPER).with_context("blockWebsites"));
+        }
+    }
+
+    let settings = state
+        .update(|settings| settings.quiet_hours = quiet_hours)
+        .map_err(CommandError::storage)?;
+    Ok(settings.quiet_hours)
+}
+
+#[tauri::command]
+fn get_usage_limit(state: State<'_, SettingsStore>) -> CommandResult<Option<UsageLimit>> {
+    Ok(state.snapshot()?.usage_limit)
+}
+
+/// Sets or clears the daily usage limit. Only actions that end the
+/// session or put the Mac to sleep make sense as a limit.
+#[tauri::command]
+fn set_usage_limit(
+    usage_limit: Option<UsageLimit>,
+    state: State<'_, SettingsStore>,
+) -> CommandResult<Option<UsageLimit>> {
+    if let Some(limit) = usage_limit.as_ref() {
+        if limit.limit_minutes == 0 || limit.limit_minutes >= 24 * 60 {
+            return Err(CommandError::validation("The limit must be between 1 minute and 24 hours")
+                .with_context("limitMinutes"));
+        }
+        if limit.warn_minutes > usage::MAX_WARN_MINUTES {
+            return Err(CommandError::validation(format!(
+                "The warning can come at most {} minutes ahead",
+                usage::MAX_WARN_MINUTES
+            ))
+            .with_context("warnMinutes"));
+        }
+        if !matches!(
+            limit.action,
+            TimerAction::Lock
+                | TimerAction::DisplaySleep
+                | TimerAction::SystemSleep
+                | TimerAction::Logout
+                | TimerAction::Shutdown
+        ) {
+            return Err(CommandError::validation(
+                "A usage limit can lock, sleep the displays or the Mac, log out or shut down",
+            )
+            .with_context("action"));
+        }
+    }
+
+    let settings = state
+        .update(|settings| settings.usage_limit = usage_limit)
+        .map_err(CommandError::storage)?;
+    Ok(settings.usage_limit)
+}
+
+/// Active minutes counted so far in the current usage day.
+#[tauri::command]
+fn get_usage_today(
+    settings: State<'_, SettingsStore>,
+    tracker: State<'_, UsageTracker>,
+) -> CommandResult<UsageToday> {
+    let limit = settings.snapshot()?.usage_limit;
+    let day_starts_at = limit
+        .as_ref()
+        .map_or_else(usage::default_day_starts_at, |limit| limit.day_starts_at);
+    let usage = tracker.on(usage::usage_day(Local::now(), day_starts_at))?;
+    Ok(UsageToday {
+        day: usage.day,
+        active_minutes: usage.active_seconds / 60,
+        limit_minutes: limit.map(|limit| limit.limit_minutes),
+        active_since: tracker.active_since(),
+    })
+}
+
+/// Battery charge, charging state, time remaining and power source, as
+/// the `battery_percent()`, `on_ac_power()` and `is_charging()` conditions
+/// see them.
+#[tauri::command]
+fn get_power_status() -> CommandResult<PowerStatus> {
+    system::power_status().map_err(|err| CommandError::new(ErrorCode::Internal, err))
+}
+
+/// Seconds since the last keyboard or mouse input; the same reading the
+/// `idle_seconds()` condition function and the usage tracker use.
+#[tauri::command]
+fn get_idle_seconds() -> CommandResult<u64> {
+    system::idle_seconds().map_err(|err| CommandError::new(ErrorCode::Internal, err))
+}
+
+/// macOS version, when the Mac last booted and what pmset has scheduled,
+/// so the UI can suggest a restart after a long uptime.
+#[tauri::command]
+fn get_system_info() -> SystemInfo {
+    system::system_info()
+}
+
+/// Screen time per usage day over `range` (default the last 30 days), to
+/// see whether a usage limit is working.
+#[tauri::command]
+fn get_usage_report(
+    range: Option<HeatmapRange>,
+    settings: State<'_, SettingsStore>,
+    tracker: State<'_, UsageTracker>,
+) -> CommandResult<UsageReport> {
+    let limit = settings.snapshot()?.usage_limit;
+    let day_starts_at = limit
+        .as_ref()
+        .map_or_else(usage::default_day_starts_at, |limit| limit.day_starts_at);
+    let today = usage::usage_day(Local::now(), day_starts_at);
+    let since = range
+        .unwrap_or_default()
+        .days()
+        .map(|days| today - ChronoDuration::days(days - 1));
+
+    let days: Vec<UsageReportDay> = tracker
+        .days_since(since)?
+        .into_iter()
+        .filter(|usage| usage.active_seconds > 0 || usage.limit_triggers > 0)
+        .map(|usage| UsageReportDay {
+            day: usage.day,
+            active_minutes: usage.active_seconds / 60,
+            first_active_at: usage.first_active_at,
+            last_active_at: usage.last_active_at,
+            limit_triggers: usage.limit_triggers,
+        })
+        .collect();
+    let limit_minutes = limit.map(|limit| limit.limit_minutes);
+    let total: u64 = days.iter().map(|day| day.active_minutes).sum();
+    Ok(UsageReport {
+        average_active_minutes: total.checked_div(days.len() as u64).unwrap_or(0),
+        days_over_limit: limit_minutes.map_or(0, |limit| {
+            days.iter().filter(|day| day.active_minutes >= u64::from(limit)).count()
+        }),
+        days,
+        limit_minutes,
+    })
+}
+
+#[tauri::command]
+fn get_break_reminders(state: State<'_, SettingsStore>) -> CommandResult<Option<BreakReminders>> {
+    Ok(state.snapshot()?.break_reminders)
+}
+
+/// Turns break reminders on with `break_reminders`, or off with `None`.
+#[tauri::command]
+fn set_break_reminders(
+    break_reminders: Option<BreakReminders>,
+    state: State<'_, SettingsStore>,
+) -> CommandResult<Option<BreakReminders>> {
+    if let Some(reminders) = break_reminders.as_ref() {
+        if !(1..=breaks::MAX_WORK_MINUTES).contains(&reminders.work_minutes) {
+            return Err(CommandError::validation(format!(
+                "Work time must be between 1 and {} minutes",
+                breaks::MAX_WORK_MINUTES
+            ))
+            .with_context("workMinutes"));
+        }
+        if !(5..=breaks::MAX_BREAK_SECONDS).contains(&reminders.break_seconds) {
+            return Err(CommandError::validation(format!(
+                "Breaks must last between 5 and {} seconds",
+                breaks::MAX_BREAK_SECONDS
+            ))
+            .with_context("breakSeconds"));
+        }
+    }
+
+    let settings = state
+        .update(|settings| settings.break_reminders = break_reminders)
+        .map_err(CommandError::storage)?;
+    Ok(settings.break_reminders)
+}
+
+/// Breaks taken and skipped per day over `range` (default the last 30
+/// days).
+#[tauri::command]
+fn get_break_stats(range: Option<HeatmapRange>, stats: State<'_, BreakStats>) -> CommandResult<BreakReport> {
+    let today = Local::now().date_naive();
+    let since = range
+        .unwrap_or_default()
+        .days()
+        .map(|days| today - ChronoDuration::days(days - 1));
+    let days = stats.days_since(since)?;
+    Ok(BreakReport {
+        nudged: days.iter().map(|day| day.nudged).sum(),
+        taken: days.iter().map(|day| day.taken).sum(),
+        skipped: days.iter().map(|day| day.skipped).sum(),
+        postponed: days.iter().map(|day| day.postponed).sum(),
+        days,
+    })
+}
+
+#[tauri::command]
+fn get_history_retention(state: State<'_, SettingsStore>) -> CommandResult<HistoryRetention> {
+    Ok(state.snapshot()?.history_retention)
+}
+
+/// Rejects keeping `what` for no days, or fewer than one or more than
+/// `cap` of its `entries`.
+fn validate_retention(retention: &HistoryRetention, what: &str, entries: &str, cap: usize) -> CommandResult<()> {
+    if retention.max_days == Some(0) {
+        return Err(CommandError::validation(format!("Keep {what} for at least one day")).with_context("maxDays"));
+    }
+    if retention
+        .max_entries
+        .is_some_and(|max_entries| !(1..=cap).contains(&max_entries))
+    {
+        return Err(
+            CommandError::validation(format!("Keep between 1 and {cap} {entries}")).with_context("maxEntries"),
+        );
+    }
+    Ok(())
+}
+
+/// Saves the policy and applies it right away rather than on the next
+/// janitor pass.
+#[tauri::command]
+fn set_history_retention(
+    retention: HistoryRetention,
+    state: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> CommandResult<HistoryRetention> {
+    validate_retention(&retention, "history", "history entries", MAX_HISTORY_ENTRIES)?;
+
+    let settings = state
+        .update(|settings| settings.history_retention = retention)
+        .map_err(CommandError::storage)?;
+    history.prune(&settings.history_retention, Utc::now())?;
+    Ok(settings.history_retention)
+}
+
+/// Timers removed because they expired or ran out of runs, newest first.
+#[tauri::command]
+fn list_archived_timers(state: State<'_, TimerArchive>) -> CommandResult<Vec<ArchivedTimer>> {
+    Ok(state.list()?)
+}
+
+#[tauri::command]
+fn get_archive_retention(state: State<'_, SettingsStore>) -> CommandResult<HistoryRetention> {
+    Ok(state.snapshot()?.archive_retention)
+}
+
+/// Like `set_history_retention`, for the archive.
+#[tauri::command]
+fn set_archive_retention(
+    retention: HistoryRetention,
+    state: State<'_, SettingsStore>,
+    archive: State<'_, TimerArchive>,
+) -> CommandResult<HistoryRetention> {
+    validate_retention(&retention, "archived timers", "archived timers", MAX_ARCHIVED_TIMERS)?;
+
+    let settings = state
+        .update(|settings| settings.archive_retention = retention)
+        .map_err(CommandError::storage)?;
+    archive.prune(&settings.archive_retention, Utc::now())?;
+    Ok(settings.archive_retention)
+}
+
+#[tauri::command]
+fn get_lock_compatibility(state: State<'_, SettingsStore>) -> CommandResult<bool> {
+    Ok(state.snapshot()?.lock_compatibility)
+}
+
+#[tauri::command]
+fn set_lock_compatibility(enabled: bool, state: State<'_, SettingsStore>) -> CommandResult<bool> {
+    let settings = state
+        .update(|settings| settings.lock_compatibility = enabled)
+        .map_err(CommandError::storage)?;
+    Ok(settings.lock_compatibility)
+}
+
+#[tauri::command]
+fn get_calendar_log(state: State<'_, SettingsStore>) -> CommandResult<bool> {
+    Ok(state.snapshot()?.calendar_log)
+}
+
+/// Turning logging on creates the LockPilot calendar straight away, so a
+/// refused Automation prompt is reported here rather than on every run.
+#[tauri::command]
+fn set_calendar_log(enabled: bool, state: State<'_, SettingsStore>) -> CommandResult<bool> {
+    if enabled {
+        calendar_log::ensure_calendar().map_err(|err| {
+            let code = match system::denied_permission(&err) {
+                Some(_) => ErrorCode::Permission,
+                None => ErrorCode::Internal,
+            };
+            CommandError::new(code, err)
+        })?;
+    }
+    let settings = state
+        .update(|settings| settings.calendar_log = enabled)
+        .map_err(CommandError::storage)?;
+    Ok(settings.calendar_log)
+}
+
+#[tauri::command]
+fn get_push_settings(state: State<'_, SettingsStore>) -> CommandResult<PushSettings> {
+    Ok(PushSettings::from_settings(&state.snapshot()?))
+}
+
+/// Sets where `Push` timers deliver. `secret` is the ntfy access token or
+/// Pushover application token: omitted keeps the stored one, empty removes
+/// it. Clearing `target` also removes the stored tokens.
+#[tauri::command]
+fn set_push_settings(
+    target: Option<PushTarget>,
+    secret: Option<String>,
+    state: State<'_, SettingsStore>,
+) -> CommandResult<PushSettings> {
+    match &target {
+        Some(target) => {
+            target
+                .validate()
+                .map_err(|err| CommandError::validation(err).with_context("target"))?;
+            if let Some(secret) = secret.as_deref() {
+                target.store_secret(secret).map_err(CommandError::storage)?;
+            }
+        }
+        None => {
+            if let Some(previous) = state.snapshot()?.push {
+                previous.forget_secret().map_err(CommandError::storage)?;
+            }
+        }
+    }
+    let settings = state
+        .update(|settings| settings.push = target)
+        .map_err(CommandError::storage)?;
+    Ok(PushSettings::from_settings(&settings))
+}
+
+/// Sends a test message with the saved push settings.
+#[tauri::command]
+fn send_test_push(state: State<'_, SettingsStore>) -> CommandResult<String> {
+    let target = state
+        .snapshot()?
+        .push
+        .ok_or_else(|| CommandError::validation("Push notifications are not configured").with_context("target"))?;
+    push::send(&target, "Test message from LockPilot").map_err(CommandError::network)
+}
+
+#[tauri::command]
+fn get_email_settings(state: State<'_, SettingsStore>) -> CommandResult<EmailSettings> {
+    Ok(EmailSettings::from_settings(&state.snapshot()?))
+}
+
+/// Sets the SMTP server for `Email` timers and failure notices. `password`
+/// follows `set_push_settings`' `secret`: omitted keeps the stored one,
+/// empty removes it. Clearing `smtp` also removes the stored password.
+#[tauri::command]
+fn set_email_settings(
+    smtp: Option<SmtpSettings>,
+    password: Option<String>,
+    state: State<'_, SettingsStore>,
+) -> CommandResult<EmailSettings> {
+    match &smtp {
+        Some(smtp) => {
+            smtp.validate().map_err(|err| CommandError::validation(err).with_context("smtp"))?;
+            if let Some(password) = password.as_deref() {
+                smtp.store_password(password)
+                    .map_err(|err| CommandError::validation(err).with_context("password"))?;
+            }
+        }
+        None => {
+            if let Some(previous) = state.snapshot()?.smtp {
+                previous.forget_password().map_err(CommandError::storage)?;
+            }
+        }
+    }
+    let settings = state
+        .update(|settings| settings.smtp = smtp)
+        .map_err(CommandError::storage)?;
+    Ok(EmailSettings::from_settings(&settings))
+}
+
+/// Sends a test email with the saved SMTP settings.
+#[tauri::command]
+fn send_test_email(state: State<'_, SettingsStore>) -> CommandResult<String> {
+    let smtp = state
+        .snapshot()?
+        .smtp
+        .ok_or_else(|| CommandError::validation("Email is not configured").with_context("smtp"))?;
+    email::send(&smtp, "LockPilot test", "Test message from LockPilot").map_err(CommandError::network)
+}
+
+#[tauri::command]
+fn get_global_pause(state: State<'_, SettingsStore>) -> CommandResult<PauseState> {
+    Ok(PauseState::from_settings(&state.snapshot()?))
+}
+
+#[tauri::command]
+fn set_global_pause(until: Option<String>, state: State<'_, TimerStore>) -> CommandResult<PauseState> {
+    let until = parse_pause_until(until.as_deref()).map_err(|err| CommandError::validation(err).with_context("until"))?;
+    state
+        .set_pause(until, &AuditOrigin::Ui)
+        .map_err(CommandError::storage)
+}
+
+/// Creates one recurring timer per supported crontab line. Lines that parse
+/// but fail timer validation are reported as skipped alongside the rest.
+#[tauri::command]
+fn import_crontab(text: String, state: State<'_, TimerStore>) -> CommandResult<CrontabImportResult> {
+    let (parsed, mut skipped) = crontab::parse(&text);
+    let lines: Vec<&str> = text.lines().collect();
+
+    let mut created = Vec::new();
+    for entry in parsed {
+        match state.create(entry.request, &AuditOrigin::Ui) {
+            Ok(info) => created.push(info),
+            Err(reason) => skipped.push(SkippedCrontabLine {
+                line: entry.line,
+                text: lines[entry.line - 1].trim().to_string(),
+                reason,
+            }),
+        }
+    }
+    skipped.sort_by_key(|line| line.line);
+
+    Ok(CrontabImportResult { created, skipped })
+}
+
+#[tauri::command]
+fn export_launchd(id: String, path: String, state: State<'_, TimerStore>) -> CommandResult<LaunchdExport> {
+    let info = state
+        .get(&id)?
+        .ok_or_else(|| CommandError::not_found(format!("Timer not found: {id}")).with_context(id.clone()))?;
+    launchd::export(&info, &state.plugins, std::path::Path::new(&path))
+}
+
+#[tauri::command]
+fn list_profiles(state: State<'_, TimerStore>) -> CommandResult<ProfilesOverview> {
+    Ok(state.profiles_overview()?)
+}
+
+#[tauri::command]
+fn create_profile(name: String, state: State<'_, TimerStore>) -> CommandResult<ProfilesOverview> {
+    state
+        .create_profile(&name)
+        .map_err(|err| CommandError::validation(err).with_context("name"))
+}
+
+#[tauri::command]
+fn delete_profile(name: String, state: State<'_, TimerStore>) -> CommandResult<ProfilesOverview> {
+    profile_exists(&state, &name)?;
+    state
+        .delete_profile(&name, &AuditOrigin::Ui)
+        .map_err(|err| CommandError::validation(err).with_context("name"))
+}
+
+#[tauri::command]
+fn set_active_profile(name: String, state: State<'_, TimerStore>) -> CommandResult<ProfilesOverview> {
+    profile_exists(&state, &name)?;
+    Ok(state.set_active_profile(&name, &AuditOrigin::Ui)?)
+}
+
+#[tauri::command]
+fn list_workspaces(state: State<'_, TimerStore>) -> CommandResult<WorkspacesOverview> {
+    Ok(state.workspaces_overview()?)
+}
+
+#[tauri::command]
+fn create_workspace(name: String, state: State<'_, TimerStore>) -> CommandResult<WorkspacesOverview> {
+    state
+        .create_workspace(&name)
+        .map_err(|err| CommandError::validation(err).with_context("name"))
+}
+
+#[tauri::command]
+fn delete_workspace(name: String, state: State<'_, TimerStore>) -> CommandResult<WorkspacesOverview> {
+    workspace_exists(&state, &name)?;
+    state
+        .delete_workspace(&name, &AuditOrigin::Ui)
+        .map_err(|err| CommandError::validation(err).with_context("name"))
+}
+
+#[tauri::command]
+fn switch_workspace(name: String, state: State<'_, TimerStore>) -> CommandResult<WorkspacesOverview> {
+    workspace_exists(&state, &name)?;
+    state
+        .switch_workspace(&name, &AuditOrigin::Ui)
+        .map_err(|err| CommandError::storage(err).with_context("name"))
+}
+
+#[tauri::command]
+fn get_audit_log(limit: Option<usize>, state: State<'_, TimerStore>) -> CommandResult<Vec<AuditEntry>> {
+    state.audit.read(limit).map_err(CommandError::storage)
+}
+
+#[tauri::command]
+fn get_remote_api_settings(state: State<'_, SettingsStore>) -> CommandResult<RemoteApiSettings> {
+    Ok(state.snapshot()?.remote_api)
+}
+
+#[tauri::command]
+fn set_remote_api_settings(
+    remote_api: RemoteApiSettings,
+    settings: State<'_, SettingsStore>,
+    api: State<'_, RemoteApi>,
+    timers: State<'_, TimerStore>,
+) -> CommandResult<RemoteApiSettings> {
+    if remote_api.port < 1024 {
+        return Err(CommandError::validation("Remote API port must be 1024 or higher").with_context("port"));
+    }
+
+    api.apply(&remote_api, timers.inner().clone())
+        .map_err(|err| CommandError::network(err).with_context("port"))?;
+    let updated = settings
+        .update(|settings| settings.remote_api = remote_api)
+        .map_err(CommandError::storage)?;
+    Ok(updated.remote_api)
+}
+
+#[tauri::command]
+fn create_api_token(name: String, scope: ApiScope, api: State<'_, RemoteApi>) -> CommandResult<CreatedApiToken> {
+    if name.trim().is_empty() {
+        return Err(CommandError::validation("Token name is required").with_context("name"));
+    }
+    api.tokens.create(&name, scope).map_err(CommandError::storage)
+}
+
+#[tauri::command]
+fn list_api_tokens(api: State<'_, RemoteApi>) -> CommandResult<Vec<ApiTokenInfo>> {
+    Ok(api.tokens.list()?)
+}
+
+#[tauri::command]
+fn revoke_api_token(id: String, api: State<'_, RemoteApi>) -> CommandResult<bool> {
+    api.tokens.revoke(&id).map_err(CommandError::storage)
+}
+
+#[tauri::command]
+fn generate_tls_certificate(api: State<'_, RemoteApi>) -> CommandResult<TlsCertificateInfo> {
+    api.generate_certificate().map_err(CommandError::storage)
+}
+
+/// Other LockPilots advertising their remote API on the local network.
+/// Takes about five seconds.
+#[tauri::command]
+fn discover_fleet_peers() -> CommandResult<Vec<FleetPeer>> {
+    fleet::discover(remote_api::local_host_name().as_deref()).map_err(|err| CommandError::new(ErrorCode::Internal, err))
+}
+
+/// Creates the timer on another Mac through its remote API, e.g. the same
+/// bedtime lock on every Mac in the house. `credentials` are needed the
+/// first time, and are then kept in the keychain.
+#[tauri::command]
+fn push_timer(
+    target: FleetTarget,
+    request: CreateTimerRequest,
+    credentials: Option<PeerCredentials>,
+) -> CommandResult<PushedTimer> {
+    if target.host.trim().is_empty() {
+        return Err(CommandError::validation("Choose a Mac to push to").with_context("target"));
+    }
+    if credentials.as_ref().is_some_and(|credentials| credentials.token.trim().is_empty()) {
+        return Err(CommandError::validation("Token is required").with_context("credentials"));
+    }
+    fleet::push_timer(&target, &request, credentials).map_err(CommandError::network)
+}
+
+/// Whether this Mac only displays timers; see `guard_read_only`.
+#[tauri::command]
+fn get_read_only(app: tauri::AppHandle) -> bool {
+    kiosk::is_read_only(&app)
+}
+
+/// The policy in force, so the form can leave out forbidden actions.
+#[tauri::command]
+fn get_policy(state: State<'_, TimerStore>) -> Policy {
+    state.policy.snapshot()
+}
+
+/// Settings and timers locked by the organization through managed
+/// preferences, if any.
+#[tauri::command]
+fn get_managed_config(state: State<'_, ManagedSummary>) -> ManagedSummary {
+    state.inner().clone()
+}
+
+#[tauri::command]
+fn get_sync_settings(state: State<'_, SettingsStore>) -> CommandResult<SyncSettings> {
+    Ok(state.snapshot()?.sync)
+}
+
+#[tauri::command]
+fn set_sync_settings(
+    sync: SyncSettings,
+    settings: State<'_, SettingsStore>,
+    state: State<'_, SyncState>,
+) -> CommandResult<SyncSettings> {
+    let tags = normalize_tags(sync.tags).map_err(|err| CommandError::validation(err).with_context("tags"))?;
+    if sync.enabled && tags.is_empty() {
+        return Err(CommandError::validation("Choose at least one tag to sync").with_context("tags"));
+    }
+    let sync = SyncSettings { enabled: sync.enabled, tags };
+    let updated = settings
+        .update(|settings| settings.sync = sync)
+        .map_err(CommandError::storage)?;
+    state.sync_soon();
+    Ok(updated.sync)
+}
+
+#[tauri::command]
+fn list_sync_peers(state: State<'_, SyncState>) -> CommandResult<Vec<SyncPeer>> {
+    Ok(state.peers()?)
+}
+
+/// Pairs with another Mac for two-way sync of tagged timers. `credentials`
+/// are a control token created on that Mac; a token for this Mac is sent
+/// back, so this Mac's remote API must be on.
+#[tauri::command]
+fn pair_sync_peer(
+    target: FleetTarget,
+    credentials: PeerCredentials,
+    settings: State<'_, SettingsStore>,
+    api: State<'_, RemoteApi>,
+    state: State<'_, SyncState>,
+) -> CommandResult<SyncPeer> {
+    if target.host.trim().is_empty() {
+        return Err(CommandError::validation("Choose a Mac to pair with").with_context("target"));
+    }
+    if credentials.token.trim().is_empty() {
+        return Err(CommandError::validation("Token is required").with_context("credentials"));
+    }
+    let remote_api = settings.snapshot()?.remote_api;
+    let peer = sync::pair(&state, &api, &remote_api, &target, credentials).map_err(CommandError::network)?;
+    state.sync_soon();
+    Ok(peer)
+}
+
+#[tauri::command]
+fn unpair_sync_peer(id: String, api: State<'_, RemoteApi>, state: State<'_, SyncState>) -> CommandResult<SyncPeer> {
+    sync::unpair(&state, &api, &id)
+        .map_err(CommandError::storage)?
+        .ok_or_else(|| CommandError::not_found(format!("Sync peer not found: {id}")).with_context(id))
+}
+
+/// Syncs with every paired Mac in the background; `sync-finished` follows.
+#[tauri::command]
+fn sync_now(settings: State<'_, SettingsStore>, state: State<'_, SyncState>) -> CommandResult<()> {
+    let settings = settings.snapshot()?;
+    if !settings.sync.enabled {
+        return Err(CommandError::validation("Sync is turned off"));
+    }
+    state.sync_soon();
+    Ok(())
+}
+
+#[tauri::command]
+fn list_release_versions() -> CommandResult<Vec<ReleaseVersion>> {
+    let mut releases = stable_releases(fetch_releases().map_err(CommandError::network)?);
+    releases.sort_by(release_version_desc);
+
+    Ok(releases
+        .into_iter()
+        .map(|release| {
+            let mut dmg_architectures: Vec<&'static str> = release
+                .assets
+                .iter()
+                .filter(|asset| is_dmg(asset))
+                .map(|asset| dmg_architecture(&asset.name).unwrap_or("unlabeled"))
+                .collect();
+            dmg_architectures.sort_unstable();
+            dmg_architectures.dedup();
+            let native_dmg = dmg_architectures
+                .iter()
+                .any(|arch| *arch == std::env::consts::ARCH || *arch == "universal");
+            ReleaseVersion {
+                tag: release.tag_name.clone(),
+                name: release.name.unwrap_or_else(|| release.tag_name.clone()),
+                published_at: release.published_at,
+                assets: release
+                    .assets
+                    .into_iter()
+                    .map(|asset| ReleaseAsset {
+                        name: asset.name,
+                        size: asset.size,
+                        download_count: asset.download_count,
+                    })
+                    .collect(),
+                dmg_architectures,
+                native_dmg,
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+fn check_channel_update(
+    current_version: String,
+    channel: UpdateChannel,
+    pending: State<'_, PendingUpdate>,
+) -> CommandResult<Option<UpdateInfo>> {
+    let current = normalize_version(&current_version).ok_or_else(|| {
+        CommandError::validation(format!("Invalid current version: {current_version}")).with_context("currentVersion")
+    })?;
+
+    let mut releases = releases_for_channel(fetch_releases().map_err(CommandError::network)?, &channel);
+    releases.sort_by(release_version_desc);
+
+    let update = match channel {
+        UpdateChannel::Dev | UpdateChannel::Main => releases.into_iter().find(|release| {
+            // Only strictly newer builds; a rebuild with new `+...` metadata
+            // isn't an update.
+            normalize_version(&release.tag_name)
+                .is_some_and(|version| version.cmp_precedence(&current).is_gt())
+        }),
+    };
+
+    pending.set(update.as_ref().and_then(|release| {
+        pick_dmg_asset(&release.assets).map(|asset| PendingRelease {
+            tag: release.tag_name.clone(),
+            download_url: asset.browser_download_url,
+        })
+    }));
+
+    Ok(update.map(|release| UpdateInfo {
+        tag: release.tag_name.clone(),
+        name: release.name.unwrap_or_else(|| release.tag_name.clone()),
+        notes: release.body,
+        published_at: release.published_at,
+    }))
+}
+
+#[tauri::command]
+fn install_channel_update(channel: UpdateChannel) -> CommandResult<String> {
+    let mut releases = releases_for_channel(fetch_releases().map_err(CommandError::network)?, &channel);
+    releases.sort_by(release_version_desc);
+    let release = releases.into_iter().next().ok_or_else(|| {
+        CommandError::not_found(format!("No releases found for {} channel", channel_name(&channel)))
+    })?;
+
+    let dmg_asset = pick_dmg_asset(&release.assets).ok_or_else(|| {
+        CommandError::not_found(format!("No DMG asset found for release {}", release.tag_name))
+    })?;
+
+    let local_dmg = download_asset_to_temp(&dmg_asset.browser_download_url, &release.tag_name)
+        .map_err(CommandError::network)?;
+    if let Err(err) = installer::verify_dmg(&local_dmg) {
+        let _ = fs::remove_file(&local_dmg);
+        return Err(CommandError::validation(err).with_context(release.tag_name));
+    }
+    exec::spawn(Command::new("/usr/bin/open").arg(&local_dmg))
+        .map_err(|err| CommandError::io("Failed to open installer DMG", err))?;
+
+    Ok(format!(
+        "Opened {} channel installer {} from {}",
+        channel_name(&channel),
+        release.tag_name,
+        local_dmg.display()
+    ))
+}
+
+#[tauri::command]
+fn get_install_update_on_quit(state: State<'_, SettingsStore>) -> CommandResult<bool> {
+    Ok(state.snapshot()?.install_update_on_quit)
+}
+
+#[tauri::command]
+fn set_install_update_on_quit(enabled: bool, state: State<'_, SettingsStore>) -> CommandResult<bool> {
+    let settings = state
+        .update(|settings| settings.install_update_on_quit = enabled)
+        .map_err(CommandError::storage)?;
+    Ok(settings.install_update_on_quit)
+}
+
+/// On exit, when the user opted in: installs the update the last check
+/// found, reusing its DMG if it was already downloaded, and relaunches.
+/// Anything going wrong just leaves the current version in place.
+fn install_pending_update(app: &tauri::AppHandle) {
+    let opted_in = app
+        .state::<SettingsStore>()
+        .snapshot()
+        .is_ok_and(|settings| settings.install_update_on_quit);
+    let Some(release) = app.state::<PendingUpdate>().take().filter(|_| opted_in) else {
+        return;
+    };
+
+    let cached = installer::dmg_path(&release.tag);
+    let dmg = if cached.exists() {
+        Ok(cached)
+    } else {
+        download_asset_to_temp(&release.download_url, &release.tag)
+    };
+    let installed = dmg.and_then(|dmg| installer::install_in_place(&dmg));
+    match installed.and_then(|bundle| installer::relaunch_after_exit(&bundle)) {
+        Ok(()) => eprintln!("Installed {} on quit", release.tag),
+        Err(err) => eprintln!("Failed to install {} on quit: {err}", release.tag),
+    }
+}
+
+#[tauri::command]
+fn get_stay_awake_minutes(state: State<'_, SettingsStore>) -> CommandResult<u32> {
+    Ok(state.snapshot()?.stay_awake_minutes)
+}
+
+/// Applies to occurrences queued from now on; ones already waiting keep
+/// the window they were queued with.
+#[tauri::command]
+fn set_stay_awake_minutes(minutes: u32, state: State<'_, SettingsStore>) -> CommandResult<u32> {
+    if minutes > MAX_STAY_AWAKE_MINUTES {
+        return Err(
+            CommandError::validation(format!("Stay-awake window must be at most {MAX_STAY_AWAKE_MINUTES} minutes"))
+                .with_context("minutes"),
+        );
+    }
+    let settings = state
+        .update(|settings| settings.stay_awake_minutes = minutes)
+        .map_err(CommandError::storage)?;
+    Ok(settings.stay_awake_minutes)
+}
+
+#[tauri::command]
+fn get_conflict_policy(state: State<'_, SettingsStore>) -> CommandResult<ConflictPolicy> {
+    Ok(state.snapshot()?.conflict_policy)
+}
+
+#[tauri::command]
+fn set_conflict_policy(policy: ConflictPolicy, state: State<'_, SettingsStore>) -> CommandResult<ConflictPolicy> {
+    let settings = state
+        .update(|settings| settings.conflict_policy = policy)
+        .map_err(CommandError::storage)?;
+    Ok(settings.conflict_policy)
+}
+
+#[tauri::command]
+fn get_helper_status() -> HelperStatus {
+    helper::status()
+}
+
+/// Installs the root daemon forced shutdowns and reboots go through.
+/// Prompts for an administrator password.
+#[tauri::command]
+fn install_helper() -> CommandResult<HelperStatus> {
+    helper::install().map_err(|err| CommandError::new(ErrorCode::Permission, err))
+}
+
+#[tauri::command]
+fn uninstall_helper() -> CommandResult<HelperStatus> {
+    helper::uninstall().map_err(|err| CommandError::new(ErrorCode::Permission, err))
+}
+
+/// Deletes every downloaded installer DMG and reports how much was freed.
+#[tauri::command]
+fn clear_update_cache() -> UpdateCacheReport {
+    installer::clear_cache()
+}
+
+#[tauri::command]
+fn install_release(tag: String) -> CommandResult<String> {
+    let releases = stable_releases(fetch_releases().map_err(CommandError::network)?);
+    let release = releases
+        .into_iter()
+        .find(|release| tags_match(&release.tag_name, &tag))
+        .ok_or_else(|| CommandError::not_found(format!("Release not found for tag: {tag}")).with_context(tag.clone()))?;
+
+    let dmg_asset = pick_dmg_asset(&release.assets).ok_or_else(|| {
+        CommandError::not_found(format!("No DMG asset found for release {}", release.tag_name))
+    })?;
+
+    let local_dmg = download_asset_to_temp(&dmg_asset.browser_download_url, &release.tag_name)
+        .map_err(CommandError::network)?;
+    if let Err(err) = installer::verify_dmg(&local_dmg) {
+        let _ = fs::remove_file(&local_dmg);
+        return Err(CommandError::validation(err).with_context(release.tag_name));
+    }
+    exec::spawn(Command::new("/usr/bin/open").arg(&local_dmg))
+        .map_err(|err| CommandError::io("Failed to open installer DMG", err))?;
+
+    Ok(format!(
+        "Opened installer for {} from {}",
+        release.tag_name,
+        local_dmg.display()
+    ))
+}
+
+fn app_data_dir(app: &tauri::AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("lockpilot"))
+}
+
+fn app_data_file(app: &tauri::AppHandle, file_name: &str) -> PathBuf {
+    app_data_dir(app).join(file_name)
+}
+
+fn api_tokens_storage_path(app: &tauri::AppHandle) -> PathBuf {
+    app_data_file(app, "api_tokens.json")
+}
+
+fn tls_dir(app: &tauri::AppHandle) -> PathBuf {
+    app_data_file(app, "tls")
+}
+
+/// Wraps the command handler so that in read-only mode every command not
+/// known to only read is rejected before it runs. Timers keep firing, and
+/// the remote API and managed preferences can still change the schedule.
+fn guard_read_only<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command().to_string();
+        if !kiosk::allows(&command) && kiosk::is_read_only(&invoke.message.webview()) {
+            let error = CommandError::new(ErrorCode::ReadOnly, "LockPilot is read-only on this Mac");
+            invoke.resolver.reject(error.with_context(command));
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+pub fn run() {
+    tauri::Builder::default()
+        .setup(|app| {
+            let store = TimerStore::open(&app_data_dir(app.handle()));
+            let settings = store.settings.clone();
+            let history = store.history.clone();
+            let archive = store.archive.clone();
+            let crash_reports = CrashReports::new(app_data_file(app.handle(), "crash_reports"));
+            crash_reports.install_panic_hook(app.package_info().version.to_string(), store.clone());
+            if let Err(err) = restore_timers(&store) {
+                eprintln!("Failed to restore timers: {err}");
+            }
+            let managed = managed::apply(&store);
+            store.attach_frontend(Arc::new(app.handle().clone()));
+            let read_only_launch = ReadOnlyLaunch::from_args();
+            if read_only_launch.0 {
+                settings.mark_read_only_launch();
+            }
+            let responder = store.clone();
+            let handle = app.handle().clone();
+            let registered = notifications::register(move |category, action, timer_id| {
+                // Like `guard_read_only` for the command of the same name.
+                if kiosk::is_read_only(&handle) {
+                    eprintln!("Ignored notification action {action}: LockPilot is read-only on this Mac");
+                    return;
+                }
+                if let Err(err) = responder.notification_action(category, action, timer_id) {
+                    eprintln!("Failed to handle notification action {action}: {}", err.message);
+                }
+            });
+            if let Err(err) = registered {
+                eprintln!("Failed to register notification actions: {err}");
+            }
+            if let Err(err) = system::prevent_app_nap() {
+                eprintln!("Failed to opt out of App Nap: {err}");
+            }
+            spawn_scheduler(store.clone());
+            spawn_janitor(store.clone());
+            spawn_countdown_ticker(store.clone());
+            spawn_timezone_watcher(store.clone());
+            spawn_incoming_watcher(store.clone());
+            let calendars = IcsSubscriptions::new(app_data_file(app.handle(), "ics_subscriptions.json"));
+            ics::spawn_sync(calendars.clone(), store.clone());
+            let focus_sessions = FocusSessions::new(app_data_file(app.handle(), "focus_sessions.json"));
+            focus::spawn_watcher(focus_sessions.clone(), store.clone());
+            let usage = UsageTracker::new(app_data_file(app.handle(), "usage.json"));
+            usage::spawn_tracker(usage.clone(), store.clone());
+            let break_stats = BreakStats::new(app_data_file(app.handle(), "breaks.json"));
+            breaks::spawn_reminder(break_stats.clone(), store.clone());
+
+            let tokens = ApiTokenStore::new(api_tokens_storage_path(app.handle()));
+            if let Err(err) = tokens.load_persisted() {
+                eprintln!("Failed to load API tokens: {err}");
+            }
+            let sync_state = SyncState::new(app_data_file(app.handle(), "sync.json"));
+            sync::spawn_syncer(sync_state.clone(), store.clone());
+            let remote_api = RemoteApi::new(tokens, sync_state.clone(), tls_dir(app.handle()));
+            let remote_settings = settings.snapshot().map(|settings| settings.remote_api).unwrap_or_default();
+            if let Err(err) = remote_api.apply(&remote_settings, store.clone()) {
+                eprintln!("Failed to start remote API: {err}");
+            }
+            app.manage(settings);
+            app.manage(history);
+            app.manage(archive);
+            app.manage(remote_api);
+            app.manage(store);
+            app.manage(crash_reports);
+            app.manage(calendars);
+            app.manage(focus_sessions);
+            app.manage(usage);
+            app.manage(break_stats);
+            app.manage(sync_state);
+            app.manage(managed);
+            app.manage(PendingUpdate::default());
+            app.manage(read_only_launch);
+            if let Err(err) = tray::install(app.handle()) {
+                eprintln!("Failed to add the menu bar icon: {err}");
+            }
+            Ok(())
+        })
+        .invoke_handler(guard_read_only(tauri::generate_handler![
+            create_timer,
+            create_timers,
+            snooze_timer,
+            shift_timers,
+            get_dashboard_state,
+            create_timer_from_text,
+            preview_occurrences,
+            validate_timer,
+            health_check,
+            run_self_test,
+            get_app_info,
+            list_crash_reports,
+            delete_crash_report,
+            send_crash_report,
+            get_permission_status,
+            request_permissions,
+            handle_notification_action,
+            list_timers,
+            cancel_timer,
+            get_global_pause,
+            set_global_pause,
+            get_quiet_hours,
+            set_quiet_hours,
+            get_working_hours,
+            set_working_hours,
+            get_usage_limit,
+            set_usage_limit,
+            get_usage_today,
+            get_usage_report,
+            get_break_reminders,
+            set_break_reminders,
+            get_break_stats,
+            get_idle_seconds,
+            get_power_status,
+            get_system_info,
+            get_history_retention,
+            set_history_retention,
+            list_archived_timers,
+            get_archive_retention,
+            set_archive_retention,
+            get_lock_compatibility,
+            set_lock_compatibility,
+            get_calendar_log,
+            set_calendar_log,
+            get_push_settings,
+            set_push_settings,
+            send_test_push,
+            get_email_settings,
+            set_email_settings,
+            send_test_email,
+            get_holiday_region,
+            set_holiday_region,
+            subscribe_ics,
+            list_ics_subscriptions,
+            unsubscribe_ics,
+            create_focus_session,
+            list_focus_sessions,
+            cancel_focus_session,
+            list_blocked_domains,
+            set_blocked_domains,
+            get_history,
+            get_activity_heatmap,
+            get_audit_log,
+            import_crontab,
+            export_launchd,
+            list_profiles,
+            create_profile,
+            delete_profile,
+            set_active_profile,
+            list_workspaces,
+            create_workspace,
+            delete_workspace,
+            switch_workspace,
+            list_plugins,
+            list_shortcuts,
+            list_vpn_services,
+            list_alarm_sounds,
+            get_remote_api_settings,
+            set_remote_api_settings,
+            create_api_token,
+            list_api_tokens,
+            revoke_api_token,
+            generate_tls_certificate,
+            discover_fleet_peers,
+            push_timer,
+            get_managed_config,
+            get_policy,
+            get_read_only,
+            get_sync_settings,
+            set_sync_settings,
+            list_sync_peers,
+            pair_sync_peer,
+            unpair_sync_peer,
+            sync_now,
+            list_release_versions,
+            check_channel_update,
+            install_channel_update,
+            install_release,
+            clear_update_cache,
+            get_install_update_on_quit,
+            set_install_update_on_quit,
+            get_stay_awake_minutes,
+            set_stay_awake_minutes,
+            get_conflict_policy,
+            set_conflict_policy,
+            get_helper_status,
+            install_helper,
+            uninstall_helper
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                install_pending_update(app);
+            }
+        });
+}
\ No newline at end of file
@@ -0,0 +1,69 @@
+use std::{env, path::Path};
+
+use semver::Version;
+use serde::Serialize;
+
+use crate::{channel_name, UpdateChannel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallSource {
+    /// Installed with `brew install --cask`.
+    Homebrew,
+    /// Copied out of a release DMG (or anything else in an .app bundle).
+    Dmg,
+    /// Still running from the mounted DMG; updates can't replace it.
+    MountedImage,
+    /// `cargo tauri dev` or another unbundled build.
+    Development,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppInfo {
+    pub version: String,
+    pub build_hash: &'static str,
+    /// Inferred from the running version: prereleases come from `dev`.
+    pub update_channel: &'static str,
+    pub architecture: &'static str,
+    pub storage_dir: String,
+    pub install_source: InstallSource,
+}
+
+pub fn collect(version: &str, storage_dir: &Path) -> AppInfo {
+    let channel = match Version::parse(version) {
+        Ok(parsed) if !parsed.pre.is_empty() => UpdateChannel::Dev,
+        _ => UpdateChannel::Main,
+    };
+
+    AppInfo {
+        version: version.to_string(),
+        build_hash: env!("LOCKPILOT_BUILD_HASH"),
+        update_channel: channel_name(&channel),
+        architecture: env::consts::ARCH,
+        storage_dir: storage_dir.display().to_string(),
+        install_source: install_source(),
+    }
+}
+
+fn install_source() -> InstallSource {
+    let Ok(exe) = env::current_exe() else {
+        return InstallSource::Development;
+    };
+    let exe = exe.to_string_lossy();
+    if !exe.contains(".app/Contents/MacOS/") {
+        return InstallSource::Development;
+    }
+    if exe.starts_with("/Volumes/") {
+        return InstallSource::MountedImage;
+    }
+
+    let cask_installed = ["/opt/homebrew/Caskroom/lockpilot", "/usr/local/Caskroom/lockpilot"]
+        .iter()
+        .any(|caskroom| Path::new(caskroom).exists());
+    if cask_installed {
+        InstallSource::Homebrew
+    } else {
+        InstallSource::Dmg
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Where a mutation came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AuditOrigin {
+    Ui,
+    Cli,
+    Remote { address: String },
+    DeepLink,
+    Notification,
+    /// Changes LockPilot makes on its own, such as expiring timers.
+    System,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditOperation {
+    Create,
+    Update,
+    Cancel,
+    Pause,
+    Resume,
+    SwitchProfile,
+    SwitchWorkspace,
+    Expire,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    id: String,
+    timestamp: DateTime<Utc>,
+    origin: AuditOrigin,
+    operation: AuditOperation,
+    timer_id: Option<String>,
+    summary: String,
+}
+
+/// Append-only JSON Lines log. Entries are never rewritten, so it is kept
+/// out of the retention and cleanup paths that touch history.
+#[derive(Clone)]
+pub struct AuditLog {
+    path: Arc<PathBuf>,
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path: Arc::new(path),
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn record(
+        &self,
+        origin: &AuditOrigin,
+        operation: AuditOperation,
+        timer_id: Option<&str>,
+        summary: impl Into<String>,
+    ) -> Result<(), String> {
+        let entry = AuditEntry {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            origin: origin.clone(),
+            operation,
+            timer_id: timer_id.map(str::to_string),
+            summary: summary.into(),
+        };
+        let mut line =
+            serde_json::to_string(&entry).map_err(|err| format!("Failed to encode audit entry: {err}"))?;
+        line.push('\n');
+
+        let _guard = self
+            .write_lock
+            .lock()
+            .map_err(|_| "Failed to lock audit log".to_string())?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("Failed to create audit log directory: {err}"))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path.as_ref())
+            .map_err(|err| format!("Failed to open audit log: {err}"))?;
+        file.write_all(line.as_bytes())
+            .map_err(|err| format!("Failed to write audit log: {err}"))
+    }
+
+    /// Newest entries first. Lines that fail to parse are skipped rather
+    /// than hiding the rest of the log.
+    #[cfg(feature = "app")]
+    pub fn read(&self, limit: Option<usize>) -> Result<Vec<AuditEntry>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let raw = fs::read_to_string(self.path.as_ref()).map_err(|err| format!("Failed to read audit log: {err}"))?;
+        let entries = raw
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok());
+        Ok(match limit {
+            Some(limit) => entries.take(limit).collect(),
+            None => entries.collect(),
+        })
+    }
+}
@@ -0,0 +1,41 @@
+use std::{path::Path, process::Command};
+
+use crate::exec;
+
+const AUTOMATOR: &str = "/usr/bin/automator";
+/// Longest workflow output kept in a history entry.
+const MAX_OUTPUT_CHARS: usize = 500;
+
+/// Fails unless `path` is an absolute path to a `.workflow` bundle.
+pub fn validate(path: &str) -> Result<(), String> {
+    let workflow = Path::new(path);
+    if !workflow.is_absolute() {
+        return Err("Workflow path must be absolute".to_string());
+    }
+    if !workflow.extension().is_some_and(|extension| extension == "workflow") {
+        return Err("Workflow path must end in .workflow".to_string());
+    }
+    if !workflow.join("Contents/document.wflow").is_file() {
+        return Err(format!("No Automator workflow at {path}"));
+    }
+    Ok(())
+}
+
+/// Runs the workflow and returns what it printed, shortened, if anything.
+pub fn run(path: &str) -> Result<Option<String>, String> {
+    validate(path)?;
+    let output = exec::output(Command::new(AUTOMATOR).arg(path))
+        .map_err(|err| format!("Failed to run automator: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Workflow failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let text: String = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .chars()
+        .take(MAX_OUTPUT_CHARS)
+        .collect();
+    Ok(Some(text).filter(|text| !text.is_empty()))
+}
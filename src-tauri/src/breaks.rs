@@ -0,0 +1,247 @@
+#[cfg(feature = "app")]
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "app")]
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "app")]
+use crate::{notify_user, system, TimerStore};
+
+/// Short enough to notice a 20-second break.
+#[cfg(feature = "app")]
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// How long after a nudge, on top of the break itself, a break still
+/// counts as taken rather than skipped.
+#[cfg(feature = "app")]
+const GRACE_SECONDS: i64 = 2 * 60;
+/// Days of break stats kept.
+#[cfg(feature = "app")]
+const MAX_DAYS: usize = 400;
+#[cfg(feature = "app")]
+pub const MAX_WORK_MINUTES: u32 = 4 * 60;
+#[cfg(feature = "app")]
+pub const MAX_BREAK_SECONDS: u32 = 60 * 60;
+
+/// "After this much continuous use, nudge me to take a break this long."
+/// 20-20-20 is `workMinutes: 20, breakSeconds: 20`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakReminders {
+    pub work_minutes: u32,
+    /// Being idle this long is a break, whether or not a nudge asked for it.
+    pub break_seconds: u32,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Hold nudges back while an app keeps the display awake, as Keynote
+    /// and video calls do while presenting or sharing the screen.
+    #[serde(default = "default_suppress_while_presenting")]
+    pub suppress_while_presenting: bool,
+}
+
+fn default_suppress_while_presenting() -> bool {
+    true
+}
+
+impl BreakReminders {
+    #[cfg(feature = "app")]
+    fn nudge_text(&self) -> String {
+        match self.message.as_deref().map(str::trim).filter(|message| !message.is_empty()) {
+            Some(message) => message.to_string(),
+            None if self.break_seconds < 60 => {
+                format!("Time for a {}-second break: look at something far away", self.break_seconds)
+            }
+            None => format!("Time for a {}-minute break: step away from the screen", self.break_seconds / 60),
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyBreaks {
+    pub day: NaiveDate,
+    pub nudged: u32,
+    /// Nudges followed by a break in time.
+    pub taken: u32,
+    pub skipped: u32,
+    /// Nudges held back because the user was presenting; each held nudge
+    /// counts once, however long it waited.
+    pub postponed: u32,
+}
+
+#[cfg(feature = "app")]
+impl DailyBreaks {
+    fn new(day: NaiveDate) -> Self {
+        Self {
+            day,
+            nudged: 0,
+            taken: 0,
+            skipped: 0,
+            postponed: 0,
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedBreaks {
+    days: Vec<DailyBreaks>,
+}
+
+/// Break counts per local day, oldest first, saved in `breaks.json`.
+#[cfg(feature = "app")]
+#[derive(Clone)]
+pub struct BreakStats {
+    inner: Arc<Mutex<Vec<DailyBreaks>>>,
+    storage_path: Arc<PathBuf>,
+}
+
+#[cfg(feature = "app")]
+impl BreakStats {
+    pub fn new(storage_path: PathBuf) -> Self {
+        let days = fs::read_to_string(&storage_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<PersistedBreaks>(&raw).ok())
+            .map(|persisted| persisted.days)
+            .unwrap_or_default();
+        Self {
+            inner: Arc::new(Mutex::new(days)),
+            storage_path: Arc::new(storage_path),
+        }
+    }
+
+    /// Recorded days from `since` on, oldest first.
+    pub fn days_since(&self, since: Option<NaiveDate>) -> Result<Vec<DailyBreaks>, String> {
+        Ok(self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock break stats".to_string())?
+            .iter()
+            .filter(|breaks| since.map_or(true, |since| breaks.day >= since))
+            .cloned()
+            .collect())
+    }
+
+    /// Applies `apply` to today's counts, starting a new day if needed.
+    fn modify<F>(&self, apply: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut DailyBreaks),
+    {
+        let day = Local::now().date_naive();
+        let mut locked = self.inner.lock().map_err(|_| "Failed to lock break stats".to_string())?;
+        if locked.last().map(|breaks| breaks.day) != Some(day) {
+            locked.push(DailyBreaks::new(day));
+            let excess = locked.len().saturating_sub(MAX_DAYS);
+            locked.drain(..excess);
+        }
+        apply(locked.last_mut().expect("a day was just ensured"));
+        let days = locked.clone();
+        drop(locked);
+
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("Failed to create break stats directory: {err}"))?;
+        }
+        let data = serde_json::to_string_pretty(&PersistedBreaks { days })
+            .map_err(|err| format!("Failed to encode break stats: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data).map_err(|err| format!("Failed to write break stats: {err}"))
+    }
+}
+
+#[cfg(feature = "app")]
+enum Phase {
+    /// Using the Mac without a break since `since`; `held` once a due nudge
+    /// was postponed for a presentation.
+    Working { since: DateTime<Utc>, held: bool },
+    Nudged { at: DateTime<Utc> },
+}
+
+/// Every `SAMPLE_INTERVAL` while break reminders are on: a stretch of
+/// idle time as long as a break resets the work clock; once the clock
+/// reaches `work_minutes` a notification nudges the user, and the next
+/// break counts as taken if it comes within the grace period, or as
+/// skipped. Nudges wait while the user is presenting. The frontend hears
+/// `break-due` and `breaks-changed`.
+#[cfg(feature = "app")]
+pub fn spawn_reminder(stats: BreakStats, store: TimerStore) {
+    thread::spawn(move || {
+        let mut phase = Phase::Working {
+            since: Utc::now(),
+            held: false,
+        };
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+            let now = Utc::now();
+            let fresh = Phase::Working { since: now, held: false };
+            let Some(config) = store.settings.snapshot().ok().and_then(|settings| settings.break_reminders) else {
+                phase = fresh;
+                continue;
+            };
+            let on_break = system::idle_seconds().is_ok_and(|idle| idle >= u64::from(config.break_seconds));
+            let work = ChronoDuration::minutes(config.work_minutes.into());
+            let grace = ChronoDuration::seconds(GRACE_SECONDS + i64::from(config.break_seconds));
+
+            phase = match phase {
+                Phase::Working { .. } if on_break => fresh,
+                Phase::Working { since, held } if now - since < work => Phase::Working { since, held },
+                Phase::Working { since, held } => {
+                    if config.suppress_while_presenting && is_presenting() {
+                        if !held {
+                            record(&stats, &store, &config, Recorded::Postponed);
+                        }
+                        Phase::Working { since, held: true }
+                    } else {
+                        record(&stats, &store, &config, Recorded::Nudged);
+                        Phase::Nudged { at: now }
+                    }
+                }
+                Phase::Nudged { .. } if on_break => {
+                    record(&stats, &store, &config, Recorded::Taken);
+                    fresh
+                }
+                Phase::Nudged { at } if now - at > grace => {
+                    record(&stats, &store, &config, Recorded::Skipped);
+                    fresh
+                }
+                nudged => nudged,
+            };
+        }
+    });
+}
+
+#[cfg(feature = "app")]
+enum Recorded {
+    Nudged,
+    Taken,
+    Skipped,
+    Postponed,
+}
+
+#[cfg(feature = "app")]
+fn record(stats: &BreakStats, store: &TimerStore, config: &BreakReminders, event: Recorded) {
+    if matches!(event, Recorded::Nudged) {
+        notify_user(&config.nudge_text());
+        store.emit("break-due", config.break_seconds);
+    }
+    let result = stats.modify(|day| match event {
+        Recorded::Nudged => day.nudged += 1,
+        Recorded::Taken => day.taken += 1,
+        Recorded::Skipped => day.skipped += 1,
+        Recorded::Postponed => day.postponed += 1,
+    });
+    if let Err(err) = result {
+        eprintln!("Failed to record break stats: {err}");
+    }
+    store.emit("breaks-changed", ());
+}
+
+#[cfg(feature = "app")]
+fn is_presenting() -> bool {
+    system::display_sleep_holders().is_ok_and(|holders| !holders.is_empty())
+}
@@ -0,0 +1,46 @@
+use crate::{action_label, applescript_string, run_osascript, TimerInfo};
+
+const CALENDAR_NAME: &str = "LockPilot";
+/// Calendar hides zero-length events in most views.
+const EVENT_MINUTES: u32 = 5;
+
+/// Creates the LockPilot calendar in Calendar.app if it doesn't exist yet.
+/// The first call triggers the Automation prompt for Calendar.
+#[cfg(feature = "app")]
+pub fn ensure_calendar() -> Result<(), String> {
+    run_osascript(&format!(
+        "tell application \"Calendar\"\n\
+         if not (exists calendar {name}) then make new calendar with properties {{name:{name}}}\n\
+         end tell",
+        name = applescript_string(CALENDAR_NAME),
+    ))
+    .map_err(|err| format!("Failed to create the {CALENDAR_NAME} calendar: {}", err.trim()))
+}
+
+/// Adds an event starting now for a run that just finished, e.g.
+/// "Restart failed", with the history detail as its notes.
+pub fn log_run(info: &TimerInfo, failed: bool, detail: Option<&str>) -> Result<(), String> {
+    let label = action_label(&info.action);
+    let mut title = format!("{}{}", label[..1].to_uppercase(), &label[1..]);
+    if failed {
+        title.push_str(" failed");
+    }
+    let mut notes = format!("Timer {}", info.id);
+    if let Some(detail) = detail {
+        notes.push_str(&format!("\n{detail}"));
+    }
+
+    // `current date` sidesteps parsing a date string in the user's locale.
+    run_osascript(&format!(
+        "set startDate to current date\n\
+         tell application \"Calendar\"\n\
+         if not (exists calendar {name}) then make new calendar with properties {{name:{name}}}\n\
+         tell calendar {name} to make new event with properties \
+         {{summary:{title}, description:{notes}, start date:startDate, end date:startDate + {EVENT_MINUTES} * minutes}}\n\
+         end tell",
+        name = applescript_string(CALENDAR_NAME),
+        title = applescript_string(&title),
+        notes = applescript_string(&notes),
+    ))
+    .map_err(|err| err.trim().to_string())
+}
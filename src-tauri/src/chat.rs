@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use chrono::Local;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{action_label, TimerInfo};
+
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_TEMPLATE: &str = "{timer}: {action} {outcome} at {time}";
+const PLACEHOLDERS: &[&str] = &["timer", "action", "time", "outcome", "detail"];
+/// Discord rejects longer `content`.
+const DISCORD_MAX_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatService {
+    Slack,
+    Discord,
+}
+
+/// Posts to a Slack or Discord incoming webhook when the timer runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatWebhook {
+    pub service: ChatService,
+    /// The channel's incoming-webhook URL. Anyone holding it can post, so
+    /// treat it like a password.
+    pub url: String,
+    /// Text with `{timer}`, `{action}`, `{time}`, `{outcome}` and
+    /// `{detail}` placeholders; defaults to "{timer}: {action} {outcome} at
+    /// {time}".
+    pub template: Option<String>,
+    /// Only post when the action fails.
+    #[serde(default)]
+    pub only_failures: bool,
+}
+
+impl ChatWebhook {
+    pub fn validate(&self) -> Result<(), String> {
+        let url = reqwest::Url::parse(self.url.trim()).map_err(|err| format!("Invalid webhook URL: {err}"))?;
+        if url.scheme() != "https" {
+            return Err("Webhook URL must start with https://".to_string());
+        }
+
+        let template = self.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+        if template.trim().is_empty() {
+            return Err("Message template must not be empty".to_string());
+        }
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 1..start + end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "Unknown placeholder {{{name}}}; use {}",
+                    PLACEHOLDERS
+                        .iter()
+                        .map(|name| format!("{{{name}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            rest = &rest[start + end + 1..];
+        }
+        Ok(())
+    }
+}
+
+/// Fills in the template for a run that just finished and posts it.
+pub fn notify(webhook: &ChatWebhook, info: &TimerInfo, failed: bool, detail: Option<&str>) -> Result<(), String> {
+    let text = webhook
+        .template
+        .as_deref()
+        .unwrap_or(DEFAULT_TEMPLATE)
+        .replace("{timer}", &timer_name(info))
+        .replace("{action}", action_label(&info.action))
+        .replace("{time}", &Local::now().format("%Y-%m-%d %H:%M").to_string())
+        .replace("{outcome}", if failed { "failed" } else { "ran" })
+        .replace("{detail}", detail.unwrap_or_default());
+    post(webhook, &text)
+}
+
+/// The first line of the notes, else the message, else the id.
+fn timer_name(info: &TimerInfo) -> String {
+    [info.notes.as_deref(), info.message.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|text| text.lines().next())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or(&info.id)
+        .to_string()
+}
+
+/// Slack takes `text`; Discord takes `content`.
+fn post(webhook: &ChatWebhook, text: &str) -> Result<(), String> {
+    let payload = match webhook.service {
+        ChatService::Slack => serde_json::json!({ "text": text }),
+        ChatService::Discord => {
+            let content: String = text.chars().take(DISCORD_MAX_CHARS).collect();
+            serde_json::json!({ "content": content })
+        }
+    };
+    let response = Client::builder()
+        .user_agent("LockPilot-Chat")
+        .timeout(POST_TIMEOUT)
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?
+        .post(webhook.url.trim())
+        .json(&payload)
+        .send()
+        .map_err(|err| format!("Failed to reach webhook: {err}"))?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Webhook returned status {}", response.status()))
+    }
+}
@@ -0,0 +1,113 @@
+use std::{env, path::PathBuf};
+
+use chrono::{DateTime, Duration as ChronoDuration, Local, Utc};
+
+use crate::{
+    action_label, parse_target_time,
+    quick_entry::{self, DiagnosticSeverity},
+    AuditOrigin, TimerStore,
+};
+
+/// As in `tauri.conf.json`; Tauri keeps app data in a folder named after it.
+const IDENTIFIER: &str = "com.maks.lockpilot";
+const USAGE: &str = "Usage: lockpilot --pause <minutes | RFC 3339 time> | --resume | --create <text>";
+
+/// Handles `lockpilot --pause <minutes | time>`, `lockpilot --resume` and
+/// `lockpilot --create "lock at 10pm every weekday"`, e.g. from a script
+/// or `/Applications/LockPilot.app/Contents/MacOS/lockpilot`, and returns
+/// the exit code. `None` for any other launch, which starts the app. A
+/// running LockPilot picks a pause up from `settings.json` and a created
+/// timer from `INCOMING_DIR`.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (flag, value) = (args.first()?.as_str(), args.get(1).map(String::as_str));
+    let until = match (flag, value) {
+        ("--pause", Some(value)) => Some(pause_end(value, Utc::now())),
+        ("--pause", None) | ("--create", None) => Some(Err(USAGE.to_string())),
+        // The phrase may be quoted or not.
+        ("--create", Some(_)) => return Some(create(&args[1..].join(" "))),
+        ("--resume", _) => None,
+        _ => return None,
+    };
+    let result = until
+        .transpose()
+        .and_then(|until| open_store()?.set_pause(until, &AuditOrigin::Cli));
+    match result {
+        Ok(state) => {
+            match state.paused_until {
+                Some(until) => println!("Paused all timers until {}", until.with_timezone(&Local).format("%c")),
+                None => println!("Resumed all timers"),
+            }
+            Some(0)
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            Some(1)
+        }
+    }
+}
+
+/// Parses `text` as quick entry does and queues the timer for the app.
+fn create(text: &str) -> i32 {
+    let (request, diagnostics) = quick_entry::parse(text, Local::now());
+    for diagnostic in &diagnostics {
+        let severity = match diagnostic.severity {
+            DiagnosticSeverity::Error => "error",
+            DiagnosticSeverity::Warning => "warning",
+        };
+        match &diagnostic.fragment {
+            Some(fragment) => eprintln!("{severity}: {} ({fragment})", diagnostic.message),
+            None => eprintln!("{severity}: {}", diagnostic.message),
+        }
+    }
+    let Some(request) = request else {
+        return 1;
+    };
+    match open_store().and_then(|store| store.queue_create(&request)) {
+        Ok(()) => {
+            let at = parse_target_time(&request.target_time).map_or_else(
+                |_| request.target_time.clone(),
+                |(at, _)| at.with_timezone(&Local).format("%c").to_string(),
+            );
+            println!(
+                "Queued {} timer for {at}; LockPilot creates it within seconds, or when it next starts",
+                action_label(&request.action)
+            );
+            0
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+fn open_store() -> Result<TimerStore, String> {
+    let data_dir = data_dir().ok_or_else(|| "HOME is not set".to_string())?;
+    let store = TimerStore::open(&data_dir);
+    // Only `readOnly`; a running app launched with `--read-only` ignores
+    // the change instead.
+    if store.settings.snapshot()?.read_only {
+        return Err("LockPilot is read-only on this Mac".to_string());
+    }
+    Ok(store)
+}
+
+/// A number of minutes from `now`, or an RFC 3339 time after it.
+fn pause_end(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let until = match value.parse::<u32>() {
+        Ok(minutes) => now + ChronoDuration::minutes(minutes.into()),
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .map(|until| until.with_timezone(&Utc))
+            .map_err(|_| format!("Expected minutes or an RFC 3339 time, got {value}"))?,
+    };
+    if until <= now {
+        return Err("Pause end must be in the future".to_string());
+    }
+    Ok(until)
+}
+
+fn data_dir() -> Option<PathBuf> {
+    let home = env::var_os("HOME")?;
+    Some(PathBuf::from(home).join("Library/Application Support").join(IDENTIFIER))
+}
+
@@ -0,0 +1,59 @@
+use chrono::{Datelike, Local, Timelike};
+use rhai::{Engine, EvalAltResult, INT};
+
+use crate::system;
+
+const MAX_OPERATIONS: u64 = 50_000;
+const MAX_EXPRESSION_DEPTH: usize = 32;
+
+/// Builds a sandboxed engine exposing read-only system facts:
+///
+/// - `idle_seconds()` — seconds since the last input
+/// - `battery_percent()` — charge level, or -1 without a battery
+/// - `on_ac_power()` / `is_charging()`
+/// - `is_app_running(name)` — exact process name match
+/// - `hour()`, `minute()`, `weekday()` — local time, Monday = 1
+fn build_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_expr_depths(MAX_EXPRESSION_DEPTH, MAX_EXPRESSION_DEPTH);
+    engine.disable_symbol("eval");
+
+    engine.register_fn("idle_seconds", || -> Result<INT, Box<EvalAltResult>> {
+        Ok(system::idle_seconds()? as INT)
+    });
+    engine.register_fn("battery_percent", || -> Result<INT, Box<EvalAltResult>> {
+        Ok(system::power_status()?
+            .battery_percent
+            .map(INT::from)
+            .unwrap_or(-1))
+    });
+    engine.register_fn("on_ac_power", || -> Result<bool, Box<EvalAltResult>> {
+        Ok(system::power_status()?.on_ac_power)
+    });
+    engine.register_fn("is_charging", || -> Result<bool, Box<EvalAltResult>> {
+        Ok(system::power_status()?.charging)
+    });
+    engine.register_fn("is_app_running", |name: &str| system::is_process_running(name));
+    engine.register_fn("hour", || Local::now().hour() as INT);
+    engine.register_fn("minute", || Local::now().minute() as INT);
+    engine.register_fn("weekday", || Local::now().weekday().number_from_monday() as INT);
+
+    engine
+}
+
+/// Checks that a condition parses, without evaluating it.
+pub fn validate(expression: &str) -> Result<(), String> {
+    build_engine()
+        .compile_expression(expression)
+        .map(|_| ())
+        .map_err(|err| format!("Invalid condition: {err}"))
+}
+
+/// Evaluates a condition at fire time. Anything other than a boolean result
+/// is an error rather than being coerced.
+pub fn evaluate(expression: &str) -> Result<bool, String> {
+    build_engine()
+        .eval_expression::<bool>(expression)
+        .map_err(|err| format!("Condition failed: {err}"))
+}
@@ -0,0 +1,197 @@
+use std::{
+    backtrace::Backtrace,
+    cmp::Reverse,
+    fs, panic,
+    path::PathBuf,
+    sync::{atomic::Ordering, Arc},
+    thread,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{TimerStore, GITHUB_OWNER, GITHUB_REPO};
+
+/// GitHub rejects very long new-issue URLs; the backtrace is cut to fit.
+const MAX_ISSUE_BODY_CHARS: usize = 6000;
+
+/// What the scheduler looked like when the panic happened. Missing if the
+/// timer store was locked at the time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerSnapshot {
+    pub timers: usize,
+    pub scheduled: usize,
+    /// Occurrences being fired; older reports counted scheduler threads.
+    #[serde(alias = "liveThreads")]
+    pub firing: usize,
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub version: String,
+    pub thread: Option<String>,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub scheduler: Option<SchedulerSnapshot>,
+}
+
+/// Crash reports saved as one JSON file each under the app data dir.
+#[derive(Clone)]
+pub struct CrashReports {
+    dir: Arc<PathBuf>,
+}
+
+impl CrashReports {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir: Arc::new(dir) }
+    }
+
+    /// Writes a report for every panic, on any thread, then hands over to
+    /// the default hook so the message still reaches stderr. Nothing is
+    /// sent anywhere until the user chooses to.
+    pub fn install_panic_hook(&self, version: String, store: TimerStore) {
+        let reports = self.clone();
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Unknown panic".to_string());
+            let location = info
+                .location()
+                .map(|location| format!("{}:{}", location.file(), location.line()));
+            let report = build_report(message, location, &version, &store);
+            if let Err(err) = reports.save(&report) {
+                eprintln!("Failed to write crash report: {err}");
+            }
+            previous(info);
+        }));
+    }
+
+    fn save(&self, report: &CrashReport) -> Result<(), String> {
+        fs::create_dir_all(self.dir.as_ref())
+            .map_err(|err| format!("Failed to create crash report directory: {err}"))?;
+        let data = serde_json::to_string_pretty(report)
+            .map_err(|err| format!("Failed to encode crash report: {err}"))?;
+        fs::write(self.path(&report.id), data).map_err(|err| format!("Failed to write crash report: {err}"))
+    }
+
+    /// Newest first. Unreadable files are skipped.
+    pub fn list(&self) -> Result<Vec<CrashReport>, String> {
+        let entries = match fs::read_dir(self.dir.as_ref()) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("Failed to read crash reports: {err}")),
+        };
+
+        let mut reports: Vec<CrashReport> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+            .filter_map(|raw| serde_json::from_str(&raw).ok())
+            .collect();
+        reports.sort_by_key(|report| Reverse(report.timestamp));
+        Ok(reports)
+    }
+
+    pub fn get(&self, id: &str) -> Result<CrashReport, String> {
+        self.list()?
+            .into_iter()
+            .find(|report| report.id == id)
+            .ok_or_else(|| format!("Crash report not found: {id}"))
+    }
+
+    pub fn delete(&self, id: &str) -> Result<bool, String> {
+        // Ids are UUIDs we generated; anything else can't name a report.
+        if Uuid::parse_str(id).is_err() {
+            return Ok(false);
+        }
+        match fs::remove_file(self.path(id)) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(format!("Failed to delete crash report: {err}")),
+        }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+}
+
+/// A prefilled "new issue" link on the project's GitHub repo. The user
+/// reviews the text in the browser before anything is submitted.
+pub fn issue_url(report: &CrashReport) -> Result<Url, String> {
+    let scheduler = match &report.scheduler {
+        Some(snapshot) => format!(
+            "{} timers, {} scheduled, {} firing, next run {}",
+            snapshot.timers,
+            snapshot.scheduled,
+            snapshot.firing,
+            snapshot
+                .next_run
+                .map(|next| next.to_rfc3339())
+                .unwrap_or_else(|| "none".to_string())
+        ),
+        None => "unavailable".to_string(),
+    };
+    let mut body = format!(
+        "**Version:** {}\n**Time:** {}\n**Thread:** {}\n**Location:** {}\n**Scheduler:** {}\n\n**Panic:** {}\n\n```\n{}\n```\n",
+        report.version,
+        report.timestamp.to_rfc3339(),
+        report.thread.as_deref().unwrap_or("unnamed"),
+        report.location.as_deref().unwrap_or("unknown"),
+        scheduler,
+        report.message,
+        report.backtrace,
+    );
+    if body.chars().count() > MAX_ISSUE_BODY_CHARS {
+        body = body.chars().take(MAX_ISSUE_BODY_CHARS).collect::<String>() + "\n```\n(truncated)";
+    }
+
+    Url::parse_with_params(
+        &format!("https://github.com/{GITHUB_OWNER}/{GITHUB_REPO}/issues/new"),
+        &[
+            ("title", format!("Crash: {}", report.message.lines().next().unwrap_or_default())),
+            ("body", body),
+        ],
+    )
+    .map_err(|err| format!("Failed to build issue link: {err}"))
+}
+
+fn build_report(message: String, location: Option<String>, version: &str, store: &TimerStore) -> CrashReport {
+    CrashReport {
+        id: Uuid::new_v4().to_string(),
+        timestamp: Utc::now(),
+        version: version.to_string(),
+        thread: thread::current().name().map(str::to_string),
+        message,
+        location,
+        backtrace: Backtrace::force_capture().to_string(),
+        scheduler: scheduler_snapshot(store),
+    }
+}
+
+/// Uses `try_lock`: the panicking thread may be the one holding the lock.
+fn scheduler_snapshot(store: &TimerStore) -> Option<SchedulerSnapshot> {
+    let timers = store.inner.try_lock().ok()?;
+    Some(SchedulerSnapshot {
+        timers: timers.len(),
+        scheduled: timers.values().filter(|entry| entry.cancel_tx.is_some()).count(),
+        firing: store.firing.load(Ordering::SeqCst),
+        next_run: timers
+            .values()
+            .filter(|entry| entry.cancel_tx.is_some())
+            .map(|entry| entry.info.target_time)
+            .min(),
+    })
+}
@@ -0,0 +1,367 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, Timelike, Utc, Weekday};
+use serde::Serialize;
+
+use crate::{local_at, CreateTimerRequest, RecurrenceConfig, RecurrencePreset, TimerAction};
+
+/// Trailing comment that names the action explicitly, e.g.
+/// `0 18 * * 1-5 /usr/bin/true # lockpilot: popup Time to stop`.
+const ACTION_MARKER: &str = "lockpilot:";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedCrontabLine {
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+/// A crontab line that maps onto one of LockPilot's recurrence presets.
+pub struct ParsedCrontabLine {
+    pub line: usize,
+    pub request: CreateTimerRequest,
+}
+
+/// Parses a pasted crontab. Blank lines, comments and environment
+/// assignments are ignored; lines that can't be expressed as a LockPilot
+/// timer are reported rather than failing the whole import.
+pub fn parse(text: &str) -> (Vec<ParsedCrontabLine>, Vec<SkippedCrontabLine>) {
+    let mut parsed = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || is_env_assignment(trimmed) {
+            continue;
+        }
+
+        match parse_line(trimmed) {
+            Ok(request) => parsed.push(ParsedCrontabLine {
+                line: index + 1,
+                request,
+            }),
+            Err(reason) => skipped.push(SkippedCrontabLine {
+                line: index + 1,
+                text: trimmed.to_string(),
+                reason,
+            }),
+        }
+    }
+
+    (parsed, skipped)
+}
+
+fn is_env_assignment(line: &str) -> bool {
+    line.split_once('=')
+        .map(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+        .unwrap_or(false)
+}
+
+fn parse_line(line: &str) -> Result<CreateTimerRequest, String> {
+    let (entry, spec) = split_action_marker(line);
+    let (fields, command) = split_schedule(entry)?;
+    let (action, message) = match spec {
+        Some(spec) => parse_action_spec(spec)?,
+        None => (infer_action(command)?, None),
+    };
+    let (recurrence, first_run) = schedule_from_fields(&fields, Local::now())?;
+
+    Ok(CreateTimerRequest {
+        action,
+        target_time: first_run.to_rfc3339(),
+        recurrence: Some(recurrence),
+        message,
+        notes: None,
+        shutdown_options: None,
+        hooks: None,
+        condition: None,
+        trigger: None,
+        profile: None,
+        expires_at: None,
+        max_runs: None,
+        jitter_minutes: None,
+        warning_minutes: None,
+        confirmation: None,
+        escalation: None,
+        repeat_until_acknowledged: None,
+        working_hours: None,
+        ignore_working_hours: false,
+        chat_webhook: None,
+        must_run: false,
+        pause_media: false,
+        eject_disks: None,
+        tags: Vec::new(),
+        source: None,
+        managed: None,
+    })
+}
+
+/// Splits off a trailing `# lockpilot: <action>` comment. Any other `#`
+/// belongs to the command, as cron only treats `#` as a comment at the
+/// start of a line.
+fn split_action_marker(line: &str) -> (&str, Option<&str>) {
+    for (index, _) in line.rmatch_indices('#') {
+        if let Some(spec) = line[index + 1..].trim_start().strip_prefix(ACTION_MARKER) {
+            return (line[..index].trim(), Some(spec.trim()));
+        }
+    }
+    (line, None)
+}
+
+/// Splits off the five schedule fields, expanding `@daily`-style macros.
+fn split_schedule(entry: &str) -> Result<([String; 5], &str), String> {
+    if let Some(rest) = entry.strip_prefix('@') {
+        let (name, command) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let fields = match name {
+            "daily" | "midnight" => ["0", "0", "*", "*", "*"],
+            "hourly" => ["0", "*", "*", "*", "*"],
+            _ => return Err(format!("Unsupported schedule @{name}")),
+        };
+        return Ok((fields.map(str::to_string), command.trim()));
+    }
+
+    let mut fields: [String; 5] = Default::default();
+    let mut rest = entry;
+    for field in fields.iter_mut() {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+        if end == 0 {
+            return Err("Expected five schedule fields".to_string());
+        }
+        *field = trimmed[..end].to_string();
+        rest = &trimmed[end..];
+    }
+    Ok((fields, rest.trim()))
+}
+
+/// `lock`, `shutdown`, `reboot`/`restart`, `logout`, `displaysleep`, `sleep`,
+/// `popup <message>` or `plugin:<id>`.
+fn parse_action_spec(spec: &str) -> Result<(TimerAction, Option<String>), String> {
+    let (name, rest) = spec.split_once(char::is_whitespace).unwrap_or((spec, ""));
+    if let Some(id) = name.strip_prefix("plugin:").filter(|id| !id.is_empty()) {
+        return Ok((TimerAction::Plugin { id: id.to_string() }, None));
+    }
+
+    let action = match name.to_lowercase().as_str() {
+        "lock" => TimerAction::Lock,
+        "shutdown" => TimerAction::Shutdown,
+        "reboot" | "restart" => TimerAction::Reboot,
+        "logout" => TimerAction::Logout,
+        "displaysleep" => TimerAction::DisplaySleep,
+        "sleep" => TimerAction::SystemSleep,
+        "popup" => {
+            let message = rest.trim();
+            if message.is_empty() {
+                return Err("Popup lines need a message after the action".to_string());
+            }
+            return Ok((TimerAction::Popup, Some(message.to_string())));
+        }
+        _ => return Err(format!("Unknown action {name}")),
+    };
+    Ok((action, None))
+}
+
+/// Recognises the usual lock/shutdown/reboot commands when no
+/// `# lockpilot:` comment is given.
+fn infer_action(command: &str) -> Result<TimerAction, String> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let program = words
+        .iter()
+        .find(|word| **word != "sudo")
+        .map(|word| word.rsplit('/').next().unwrap_or(word))
+        .unwrap_or("");
+
+    match program {
+        "reboot" => Ok(TimerAction::Reboot),
+        "halt" | "poweroff" => Ok(TimerAction::Shutdown),
+        "shutdown" if words.contains(&"-r") => Ok(TimerAction::Reboot),
+        "shutdown" => Ok(TimerAction::Shutdown),
+        "pmset" if words.contains(&"displaysleepnow") => Ok(TimerAction::DisplaySleep),
+        "pmset" if words.contains(&"sleepnow") => Ok(TimerAction::SystemSleep),
+        "CGSession" if words.contains(&"-suspend") => Ok(TimerAction::Lock),
+        _ => Err("Can't tell the action; add a `# lockpilot: <action>` comment".to_string()),
+    }
+}
+
+/// Maps cron fields onto a recurrence preset and its first run. Supported:
+/// `M H * * *` (daily), `M H * * 1-5` (weekdays), `M * * * *` (hourly) and
+/// `*/N * * * *` where N divides 60 (every N minutes). Cron restarts other
+/// steps every hour or day, which no preset can follow.
+fn schedule_from_fields(fields: &[String; 5], now: DateTime<Local>) -> Result<(RecurrenceConfig, DateTime<Utc>), String> {
+    let [minute, hour, day, month, weekday] = fields.each_ref().map(String::as_str);
+    if day != "*" || month != "*" {
+        return Err("Day-of-month and month fields must be *".to_string());
+    }
+
+    if let Some(step) = minute.strip_prefix("*/") {
+        if hour != "*" || weekday != "*" {
+            return Err("Minute steps are only supported with * for hour and weekday".to_string());
+        }
+        let interval = parse_number(step, 1, 59)?;
+        if 60 % interval != 0 {
+            return Err(format!(
+                "*/{interval} minutes restarts every hour in cron; only steps that divide 60 are supported"
+            ));
+        }
+        let start = now.with_second(0).and_then(|time| time.with_nanosecond(0)).unwrap_or(now);
+        let mut next = start + ChronoDuration::minutes(1);
+        while next.minute() % interval != 0 {
+            next += ChronoDuration::minutes(1);
+        }
+        return Ok((interval_config(RecurrencePreset::EveryNMinutes, None, Some(interval)), next.with_timezone(&Utc)));
+    }
+
+    let minute = parse_number(minute, 0, 59)?;
+    if hour.starts_with("*/") {
+        return Err(format!("Hour step {hour} restarts every day in cron, which isn't supported"));
+    }
+    if hour == "*" {
+        if weekday != "*" {
+            return Err("Hourly lines are only supported with * for weekday".to_string());
+        }
+        let mut next = local_at(now.date_naive(), 0, minute)?;
+        while next <= now {
+            next += ChronoDuration::hours(1);
+        }
+        return Ok((interval_config(RecurrencePreset::EveryNHours, Some(1), None), next.with_timezone(&Utc)));
+    }
+
+    let hour = parse_number(hour, 0, 23)?;
+    let weekdays_only = match weekday {
+        "*" => false,
+        "1-5" | "mon-fri" | "MON-FRI" => true,
+        _ => return Err(format!("Unsupported weekday field {weekday}")),
+    };
+
+    let mut date = now.date_naive();
+    loop {
+        let candidate = local_at(date, hour, minute)?;
+        let is_weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        if candidate > now && !(weekdays_only && is_weekend) {
+            let preset = if weekdays_only {
+                RecurrencePreset::Weekdays
+            } else {
+                RecurrencePreset::Daily
+            };
+            return Ok((interval_config(preset, None, None), candidate.with_timezone(&Utc)));
+        }
+        date += ChronoDuration::days(1);
+    }
+}
+
+fn interval_config(preset: RecurrencePreset, interval_hours: Option<u32>, interval_minutes: Option<u32>) -> RecurrenceConfig {
+    RecurrenceConfig {
+        preset,
+        interval_hours,
+        interval_minutes,
+        skip_holidays: false,
+    }
+}
+
+fn parse_number(field: &str, min: u32, max: u32) -> Result<u32, String> {
+    field
+        .parse::<u32>()
+        .ok()
+        .filter(|value| (min..=max).contains(value))
+        .ok_or_else(|| format!("Unsupported field {field} (expected a number from {min} to {max})"))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// A Thursday.
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 15, 10, 7, 0).unwrap()
+    }
+
+    fn schedule(line: &str) -> Result<(RecurrenceConfig, DateTime<Utc>), String> {
+        let (fields, _) = split_schedule(line)?;
+        schedule_from_fields(&fields, now())
+    }
+
+    #[test]
+    fn maps_supported_schedules() {
+        let cases = [
+            ("30 18 * * * true", "Daily", None, None, (15, 18, 30)),
+            ("0 9 * * 1-5 true", "Weekdays", None, None, (16, 9, 0)),
+            ("0 9 * * mon-fri true", "Weekdays", None, None, (16, 9, 0)),
+            ("15 * * * * true", "EveryNHours", Some(1), None, (15, 10, 15)),
+            ("@hourly true", "EveryNHours", Some(1), None, (15, 11, 0)),
+            ("@daily true", "Daily", None, None, (16, 0, 0)),
+            ("*/15 * * * * true", "EveryNMinutes", None, Some(15), (15, 10, 15)),
+            ("*/1 * * * * true", "EveryNMinutes", None, Some(1), (15, 10, 8)),
+            ("*/30 * * * * true", "EveryNMinutes", None, Some(30), (15, 10, 30)),
+        ];
+        for (line, preset, hours, minutes, (day, hour, minute)) in cases {
+            let (config, first) = schedule(line).unwrap_or_else(|err| panic!("{line}: {err}"));
+            assert_eq!(format!("{:?}", config.preset), preset, "{line}");
+            assert_eq!(config.interval_hours, hours, "{line}");
+            assert_eq!(config.interval_minutes, minutes, "{line}");
+            let expected = Local.with_ymd_and_hms(2026, 1, day, hour, minute, 0).unwrap();
+            assert_eq!(first, expected.with_timezone(&Utc), "{line}");
+        }
+    }
+
+    #[test]
+    fn skips_schedules_cron_runs_differently() {
+        let cases = [
+            "*/7 * * * * true",
+            "*/45 * * * * true",
+            "0 */2 * * * true",
+            "0 */5 * * * true",
+            "0 9 1 * * true",
+            "0 9 * 1 * true",
+            "0 9 * * 6 true",
+            "*/15 9 * * * true",
+            "15 * * * 1-5 true",
+            "@reboot true",
+            "60 9 * * * true",
+            "0 9 * *",
+        ];
+        for line in cases {
+            assert!(schedule(line).is_err(), "{line} should be skipped");
+        }
+    }
+
+    #[test]
+    fn reads_actions_from_commands_and_markers() {
+        let cases = [
+            ("0 23 * * * /sbin/shutdown -h now", TimerAction::Shutdown, None),
+            ("0 23 * * * sudo shutdown -r now", TimerAction::Reboot, None),
+            ("0 23 * * * pmset sleepnow", TimerAction::SystemSleep, None),
+            ("0 23 * * * pmset displaysleepnow", TimerAction::DisplaySleep, None),
+            ("0 18 * * 1-5 /usr/bin/true # lockpilot: lock", TimerAction::Lock, None),
+            ("0 18 * * * true #lockpilot: popup Time to stop", TimerAction::Popup, Some("Time to stop")),
+            ("0 18 * * * echo '#1' # lockpilot: logout", TimerAction::Logout, None),
+            ("0 18 * * * true # lockpilot: plugin:backup", TimerAction::Plugin { id: "backup".to_string() }, None),
+        ];
+        for (line, action, message) in cases {
+            let request = parse_line(line).unwrap_or_else(|err| panic!("{line}: {err}"));
+            assert_eq!(request.action, action, "{line}");
+            assert_eq!(request.message.as_deref(), message, "{line}");
+        }
+    }
+
+    #[test]
+    fn keeps_hashes_inside_commands() {
+        assert_eq!(split_action_marker("0 1 * * * reboot # nightly"), ("0 1 * * * reboot # nightly", None));
+        assert_eq!(split_action_marker("0 1 * * * curl host/#frag"), ("0 1 * * * curl host/#frag", None));
+        assert_eq!(
+            split_action_marker("0 1 * * * echo # x # lockpilot: sleep"),
+            ("0 1 * * * echo # x", Some("sleep"))
+        );
+        assert!(parse_line("0 1 * * * reboot # nightly").is_ok_and(|request| request.action == TimerAction::Reboot));
+    }
+
+    #[test]
+    fn ignores_comments_blanks_and_variables() {
+        let text = "# m h dom mon dow command\n\nMAILTO=me@example.com\n0 23 * * * shutdown -h now\n*/7 * * * * true\n";
+        let (parsed, skipped) = parse(text);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].line, 4);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].line, 5);
+    }
+}
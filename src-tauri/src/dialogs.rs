@@ -0,0 +1,115 @@
+use std::{
+    sync::{mpsc, Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use crate::exec;
+
+/// How long the first popup waits for others firing at the same moment
+/// before its dialog opens.
+const MERGE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Puts dialogs on screen one at a time. Popups firing together would
+/// otherwise open overlapping `osascript` dialogs that fight for focus.
+#[derive(Clone, Default)]
+pub struct DialogQueue {
+    pending: Arc<Mutex<Pending>>,
+    /// Held while any dialog is open.
+    screen: Arc<Mutex<()>>,
+}
+
+#[derive(Default)]
+struct Pending {
+    messages: Vec<(String, mpsc::Sender<Result<(), String>>)>,
+    /// A thread is presenting the queued messages.
+    presenting: bool,
+}
+
+impl DialogQueue {
+    /// Shows `message` with `present` and waits until it is dismissed.
+    /// Messages queued while a dialog is open, or within `MERGE_WINDOW` of
+    /// the first, are merged into one dialog, and every caller gets that
+    /// dialog's result. Dialogs are presented on a thread of their own, so
+    /// each caller only waits for the dialog holding its message; each one
+    /// gets the process limit of the caller that started the thread.
+    pub fn show<F>(&self, message: &str, present: F) -> Result<(), String>
+    where
+        F: Fn(&str) -> Result<(), String> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let start_presenter = {
+            let mut pending = lock(&self.pending);
+            pending.messages.push((message.to_string(), tx));
+            !std::mem::replace(&mut pending.presenting, true)
+        };
+
+        if start_presenter {
+            let queue = self.clone();
+            let limit = exec::process_limit();
+            thread::spawn(move || queue.present_pending(present, limit));
+        }
+
+        rx.recv()
+            .unwrap_or_else(|_| Err("Popup was dropped before it was shown".to_string()))
+    }
+
+    fn present_pending(&self, present: impl Fn(&str) -> Result<(), String>, limit: Option<Duration>) {
+        let presenting = Presenting(&self.pending);
+        thread::sleep(MERGE_WINDOW);
+        loop {
+            let batch = {
+                let mut pending = lock(&self.pending);
+                if pending.messages.is_empty() {
+                    pending.presenting = false;
+                    presenting.done();
+                    return;
+                }
+                std::mem::take(&mut pending.messages)
+            };
+            let text = batch
+                .iter()
+                .map(|(message, _)| message.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let result = self.exclusive(|| match limit {
+                Some(limit) => exec::supervised(limit, || present(&text)).and_then(|result| result),
+                None => present(&text),
+            });
+            for (_, tx) in batch {
+                let _ = tx.send(result.clone());
+            }
+        }
+    }
+
+    /// Runs a dialog that can't be merged, e.g. one with its own buttons,
+    /// once no other dialog is on screen.
+    pub fn exclusive<T>(&self, dialog: impl FnOnce() -> T) -> T {
+        let _screen = lock(&self.screen);
+        dialog()
+    }
+}
+
+/// Hands presenting back if `present` panics: the messages still queued
+/// fail rather than wait for a thread that is gone, and the next popup
+/// starts a new one.
+struct Presenting<'a>(&'a Mutex<Pending>);
+
+impl Presenting<'_> {
+    fn done(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Presenting<'_> {
+    fn drop(&mut self) {
+        let mut pending = lock(self.0);
+        pending.messages.clear();
+        pending.presenting = false;
+    }
+}
+
+/// A panic while presenting leaves nothing half-updated worth refusing.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
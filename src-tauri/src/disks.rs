@@ -0,0 +1,84 @@
+use std::process::Command;
+
+use crate::exec;
+
+const DISKUTIL: &str = "/usr/sbin/diskutil";
+const DF: &str = "/bin/df";
+
+/// What `eject_external` managed to eject.
+#[derive(Debug, Default)]
+pub struct EjectReport {
+    pub ejected: Vec<String>,
+    /// Disk and diskutil's reason, usually an app holding a file open.
+    pub busy: Vec<(String, String)>,
+}
+
+impl EjectReport {
+    pub fn busy_summary(&self) -> String {
+        self.busy
+            .iter()
+            .map(|(disk, reason)| format!("{disk} ({reason})"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Ejects every external physical disk, all its volumes at once. Disk
+/// images and network volumes are left alone.
+pub fn eject_external() -> Result<EjectReport, String> {
+    let mut report = EjectReport::default();
+    for disk in external_disks()? {
+        let output = exec::output(Command::new(DISKUTIL).arg("eject").arg(&disk))
+            .map_err(|err| format!("Failed to run diskutil: {err}"))?;
+        if output.status.success() {
+            report.ejected.push(disk);
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let reason = if stderr.trim().is_empty() { stdout } else { stderr };
+            report.busy.push((disk, reason.trim().to_string()));
+        }
+    }
+    Ok(report)
+}
+
+/// `/dev/diskN` for each external physical disk. `diskutil list` starts
+/// each disk's block with e.g. `/dev/disk4 (external, physical):`.
+fn external_disks() -> Result<Vec<String>, String> {
+    let output = exec::output(Command::new(DISKUTIL).args(["list", "external", "physical"]))
+        .map_err(|err| format!("Failed to run diskutil: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list external disks: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with("/dev/disk"))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Bytes free for new files on the volume holding `path`, as `df` counts
+/// them. On APFS that leaves out purgeable space, so it can be less than
+/// Finder shows.
+pub fn free_bytes(path: &str) -> Result<u64, String> {
+    let output = exec::output(Command::new(DF).args(["-Pk", path])).map_err(|err| format!("Failed to run df: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to read free space on {path}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+        .map(|kilobytes| kilobytes * 1024)
+        .ok_or_else(|| format!("Unexpected df output for {path}"))
+}
+
+/// The available column of `df -Pk`, in kilobytes: a header line, then e.g.
+/// `/dev/disk3s1s1  971350180  10485764  412345678  3%  /`.
+fn parse_df_available(text: &str) -> Option<u64> {
+    text.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::system;
+
+const KEYCHAIN_SERVICE: &str = "com.maks.lockpilot.smtp";
+const SEND_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SmtpSecurity {
+    /// Implicit TLS, usually port 465.
+    Tls,
+    /// Upgrades a plain connection, usually port 587.
+    StartTls,
+    /// No encryption; only for relays on the local network.
+    None,
+}
+
+/// The mail server `Email` timers and failure notices go through. The
+/// password is kept in the login keychain, keyed by `username`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    /// Sender, e.g. `LockPilot <lockpilot@example.com>`.
+    pub from: String,
+    /// Comma-separated recipients.
+    pub to: String,
+    /// Also mail `to` whenever any timer's action fails.
+    #[serde(default)]
+    pub notify_failures: bool,
+}
+
+impl SmtpSettings {
+    #[cfg(feature = "app")]
+    pub fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("SMTP host is required".to_string());
+        }
+        if self.port == 0 {
+            return Err("SMTP port must be between 1 and 65535".to_string());
+        }
+        self.from
+            .trim()
+            .parse::<Mailbox>()
+            .map_err(|err| format!("Invalid sender address: {err}"))?;
+        self.recipients()?;
+        Ok(())
+    }
+
+    fn recipients(&self) -> Result<Vec<Mailbox>, String> {
+        let recipients = self
+            .to
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| {
+                address
+                    .parse::<Mailbox>()
+                    .map_err(|err| format!("Invalid recipient {address}: {err}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if recipients.is_empty() {
+            return Err("At least one recipient is required".to_string());
+        }
+        Ok(recipients)
+    }
+
+    fn account(&self) -> Option<&str> {
+        self.username.as_deref().map(str::trim).filter(|username| !username.is_empty())
+    }
+
+    #[cfg(feature = "app")]
+    pub fn has_password(&self) -> bool {
+        self.account()
+            .is_some_and(|account| matches!(system::keychain_password(KEYCHAIN_SERVICE, account), Ok(Some(_))))
+    }
+
+    /// Saves `password` to the keychain, or removes it when empty.
+    #[cfg(feature = "app")]
+    pub fn store_password(&self, password: &str) -> Result<(), String> {
+        let Some(account) = self.account() else {
+            return Err("Set a username before saving a password".to_string());
+        };
+        match password {
+            "" => system::delete_keychain_password(KEYCHAIN_SERVICE, account),
+            password => system::set_keychain_password(KEYCHAIN_SERVICE, account, password),
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn forget_password(&self) -> Result<(), String> {
+        match self.account() {
+            Some(account) => system::delete_keychain_password(KEYCHAIN_SERVICE, account),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Mails `body` to the configured recipients and returns a one-line
+/// delivery note for history.
+pub fn send(settings: &SmtpSettings, subject: &str, body: &str) -> Result<String, String> {
+    let mut message = Message::builder()
+        .from(
+            settings
+                .from
+                .trim()
+                .parse()
+                .map_err(|err| format!("Invalid sender address: {err}"))?,
+        )
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for recipient in settings.recipients()? {
+        message = message.to(recipient);
+    }
+    let message = message
+        .body(body.to_string())
+        .map_err(|err| format!("Failed to build email: {err}"))?;
+
+    let host = settings.host.trim();
+    let builder = match settings.security {
+        SmtpSecurity::Tls => SmtpTransport::relay(host),
+        SmtpSecurity::StartTls => SmtpTransport::starttls_relay(host),
+        SmtpSecurity::None => Ok(SmtpTransport::builder_dangerous(host)),
+    }
+    .map_err(|err| format!("Failed to set up SMTP for {host}: {err}"))?;
+    let mut builder = builder.port(settings.port).timeout(Some(SEND_TIMEOUT));
+    if let Some(account) = settings.account() {
+        let password = system::keychain_password(KEYCHAIN_SERVICE, account)?
+            .ok_or_else(|| format!("No SMTP password for {account} in the keychain"))?;
+        builder = builder.credentials(Credentials::new(account.to_string(), password));
+    }
+
+    builder
+        .build()
+        .send(&message)
+        .map_err(|err| format!("Failed to send email via {host}: {err}"))?;
+    Ok(format!("Emailed {}", settings.to.trim()))
+}
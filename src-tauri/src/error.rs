@@ -0,0 +1,87 @@
+use std::io;
+
+use serde::Serialize;
+
+/// Broad error kinds the frontend can branch on without matching message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCode {
+    Validation,
+    NotFound,
+    Permission,
+    Network,
+    Storage,
+    Internal,
+    /// Forbidden by the organization's policy; retrying won't help.
+    PolicyViolation,
+    /// The app is in read-only mode and the command would change something.
+    ReadOnly,
+}
+
+/// Error returned by every Tauri command, serialized as
+/// `{ "code": "validation", "message": "...", "context": "targetTime" }`.
+/// `message` is English for display; `context` names the field or resource
+/// involved, when there is one.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub context: Option<String>,
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            context: None,
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Validation, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Network, message)
+    }
+
+    pub fn storage(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Storage, message)
+    }
+
+    pub fn policy(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::PolicyViolation, message)
+    }
+
+    /// Classifies a filesystem error, e.g. writing outside the sandbox
+    /// reports `permission` rather than `storage`.
+    pub fn io(action: &str, err: io::Error) -> Self {
+        let code = match err.kind() {
+            io::ErrorKind::PermissionDenied => ErrorCode::Permission,
+            io::ErrorKind::NotFound => ErrorCode::NotFound,
+            _ => ErrorCode::Storage,
+        };
+        Self::new(code, format!("{action}: {err}"))
+    }
+
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+/// Internal helpers still report plain strings; anything not classified at
+/// the command boundary surfaces as `internal`.
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    cell::Cell,
+    io::{self, Read, Write},
+    process::{Command, ExitStatus, Output, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+thread_local! {
+    /// Set by `supervised` while an action runs on this thread.
+    static PROCESS_LIMIT: Cell<Option<Duration>> = const { Cell::new(None) };
+    static TIMED_OUT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with every process it starts through this module limited to
+/// `limit`, so a dialog nobody answers or a stuck command can't hold the
+/// thread forever. A process still running at its limit is killed, and
+/// the whole call is reported as timed out, whatever `f` made of it.
+pub fn supervised<T>(limit: Duration, f: impl FnOnce() -> T) -> Result<T, String> {
+    struct Reset(Option<Duration>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            PROCESS_LIMIT.set(self.0);
+        }
+    }
+
+    let _reset = Reset(PROCESS_LIMIT.replace(Some(limit)));
+    TIMED_OUT.set(false);
+    let result = f();
+    if TIMED_OUT.replace(false) {
+        Err(format!("Timed out after {}s; the stuck process was killed", limit.as_secs()))
+    } else {
+        Ok(result)
+    }
+}
+
+/// The limit `supervised` set for this thread, to carry over to work it
+/// hands to another thread.
+pub fn process_limit() -> Option<Duration> {
+    PROCESS_LIMIT.get()
+}
+
+/// Runs `command` to completion and collects its output. With the
+/// `fake-system` feature nothing is executed; see `fake`.
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    #[cfg(feature = "fake-system")]
+    {
+        Ok(fake::run(command))
+    }
+    #[cfg(not(feature = "fake-system"))]
+    {
+        match PROCESS_LIMIT.get() {
+            Some(limit) => run_with_timeout(command, None, limit).map_err(io::Error::other),
+            None => command.output(),
+        }
+    }
+}
+
+/// Runs `command` to completion; its output is discarded.
+pub fn status(command: &mut Command) -> io::Result<ExitStatus> {
+    output(command).map(|output| output.status)
+}
+
+/// Starts `command` without waiting for it, e.g. `open` handing a URL to
+/// the browser.
+#[cfg(feature = "app")]
+pub fn spawn(command: &mut Command) -> io::Result<()> {
+    #[cfg(feature = "fake-system")]
+    {
+        fake::run(command);
+        Ok(())
+    }
+    #[cfg(not(feature = "fake-system"))]
+    {
+        command.spawn().map(|_| ())
+    }
+}
+
+/// Runs `command` to completion, killing it if it outlives `timeout`.
+pub fn run_with_timeout(command: &mut Command, input: Option<&[u8]>, timeout: Duration) -> Result<Output, String> {
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command
+        .spawn()
+        .map_err(|err| format!("Failed to start process: {err}"))?;
+
+    let stdout_reader = child.stdout.take().map(|mut pipe| {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            buf
+        })
+    });
+    let stderr_reader = child.stderr.take().map(|mut pipe| {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            buf
+        })
+    });
+
+    // Written on its own thread, so a child that doesn't read its input
+    // can't block past the deadline; killing it breaks the pipe. Without
+    // input, stdin is closed at once.
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        let input = input.to_vec();
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                TIMED_OUT.set(true);
+                return Err(format!("Process timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(err) => return Err(format!("Failed to wait for process: {err}")),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout_reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default(),
+        stderr: stderr_reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default(),
+    })
+}
+
+/// A stand-in for the `osascript`, `open` and `pmset` invocations, the
+/// native lock and the privileged helper, so the scheduler can be driven
+/// headless: every call is recorded instead of run, and answered with the
+/// stub registered for its program (success with empty output by default).
+#[cfg(feature = "fake-system")]
+pub mod fake {
+    use std::{
+        os::unix::process::ExitStatusExt,
+        process::{Command, ExitStatus, Output},
+        sync::Mutex,
+        time::Duration,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Invocation {
+        pub program: String,
+        pub args: Vec<String>,
+        /// What `supervised` would have let the process run for.
+        pub limit: Option<Duration>,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct Stub {
+        pub failed: bool,
+        pub stdout: String,
+        pub stderr: String,
+    }
+
+    /// What the native lock is recorded as.
+    pub const NATIVE_LOCK: &str = "SACLockScreenImmediate";
+    /// What requests to the privileged helper are recorded as, with the
+    /// request line as the only argument. The helper counts as installed
+    /// once this is stubbed.
+    pub const HELPER: &str = "com.maks.lockpilot.helper";
+
+    static INVOCATIONS: Mutex<Vec<Invocation>> = Mutex::new(Vec::new());
+    static STUBS: Mutex<Vec<(String, Stub)>> = Mutex::new(Vec::new());
+
+    /// Answers later invocations of `program` (the full path, e.g.
+    /// `/usr/bin/osascript`, or `NATIVE_LOCK` or `HELPER`) with `stub`.
+    pub fn stub(program: &str, stub: Stub) {
+        let mut stubs = STUBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        stubs.retain(|(existing, _)| existing != program);
+        stubs.push((program.to_string(), stub));
+    }
+
+    /// Everything run so far, oldest first.
+    pub fn invocations() -> Vec<Invocation> {
+        INVOCATIONS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Forgets recorded invocations and stubs.
+    pub fn reset() {
+        INVOCATIONS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+        STUBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+    }
+
+    pub(crate) fn stubbed(program: &str) -> bool {
+        STUBS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .any(|(existing, _)| existing == program)
+    }
+
+    /// Records a call that starts no process, such as the native lock or a
+    /// request to the privileged helper, as if `program` had been run:
+    /// `Err` with the stub's stderr when it is stubbed to fail.
+    pub(crate) fn call(program: &str, args: &[&str]) -> Result<String, String> {
+        let stub = record(program.to_string(), args.iter().map(|arg| arg.to_string()).collect());
+        if stub.failed {
+            Err(stub.stderr)
+        } else {
+            Ok(stub.stdout)
+        }
+    }
+
+    pub(super) fn run(command: &Command) -> Output {
+        let stub = record(
+            command.get_program().to_string_lossy().into_owned(),
+            command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+        );
+        Output {
+            // Raw wait status: the exit code sits in the second byte.
+            status: ExitStatus::from_raw(if stub.failed { 1 << 8 } else { 0 }),
+            stdout: stub.stdout.into_bytes(),
+            stderr: stub.stderr.into_bytes(),
+        }
+    }
+
+    fn record(program: String, args: Vec<String>) -> Stub {
+        let stub = STUBS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .find(|(existing, _)| *existing == program)
+            .map(|(_, stub)| stub.clone())
+            .unwrap_or_default();
+        INVOCATIONS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(Invocation {
+                program,
+                args,
+                limit: super::process_limit(),
+            });
+        stub
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    process::{Child, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use reqwest::{
+    blocking::{Client, ClientBuilder},
+    tls::{Certificate, TlsInfo},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{remote_api, system, CreateTimerRequest};
+
+const DNS_SD: &str = "/usr/bin/dns-sd";
+/// The Bonjour service type every LockPilot remote API is advertised as.
+const SERVICE_TYPE: &str = "_lockpilot._tcp";
+/// How long `discover` listens for advertisements, and then for each
+/// instance's address.
+const BROWSE_TIME: Duration = Duration::from_secs(3);
+const RESOLVE_TIME: Duration = Duration::from_secs(2);
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+const KEYCHAIN_SERVICE: &str = "com.maks.lockpilot.fleet";
+
+/// Keeps this Mac's remote API advertised over Bonjour for as long as it
+/// is held; dropping it withdraws the advertisement.
+pub struct Advertisement(Child);
+
+impl Advertisement {
+    /// Registers `name` (renamed by mDNSResponder on a clash) on `port`.
+    /// The TXT record says whether the API speaks HTTPS.
+    pub fn start(name: &str, port: u16, tls: bool) -> Result<Self, String> {
+        Command::new(DNS_SD)
+            .args(["-R", name, SERVICE_TYPE, "local", &port.to_string()])
+            .arg(format!("tls={}", u8::from(tls)))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(Self)
+            .map_err(|err| format!("Failed to run dns-sd: {err}"))
+    }
+}
+
+impl Drop for Advertisement {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Another LockPilot found on the local network.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetPeer {
+    /// The advertised instance name, e.g. "LockPilot on Kids-iMac".
+    pub name: String,
+    /// Bonjour host name, e.g. "Kids-iMac.local".
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    /// Whether a token for this peer is saved in the keychain.
+    pub has_token: bool,
+    /// This Mac's own advertisement.
+    pub this_mac: bool,
+}
+
+/// Where `push_timer` sends a timer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetTarget {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+}
+
+impl FleetTarget {
+    fn account(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+        format!("{scheme}://{}:{}{path}", self.host, self.port)
+    }
+}
+
+/// What a peer needs to accept a timer: a `control` token created on that
+/// Mac and, for HTTPS, the fingerprint of its certificate, as shown by
+/// Generate Certificate there. Kept together in the login keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerCredentials {
+    pub token: String,
+    #[serde(default)]
+    pub fingerprint_sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushedTimer {
+    pub host: String,
+    /// The timer's id on the peer.
+    pub timer_id: String,
+}
+
+/// Browses for other LockPilot instances for `BROWSE_TIME`, then looks up
+/// each one's address. Instances that can't be resolved are left out.
+pub fn discover(own_host: Option<&str>) -> Result<Vec<FleetPeer>, String> {
+    let browse = listen(Command::new(DNS_SD).args(["-B", SERVICE_TYPE, "local"]), BROWSE_TIME)?;
+    let names = parse_browse(&browse);
+    let peers = thread::scope(|scope| {
+        let lookups: Vec<_> = names
+            .iter()
+            .map(|name| {
+                scope.spawn(move || {
+                    let output = listen(Command::new(DNS_SD).args(["-L", name, SERVICE_TYPE, "local"]), RESOLVE_TIME);
+                    output.ok().and_then(|output| parse_lookup(name, &output))
+                })
+            })
+            .collect();
+        lookups
+            .into_iter()
+            .filter_map(|lookup| lookup.join().ok().flatten())
+            .collect::<Vec<FleetPeer>>()
+    });
+    Ok(peers
+        .into_iter()
+        .map(|mut peer| {
+            peer.this_mac = own_host.is_some_and(|own| peer.host.eq_ignore_ascii_case(&format!("{own}.local")));
+            peer.has_token = matches!(
+                system::keychain_password(KEYCHAIN_SERVICE, &format!("{}:{}", peer.host, peer.port)),
+                Ok(Some(_))
+            );
+            peer
+        })
+        .collect())
+}
+
+/// Runs `command` for `duration` and returns what it printed. dns-sd never
+/// exits on its own; it flushes each result as it arrives.
+fn listen(command: &mut Command, duration: Duration) -> Result<String, String> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Failed to run dns-sd: {err}"))?;
+    thread::sleep(duration);
+    let _ = child.kill();
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to read dns-sd output: {err}"))?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Instance names from `dns-sd -B`, e.g.
+/// `12:00:00.123  Add        3   4 local.   _lockpilot._tcp.   LockPilot on Kids-iMac`.
+/// An instance seen on several interfaces is listed once.
+fn parse_browse(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let marker = format!("{SERVICE_TYPE}.");
+    for line in text.lines() {
+        let Some((before, name)) = line.split_once(&marker) else {
+            continue;
+        };
+        let name = name.trim().to_string();
+        match before.split_whitespace().nth(1) {
+            Some("Add") if !name.is_empty() && !names.contains(&name) => names.push(name),
+            Some("Rmv") => names.retain(|existing| existing != &name),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Parses `dns-sd -L`, e.g.
+/// `... can be reached at Kids-iMac.local.:47800 (interface 4)` followed by
+/// the TXT record, ` tls=1`.
+fn parse_lookup(name: &str, text: &str) -> Option<FleetPeer> {
+    let (_, reached) = text.split_once("can be reached at ")?;
+    let address = reached.split_whitespace().next()?;
+    let (host, port) = address.rsplit_once(':')?;
+    Some(FleetPeer {
+        name: name.to_string(),
+        host: host.trim_end_matches('.').to_string(),
+        port: port.parse().ok()?,
+        tls: text.split_whitespace().any(|word| word == "tls=1"),
+        has_token: false,
+        this_mac: false,
+    })
+}
+
+/// Creates the timer on `target` through its remote API. `credentials`
+/// replace the saved ones once the peer accepts them; without them the
+/// saved ones are used.
+pub fn push_timer(
+    target: &FleetTarget,
+    request: &CreateTimerRequest,
+    credentials: Option<PeerCredentials>,
+) -> Result<PushedTimer, String> {
+    let account = target.account();
+    let provided = credentials.is_some();
+    let credentials = match credentials {
+        Some(credentials) => credentials,
+        None => system::keychain_password(KEYCHAIN_SERVICE, &account)?
+            .and_then(|saved| serde_json::from_str::<PeerCredentials>(&saved).ok())
+            .ok_or_else(|| format!("No token saved for {}; paste a control token from that Mac", target.host))?,
+    };
+
+    let client = client_for(target, credentials.fingerprint_sha256.as_deref())?;
+    let response = client
+        .post(target.url("/api/timers"))
+        .bearer_auth(credentials.token.trim())
+        .json(request)
+        .send()
+        .map_err(|err| format!("Failed to reach {}: {err}", target.host))?;
+    let status = response.status();
+    let body: serde_json::Value = response.json().unwrap_or_default();
+    match status {
+        StatusCode::OK => {}
+        StatusCode::UNAUTHORIZED => return Err(format!("{} refused the token", target.host)),
+        StatusCode::FORBIDDEN => return Err(format!("{} needs a control token, not a read one", target.host)),
+        _ => {
+            let error = body["error"].as_str().unwrap_or("no details");
+            return Err(format!("{} rejected the timer ({status}): {error}", target.host));
+        }
+    }
+
+    if provided {
+        let saved = serde_json::to_string(&credentials).map_err(|err| format!("Failed to encode credentials: {err}"))?;
+        system::set_keychain_password(KEYCHAIN_SERVICE, &account, &saved)?;
+    }
+    Ok(PushedTimer {
+        host: target.host.clone(),
+        timer_id: body["id"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// A plain client for HTTP peers. For HTTPS peers, whose certificates are
+/// self-signed, the certificate is fetched without sending the token and
+/// compared with `fingerprint`; the returned client then trusts only that
+/// certificate.
+pub fn client_for(target: &FleetTarget, fingerprint: Option<&str>) -> Result<Client, String> {
+    let builder = || ClientBuilder::new().timeout(PUSH_TIMEOUT);
+    if !target.tls {
+        return builder().build().map_err(|err| format!("Failed to build HTTP client: {err}"));
+    }
+    let expected: String = fingerprint
+        .ok_or_else(|| format!("{} uses HTTPS; enter its certificate fingerprint", target.host))?
+        .chars()
+        .filter(char::is_ascii_hexdigit)
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    let probe = builder()
+        .danger_accept_invalid_certs(true)
+        .tls_info(true)
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
+    let response = probe
+        .get(target.url("/api/pause"))
+        .send()
+        .map_err(|err| format!("Failed to reach {}: {err}", target.host))?;
+    let certificate = response
+        .extensions()
+        .get::<TlsInfo>()
+        .and_then(TlsInfo::peer_certificate)
+        .ok_or_else(|| format!("{} did not present a certificate", target.host))?
+        .to_vec();
+    if remote_api::hex(&Sha256::digest(&certificate)) != expected {
+        return Err(format!("{}'s certificate does not match the fingerprint", target.host));
+    }
+
+    let trusted = Certificate::from_der(&certificate).map_err(|err| format!("Invalid peer certificate: {err}"))?;
+    builder()
+        .tls_built_in_root_certs(false)
+        .add_root_certificate(trusted)
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))
+}
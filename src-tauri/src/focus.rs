@@ -0,0 +1,357 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    applescript_string, hosts, notify_user, osascript_output, run_osascript, system, TimerStore, QUIT_EXCLUDED_APPS,
+};
+
+/// How often blocked apps are looked for during a session.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+pub const MAX_SESSION_HOURS: i64 = 24;
+const MAX_BLOCKED_APPS: usize = 50;
+
+/// What happens to a blocked app that is open during a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockMode {
+    /// Asked to quit, as if from its menu, so it can still save.
+    #[default]
+    Quit,
+    /// Hidden through System Events; needs Automation permission.
+    Hide,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub id: String,
+    pub label: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// App names as shown in Activity Monitor, e.g. "Slack".
+    pub blocked_apps: Vec<String>,
+    pub mode: BlockMode,
+    /// Block the domains from `set_blocked_domains` in /etc/hosts too.
+    #[serde(default)]
+    pub block_websites: bool,
+    pub created_at: DateTime<Utc>,
+    /// Times a blocked app was found open and quit or hidden.
+    #[serde(default)]
+    pub violations: u32,
+}
+
+impl FocusSession {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedSessions {
+    sessions: Vec<FocusSession>,
+}
+
+/// Scheduled and running focus sessions, saved in `focus_sessions.json`.
+/// A session is dropped once it ends or is cancelled.
+#[derive(Clone)]
+pub struct FocusSessions {
+    inner: Arc<Mutex<Vec<FocusSession>>>,
+    storage_path: Arc<PathBuf>,
+}
+
+impl FocusSessions {
+    pub fn new(storage_path: PathBuf) -> Self {
+        let sessions = fs::read_to_string(&storage_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<PersistedSessions>(&raw).ok())
+            .map(|persisted| persisted.sessions)
+            .unwrap_or_default();
+        Self {
+            inner: Arc::new(Mutex::new(sessions)),
+            storage_path: Arc::new(storage_path),
+        }
+    }
+
+    fn persist(&self, sessions: Vec<FocusSession>) -> Result<(), String> {
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("Failed to create focus session directory: {err}"))?;
+        }
+        let data = serde_json::to_string_pretty(&PersistedSessions { sessions })
+            .map_err(|err| format!("Failed to encode focus sessions: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data).map_err(|err| format!("Failed to write focus sessions: {err}"))
+    }
+
+    fn modify<T, F>(&self, apply: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Vec<FocusSession>) -> T,
+    {
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock focus sessions".to_string())?;
+        let result = apply(&mut locked);
+        let sessions = locked.clone();
+        drop(locked);
+        self.persist(sessions)?;
+        Ok(result)
+    }
+
+    /// Sessions by start time.
+    pub fn list(&self) -> Result<Vec<FocusSession>, String> {
+        let mut sessions = self
+            .inner
+            .lock()
+            .map(|sessions| sessions.clone())
+            .map_err(|_| "Failed to lock focus sessions".to_string())?;
+        sessions.sort_by_key(|session| session.starts_at);
+        Ok(sessions)
+    }
+
+    /// Adds a session; the command checks the times and normalizes the
+    /// apps first.
+    pub fn add(
+        &self,
+        label: Option<String>,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        blocked_apps: Vec<String>,
+        mode: BlockMode,
+        block_websites: bool,
+    ) -> Result<FocusSession, String> {
+        let session = FocusSession {
+            id: Uuid::new_v4().to_string(),
+            label: label.map(|label| label.trim().to_string()).filter(|label| !label.is_empty()),
+            starts_at,
+            ends_at,
+            blocked_apps,
+            mode,
+            block_websites,
+            created_at: Utc::now(),
+            violations: 0,
+        };
+        let added = session.clone();
+        self.modify(|sessions| sessions.push(added))?;
+        Ok(session)
+    }
+
+    /// Ends or unschedules a session. `None` if there was no such session.
+    pub fn cancel(&self, id: &str) -> Result<Option<FocusSession>, String> {
+        self.modify(|sessions| {
+            let index = sessions.iter().position(|session| session.id == id)?;
+            Some(sessions.remove(index))
+        })
+    }
+
+    fn remove_finished(&self, now: DateTime<Utc>) -> Result<Vec<FocusSession>, String> {
+        if !self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock focus sessions".to_string())?
+            .iter()
+            .any(|session| session.ends_at <= now)
+        {
+            return Ok(Vec::new());
+        }
+        self.modify(|sessions| {
+            let (finished, remaining) = std::mem::take(sessions)
+                .into_iter()
+                .partition(|session| session.ends_at <= now);
+            *sessions = remaining;
+            finished
+        })
+    }
+
+    fn record_violations(&self, id: &str, count: u32) -> Result<(), String> {
+        self.modify(|sessions| {
+            if let Some(session) = sessions.iter_mut().find(|session| session.id == id) {
+                session.violations += count;
+            }
+        })
+    }
+}
+
+/// Trims and de-duplicates app names, refusing LockPilot itself and
+/// Finder. The list may end up empty for a websites-only session.
+pub fn normalize_apps(blocked_apps: Vec<String>) -> Result<Vec<String>, String> {
+    let mut apps: Vec<String> = Vec::new();
+    for app in blocked_apps.iter().map(|app| app.trim()).filter(|app| !app.is_empty()) {
+        if QUIT_EXCLUDED_APPS.iter().any(|excluded| excluded.eq_ignore_ascii_case(app)) {
+            return Err(format!("{app} can't be blocked"));
+        }
+        if !apps.iter().any(|existing| existing.eq_ignore_ascii_case(app)) {
+            apps.push(app.to_string());
+        }
+    }
+    if apps.len() > MAX_BLOCKED_APPS {
+        return Err(format!("At most {MAX_BLOCKED_APPS} apps can be blocked"));
+    }
+    Ok(apps)
+}
+
+/// Every `CHECK_INTERVAL` for the life of the app: quits or hides blocked
+/// apps of running sessions, drops sessions that have ended, and blocks
+/// websites while a session or quiet hours ask for it. The frontend hears
+/// `focus-sessions-changed` whenever the list changes.
+pub fn spawn_watcher(sessions: FocusSessions, store: TimerStore) {
+    thread::spawn(move || {
+        let mut announced: HashSet<String> = HashSet::new();
+        // Apps caught in the previous check, keyed by session, so one that
+        // takes a while to quit is reported once.
+        let mut caught: HashSet<(String, String)> = HashSet::new();
+        // What LockPilot's section of /etc/hosts holds; unknown at launch,
+        // so a block left behind by a crash is lifted on the first check.
+        let mut blocked_domains: Option<Vec<String>> = None;
+        let mut failed_domains: Option<Vec<String>> = None;
+        loop {
+            let now = Utc::now();
+            let mut changed = false;
+            match sessions.remove_finished(now) {
+                Ok(finished) => {
+                    for session in finished {
+                        if announced.remove(&session.id) {
+                            notify_user(&format!("{} is over", title(&session)));
+                        }
+                        changed = true;
+                    }
+                }
+                Err(err) => eprintln!("Failed to end focus sessions: {err}"),
+            }
+
+            let active: Vec<FocusSession> = sessions
+                .list()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|session| session.is_active(now))
+                .collect();
+            let mut still_caught = HashSet::new();
+            for session in &active {
+                if announced.insert(session.id.clone()) {
+                    notify_user(&format!(
+                        "{} until {}: {} blocked",
+                        title(session),
+                        local_time(session.ends_at),
+                        session.blocked_apps.join(", ")
+                    ));
+                    changed = true;
+                }
+                let mut new_violations = 0;
+                for app in enforce(session) {
+                    let key = (session.id.clone(), app.clone());
+                    if !caught.contains(&key) {
+                        notify_user(&format!("{app} is blocked until {}", local_time(session.ends_at)));
+                        new_violations += 1;
+                    }
+                    still_caught.insert(key);
+                }
+                if new_violations > 0 {
+                    if let Err(err) = sessions.record_violations(&session.id, new_violations) {
+                        eprintln!("Failed to record focus session violations: {err}");
+                    }
+                    changed = true;
+                }
+            }
+            caught = still_caught;
+
+            let wanted = wanted_domains(&store, &active);
+            if blocked_domains.as_ref() != Some(&wanted) {
+                match hosts::apply(&wanted) {
+                    Ok(()) => {
+                        blocked_domains = Some(wanted);
+                        failed_domains = None;
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to update blocked websites: {err}");
+                        if failed_domains.as_ref() != Some(&wanted) {
+                            let verb = if wanted.is_empty() { "unblocked" } else { "blocked" };
+                            notify_user(&format!("Websites could not be {verb}: {err}"));
+                            failed_domains = Some(wanted);
+                        }
+                    }
+                }
+            }
+            announced.retain(|id| active.iter().any(|session| &session.id == id));
+
+            if changed {
+                store.emit("focus-sessions-changed", ());
+            }
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+/// The domains to block now: the saved list while a running session or
+/// the current quiet hours block websites, otherwise none.
+fn wanted_domains(store: &TimerStore, active: &[FocusSession]) -> Vec<String> {
+    let Ok(settings) = store.settings.snapshot() else {
+        return Vec::new();
+    };
+    let wanted = active.iter().any(|session| session.block_websites)
+        || settings
+            .active_quiet_hours(Local::now())
+            .is_some_and(|quiet| quiet.block_websites);
+    if wanted {
+        settings.blocked_domains
+    } else {
+        Vec::new()
+    }
+}
+
+/// Quits or hides the session's blocked apps that are open, returning
+/// those it acted on.
+fn enforce(session: &FocusSession) -> Vec<String> {
+    session
+        .blocked_apps
+        .iter()
+        .filter(|app| match session.mode {
+            BlockMode::Quit => {
+                if !system::is_process_running(app) {
+                    return false;
+                }
+                // Ignore responses so an app showing a save dialog can't
+                // stall the watcher; it is asked again next check.
+                let _ = run_osascript(&format!(
+                    "ignoring application responses\ntell application {} to quit\nend ignoring",
+                    applescript_string(app)
+                ));
+                true
+            }
+            BlockMode::Hide => {
+                let process = applescript_string(app);
+                osascript_output(&format!(
+                    "tell application \"System Events\"\n\
+                     if exists process {process} then\n\
+                     if visible of process {process} then\n\
+                     set visible of process {process} to false\n\
+                     return true\n\
+                     end if\n\
+                     end if\n\
+                     end tell\n\
+                     return false"
+                ))
+                .is_ok_and(|output| output.trim() == "true")
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+fn title(session: &FocusSession) -> String {
+    match &session.label {
+        Some(label) => format!("Focus session \"{label}\""),
+        None => "Focus session".to_string(),
+    }
+}
+
+fn local_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%H:%M").to_string()
+}
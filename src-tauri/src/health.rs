@@ -0,0 +1,171 @@
+use std::{fs, process::Command, sync::atomic::Ordering, time::Duration};
+
+use reqwest::blocking::Client;
+use serde::Serialize;
+
+use crate::{
+    exec,
+    system::{self, PermissionState},
+    TimerStore, GITHUB_OWNER, GITHUB_REPO,
+};
+
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ordered by severity, so the worst of several is their `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// `false` if any check is an error; warnings don't count.
+    pub healthy: bool,
+    pub checks: Vec<HealthCheck>,
+}
+
+/// Runs every check in order. Each one is independent, so a failure is
+/// reported rather than short-circuiting the rest.
+pub fn run(store: &TimerStore) -> HealthReport {
+    let checks = vec![
+        check_osascript(),
+        check_permission(
+            "automation",
+            system::automation_permission(false),
+            "System Events can be controlled",
+            "Allow LockPilot to control System Events in System Settings > Privacy & Security > Automation",
+        ),
+        check_permission(
+            "accessibility",
+            system::accessibility_permission(),
+            "Keystrokes can be sent for locking",
+            "Add LockPilot under System Settings > Privacy & Security > Accessibility",
+        ),
+        check_storage(store),
+        check_timer_store(store),
+        check_scheduler(store),
+        check_network(),
+    ];
+
+    HealthReport {
+        healthy: checks.iter().all(|check| check.status != CheckStatus::Error),
+        checks,
+    }
+}
+
+fn check(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> HealthCheck {
+    HealthCheck {
+        name,
+        status,
+        detail: detail.into(),
+    }
+}
+
+fn check_osascript() -> HealthCheck {
+    match exec::output(Command::new("/usr/bin/osascript").args(["-e", "return 1"])) {
+        Ok(output) if output.status.success() => check("osascript", CheckStatus::Ok, "osascript runs"),
+        Ok(output) => check(
+            "osascript",
+            CheckStatus::Error,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ),
+        Err(err) => check("osascript", CheckStatus::Error, format!("Failed to run osascript: {err}")),
+    }
+}
+
+fn check_permission(name: &'static str, state: PermissionState, granted: &str, denied: &str) -> HealthCheck {
+    match state {
+        PermissionState::Granted => check(name, CheckStatus::Ok, granted),
+        PermissionState::Denied => check(name, CheckStatus::Error, denied),
+        PermissionState::NotDetermined => check(name, CheckStatus::Warning, "Not requested yet; it will be asked for on first use"),
+        PermissionState::Unknown => check(name, CheckStatus::Warning, "Permission status could not be determined"),
+    }
+}
+
+fn check_storage(store: &TimerStore) -> HealthCheck {
+    let Some(dir) = store.storage_path.parent() else {
+        return check("storage", CheckStatus::Error, "Storage path has no parent directory");
+    };
+
+    let probe = dir.join(".health-check");
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| fs::write(&probe, b"ok"))
+        .and_then(|_| fs::remove_file(&probe));
+    match result {
+        Ok(()) => check("storage", CheckStatus::Ok, format!("{} is writable", dir.display())),
+        Err(err) => check("storage", CheckStatus::Error, format!("{} is not writable: {err}", dir.display())),
+    }
+}
+
+/// A panic while the timer store was locked is survived, but the timer
+/// being changed at the time may be left half-updated.
+fn check_timer_store(store: &TimerStore) -> HealthCheck {
+    match store.lock_recoveries.load(Ordering::SeqCst) {
+        0 => check("timers", CheckStatus::Ok, "No panics while timers were being changed"),
+        recoveries => check(
+            "timers",
+            CheckStatus::Warning,
+            format!("Recovered from {recoveries} panic(s) while timers were being changed; review your timers"),
+        ),
+    }
+}
+
+/// Every scheduled timer should be waiting in the scheduler queue or firing
+/// right now; fewer means one was dropped without being rescheduled.
+fn check_scheduler(store: &TimerStore) -> HealthCheck {
+    let scheduled = store
+        .timers()
+        .values()
+        .filter(|entry| entry.cancel_tx.is_some())
+        .count();
+    if !store.queue.is_running() {
+        return check("scheduler", CheckStatus::Error, "Scheduler thread is not running");
+    }
+    let queued = store.queue.queued();
+    let firing = store.firing.load(Ordering::SeqCst);
+
+    if queued + firing >= scheduled {
+        check(
+            "scheduler",
+            CheckStatus::Ok,
+            format!("{scheduled} scheduled timer(s), {queued} queued, {firing} firing"),
+        )
+    } else {
+        check(
+            "scheduler",
+            CheckStatus::Error,
+            format!("{scheduled} scheduled timer(s) but only {queued} queued and {firing} firing"),
+        )
+    }
+}
+
+fn check_network() -> HealthCheck {
+    let url = format!("https://api.github.com/repos/{GITHUB_OWNER}/{GITHUB_REPO}");
+    let response = Client::builder()
+        .user_agent("LockPilot-Updater")
+        .timeout(NETWORK_TIMEOUT)
+        .build()
+        .and_then(|client| client.get(url).send());
+
+    match response {
+        Ok(response) if response.status().is_success() => check("network", CheckStatus::Ok, "GitHub releases reachable"),
+        Ok(response) => check(
+            "network",
+            CheckStatus::Warning,
+            format!("GitHub returned status {}", response.status()),
+        ),
+        Err(err) => check("network", CheckStatus::Warning, format!("Update server unreachable: {err}")),
+    }
+}
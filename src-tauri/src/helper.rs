@@ -0,0 +1,304 @@
+use std::{fs, path::Path};
+
+use chrono::{DateTime, Local};
+#[cfg(feature = "app")]
+use serde::Serialize;
+#[cfg(not(feature = "fake-system"))]
+use uuid::Uuid;
+
+#[cfg(feature = "fake-system")]
+use crate::exec;
+#[cfg(feature = "app")]
+use crate::{applescript_string, run_osascript, USER_CANCELED_ERROR};
+
+#[cfg(feature = "app")]
+const LABEL: &str = "com.maks.lockpilot.helper";
+const DAEMON_PLIST: &str = "/Library/LaunchDaemons/com.maks.lockpilot.helper.plist";
+const SCRIPT: &str = "/Library/PrivilegedHelperTools/com.maks.lockpilot.helper.sh";
+/// Owned by the user who installed the helper and closed to everyone else;
+/// the daemon wakes whenever something is written here.
+const REQUEST_DIR: &str = "/Library/Application Support/LockPilot/Helper";
+
+/// Written by `block_domains` for the daemon to read.
+#[cfg(all(feature = "app", not(feature = "fake-system")))]
+const DOMAINS_FILE: &str = "blocked.domains";
+/// The network services `NetworkOff` turned off. Kept beside the script,
+/// where only root can write, rather than in `REQUEST_DIR`, where the user
+/// could plant a symlink for the daemon to append to.
+#[cfg(feature = "app")]
+const NETWORK_STATE: &str = "/Library/PrivilegedHelperTools/com.maks.lockpilot.network.off";
+
+/// `pmset schedule` date format, in local time.
+const PMSET_DATE_FORMAT: &str = "%m/%d/%y %H:%M:%S";
+
+/// Runs as root under launchd. Each request file holds one line that must
+/// match a pattern exactly; wake dates are constrained to digits, so
+/// nothing written there reaches a shell. Blocked domains come from
+/// `DOMAINS_FILE`, one per line, and lines with anything but lowercase
+/// letters, digits, dots and dashes are dropped; the daemon refuses to
+/// follow a symlink there, and keeps its own state in `NETWORK_STATE`.
+const SCRIPT_BODY: &str = r##"#!/bin/sh
+# Installed by LockPilot. Shuts down or restarts on request, bypassing
+# loginwindow, confirmation dialogs and Automation permission, schedules
+# wakes for timers that must run, turns network services off and on, and
+# blocks domains in /etc/hosts.
+DIR="/Library/Application Support/LockPilot/Helper"
+NETWORK_STATE=/Library/PrivilegedHelperTools/com.maks.lockpilot.network.off
+D2="[0-9][0-9]"
+NS=/usr/sbin/networksetup
+unblock_hosts() {
+  /usr/bin/sed -i '' '/^# BEGIN LockPilot/,/^# END LockPilot/d' /etc/hosts
+}
+flush_dns() {
+  /usr/bin/dscacheutil -flushcache
+  /usr/bin/killall -HUP mDNSResponder
+}
+for REQUEST in "$DIR"/*.request; do
+  [ -f "$REQUEST" ] || continue
+  ACTION=$(/usr/bin/head -c 64 "$REQUEST")
+  /bin/rm -f "$REQUEST"
+  case "$ACTION" in
+    shutdown) exec /sbin/shutdown -h now ;;
+    reboot) exec /sbin/shutdown -r now ;;
+    "wake "$D2/$D2/$D2" "$D2:$D2:$D2) /usr/bin/pmset schedule wake "${ACTION#wake }" ;;
+    "cancelwake "$D2/$D2/$D2" "$D2:$D2:$D2) /usr/bin/pmset schedule cancel wake "${ACTION#cancelwake }" ;;
+    "network off")
+      [ -L "$NETWORK_STATE" ] && continue
+      $NS -listallnetworkservices | /usr/bin/tail -n +2 | /usr/bin/grep -v '^\*' >> "$NETWORK_STATE"
+      while IFS= read -r S; do $NS -setnetworkserviceenabled "$S" off; done < "$NETWORK_STATE" ;;
+    "network on")
+      [ -f "$NETWORK_STATE" ] && [ ! -L "$NETWORK_STATE" ] || continue
+      while IFS= read -r S; do $NS -setnetworkserviceenabled "$S" on; done < "$NETWORK_STATE"
+      /bin/rm -f "$NETWORK_STATE" ;;
+    "hosts block")
+      [ -L "$DIR/blocked.domains" ] && continue
+      unblock_hosts
+      {
+        echo "# BEGIN LockPilot"
+        while IFS= read -r H; do
+          case "$H" in ""|*[!a-z0-9.-]*) ;; *) echo "0.0.0.0 $H"; echo ":: $H" ;; esac
+        done < "$DIR/blocked.domains"
+        echo "# END LockPilot"
+      } >> /etc/hosts
+      flush_dns ;;
+    "hosts unblock") unblock_hosts; flush_dns ;;
+  esac
+done
+"##;
+
+#[derive(Debug, Clone, Copy)]
+pub enum HelperRequest {
+    Shutdown,
+    Reboot,
+    /// Wakes the Mac at this time if it is asleep.
+    Wake(DateTime<Local>),
+    CancelWake(DateTime<Local>),
+    /// Turns off every enabled network service, remembering which.
+    NetworkOff,
+    /// Turns back on the services `NetworkOff` turned off.
+    NetworkOn,
+    /// Removes LockPilot's section from /etc/hosts.
+    #[cfg(feature = "app")]
+    UnblockDomains,
+}
+
+/// A wake registered through the helper. Dropping it cancels the wake
+/// unless `keep` was called.
+pub struct ScheduledWake {
+    at: DateTime<Local>,
+    armed: bool,
+}
+
+impl ScheduledWake {
+    pub fn register(at: DateTime<Local>) -> Result<Self, String> {
+        request(HelperRequest::Wake(at))?;
+        Ok(Self { at, armed: true })
+    }
+
+    /// The wake time has come; there's nothing left to cancel.
+    pub fn keep(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for ScheduledWake {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = request(HelperRequest::CancelWake(self.at));
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelperStatus {
+    pub installed: bool,
+}
+
+#[cfg(feature = "app")]
+pub fn status() -> HelperStatus {
+    HelperStatus {
+        installed: is_installed(),
+    }
+}
+
+/// Only the current script counts: an older install has to be reinstalled
+/// before it understands every request.
+pub fn is_installed() -> bool {
+    #[cfg(feature = "fake-system")]
+    if exec::fake::stubbed(exec::fake::HELPER) {
+        return true;
+    }
+    Path::new(DAEMON_PLIST).exists()
+        && Path::new(REQUEST_DIR).is_dir()
+        && fs::read_to_string(SCRIPT).is_ok_and(|script| script == SCRIPT_BODY)
+}
+
+/// Installs the launchd daemon, asking for an administrator password.
+#[cfg(feature = "app")]
+pub fn install() -> Result<HelperStatus, String> {
+    let user = std::env::var("USER")
+        .ok()
+        .filter(|user| !user.is_empty())
+        .ok_or_else(|| "Failed to determine the current user".to_string())?;
+    let staging = std::env::temp_dir().join("lockpilot-helper");
+    fs::create_dir_all(&staging).map_err(|err| format!("Failed to prepare helper files: {err}"))?;
+    let staged_script = staging.join("helper.sh");
+    let staged_plist = staging.join("helper.plist");
+    fs::write(&staged_script, SCRIPT_BODY).map_err(|err| format!("Failed to prepare helper files: {err}"))?;
+    fs::write(&staged_plist, daemon_plist()).map_err(|err| format!("Failed to prepare helper files: {err}"))?;
+
+    let commands = [
+        format!("/bin/mkdir -p /Library/PrivilegedHelperTools {}", sh_quote(REQUEST_DIR)),
+        format!("/usr/bin/install -o root -g wheel -m 755 {} {SCRIPT}", sh_quote(&staged_script.to_string_lossy())),
+        format!(
+            "/usr/bin/install -o root -g wheel -m 644 {} {DAEMON_PLIST}",
+            sh_quote(&staged_plist.to_string_lossy())
+        ),
+        format!("/usr/sbin/chown {} {}", sh_quote(&user), sh_quote(REQUEST_DIR)),
+        format!("/bin/chmod 700 {}", sh_quote(REQUEST_DIR)),
+        format!("{{ /bin/launchctl bootout system/{LABEL} 2>/dev/null; true; }}"),
+        format!("/bin/launchctl bootstrap system {DAEMON_PLIST}"),
+    ];
+    let result = run_privileged(&commands.join(" && "));
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+    Ok(status())
+}
+
+/// Removes the daemon and any domains it blocked, asking for an
+/// administrator password.
+#[cfg(feature = "app")]
+pub fn uninstall() -> Result<HelperStatus, String> {
+    run_privileged(&format!(
+        "/bin/launchctl bootout system/{LABEL} 2>/dev/null; /bin/rm -f {DAEMON_PLIST} {SCRIPT} {NETWORK_STATE}; /bin/rm -rf {}; \
+         /usr/bin/sed -i '' '/^# BEGIN LockPilot/,/^# END LockPilot/d' /etc/hosts",
+        sh_quote(REQUEST_DIR)
+    ))?;
+    Ok(status())
+}
+
+/// Hands the action to the daemon, which carries it out straight away.
+pub fn request(action: HelperRequest) -> Result<(), String> {
+    if !is_installed() {
+        return Err("The privileged helper is not installed".to_string());
+    }
+    let line = match action {
+        HelperRequest::Shutdown => "shutdown".to_string(),
+        HelperRequest::Reboot => "reboot".to_string(),
+        HelperRequest::Wake(at) => format!("wake {}", at.format(PMSET_DATE_FORMAT)),
+        HelperRequest::CancelWake(at) => format!("cancelwake {}", at.format(PMSET_DATE_FORMAT)),
+        HelperRequest::NetworkOff => "network off".to_string(),
+        HelperRequest::NetworkOn => "network on".to_string(),
+        #[cfg(feature = "app")]
+        HelperRequest::UnblockDomains => "hosts unblock".to_string(),
+    };
+    write_request(&line)
+}
+
+/// Replaces LockPilot's section of /etc/hosts with one sending `domains`
+/// nowhere.
+#[cfg(feature = "app")]
+pub fn block_domains(domains: &[String]) -> Result<(), String> {
+    if !is_installed() {
+        return Err("The privileged helper is not installed".to_string());
+    }
+    #[cfg(feature = "fake-system")]
+    {
+        exec::fake::call(exec::fake::HELPER, &["hosts block", &domains.join(" ")]).map(|_| ())
+    }
+    #[cfg(not(feature = "fake-system"))]
+    {
+        write_atomically(&Path::new(REQUEST_DIR).join(DOMAINS_FILE), &(domains.join("\n") + "\n"))?;
+        write_request("hosts block")
+    }
+}
+
+/// With the `fake-system` feature the line is recorded instead; see
+/// `exec::fake`.
+fn write_request(line: &str) -> Result<(), String> {
+    #[cfg(feature = "fake-system")]
+    {
+        exec::fake::call(exec::fake::HELPER, &[line]).map(|_| ())
+    }
+    #[cfg(not(feature = "fake-system"))]
+    {
+        // One file per request so quick successive ones don't overwrite
+        // each other; written aside and renamed so the daemon never reads a
+        // partial file.
+        write_atomically(&Path::new(REQUEST_DIR).join(format!("{}.request", Uuid::new_v4())), line)
+    }
+}
+
+#[cfg(not(feature = "fake-system"))]
+fn write_atomically(path: &Path, contents: &str) -> Result<(), String> {
+    let staged = path.with_extension("tmp");
+    fs::write(&staged, contents)
+        .and_then(|_| fs::rename(&staged, path))
+        .map_err(|err| format!("Failed to reach the privileged helper: {err}"))
+}
+
+#[cfg(feature = "app")]
+fn daemon_plist() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{LABEL}</string>
+  <key>ProgramArguments</key>
+  <array>
+    <string>/bin/sh</string>
+    <string>{SCRIPT}</string>
+  </array>
+  <key>WatchPaths</key>
+  <array>
+    <string>{REQUEST_DIR}</string>
+  </array>
+</dict>
+</plist>
+"#
+    )
+}
+
+#[cfg(feature = "app")]
+fn run_privileged(command: &str) -> Result<(), String> {
+    run_osascript(&format!(
+        "do shell script {} with administrator privileges",
+        applescript_string(command)
+    ))
+    .map_err(|err| {
+        if err.contains(USER_CANCELED_ERROR) {
+            "Administrator authorization was cancelled".to_string()
+        } else {
+            format!("Failed to change the privileged helper: {}", err.trim())
+        }
+    })
+}
+
+#[cfg(feature = "app")]
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
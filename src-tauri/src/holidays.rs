@@ -0,0 +1,163 @@
+#[cfg(feature = "app")]
+use std::time::{Duration, Instant};
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{Datelike, NaiveDate};
+#[cfg(feature = "app")]
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "app")]
+const HOLIDAY_API: &str = "https://date.nager.at/api/v3/PublicHolidays";
+#[cfg(feature = "app")]
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long `prefetch` leaves a region and year alone after failing to
+/// fetch it.
+#[cfg(feature = "app")]
+const FAILURE_RETRY: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holiday {
+    pub date: NaiveDate,
+    /// English name, e.g. "Independence Day".
+    pub name: String,
+    /// `false` for holidays observed only in some subdivisions (states,
+    /// cantons); those are not skipped.
+    #[serde(default = "default_global")]
+    pub global: bool,
+}
+
+fn default_global() -> bool {
+    true
+}
+
+/// Public holidays from the Nager.Date dataset, fetched once per region
+/// and year and cached in `holidays.json`.
+#[derive(Clone)]
+pub struct HolidayCalendar {
+    #[cfg(feature = "app")]
+    cache_path: Arc<PathBuf>,
+    /// Keyed by `"<REGION>-<year>"`.
+    cache: Arc<Mutex<HashMap<String, Vec<Holiday>>>>,
+    /// When `prefetch` last failed to fetch a key, so an unreachable
+    /// dataset isn't asked again on every pass.
+    #[cfg(feature = "app")]
+    failures: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl HolidayCalendar {
+    pub fn new(cache_path: PathBuf) -> Self {
+        let cache = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        #[cfg(not(feature = "app"))]
+        let _ = cache_path;
+        Self {
+            #[cfg(feature = "app")]
+            cache_path: Arc::new(cache_path),
+            cache: Arc::new(Mutex::new(cache)),
+            #[cfg(feature = "app")]
+            failures: Arc::default(),
+        }
+    }
+
+    /// The year's public holidays in `region` (ISO 3166-1 alpha-2),
+    /// fetched unless already cached.
+    #[cfg(feature = "app")]
+    pub fn holidays(&self, region: &str, year: i32) -> Result<Vec<Holiday>, String> {
+        let key = format!("{region}-{year}");
+        if let Some(holidays) = self.cached(&key) {
+            return Ok(holidays);
+        }
+
+        let holidays = fetch(region, year)?;
+        let mut cache = self
+            .cache
+            .lock()
+            .map_err(|_| "Failed to lock holiday cache".to_string())?;
+        cache.insert(key, holidays.clone());
+        let data = serde_json::to_string_pretty(&*cache).map_err(|err| format!("Failed to encode holidays: {err}"))?;
+        fs::write(self.cache_path.as_ref(), data).map_err(|err| format!("Failed to write holidays: {err}"))?;
+        Ok(holidays)
+    }
+
+    /// Fetches whichever of `years` aren't cached yet, skipping those that
+    /// failed within `FAILURE_RETRY`. Failures are only logged: timers
+    /// keep running on days the dataset couldn't be reached for.
+    #[cfg(feature = "app")]
+    pub fn prefetch(&self, region: &str, years: &[i32]) {
+        for &year in years {
+            let key = format!("{region}-{year}");
+            let failed_recently = self
+                .failures
+                .lock()
+                .ok()
+                .and_then(|failures| failures.get(&key).copied())
+                .is_some_and(|failed_at| failed_at.elapsed() < FAILURE_RETRY);
+            if failed_recently || self.cached(&key).is_some() {
+                continue;
+            }
+            let result = self.holidays(region, year);
+            if let Ok(mut failures) = self.failures.lock() {
+                match result {
+                    Ok(_) => failures.remove(&key),
+                    Err(err) => {
+                        eprintln!("{err}");
+                        failures.insert(key, Instant::now())
+                    }
+                };
+            }
+        }
+    }
+
+    /// The nationwide holiday on `date`, if any. Only reads the cache, so a
+    /// timer never waits on the network when it fires; a year not fetched
+    /// yet counts as having no holidays.
+    pub fn holiday_on(&self, region: &str, date: NaiveDate) -> Option<Holiday> {
+        self.cached(&format!("{region}-{}", date.year()))?
+            .into_iter()
+            .find(|holiday| holiday.global && holiday.date == date)
+    }
+
+    fn cached(&self, key: &str) -> Option<Vec<Holiday>> {
+        self.cache.lock().ok()?.get(key).cloned()
+    }
+}
+
+/// Region codes are two ASCII letters, stored uppercase.
+#[cfg(feature = "app")]
+pub fn normalize_region(region: &str) -> Result<String, String> {
+    let region = region.trim().to_ascii_uppercase();
+    if region.len() == 2 && region.bytes().all(|byte| byte.is_ascii_uppercase()) {
+        Ok(region)
+    } else {
+        Err("Holiday region must be a two-letter country code, e.g. US or DE".to_string())
+    }
+}
+
+#[cfg(feature = "app")]
+fn fetch(region: &str, year: i32) -> Result<Vec<Holiday>, String> {
+    let response = Client::builder()
+        .user_agent("LockPilot-Holidays")
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?
+        .get(format!("{HOLIDAY_API}/{year}/{region}"))
+        .send()
+        .map_err(|err| format!("Failed to fetch holidays for {region}: {err}"))?;
+
+    match response.status().as_u16() {
+        200 => response
+            .json()
+            .map_err(|err| format!("Failed to read holidays for {region}: {err}")),
+        204 | 404 => Err(format!("No holiday data for region {region}")),
+        status => Err(format!("Holiday service returned status {status}")),
+    }
+}
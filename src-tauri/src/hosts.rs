@@ -0,0 +1,60 @@
+use crate::helper::{self, HelperRequest};
+
+const MAX_BLOCKED_DOMAINS: usize = 500;
+/// Longest name DNS allows.
+const MAX_DOMAIN_LENGTH: usize = 253;
+
+/// Turns what users paste, e.g. `https://www.YouTube.com/feed`, into bare
+/// lowercase domains, dropping duplicates.
+pub fn normalize_domains(domains: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for entry in domains.iter().map(|entry| entry.trim()).filter(|entry| !entry.is_empty()) {
+        let lower = entry.to_ascii_lowercase();
+        let without_scheme = lower.split_once("://").map_or(lower.as_str(), |(_, rest)| rest);
+        let host = without_scheme
+            .split(['/', '?', '#', ':'])
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches("*.")
+            .trim_end_matches('.');
+        // `apply` adds the `www.` name back.
+        let host = host.strip_prefix("www.").filter(|rest| rest.contains('.')).unwrap_or(host);
+        let valid = !host.is_empty()
+            && host.len() <= MAX_DOMAIN_LENGTH
+            && host.contains('.')
+            && host
+                .split('.')
+                .all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'))
+            && host
+                .bytes()
+                .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'.' || byte == b'-');
+        if !valid {
+            return Err(format!("Not a domain name: {entry}"));
+        }
+        if !normalized.iter().any(|existing| existing == host) {
+            normalized.push(host.to_string());
+        }
+    }
+    if normalized.len() > MAX_BLOCKED_DOMAINS {
+        return Err(format!("At most {MAX_BLOCKED_DOMAINS} domains can be blocked"));
+    }
+    Ok(normalized)
+}
+
+/// Makes LockPilot's section of /etc/hosts block exactly `domains`, and
+/// their `www.` names, through the privileged helper; an empty list removes
+/// the section. Without the helper nothing can have been blocked, so there
+/// is nothing to remove.
+pub fn apply(domains: &[String]) -> Result<(), String> {
+    if domains.is_empty() {
+        if !helper::is_installed() {
+            return Ok(());
+        }
+        return helper::request(HelperRequest::UnblockDomains);
+    }
+    let names: Vec<String> = domains
+        .iter()
+        .flat_map(|domain| [domain.clone(), format!("www.{domain}")])
+        .collect();
+    helper::block_domains(&names)
+}
@@ -0,0 +1,889 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{AuditOrigin, CreateTimerRequest, TimerAction, TimerSource, TimerStore};
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+/// Only events this close are turned into timers; later ones are picked up
+/// by a later sync.
+const HORIZON_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventAnchor {
+    #[default]
+    Start,
+    End,
+}
+
+/// Which events become timers and what they do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsMapping {
+    /// Case-insensitive part of the event summary; empty matches every event.
+    #[serde(default)]
+    pub summary_contains: String,
+    pub action: TimerAction,
+    #[serde(default)]
+    pub anchor: EventAnchor,
+    /// Minutes after the anchor; negative fires before it.
+    #[serde(default)]
+    pub offset_minutes: i64,
+    /// Popup, push or email text; defaults to the event summary.
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsSubscription {
+    pub id: String,
+    pub url: String,
+    pub mapping: IcsMapping,
+    pub created_at: DateTime<Utc>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSummary {
+    pub created: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedSubscriptions {
+    subscriptions: Vec<IcsSubscription>,
+}
+
+/// Calendar subscriptions, saved in `ics_subscriptions.json`.
+#[derive(Clone)]
+pub struct IcsSubscriptions {
+    inner: Arc<Mutex<Vec<IcsSubscription>>>,
+    storage_path: Arc<PathBuf>,
+    /// Held for a whole sync so the background thread and `subscribe_ics`
+    /// can't both create the same event's timer.
+    sync_lock: Arc<Mutex<()>>,
+}
+
+impl IcsSubscriptions {
+    pub fn new(storage_path: PathBuf) -> Self {
+        let subscriptions = fs::read_to_string(&storage_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<PersistedSubscriptions>(&raw).ok())
+            .map(|persisted| persisted.subscriptions)
+            .unwrap_or_default();
+        Self {
+            inner: Arc::new(Mutex::new(subscriptions)),
+            storage_path: Arc::new(storage_path),
+            sync_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn persist(&self, subscriptions: Vec<IcsSubscription>) -> Result<(), String> {
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("Failed to create subscription directory: {err}"))?;
+        }
+        let data = serde_json::to_string_pretty(&PersistedSubscriptions { subscriptions })
+            .map_err(|err| format!("Failed to encode subscriptions: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data).map_err(|err| format!("Failed to write subscriptions: {err}"))
+    }
+
+    fn modify<F>(&self, apply: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut Vec<IcsSubscription>),
+    {
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock subscriptions".to_string())?;
+        apply(&mut locked);
+        let subscriptions = locked.clone();
+        drop(locked);
+        self.persist(subscriptions)
+    }
+
+    pub fn list(&self) -> Result<Vec<IcsSubscription>, String> {
+        self.inner
+            .lock()
+            .map(|subscriptions| subscriptions.clone())
+            .map_err(|_| "Failed to lock subscriptions".to_string())
+    }
+
+    /// `url` should already be normalized with [`normalize_url`].
+    pub fn add(&self, url: String, mapping: IcsMapping) -> Result<IcsSubscription, String> {
+        let subscription = IcsSubscription {
+            id: Uuid::new_v4().to_string(),
+            url,
+            mapping,
+            created_at: Utc::now(),
+            last_synced_at: None,
+            last_error: None,
+        };
+        let added = subscription.clone();
+        self.modify(|subscriptions| subscriptions.push(added))?;
+        Ok(subscription)
+    }
+
+    /// Removes the subscription along with the timers it created. Returns
+    /// `false` if there was no such subscription.
+    pub fn remove(&self, id: &str, store: &TimerStore) -> Result<bool, String> {
+        let _guard = self
+            .sync_lock
+            .lock()
+            .map_err(|_| "Failed to lock calendar sync".to_string())?;
+        let mut removed = false;
+        self.modify(|subscriptions| {
+            let before = subscriptions.len();
+            subscriptions.retain(|subscription| subscription.id != id);
+            removed = subscriptions.len() != before;
+        })?;
+        for (timer_id, _) in source_timers(id, store)? {
+            store.cancel(&timer_id, &AuditOrigin::System)?;
+        }
+        Ok(removed)
+    }
+
+    /// Fetches one feed and brings its timers in line, recording the
+    /// outcome on the subscription.
+    pub fn sync(&self, id: &str, store: &TimerStore) -> Result<IcsSubscription, String> {
+        let _guard = self
+            .sync_lock
+            .lock()
+            .map_err(|_| "Failed to lock calendar sync".to_string())?;
+        let subscription = self
+            .list()?
+            .into_iter()
+            .find(|subscription| subscription.id == id)
+            .ok_or_else(|| format!("Subscription not found: {id}"))?;
+
+        let result = sync_subscription(&subscription, store);
+        let now = Utc::now();
+        let mut updated = None;
+        self.modify(|subscriptions| {
+            if let Some(entry) = subscriptions.iter_mut().find(|entry| entry.id == id) {
+                entry.last_synced_at = Some(now);
+                entry.last_error = result.as_ref().err().cloned();
+                updated = Some(entry.clone());
+            }
+        })?;
+        // Removed while the feed was being fetched.
+        updated.ok_or_else(|| format!("Subscription not found: {id}"))
+    }
+
+    pub fn sync_all(&self, store: &TimerStore) {
+        let Ok(subscriptions) = self.list() else {
+            return;
+        };
+        for subscription in subscriptions {
+            if let Err(err) = self.sync(&subscription.id, store) {
+                eprintln!("Failed to sync calendar subscription: {err}");
+            }
+        }
+    }
+}
+
+/// Feed URLs must be http(s); `webcal://` links, as calendar apps hand
+/// out, are fetched over https.
+pub fn normalize_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let url = match url.get(..9) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("webcal://") => format!("https://{}", &url[9..]),
+        _ => url.to_string(),
+    };
+    let parsed = reqwest::Url::parse(&url).map_err(|err| format!("Invalid feed URL: {err}"))?;
+    if !matches!(parsed.scheme(), "https" | "http") {
+        return Err("Feed URL must start with https://, http:// or webcal://".to_string());
+    }
+    Ok(parsed.to_string())
+}
+
+pub fn spawn_sync(subscriptions: IcsSubscriptions, store: TimerStore) {
+    thread::spawn(move || loop {
+        subscriptions.sync_all(&store);
+        thread::sleep(SYNC_INTERVAL);
+    });
+}
+
+fn sync_subscription(subscription: &IcsSubscription, store: &TimerStore) -> Result<SyncSummary, String> {
+    let feed = fetch(&subscription.url)?;
+    let now = Utc::now();
+    let horizon = now + ChronoDuration::days(HORIZON_DAYS);
+    let mapping = &subscription.mapping;
+    let needle = mapping.summary_contains.trim().to_lowercase();
+
+    let mut wanted = Vec::new();
+    let mut wanted_keys = HashSet::new();
+    // Events starting after the horizon may still fire inside it when the offset is negative.
+    let last_start = horizon + ChronoDuration::minutes(-mapping.offset_minutes.min(0));
+    for event in parse_events(&feed, last_start) {
+        if event.cancelled || !event.summary.to_lowercase().contains(&needle) {
+            continue;
+        }
+        let anchor = match mapping.anchor {
+            EventAnchor::Start => event.start,
+            EventAnchor::End => event.end.unwrap_or(event.start),
+        };
+        let fire_at = anchor + ChronoDuration::minutes(mapping.offset_minutes);
+        if fire_at <= now || fire_at > horizon {
+            continue;
+        }
+
+        let event_key = format!("{}@{}", event.uid, fire_at.to_rfc3339());
+        if wanted_keys.insert(event_key.clone()) {
+            wanted.push((event_key, event.summary, fire_at));
+        }
+    }
+
+    let existing = source_timers(&subscription.id, store)?;
+    let existing_keys: HashSet<&str> = existing.iter().map(|(_, key)| key.as_str()).collect();
+    let mut summary = SyncSummary::default();
+
+    for (id, key) in &existing {
+        if !wanted_keys.contains(key) {
+            store.cancel(id, &AuditOrigin::System)?;
+            summary.removed += 1;
+        }
+    }
+
+    let requests: Vec<CreateTimerRequest> = wanted
+        .into_iter()
+        .filter(|(key, _, _)| !existing_keys.contains(key.as_str()))
+        .map(|(event_key, event_summary, fire_at)| request_for(subscription, event_key, event_summary, fire_at))
+        .collect();
+    summary.created = requests.len();
+    if !requests.is_empty() {
+        store.create_many(requests, &AuditOrigin::System)?;
+    }
+    Ok(summary)
+}
+
+fn request_for(subscription: &IcsSubscription, event_key: String, event_summary: String, fire_at: DateTime<Utc>) -> CreateTimerRequest {
+    let message = matches!(subscription.mapping.action, TimerAction::Popup | TimerAction::Push | TimerAction::Email)
+        .then(|| subscription.mapping.message.clone().unwrap_or(event_summary));
+    CreateTimerRequest {
+        action: subscription.mapping.action.clone(),
+        target_time: fire_at.to_rfc3339(),
+        recurrence: None,
+        message,
+        notes: Some(format!("From calendar subscription {}", subscription.id)),
+        shutdown_options: None,
+        hooks: None,
+        condition: None,
+        trigger: None,
+        profile: None,
+        expires_at: None,
+        max_runs: None,
+        jitter_minutes: None,
+        warning_minutes: None,
+        confirmation: None,
+        escalation: None,
+        repeat_until_acknowledged: None,
+        working_hours: None,
+        ignore_working_hours: false,
+        chat_webhook: None,
+        must_run: false,
+        pause_media: false,
+        eject_disks: None,
+        tags: Vec::new(),
+        source: Some(TimerSource {
+            subscription_id: subscription.id.clone(),
+            event_key,
+        }),
+        managed: None,
+    }
+}
+
+/// `(timer id, event key)` for every timer this subscription created.
+fn source_timers(subscription_id: &str, store: &TimerStore) -> Result<Vec<(String, String)>, String> {
+    let timers = store
+        .inner
+        .lock()
+        .map_err(|_| "Failed to lock timer store".to_string())?;
+    Ok(timers
+        .values()
+        .filter_map(|entry| {
+            let source = entry.info.source.as_ref()?;
+            (source.subscription_id == subscription_id).then(|| (entry.info.id.clone(), source.event_key.clone()))
+        })
+        .collect())
+}
+
+fn fetch(url: &str) -> Result<String, String> {
+    let response = Client::builder()
+        .user_agent("LockPilot-Calendar")
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?
+        .get(url)
+        .send()
+        .map_err(|err| format!("Failed to fetch calendar: {err}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Calendar server returned status {}", response.status()));
+    }
+    response.text().map_err(|err| format!("Failed to read calendar: {err}"))
+}
+
+struct IcsEvent {
+    uid: String,
+    summary: String,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    cancelled: bool,
+}
+
+/// How a `DTSTART`-style value is tied to an instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    /// No `TZID`, or one chrono-tz doesn't know: the Mac's own time zone.
+    Local,
+}
+
+impl Zone {
+    fn resolve(self, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Utc => Some(Utc.from_utc_datetime(&naive)),
+            Zone::Named(tz) => tz.from_local_datetime(&naive).earliest().map(|time| time.with_timezone(&Utc)),
+            Zone::Local => Local.from_local_datetime(&naive).earliest().map(|time| time.with_timezone(&Utc)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The parts of an `RRULE` that are expanded. Rules using any other part
+/// are left unexpanded rather than guessed at.
+#[derive(Debug)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    /// Either an instant (`...Z`) or a date/time in the event's zone.
+    until: Option<NaiveDateTime>,
+    until_utc: bool,
+    /// Weekdays, with an ordinal such as `-1FR` for monthly rules.
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+}
+
+#[derive(Default)]
+struct EventDraft {
+    uid: Option<String>,
+    summary: String,
+    start: Option<(NaiveDateTime, Zone)>,
+    end: Option<(NaiveDateTime, Zone)>,
+    recurrence_id: Option<(String, Option<DateTime<Utc>>)>,
+    rrule: Option<String>,
+    exdates: Vec<DateTime<Utc>>,
+    cancelled: bool,
+}
+
+/// Reads the VEVENTs of an iCalendar feed, expanding recurring events
+/// (`RRULE`) up to `until`. Edited instances (`RECURRENCE-ID`) replace the
+/// ones they override and `EXDATE`s are left out. All-day events have no
+/// time to anchor to and are ignored.
+fn parse_events(feed: &str, until: DateTime<Utc>) -> Vec<IcsEvent> {
+    // Long lines are folded onto continuation lines starting with a space or tab.
+    let mut lines: Vec<String> = Vec::new();
+    for raw in feed.split('\n') {
+        let raw = raw.trim_end_matches('\r');
+        match (raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+
+    let mut drafts = Vec::new();
+    let mut draft: Option<EventDraft> = None;
+    for line in &lines {
+        if line.eq_ignore_ascii_case("BEGIN:VEVENT") {
+            draft = Some(EventDraft::default());
+            continue;
+        }
+        if line.eq_ignore_ascii_case("END:VEVENT") {
+            drafts.extend(draft.take());
+            continue;
+        }
+
+        let Some(event) = draft.as_mut() else {
+            continue;
+        };
+        let Some((name_and_params, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name_and_params.split_once(';').unwrap_or((name_and_params, ""));
+        match name.to_ascii_uppercase().as_str() {
+            "UID" => event.uid = Some(value.to_string()),
+            "SUMMARY" => event.summary = unescape(value),
+            "DTSTART" => event.start = parse_time(value, params),
+            "DTEND" => event.end = parse_time(value, params),
+            "RECURRENCE-ID" => {
+                let instant = parse_time(value, params).and_then(|(naive, zone)| zone.resolve(naive));
+                event.recurrence_id = Some((value.to_string(), instant));
+            }
+            "RRULE" => event.rrule = Some(value.to_string()),
+            "EXDATE" => event.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|value| parse_time(value, params))
+                    .filter_map(|(naive, zone)| zone.resolve(naive)),
+            ),
+            "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    // Instances edited on their own, by the UID of the series they belong to.
+    let overridden: HashSet<(String, DateTime<Utc>)> = drafts
+        .iter()
+        .filter_map(|draft| {
+            let (_, instant) = draft.recurrence_id.as_ref()?;
+            Some((draft.uid.clone()?, (*instant)?))
+        })
+        .collect();
+
+    let mut events = Vec::new();
+    for draft in drafts {
+        let (Some(uid), Some((start, zone))) = (draft.uid, draft.start) else {
+            continue;
+        };
+        let Some(first) = zone.resolve(start) else {
+            continue;
+        };
+        let length = draft
+            .end
+            .and_then(|(naive, zone)| zone.resolve(naive))
+            .map(|end| end - first);
+
+        if let Some((recurrence_id, _)) = draft.recurrence_id {
+            events.push(IcsEvent {
+                uid: format!("{uid}/{recurrence_id}"),
+                summary: draft.summary,
+                start: first,
+                end: length.map(|length| first + length),
+                cancelled: draft.cancelled,
+            });
+            continue;
+        }
+
+        let instances = match draft.rrule.as_deref().and_then(parse_rrule) {
+            Some(rule) => expand(start, zone, &rule, until),
+            None => vec![first],
+        };
+        for instance in instances {
+            if draft.exdates.contains(&instance) || overridden.contains(&(uid.clone(), instance)) {
+                continue;
+            }
+            events.push(IcsEvent {
+                uid: uid.clone(),
+                summary: draft.summary.clone(),
+                start: instance,
+                end: length.map(|length| instance + length),
+                cancelled: draft.cancelled,
+            });
+        }
+    }
+    events
+}
+
+/// UTC times end in `Z`. Others are read in their `TZID`, or as local time
+/// when there is none or it isn't an IANA name (Outlook writes Windows
+/// names), which matches feeds written for the Mac's own time zone.
+fn parse_time(value: &str, params: &str) -> Option<(NaiveDateTime, Zone)> {
+    if param(params, "VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE")) {
+        return None;
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((naive, Zone::Utc));
+    }
+    // A bare date without VALUE=DATE is still an all-day event.
+    if NaiveDate::parse_from_str(value, "%Y%m%d").is_ok() {
+        return None;
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = param(params, "TZID")
+        .and_then(|tzid| tzid.trim_matches('"').parse::<Tz>().ok())
+        .map_or(Zone::Local, Zone::Named);
+    Some((naive, zone))
+}
+
+/// The value of a `;NAME=value` property parameter.
+fn param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params
+        .split(';')
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut frequency = None;
+    let mut rule = Recurrence {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        until_utc: false,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+    };
+    for part in value.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|interval| *interval > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => {
+                rule.until_utc = value.ends_with('Z');
+                let value = value.trim_end_matches('Z');
+                rule.until = Some(
+                    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+                        .ok()
+                        .or_else(|| NaiveDate::parse_from_str(value, "%Y%m%d").ok()?.and_hms_opt(23, 59, 59))?,
+                );
+            }
+            "BYDAY" => {
+                for day in value.split(',') {
+                    let split = day.len().checked_sub(2)?;
+                    let ordinal = match &day[..split] {
+                        "" => None,
+                        ordinal => Some(ordinal.trim_start_matches('+').parse::<i32>().ok()?),
+                    };
+                    rule.by_day.push((ordinal, weekday(&day[split..])?));
+                }
+            }
+            "BYMONTHDAY" => {
+                for day in value.split(',') {
+                    rule.by_month_day.push(day.parse().ok()?);
+                }
+            }
+            // Weeks start on Monday unless told otherwise; only Monday is understood.
+            "WKST" if value.eq_ignore_ascii_case("MO") => {}
+            _ => return None,
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    Some(match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// The instances of a recurring event that start no later than `until`,
+/// the first one included. Instances keep their wall-clock time in the
+/// event's zone, so a 9:00 meeting stays at 9:00 across daylight saving.
+fn expand(start: NaiveDateTime, zone: Zone, rule: &Recurrence, until: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    // Periods are stepped in wall-clock time; zones are at most a day off UTC.
+    let last_period = until.naive_utc() + ChronoDuration::days(2);
+    let rule_until = rule.until.and_then(|naive| if rule.until_utc { Zone::Utc } else { zone }.resolve(naive));
+    let time = start.time();
+    let mut instances = Vec::new();
+    let mut seen = 0;
+    for period in 0u32.. {
+        let Some(step) = period.checked_mul(rule.interval) else {
+            break;
+        };
+        let (period_start, mut days) = match rule.frequency {
+            Frequency::Daily => {
+                let Some(day) = start.date().checked_add_signed(ChronoDuration::days(i64::from(step))) else {
+                    break;
+                };
+                (day, vec![day])
+            }
+            Frequency::Weekly => {
+                let from_monday = i64::from(start.weekday().num_days_from_monday());
+                let first_monday = start.date() - ChronoDuration::days(from_monday);
+                let Some(monday) = first_monday
+                    .checked_add_signed(ChronoDuration::weeks(i64::from(step)))
+                else {
+                    break;
+                };
+                let days = if rule.by_day.is_empty() {
+                    vec![monday + ChronoDuration::days(from_monday)]
+                } else {
+                    rule.by_day
+                        .iter()
+                        .map(|(_, day)| monday + ChronoDuration::days(i64::from(day.num_days_from_monday())))
+                        .collect()
+                };
+                (monday, days)
+            }
+            Frequency::Monthly => {
+                let months = u64::from(start.month0()) + u64::from(step);
+                let Some(first) = i32::try_from(months / 12)
+                    .ok()
+                    .and_then(|years| start.year().checked_add(years))
+                    .and_then(|year| NaiveDate::from_ymd_opt(year, (months % 12) as u32 + 1, 1))
+                else {
+                    break;
+                };
+                (first, month_days(first, start.day(), rule))
+            }
+            Frequency::Yearly => {
+                let Some(first) = i32::try_from(step)
+                    .ok()
+                    .and_then(|years| start.year().checked_add(years))
+                    .and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1))
+                else {
+                    break;
+                };
+                // Feb 29 only recurs in leap years.
+                (first, NaiveDate::from_ymd_opt(first.year(), start.month(), start.day()).into_iter().collect())
+            }
+        };
+        if period_start.and_time(time) > last_period {
+            break;
+        }
+        if rule.frequency == Frequency::Daily && !rule.by_day.is_empty() {
+            days.retain(|day| rule.by_day.iter().any(|(_, weekday)| *weekday == day.weekday()));
+        }
+        days.sort();
+        days.dedup();
+        for day in days {
+            let naive = day.and_time(time);
+            if naive < start {
+                continue;
+            }
+            // A wall-clock time skipped by a daylight saving change.
+            let Some(instance) = zone.resolve(naive) else {
+                continue;
+            };
+            if rule_until.is_some_and(|rule_until| instance > rule_until)
+                || rule.count.is_some_and(|count| seen >= count)
+            {
+                return instances;
+            }
+            seen += 1;
+            if instance <= until {
+                instances.push(instance);
+            }
+        }
+    }
+    instances
+}
+
+/// The days of `first`'s month a monthly rule falls on; months without the
+/// start's day (the 31st, say) are skipped.
+fn month_days(first: NaiveDate, start_day: u32, rule: &Recurrence) -> Vec<NaiveDate> {
+    let next_month = first + Months::new(1);
+    let length = (next_month - first).num_days() as i32;
+    let in_month = |day: i32| {
+        let day = if day < 0 { length + day + 1 } else { day };
+        (1..=length).contains(&day).then(|| first + ChronoDuration::days(i64::from(day - 1)))
+    };
+    if !rule.by_day.is_empty() {
+        let mut days = Vec::new();
+        for (ordinal, weekday) in &rule.by_day {
+            let matching: Vec<NaiveDate> = (1..=length)
+                .filter_map(in_month)
+                .filter(|day| day.weekday() == *weekday)
+                .collect();
+            match ordinal {
+                None => days.extend(matching),
+                Some(ordinal) if *ordinal > 0 => days.extend(matching.get(*ordinal as usize - 1)),
+                Some(ordinal) => {
+                    let index = matching.len().checked_sub(ordinal.unsigned_abs() as usize);
+                    days.extend(index.and_then(|index| matching.get(index)));
+                }
+            }
+        }
+        return days;
+    }
+    if !rule.by_month_day.is_empty() {
+        return rule.by_month_day.iter().filter_map(|day| in_month(*day)).collect();
+    }
+    in_month(start_day as i32).into_iter().collect()
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    /// A feed holding `events`, each a list of property lines.
+    fn feed(events: &[&[&str]]) -> String {
+        let mut lines = vec!["BEGIN:VCALENDAR"];
+        for event in events {
+            lines.push("BEGIN:VEVENT");
+            lines.extend_from_slice(event);
+            lines.push("END:VEVENT");
+        }
+        lines.push("END:VCALENDAR");
+        lines.join("\r\n")
+    }
+
+    fn starts(feed: &str, until: &str) -> Vec<String> {
+        parse_events(feed, utc(until))
+            .into_iter()
+            .map(|event| event.start.to_rfc3339())
+            .collect()
+    }
+
+    #[test]
+    fn reads_utc_and_tzid_times() {
+        let feed = feed(&[
+            &["UID:a", "SUMMARY:Standup\\, daily", "DTSTART:20260310T090000Z", "DTEND:20260310T091500Z"],
+            &["UID:b", "SUMMARY:Review", "DTSTART;TZID=America/New_York:20260310T090000"],
+            &["UID:c", "SUMMARY:Lunch", "DTSTART;TZID=\"Europe/Berlin\":20260310T120000"],
+            &["UID:d", "SUMMARY:Holiday", "DTSTART;VALUE=DATE:20260310"],
+        ]);
+        let events = parse_events(&feed, utc("2026-12-31T00:00:00Z"));
+        let found: Vec<(&str, &str, String)> = events
+            .iter()
+            .map(|event| (event.uid.as_str(), event.summary.as_str(), event.start.to_rfc3339()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("a", "Standup, daily", "2026-03-10T09:00:00+00:00".to_string()),
+                // New York is already on daylight saving time, Berlin is not.
+                ("b", "Review", "2026-03-10T13:00:00+00:00".to_string()),
+                ("c", "Lunch", "2026-03-10T11:00:00+00:00".to_string()),
+            ]
+        );
+        assert_eq!(events[0].end, Some(utc("2026-03-10T09:15:00Z")));
+    }
+
+    #[test]
+    fn unfolds_long_lines() {
+        let feed = feed(&[&["UID:a", "SUMMARY:Quarterly plan\r\n ning review", "DTSTART:20260310T090000Z"]]);
+        let events = parse_events(&feed, utc("2026-12-31T00:00:00Z"));
+        assert_eq!(events[0].summary, "Quarterly planning review");
+    }
+
+    #[test]
+    fn expands_weekly_rules_on_local_wall_clock_time() {
+        let feed = feed(&[&[
+            "UID:a",
+            "DTSTART;TZID=America/New_York:20260303T090000",
+            "RRULE:FREQ=WEEKLY;BYDAY=TU,TH",
+        ]]);
+        // Clocks go forward on March 8; the meeting stays at 9:00.
+        assert_eq!(
+            starts(&feed, "2026-03-12T23:59:59Z"),
+            [
+                "2026-03-03T14:00:00+00:00",
+                "2026-03-05T14:00:00+00:00",
+                "2026-03-10T13:00:00+00:00",
+                "2026-03-12T13:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_at_count_until_and_the_horizon() {
+        let daily = |rule: &str| {
+            feed(&[&["UID:a", "DTSTART:20260301T080000Z", &format!("RRULE:{rule}")]])
+        };
+        assert_eq!(starts(&daily("FREQ=DAILY;COUNT=2"), "2026-12-31T00:00:00Z").len(), 2);
+        assert_eq!(starts(&daily("FREQ=DAILY;UNTIL=20260303T080000Z"), "2026-12-31T00:00:00Z").len(), 3);
+        assert_eq!(starts(&daily("FREQ=DAILY;INTERVAL=2"), "2026-03-07T08:00:00Z").len(), 4);
+        // Rules using parts that aren't understood keep only their first instance.
+        assert_eq!(starts(&daily("FREQ=DAILY;BYHOUR=8,20"), "2026-12-31T00:00:00Z").len(), 1);
+    }
+
+    #[test]
+    fn expands_monthly_and_yearly_rules() {
+        let last_friday = feed(&[&["UID:a", "DTSTART:20260130T170000Z", "RRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=3"]]);
+        assert_eq!(
+            starts(&last_friday, "2026-12-31T00:00:00Z"),
+            ["2026-01-30T17:00:00+00:00", "2026-02-27T17:00:00+00:00", "2026-03-27T17:00:00+00:00"]
+        );
+        let the_31st = feed(&[&["UID:a", "DTSTART:20260131T090000Z", "RRULE:FREQ=MONTHLY;COUNT=3"]]);
+        assert_eq!(
+            starts(&the_31st, "2026-12-31T00:00:00Z"),
+            ["2026-01-31T09:00:00+00:00", "2026-03-31T09:00:00+00:00", "2026-05-31T09:00:00+00:00"]
+        );
+        let leap_day = feed(&[&["UID:a", "DTSTART:20240229T090000Z", "RRULE:FREQ=YEARLY"]]);
+        assert_eq!(
+            starts(&leap_day, "2029-01-01T00:00:00Z"),
+            ["2024-02-29T09:00:00+00:00", "2028-02-29T09:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn leaves_out_exdates_and_overridden_instances() {
+        let feed = feed(&[
+            &[
+                "UID:a",
+                "SUMMARY:Sync",
+                "DTSTART:20260302T100000Z",
+                "RRULE:FREQ=DAILY;COUNT=4",
+                "EXDATE:20260303T100000Z",
+            ],
+            &[
+                "UID:a",
+                "SUMMARY:Sync (moved)",
+                "RECURRENCE-ID:20260304T100000Z",
+                "DTSTART:20260304T150000Z",
+            ],
+        ]);
+        let events = parse_events(&feed, utc("2026-12-31T00:00:00Z"));
+        let found: Vec<(&str, &str, String)> = events
+            .iter()
+            .map(|event| (event.uid.as_str(), event.summary.as_str(), event.start.to_rfc3339()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("a", "Sync", "2026-03-02T10:00:00+00:00".to_string()),
+                ("a", "Sync", "2026-03-05T10:00:00+00:00".to_string()),
+                ("a/20260304T100000Z", "Sync (moved)", "2026-03-04T15:00:00+00:00".to_string()),
+            ]
+        );
+    }
+}
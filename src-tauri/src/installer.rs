@@ -0,0 +1,321 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use semver::Version;
+use serde::Serialize;
+
+use crate::normalize_version;
+
+/// Must match `identifier` in tauri.conf.json.
+const BUNDLE_IDENTIFIER: &str = "com.maks.lockpilot";
+/// Downloaded installers are named `LockPilot-<tag>.dmg` in the temp dir.
+const DMG_PREFIX: &str = "LockPilot-";
+/// Installers that were never opened are removed after this long.
+const STALE_DMG_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Installer DMGs removed from the download cache.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCacheReport {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+struct CachedDmg {
+    path: PathBuf,
+    tag: String,
+    bytes: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Where the installer for `tag` is downloaded to.
+pub fn dmg_path(tag: &str) -> PathBuf {
+    let safe_tag = tag.replace('/', "-");
+    std::env::temp_dir().join(format!("{DMG_PREFIX}{safe_tag}.dmg"))
+}
+
+fn cached_dmgs() -> Vec<CachedDmg> {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let tag = name.strip_prefix(DMG_PREFIX)?.strip_suffix(".dmg")?.to_string();
+            let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+            Some(CachedDmg {
+                path: entry.path(),
+                tag,
+                bytes: metadata.len(),
+                modified: metadata.modified().ok(),
+            })
+        })
+        .collect()
+}
+
+fn remove(dmgs: impl IntoIterator<Item = CachedDmg>) -> UpdateCacheReport {
+    let mut report = UpdateCacheReport::default();
+    for dmg in dmgs {
+        if fs::remove_file(&dmg.path).is_ok() {
+            report.files += 1;
+            report.bytes += dmg.bytes;
+        }
+    }
+    report
+}
+
+/// Deletes every downloaded installer.
+pub fn clear_cache() -> UpdateCacheReport {
+    remove(cached_dmgs())
+}
+
+/// Deletes installers for `running` or older, which were installed or
+/// skipped, and any left unopened for `STALE_DMG_AGE`.
+pub fn sweep_cache(running: Option<&Version>) -> UpdateCacheReport {
+    let now = SystemTime::now();
+    remove(cached_dmgs().into_iter().filter(|dmg| {
+        let installed = running
+            .zip(normalize_version(&dmg.tag))
+            .is_some_and(|(running, version)| version.cmp_precedence(running).is_le());
+        let stale = dmg
+            .modified
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age >= STALE_DMG_AGE);
+        installed || stale
+    }))
+}
+
+/// Mounts the downloaded DMG read-only and hidden from Finder, checks it
+/// holds a LockPilot app bundle, and detaches it again. A truncated
+/// download fails to mount; a DMG for some other app fails the bundle
+/// check.
+pub fn verify_dmg(dmg: &Path) -> Result<(), String> {
+    let mount_point = attach(dmg)?;
+    let result = find_lockpilot_app(&mount_point).map(|_| ());
+    detach(&mount_point);
+    result
+}
+
+/// The newest release found by an update check this session, installed on
+/// quit when the user opted in.
+#[derive(Clone, Default)]
+pub struct PendingUpdate {
+    inner: Arc<Mutex<Option<PendingRelease>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingRelease {
+    pub tag: String,
+    pub download_url: String,
+}
+
+impl PendingUpdate {
+    pub fn set(&self, release: Option<PendingRelease>) {
+        if let Ok(mut pending) = self.inner.lock() {
+            *pending = release;
+        }
+    }
+
+    pub fn take(&self) -> Option<PendingRelease> {
+        self.inner.lock().ok().and_then(|mut pending| pending.take())
+    }
+}
+
+/// Replaces the running app bundle with the one in `dmg`, without the
+/// user dragging it to Applications. The new bundle is copied next to the
+/// old one first and swapped in with two renames, so a failed copy leaves
+/// the installed app untouched. The copy is only swapped in if its
+/// signature is intact, Gatekeeper accepts it and it is signed by the same
+/// team as the running app.
+pub fn install_in_place(dmg: &Path) -> Result<PathBuf, String> {
+    let bundle = running_bundle()?;
+    let mount_point = attach(dmg)?;
+    let result = find_lockpilot_app(&mount_point).and_then(|app| replace_bundle(&app, &bundle));
+    detach(&mount_point);
+    result.map(|_| bundle)
+}
+
+/// Opens `bundle` once this process has exited.
+pub fn relaunch_after_exit(bundle: &Path) -> Result<(), String> {
+    // The path is passed as `$0` so it needs no quoting.
+    Command::new("/bin/sh")
+        .args(["-c", "sleep 2; exec /usr/bin/open -n \"$0\""])
+        .arg(bundle)
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| format!("Failed to schedule relaunch: {err}"))
+}
+
+/// The `.app` directory the current executable lives in.
+fn running_bundle() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|err| format!("Failed to locate LockPilot: {err}"))?;
+    exe.ancestors()
+        .find(|path| path.extension().is_some_and(|extension| extension == "app"))
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "LockPilot is not running from an app bundle".to_string())
+}
+
+fn replace_bundle(source: &Path, bundle: &Path) -> Result<(), String> {
+    let staged = bundle.with_extension("app.new");
+    let previous = bundle.with_extension("app.old");
+    let _ = fs::remove_dir_all(&staged);
+    let _ = fs::remove_dir_all(&previous);
+
+    let status = Command::new("/usr/bin/ditto")
+        .arg(source)
+        .arg(&staged)
+        .status()
+        .map_err(|err| format!("Failed to run ditto: {err}"))?;
+    if !status.success() {
+        let _ = fs::remove_dir_all(&staged);
+        return Err(format!("Failed to copy the new version into place (ditto exited with {status})"));
+    }
+    if let Err(err) = verify_signature(&staged, bundle) {
+        let _ = fs::remove_dir_all(&staged);
+        return Err(err);
+    }
+
+    fs::rename(bundle, &previous).map_err(|err| format!("Failed to move the old version aside: {err}"))?;
+    if let Err(err) = fs::rename(&staged, bundle) {
+        let _ = fs::rename(&previous, bundle);
+        return Err(format!("Failed to move the new version into place: {err}"));
+    }
+    let _ = fs::remove_dir_all(&previous);
+    Ok(())
+}
+
+/// Checks `app` the way Gatekeeper would on first open, which an in-place
+/// swap skips, and that the running `bundle`'s team signed it.
+fn verify_signature(app: &Path, bundle: &Path) -> Result<(), String> {
+    let output = Command::new("/usr/bin/codesign")
+        .args(["--verify", "--deep", "--strict"])
+        .arg(app)
+        .output()
+        .map_err(|err| format!("Failed to run codesign: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "The new version's code signature is invalid ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let output = Command::new("/usr/sbin/spctl")
+        .args(["--assess", "--type", "execute"])
+        .arg(app)
+        .output()
+        .map_err(|err| format!("Failed to run spctl: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Gatekeeper rejected the new version ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let Some(expected) = team_identifier(bundle) else {
+        return Err("This copy of LockPilot isn't signed by a developer team, so updates can't be checked; \
+            install from the DMG instead"
+            .to_string());
+    };
+    match team_identifier(app) {
+        Some(team) if team == expected => Ok(()),
+        Some(team) => Err(format!("The new version is signed by team {team}, not {expected}")),
+        None => Err(format!("The new version isn't signed by team {expected}")),
+    }
+}
+
+/// `TeamIdentifier` from `codesign -dv`, which prints it to stderr; `None`
+/// for unsigned and ad-hoc signed apps.
+fn team_identifier(app: &Path) -> Option<String> {
+    let output = Command::new("/usr/bin/codesign")
+        .args(["-d", "--verbose=2"])
+        .arg(app)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .find_map(|line| line.strip_prefix("TeamIdentifier="))
+        .map(str::trim)
+        .filter(|team| !team.is_empty() && *team != "not set")
+        .map(str::to_string)
+}
+
+fn attach(dmg: &Path) -> Result<PathBuf, String> {
+    let output = Command::new("/usr/bin/hdiutil")
+        .args(["attach", "-nobrowse", "-readonly", "-noautoopen", "-noverify"])
+        .arg(dmg)
+        .output()
+        .map_err(|err| format!("Failed to run hdiutil: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Installer DMG could not be mounted; the download may be incomplete ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // One line per partition: device, content hint, then the mount point
+    // for the ones that were mounted, tab-separated.
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split('\t').nth(2))
+        .map(str::trim)
+        .find(|mount_point| !mount_point.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| "Installer DMG has no mountable volume".to_string())
+}
+
+fn detach(mount_point: &Path) {
+    let detached = Command::new("/usr/bin/hdiutil")
+        .args(["detach", "-quiet"])
+        .arg(mount_point)
+        .status()
+        .is_ok_and(|status| status.success());
+    if !detached {
+        let _ = Command::new("/usr/bin/hdiutil")
+            .args(["detach", "-quiet", "-force"])
+            .arg(mount_point)
+            .status();
+    }
+}
+
+fn find_lockpilot_app(mount_point: &Path) -> Result<PathBuf, String> {
+    let entries = fs::read_dir(mount_point).map_err(|err| format!("Failed to read installer DMG: {err}"))?;
+    let mut found = Vec::new();
+    for app in entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "app"))
+    {
+        match bundle_identifier(&app) {
+            Some(identifier) if identifier == BUNDLE_IDENTIFIER => return Ok(app),
+            Some(identifier) => found.push(identifier),
+            None => {}
+        }
+    }
+
+    if found.is_empty() {
+        Err("Installer DMG does not contain an app bundle".to_string())
+    } else {
+        Err(format!(
+            "Installer DMG contains {} instead of LockPilot ({BUNDLE_IDENTIFIER})",
+            found.join(", ")
+        ))
+    }
+}
+
+fn bundle_identifier(app: &Path) -> Option<String> {
+    let output = Command::new("/usr/libexec/PlistBuddy")
+        .args(["-c", "Print :CFBundleIdentifier"])
+        .arg(app.join("Contents/Info.plist"))
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let identifier = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!identifier.is_empty()).then_some(identifier)
+}
@@ -0,0 +1,93 @@
+use std::env;
+
+use tauri::{Manager, Runtime};
+
+use crate::SettingsStore;
+
+/// Starts LockPilot read-only regardless of settings, e.g. from a lab
+/// machine's login item: `open -a LockPilot --args --read-only`.
+const LAUNCH_FLAG: &str = "--read-only";
+
+/// Commands that only read state, and so still work in read-only mode.
+/// Everything else, including commands added later, is rejected there.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "get_dashboard_state",
+    "preview_occurrences",
+    "validate_timer",
+    "health_check",
+    "run_self_test",
+    "get_app_info",
+    "list_crash_reports",
+    "get_permission_status",
+    "request_permissions",
+    "list_timers",
+    "get_global_pause",
+    "get_quiet_hours",
+    "get_working_hours",
+    "get_usage_limit",
+    "get_usage_today",
+    "get_usage_report",
+    "get_break_reminders",
+    "get_break_stats",
+    "get_idle_seconds",
+    "get_power_status",
+    "get_system_info",
+    "get_history_retention",
+    "list_archived_timers",
+    "get_archive_retention",
+    "get_lock_compatibility",
+    "get_calendar_log",
+    "get_push_settings",
+    "get_email_settings",
+    "get_holiday_region",
+    "list_ics_subscriptions",
+    "list_focus_sessions",
+    "list_blocked_domains",
+    "get_history",
+    "get_activity_heatmap",
+    "get_audit_log",
+    "list_profiles",
+    "list_workspaces",
+    "list_plugins",
+    "list_shortcuts",
+    "list_vpn_services",
+    "list_alarm_sounds",
+    "get_remote_api_settings",
+    "list_api_tokens",
+    "discover_fleet_peers",
+    "get_managed_config",
+    "get_policy",
+    "get_read_only",
+    "get_sync_settings",
+    "list_sync_peers",
+    "list_release_versions",
+    "check_channel_update",
+    "get_install_update_on_quit",
+    "get_stay_awake_minutes",
+    "get_conflict_policy",
+    "get_helper_status",
+];
+
+/// Whether LockPilot was started with `--read-only`; kept in app state.
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOnlyLaunch(pub bool);
+
+impl ReadOnlyLaunch {
+    pub fn from_args() -> Self {
+        Self(env::args().any(|arg| arg == LAUNCH_FLAG))
+    }
+}
+
+/// Read-only through the launch flag or the `readOnly` setting, which an
+/// organization would lock through managed preferences.
+pub fn is_read_only<R: Runtime, M: Manager<R>>(manager: &M) -> bool {
+    manager.try_state::<ReadOnlyLaunch>().is_some_and(|launch| launch.0)
+        || manager
+            .try_state::<SettingsStore>()
+            .and_then(|settings| settings.snapshot().ok())
+            .is_some_and(|settings| settings.read_only)
+}
+
+pub fn allows(command: &str) -> bool {
+    READ_ONLY_COMMANDS.contains(&command)
+}
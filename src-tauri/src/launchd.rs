@@ -0,0 +1,277 @@
+use std::{fs, path::Path};
+
+use chrono::{Datelike, Local, Timelike};
+use serde::Serialize;
+
+use crate::{
+    appearance_script, countdown_script,
+    error::{CommandError, CommandResult},
+    lock::LOCK_SHORTCUT_SCRIPT,
+    plugins::PluginRegistry,
+    popup_script, script_arguments, ssh,
+    vpn::VpnState,
+    RecurrencePreset, TimerAction, TimerInfo,
+};
+
+const LABEL_PREFIX: &str = "com.lockpilot.timer";
+const NOT_EXPORTABLE: &str = "Push and email timers can't be exported; their settings and credentials live in LockPilot";
+/// What `disks::eject_external` does, as a shell loop.
+const EJECT_EXTERNAL_SCRIPT: &str = concat!(
+    "for disk in $(/usr/sbin/diskutil list external physical | /usr/bin/grep '^/dev/disk' | /usr/bin/cut -d' ' -f1); ",
+    "do /usr/sbin/diskutil eject \"$disk\"; done"
+);
+/// What `network::disable_all` and `network::restore` do, without
+/// remembering which services were already off.
+const NETWORK_SERVICES_SCRIPT: &str = concat!(
+    "/usr/sbin/networksetup -listallnetworkservices | /usr/bin/tail -n +2 | /usr/bin/sed 's/^\\*//' | ",
+    "while IFS= read -r service; do /usr/sbin/networksetup -setnetworkserviceenabled \"$service\" \"$1\"; done"
+);
+const NIGHT_SHIFT_NOT_EXPORTABLE: &str = "Night Shift timers can't be exported; no command-line tool controls Night Shift";
+const TRIGGER_NOT_EXPORTABLE: &str = "Triggered timers can't be exported; launchd only starts jobs on a schedule";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchdExport {
+    pub path: String,
+    pub label: String,
+    /// Parts of the timer launchd can't reproduce.
+    pub warnings: Vec<String>,
+}
+
+/// Writes a LaunchAgent plist that runs the timer's action on the same
+/// schedule without LockPilot running. Load it with
+/// `launchctl bootstrap gui/$(id -u) <path>`.
+pub fn export(info: &TimerInfo, plugins: &PluginRegistry, path: &Path) -> CommandResult<LaunchdExport> {
+    if !path.is_absolute() {
+        return Err(CommandError::validation("Export path must be absolute").with_context("path"));
+    }
+    if matches!(info.action, TimerAction::Push | TimerAction::Email) {
+        return Err(CommandError::validation(NOT_EXPORTABLE).with_context("action"));
+    }
+    if matches!(info.action, TimerAction::NightShift { .. }) {
+        return Err(CommandError::validation(NIGHT_SHIFT_NOT_EXPORTABLE).with_context("action"));
+    }
+    if info.trigger.is_some() {
+        return Err(CommandError::validation(TRIGGER_NOT_EXPORTABLE).with_context("trigger"));
+    }
+
+    let label = format!("{LABEL_PREFIX}.{}", info.id);
+    let mut warnings = Vec::new();
+    let arguments = program_arguments(info, plugins).map_err(CommandError::not_found)?;
+    let schedule = schedule_keys(info, &mut warnings);
+
+    if info.condition.is_some() {
+        warnings.push("The firing condition is not exported; the action always runs.".to_string());
+    }
+    if info.escalation.is_some() || info.repeat_until_acknowledged.is_some() {
+        warnings.push("Escalation and repeat reminders are not exported; the popup is shown once.".to_string());
+    }
+    if info.hooks.is_some() {
+        warnings.push("Pre/post hooks are not exported.".to_string());
+    }
+    if info.chat_webhook.is_some() {
+        warnings.push("The chat webhook is not exported; runs are not posted.".to_string());
+    }
+    if matches!(info.action, TimerAction::FadeVolume { .. }) {
+        warnings.push("The volume is set to the target at once, without fading.".to_string());
+    }
+    if matches!(info.action, TimerAction::Alarm { .. }) {
+        warnings.push("The sound plays once, without the alarm dialog, snooze or volume change.".to_string());
+    }
+    if matches!(info.action, TimerAction::NetworkOn) {
+        warnings.push("Every network service is turned on, even ones that were off before the cutoff.".to_string());
+    }
+    if matches!(info.action, TimerAction::PauseMedia) {
+        warnings.push("Only Music and Spotify are paused; other players keep playing.".to_string());
+    }
+    if info.eject_disks.is_some() {
+        warnings.push("Disks are not ejected first; only the action is exported.".to_string());
+    }
+    if info.pause_media {
+        warnings.push("Media is not paused first; only the action is exported.".to_string());
+    }
+    if info.must_run {
+        warnings.push("launchd doesn't wake the Mac; a sleeping Mac runs the job on wake instead.".to_string());
+    }
+    if info.shutdown_options.is_some() {
+        warnings.push("Unsaved-work and quit-apps safeguards are not exported.".to_string());
+    }
+    if matches!(info.action, TimerAction::Shortcut { input: Some(_), .. }) {
+        warnings.push("The shortcut's input is not exported; it runs without input.".to_string());
+    }
+    if matches!(info.action, TimerAction::JavaScript { .. }) {
+        warnings.push("The script's scheduled-time argument is empty; launchd doesn't pass it.".to_string());
+    }
+    if matches!(info.action, TimerAction::Plugin { .. }) {
+        warnings.push("The plugin receives LOCKPILOT_* variables but no JSON on stdin.".to_string());
+    }
+
+    let mut plist = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+        "<plist version=\"1.0\">\n<dict>\n",
+    ));
+    if let Some(notes) = &info.notes {
+        // "--" may not appear inside an XML comment.
+        let mut notes = notes.replace('\n', "\n  ");
+        while notes.contains("--") {
+            notes = notes.replace("--", "- -");
+        }
+        plist.push_str(&format!("  <!--\n  {notes}\n  -->\n"));
+    }
+    plist.push_str(&format!("  <key>Label</key>\n  <string>{}</string>\n", xml_escape(&label)));
+    plist.push_str("  <key>ProgramArguments</key>\n  <array>\n");
+    for argument in &arguments {
+        plist.push_str(&format!("    <string>{}</string>\n", xml_escape(argument)));
+    }
+    plist.push_str("  </array>\n");
+    plist.push_str("  <key>EnvironmentVariables</key>\n  <dict>\n");
+    for (name, value) in environment(info) {
+        plist.push_str(&format!(
+            "    <key>{name}</key>\n    <string>{}</string>\n",
+            xml_escape(&value)
+        ));
+    }
+    plist.push_str("  </dict>\n");
+    plist.push_str(&schedule);
+    plist.push_str("</dict>\n</plist>\n");
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| CommandError::io("Failed to create export directory", err))?;
+    }
+    fs::write(path, plist).map_err(|err| CommandError::io("Failed to write launchd plist", err))?;
+
+    Ok(LaunchdExport {
+        path: path.display().to_string(),
+        label,
+        warnings,
+    })
+}
+
+/// The closest standalone equivalent of what `run_action` does in-app.
+fn program_arguments(info: &TimerInfo, plugins: &PluginRegistry) -> Result<Vec<String>, String> {
+    let osascript = |script: String| vec!["/usr/bin/osascript".to_string(), "-e".to_string(), script];
+
+    Ok(match &info.action {
+        TimerAction::Popup => osascript(popup_script(info.message.as_deref().unwrap_or_default())),
+        TimerAction::Lock => osascript(LOCK_SHORTCUT_SCRIPT.to_string()),
+        TimerAction::Shutdown => osascript("tell application \"System Events\" to shut down".to_string()),
+        TimerAction::Reboot => osascript("tell application \"System Events\" to restart".to_string()),
+        TimerAction::Logout => osascript("tell application \"System Events\" to log out".to_string()),
+        TimerAction::DisplaySleep => vec!["/usr/bin/pmset".to_string(), "displaysleepnow".to_string()],
+        TimerAction::SystemSleep => vec!["/usr/bin/pmset".to_string(), "sleepnow".to_string()],
+        TimerAction::Plugin { id } => vec![plugins.find(id)?.executable().display().to_string()],
+        TimerAction::AppleScript { script } => osascript(script.clone()),
+        TimerAction::JavaScript { script } => ["/usr/bin/osascript", "-l", "JavaScript", "-e", script]
+            .into_iter()
+            .map(str::to_string)
+            .chain(script_arguments(info, None))
+            .collect(),
+        TimerAction::Shortcut { name, .. } => vec!["/usr/bin/shortcuts".to_string(), "run".to_string(), name.clone()],
+        TimerAction::Automator { path } => vec!["/usr/bin/automator".to_string(), path.clone()],
+        TimerAction::SetAppearance { mode } => osascript(appearance_script(*mode)),
+        TimerAction::PauseMedia => osascript(
+            ["Music", "Spotify"]
+                .map(|player| format!("if application \"{player}\" is running then tell application \"{player}\" to pause"))
+                .join("\n"),
+        ),
+        TimerAction::FadeVolume { target_percent, .. } => {
+            osascript(format!("set volume output volume {target_percent}"))
+        }
+        TimerAction::Countdown { sound } => osascript(countdown_script(
+            info.message.as_deref().unwrap_or("Countdown finished"),
+            sound.as_deref(),
+        )),
+        TimerAction::Alarm { sound, .. } => vec![
+            "/usr/bin/afplay".to_string(),
+            format!("/System/Library/Sounds/{sound}.aiff"),
+        ],
+        TimerAction::EjectDisks => vec!["/bin/sh".to_string(), "-c".to_string(), EJECT_EXTERNAL_SCRIPT.to_string()],
+        TimerAction::Vpn { service, state } => vec![
+            "/usr/sbin/scutil".to_string(),
+            "--nc".to_string(),
+            match state {
+                VpnState::Connect => "start",
+                VpnState::Disconnect => "stop",
+            }
+            .to_string(),
+            service.clone(),
+        ],
+        TimerAction::NetworkOff | TimerAction::NetworkOn => vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            NETWORK_SERVICES_SCRIPT.to_string(),
+            "sh".to_string(),
+            if matches!(info.action, TimerAction::NetworkOn) { "on" } else { "off" }.to_string(),
+        ],
+        TimerAction::SshCommand { host, user, command } => std::iter::once("/usr/bin/ssh".to_string())
+            .chain(ssh::arguments(host, user.as_deref(), command))
+            .collect(),
+        TimerAction::Push | TimerAction::Email => return Err(NOT_EXPORTABLE.to_string()),
+        TimerAction::NightShift { .. } => return Err(NIGHT_SHIFT_NOT_EXPORTABLE.to_string()),
+    })
+}
+
+fn environment(info: &TimerInfo) -> Vec<(&'static str, String)> {
+    let mut vars = vec![
+        ("LOCKPILOT_TIMER_ID", info.id.clone()),
+        ("LOCKPILOT_ACTION", crate::action_label(&info.action).to_string()),
+        ("LOCKPILOT_RECURRING", if info.recurrence.is_some() { "1" } else { "0" }.to_string()),
+    ];
+    if let Some(message) = &info.message {
+        vars.push(("LOCKPILOT_MESSAGE", message.clone()));
+    }
+    if let TimerAction::Plugin { id } = &info.action {
+        vars.push(("LOCKPILOT_PLUGIN_ID", id.clone()));
+    }
+    vars
+}
+
+/// launchd calendar intervals are in local time, like the app's presets.
+fn schedule_keys(info: &TimerInfo, warnings: &mut Vec<String>) -> String {
+    let target = info.target_time.with_timezone(&Local);
+    let calendar = |extra: &str| {
+        format!(
+            "    <dict>\n{extra}      <key>Hour</key>\n      <integer>{}</integer>\n      <key>Minute</key>\n      <integer>{}</integer>\n    </dict>\n",
+            target.hour(),
+            target.minute()
+        )
+    };
+    let interval = |seconds: u32| format!("  <key>StartInterval</key>\n  <integer>{seconds}</integer>\n");
+
+    let Some(recurrence) = &info.recurrence else {
+        warnings.push("launchd has no one-shot schedule; the job repeats yearly on this date until unloaded.".to_string());
+        let extra = format!(
+            "      <key>Month</key>\n      <integer>{}</integer>\n      <key>Day</key>\n      <integer>{}</integer>\n",
+            target.month(),
+            target.day()
+        );
+        return format!("  <key>StartCalendarInterval</key>\n  <array>\n{}  </array>\n", calendar(&extra));
+    };
+
+    match recurrence.preset {
+        RecurrencePreset::Daily => format!("  <key>StartCalendarInterval</key>\n  <array>\n{}  </array>\n", calendar("")),
+        RecurrencePreset::Weekdays => {
+            let days: String = (1..=5)
+                .map(|day| calendar(&format!("      <key>Weekday</key>\n      <integer>{day}</integer>\n")))
+                .collect();
+            format!("  <key>StartCalendarInterval</key>\n  <array>\n{days}  </array>\n")
+        }
+        RecurrencePreset::EveryNHours => {
+            warnings.push("Intervals are counted from when launchd loads the job, not from the timer's next run.".to_string());
+            interval(recurrence.interval_hours.unwrap_or(1) * 3600)
+        }
+        RecurrencePreset::EveryNMinutes => {
+            warnings.push("Intervals are counted from when launchd loads the job, not from the timer's next run.".to_string());
+            interval(recurrence.interval_minutes.unwrap_or(1) * 60)
+        }
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
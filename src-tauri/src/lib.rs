@@ -0,0 +1,5492 @@
+
+mod alarm;
+#[cfg(feature = "app")]
+mod app;
+#[cfg(feature = "app")]
+mod app_info;
+mod audit;
+mod automator;
+mod breaks;
+mod calendar_log;
+mod chat;
+pub mod cli;
+mod conditions;
+#[cfg(feature = "app")]
+mod crash;
+#[cfg(feature = "app")]
+mod crontab;
+mod dialogs;
+mod disks;
+mod email;
+#[cfg(feature = "app")]
+mod error;
+mod exec;
+#[cfg(feature = "app")]
+mod fleet;
+#[cfg(feature = "app")]
+mod focus;
+#[cfg(feature = "app")]
+mod health;
+mod helper;
+mod holidays;
+#[cfg(feature = "app")]
+mod hosts;
+#[cfg(feature = "app")]
+mod ics;
+#[cfg(feature = "app")]
+mod installer;
+#[cfg(feature = "app")]
+mod kiosk;
+#[cfg(feature = "app")]
+mod launchd;
+mod lock;
+#[cfg(target_os = "macos")]
+mod macos_ffi;
+mod managed;
+mod media;
+mod notifications;
+mod network;
+mod plugins;
+mod policy;
+mod push;
+mod quick_entry;
+mod remote_api;
+mod scheduler;
+#[cfg(feature = "app")]
+mod self_test;
+mod shortcuts;
+mod ssh;
+mod sync;
+mod system;
+#[cfg(feature = "app")]
+mod tray;
+mod triggers;
+mod usage;
+mod vpn;
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+#[cfg(feature = "app")]
+use std::collections::BTreeMap;
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+#[cfg(feature = "app")]
+use chrono::{FixedOffset, Timelike};
+#[cfg(feature = "app")]
+use reqwest::blocking::Client;
+#[cfg(feature = "app")]
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "app")]
+use app_info::AppInfo;
+use audit::{AuditLog, AuditOperation};
+#[cfg(feature = "app")]
+use audit::AuditEntry;
+use breaks::BreakReminders;
+#[cfg(feature = "app")]
+use breaks::{BreakStats, DailyBreaks};
+use chat::ChatWebhook;
+#[cfg(feature = "app")]
+use crash::{CrashReport, CrashReports};
+#[cfg(feature = "app")]
+use crontab::SkippedCrontabLine;
+use dialogs::DialogQueue;
+use email::SmtpSettings;
+#[cfg(feature = "app")]
+use error::{CommandError, CommandResult, ErrorCode};
+#[cfg(feature = "app")]
+use fleet::{FleetPeer, FleetTarget, PeerCredentials, PushedTimer};
+#[cfg(feature = "app")]
+use focus::{BlockMode, FocusSession, FocusSessions};
+#[cfg(feature = "app")]
+use health::HealthReport;
+use helper::{HelperRequest, ScheduledWake};
+#[cfg(feature = "app")]
+use helper::HelperStatus;
+use holidays::{Holiday, HolidayCalendar};
+#[cfg(feature = "app")]
+use ics::{IcsMapping, IcsSubscription, IcsSubscriptions};
+#[cfg(feature = "app")]
+use installer::{PendingRelease, PendingUpdate, UpdateCacheReport};
+#[cfg(feature = "app")]
+use launchd::LaunchdExport;
+use lock::LockReport;
+use managed::ManagedTimerKey;
+#[cfg(feature = "app")]
+use managed::ManagedSummary;
+use plugins::PluginRegistry;
+#[cfg(feature = "app")]
+use plugins::PluginInfo;
+use policy::PolicyStore;
+#[cfg(feature = "app")]
+use policy::Policy;
+use push::PushTarget;
+#[cfg(feature = "app")]
+use quick_entry::Diagnostic;
+use scheduler::DeadlineQueue;
+#[cfg(feature = "app")]
+use self_test::SelfTestReport;
+use sync::SyncSettings;
+#[cfg(feature = "app")]
+use sync::{SyncPeer, SyncState};
+use system::{Permission, PowerEvent};
+#[cfg(feature = "app")]
+use system::{PermissionState, PowerStatus, SystemInfo};
+use triggers::{TimerTrigger, TriggerCondition, TriggerMonitor};
+use usage::UsageLimit;
+#[cfg(feature = "app")]
+use usage::UsageTracker;
+use vpn::VpnState;
+use remote_api::RemoteApiSettings;
+#[cfg(feature = "app")]
+use remote_api::{ApiScope, ApiTokenInfo, ApiTokenStore, CreatedApiToken, RemoteApi, TlsCertificateInfo};
+
+#[cfg(feature = "app")]
+pub use app::run;
+pub use audit::AuditOrigin;
+#[cfg(feature = "fake-system")]
+pub use exec::fake;
+
+#[cfg(feature = "app")]
+const GITHUB_OWNER: &str = "maxacode";
+#[cfg(feature = "app")]
+const GITHUB_REPO: &str = "LockPilotMac";
+const MAX_HISTORY_ENTRIES: usize = 500;
+const MAX_ARCHIVED_TIMERS: usize = 200;
+const DEFAULT_PROFILE: &str = "Default";
+/// The workspace kept in `timers.json`, which can't be deleted.
+const DEFAULT_WORKSPACE: &str = "Default";
+#[cfg(feature = "app")]
+const UPCOMING_OCCURRENCES: usize = 3;
+#[cfg(feature = "app")]
+const MAX_SNOOZE_MINUTES: u32 = 24 * 60;
+#[cfg(feature = "app")]
+const MAX_SHIFT_MINUTES: i64 = 24 * 60;
+const MAX_TAGS: usize = 10;
+const MAX_TAG_CHARS: usize = 32;
+#[cfg(feature = "app")]
+const MAX_PREVIEW_OCCURRENCES: usize = 50;
+const MAX_JITTER_MINUTES: u32 = 120;
+/// Timers this close together are reported by `validate_timer`.
+const CONFLICT_WINDOW_SECONDS: i64 = 60;
+/// Notification category whose action buttons map onto timer operations.
+#[cfg(feature = "app")]
+const TIMER_NOTIFICATION_CATEGORY: &str = "LOCKPILOT_TIMER";
+#[cfg(feature = "app")]
+const NOTIFICATION_ACTION_SNOOZE: &str = "snooze-10m";
+#[cfg(feature = "app")]
+const NOTIFICATION_ACTION_CANCEL: &str = "cancel-timer";
+#[cfg(feature = "app")]
+const NOTIFICATION_SNOOZE_MINUTES: u32 = 10;
+const MAX_WARNING_MINUTES: u32 = 24 * 60;
+/// How often the janitor looks for expired and stale timers.
+#[cfg(feature = "app")]
+const JANITOR_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Where `lockpilot --create` leaves timers for the running app, one
+/// `CreateTimerRequest` per JSON file.
+const INCOMING_DIR: &str = "Incoming";
+/// How often the running app looks in `INCOMING_DIR`.
+#[cfg(feature = "app")]
+const INCOMING_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+#[cfg(feature = "app")]
+const COUNTDOWN_TICK_INTERVAL: Duration = Duration::from_secs(1);
+/// One-time timers this far past their target are considered dead.
+const STALE_TIMER_HOURS: i64 = 24;
+/// Longest a scheduler thread sleeps before re-checking the wall clock, so
+/// a clock jump or a system sleep only delays a timer by this much.
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// How long a stay-awake assertion outlasts the due time, covering
+/// confirmations and hooks before the action itself.
+const STAY_AWAKE_GRACE: Duration = Duration::from_secs(10 * 60);
+#[cfg(feature = "app")]
+const MAX_STAY_AWAKE_MINUTES: u32 = 60;
+/// Longest any process started by an action may run; a lock or power
+/// event that takes longer is stuck.
+const ACTION_PROCESS_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+/// Popups wait for someone to click OK, so they get much longer before the
+/// dialog is taken down.
+const POPUP_PROCESS_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+/// How much longer than its `giving up after` a repeating or escalating
+/// popup's dialog may run before it counts as stuck.
+const POPUP_GIVE_UP_GRACE: Duration = Duration::from_secs(60);
+/// User scripts may drive slow apps, so they get longer than built-in
+/// actions.
+const SCRIPT_PROCESS_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const HOOK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const MAX_FADE_MINUTES: u32 = 120;
+/// Longest script result kept in a history entry.
+const MAX_SCRIPT_RESULT_CHARS: usize = 500;
+/// AppleScript error when the user cancels a dialog or prompt.
+const USER_CANCELED_ERROR: &str = "-128";
+#[cfg(feature = "app")]
+const WEBSITE_BLOCKING_NEEDS_HELPER: &str = "Blocking websites needs the privileged helper; install it first";
+#[cfg(feature = "app")]
+const MANAGED_TIMER_SNOOZE: &str = "This timer is managed by your organization and can't be snoozed";
+/// A lock this close to a shutdown, restart or logout counts as
+/// conflicting with it.
+const ACTION_CONFLICT_WINDOW: ChronoDuration = ChronoDuration::seconds(60);
+/// How often an action held by `resolve_conflict` checks on the one it
+/// waits for.
+const CONFLICT_CHECK_INTERVAL: ChronoDuration = ChronoDuration::seconds(1);
+/// How long before a `must_run` timer (and its stay-awake window) the Mac
+/// is woken.
+const WAKE_LEAD_MINUTES: i64 = 2;
+/// How often the Mac's UTC offset is polled for time zone (or DST) changes.
+#[cfg(feature = "app")]
+const TIMEZONE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum TimerAction {
+    Popup,
+    Lock,
+    Shutdown,
+    Reboot,
+    Logout,
+    /// Turns the displays off; the Mac keeps running.
+    DisplaySleep,
+    SystemSleep,
+    /// A user-installed action from the plugin directory.
+    Plugin { id: String },
+    /// Runs a user-written AppleScript; its result is kept in history.
+    AppleScript { script: String },
+    /// Runs a user-written JavaScript for Automation script, which gets the
+    /// timer's metadata as `run(argv)` arguments.
+    JavaScript { script: String },
+    /// Runs a shortcut from Shortcuts.app, optionally with text input; its
+    /// text output is kept in history.
+    Shortcut {
+        name: String,
+        #[serde(default)]
+        input: Option<String>,
+    },
+    /// Runs an Automator `.workflow` bundle at this absolute path.
+    Automator { path: String },
+    /// Switches between Dark and Light Mode.
+    SetAppearance { mode: AppearanceMode },
+    /// Pauses whatever music or video is playing.
+    PauseMedia,
+    /// Ejects every external disk.
+    EjectDisks,
+    /// Turns off every network service, for internet-free evenings.
+    NetworkOff,
+    /// Turns back on the services `NetworkOff` turned off.
+    NetworkOn,
+    /// Runs `command` on another machine over ssh with key authentication;
+    /// the remote exit status is kept in history.
+    SshCommand {
+        host: String,
+        /// Defaults to the user `~/.ssh/config` names for the host.
+        #[serde(default)]
+        user: Option<String>,
+        command: String,
+    },
+    /// Connects or disconnects a VPN service from System Settings.
+    Vpn { service: String, state: VpnState },
+    /// Lowers the output volume to `target_percent` gradually over
+    /// `over_minutes`.
+    #[serde(rename_all = "camelCase")]
+    FadeVolume { target_percent: u8, over_minutes: u32 },
+    /// Turns Night Shift on or off; `strength` is a percentage.
+    NightShift {
+        enabled: bool,
+        #[serde(default)]
+        strength: Option<u8>,
+    },
+    /// Sends the message to the ntfy topic or Pushover user in settings.
+    Push,
+    /// Mails the message through the SMTP server in settings.
+    Email,
+    /// Wakes the display and plays a system sound at `volume_percent` or
+    /// louder until the alarm dialog is stopped; see `alarm`.
+    #[serde(rename_all = "camelCase")]
+    Alarm {
+        sound: String,
+        volume_percent: u8,
+        #[serde(default = "alarm::default_snooze_minutes")]
+        snooze_minutes: u32,
+    },
+    /// A kitchen timer: only a notification, with an optional system sound,
+    /// when it reaches zero. Its remaining time is sent to the UI every
+    /// second as `countdown-tick`.
+    Countdown {
+        #[serde(default)]
+        sound: Option<String>,
+    },
+}
+
+impl TimerAction {
+    /// Actions that end the login session and take `ShutdownOptions`.
+    fn ends_session(&self) -> bool {
+        matches!(self, TimerAction::Shutdown | TimerAction::Reboot | TimerAction::Logout)
+    }
+
+    /// Where the action stands in `resolve_conflict`: a lock gives way to
+    /// any session end, a logout to a restart or shutdown, a restart to a
+    /// shutdown. `None` for actions that never conflict.
+    fn finality(&self) -> Option<u8> {
+        match self {
+            TimerAction::Lock => Some(0),
+            TimerAction::Logout => Some(1),
+            TimerAction::Reboot => Some(2),
+            TimerAction::Shutdown => Some(3),
+            _ => None,
+        }
+    }
+
+    /// Actions that must run on time, so the Mac is kept awake for
+    /// `stay_awake_minutes` beforehand.
+    fn needs_mac_awake(&self) -> bool {
+        matches!(
+            self,
+            TimerAction::Shutdown | TimerAction::Reboot | TimerAction::Plugin { .. } | TimerAction::Alarm { .. }
+        )
+    }
+
+    /// How long a process started by this action may run before it is
+    /// killed and the run recorded as failed. Plugins also have their own
+    /// timeout.
+    fn process_timeout(&self) -> Duration {
+        match self {
+            TimerAction::Popup | TimerAction::Alarm { .. } => POPUP_PROCESS_TIMEOUT,
+            TimerAction::AppleScript { .. }
+            | TimerAction::JavaScript { .. }
+            | TimerAction::Shortcut { .. }
+            | TimerAction::Automator { .. }
+            | TimerAction::SshCommand { .. } => SCRIPT_PROCESS_TIMEOUT,
+            _ => ACTION_PROCESS_TIMEOUT,
+        }
+    }
+
+    /// Actions that may pause media playback first.
+    fn can_pause_media_first(&self) -> bool {
+        matches!(self, TimerAction::Lock | TimerAction::DisplaySleep | TimerAction::SystemSleep)
+    }
+
+    /// The OSA language and body of a user-written script action.
+    fn user_script(&self) -> Option<(&'static str, &str)> {
+        match self {
+            TimerAction::AppleScript { script } => Some(("AppleScript", script)),
+            TimerAction::JavaScript { script } => Some(("JavaScript", script)),
+            _ => None,
+        }
+    }
+
+    /// Actions that only inform the user and may be held back by quiet hours.
+    fn is_non_critical(&self) -> bool {
+        matches!(self, TimerAction::Popup)
+    }
+}
+
+/// Whether a timer would run code its own request supplied: a hook script,
+/// or an AppleScript, JavaScript or SSH command action or escalation.
+/// Shortcuts, workflows and plugins only name what is already installed.
+#[cfg(feature = "app")]
+fn runs_code(action: &TimerAction, escalation: Option<&TimerAction>, hooks: Option<&TimerHooks>) -> bool {
+    let is_script = |action: &TimerAction| {
+        matches!(
+            action,
+            TimerAction::AppleScript { .. } | TimerAction::JavaScript { .. } | TimerAction::SshCommand { .. }
+        )
+    };
+    is_script(action)
+        || escalation.is_some_and(is_script)
+        || hooks.is_some_and(|hooks| hooks.pre_script.is_some() || hooks.post_script.is_some())
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum UpdateChannel {
+    Main,
+    Dev,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecurrencePreset {
+    Daily,
+    Weekdays,
+    EveryNHours,
+    EveryNMinutes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecurrenceConfig {
+    preset: RecurrencePreset,
+    interval_hours: Option<u32>,
+    interval_minutes: Option<u32>,
+    /// Skip occurrences that fall on a public holiday in the settings'
+    /// `holiday_region`.
+    #[serde(default)]
+    skip_holidays: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum UnsavedWorkPolicy {
+    Abort,
+    Proceed,
+}
+
+/// Extra steps for Logout/Shutdown/Reboot timers before the request is sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShutdownOptions {
+    /// Ask running apps to quit first and wait up to this many seconds.
+    quit_apps_timeout_seconds: Option<u32>,
+    #[serde(default)]
+    abort_if_apps_refuse: bool,
+    /// Check for windows with unsaved changes before anything is quit.
+    unsaved_work_policy: Option<UnsavedWorkPolicy>,
+    /// Skip the "Are you sure?" dialog. Unforced requests show the system
+    /// confirmation, which proceeds by itself after its countdown unless
+    /// someone cancels it.
+    #[serde(default)]
+    force: bool,
+    /// Sets "Reopen windows when logging back in" for this run; `None`
+    /// leaves the user's current choice alone.
+    reopen_windows: Option<bool>,
+}
+
+/// Ejecting external disks before the Mac shuts down or sleeps, so they
+/// aren't cut off mid-write.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EjectDisksOptions {
+    /// Cancel the action when a disk is in use; otherwise it proceeds and
+    /// the user is told which disks are still attached.
+    #[serde(default)]
+    abort_if_busy: bool,
+}
+
+/// Asks before the action runs; it only proceeds if someone accepts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfirmationOptions {
+    /// How long the dialog waits for an answer.
+    timeout_seconds: u32,
+    /// Whether the action runs when nobody answers in time.
+    #[serde(default)]
+    proceed_on_timeout: bool,
+}
+
+/// For Popup timers: if nobody clicks OK within `after_minutes`, the popup
+/// is withdrawn and `action` runs instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EscalationPolicy {
+    after_minutes: u32,
+    action: TimerAction,
+}
+
+/// For Popup timers: show the popup again every `every_minutes` until
+/// someone clicks OK, at most `max_repeats` more times.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NagPolicy {
+    every_minutes: u32,
+    max_repeats: u32,
+}
+
+/// The random offset of one occurrence of a jittered timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JitterRoll {
+    /// The unjittered time of the occurrence it was rolled for.
+    occurrence: DateTime<Utc>,
+    offset_seconds: i64,
+}
+
+/// Shell snippets run with `/bin/sh -c` around the timer's action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimerHooks {
+    pre_script: Option<String>,
+    post_script: Option<String>,
+    #[serde(default)]
+    abort_on_pre_failure: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerInfo {
+    id: String,
+    action: TimerAction,
+    target_time: DateTime<Utc>,
+    recurrence: Option<RecurrenceConfig>,
+    message: Option<String>,
+    /// Free-form, possibly multi-line; why the timer exists. Never shown
+    /// by the action itself.
+    notes: Option<String>,
+    shutdown_options: Option<ShutdownOptions>,
+    hooks: Option<TimerHooks>,
+    /// Rhai expression checked at fire time; the action only runs if true.
+    condition: Option<String>,
+    /// Fires when this is met rather than at `target_time`, which only
+    /// arms it; see `triggers`.
+    trigger: Option<TimerTrigger>,
+    #[serde(default = "default_profile")]
+    profile: String,
+    /// Which workspace lists the timer; set from the file it's kept in.
+    #[serde(default = "default_workspace")]
+    workspace: String,
+    created_at: DateTime<Utc>,
+    /// The timer is removed once this passes, even if it would run again.
+    expires_at: Option<DateTime<Utc>>,
+    /// The timer is removed once `run_count` reaches this.
+    max_runs: Option<u32>,
+    /// Occurrences whose action actually ran, successfully or not; skipped
+    /// and suppressed ones don't count.
+    #[serde(default)]
+    run_count: u32,
+    last_run_at: Option<DateTime<Utc>>,
+    /// Each occurrence fires at a random point within this many minutes
+    /// either side of its scheduled time. `target_time` stays unjittered so
+    /// recurrences don't drift.
+    jitter_minutes: Option<u32>,
+    /// The offset rolled for the coming occurrence; see `jittered_due`.
+    #[serde(default)]
+    jitter: Option<JitterRoll>,
+    /// Posts a notification with Snooze and Cancel buttons this many
+    /// minutes before each run.
+    #[serde(default)]
+    warning_minutes: Option<u32>,
+    /// Where `target_time` was before `shift_timers` moved it. Only that
+    /// one occurrence moves: the recurrence continues from this time.
+    shifted_from: Option<DateTime<Utc>>,
+    confirmation: Option<ConfirmationOptions>,
+    escalation: Option<EscalationPolicy>,
+    repeat_until_acknowledged: Option<NagPolicy>,
+    /// Overrides the global working hours for this timer.
+    working_hours: Option<WorkingHours>,
+    /// Runs at any hour, even when global working hours are set.
+    #[serde(default)]
+    ignore_working_hours: bool,
+    /// Posts to a Slack or Discord channel after each run.
+    chat_webhook: Option<ChatWebhook>,
+    /// Wake the Mac shortly before each run if it is asleep.
+    #[serde(default)]
+    must_run: bool,
+    /// Pause media playback right before a lock or sleep.
+    #[serde(default)]
+    pause_media: bool,
+    /// Eject external disks before a shutdown, restart, logout or sleep.
+    eject_disks: Option<EjectDisksOptions>,
+    /// Lowercase labels; timers with a tag picked in sync settings are
+    /// replicated to paired Macs.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Last snooze or shift; `None` until then. Sync keeps whichever copy
+    /// of a timer was changed last.
+    #[serde(default)]
+    modified_at: Option<DateTime<Utc>>,
+    /// Set on timers materialized from a calendar subscription.
+    source: Option<TimerSource>,
+    /// Set on mandatory timers from managed preferences, which only
+    /// LockPilot itself may cancel.
+    #[serde(default)]
+    managed: Option<ManagedTimerKey>,
+}
+
+impl TimerInfo {
+    /// The request `create` would have needed for this timer, so copies
+    /// from elsewhere (e.g. a sync peer) go through the same `validate`.
+    /// The profile is left out; callers map it to one of theirs.
+    #[cfg(feature = "app")]
+    fn to_request(&self) -> CreateTimerRequest {
+        CreateTimerRequest {
+            action: self.action.clone(),
+            target_time: self.target_time.to_rfc3339(),
+            recurrence: self.recurrence.clone(),
+            message: self.message.clone(),
+            notes: self.notes.clone(),
+            shutdown_options: self.shutdown_options.clone(),
+            hooks: self.hooks.clone(),
+            condition: self.condition.clone(),
+            trigger: self.trigger.clone(),
+            profile: None,
+            expires_at: self.expires_at.map(|expires_at| expires_at.to_rfc3339()),
+            max_runs: self.max_runs,
+            jitter_minutes: self.jitter_minutes,
+            warning_minutes: self.warning_minutes,
+            confirmation: self.confirmation.clone(),
+            escalation: self.escalation.clone(),
+            repeat_until_acknowledged: self.repeat_until_acknowledged.clone(),
+            working_hours: self.working_hours.clone(),
+            ignore_working_hours: self.ignore_working_hours,
+            chat_webhook: self.chat_webhook.clone(),
+            must_run: self.must_run,
+            pause_media: self.pause_media,
+            eject_disks: self.eject_disks.clone(),
+            tags: self.tags.clone(),
+            source: self.source.clone(),
+            managed: self.managed.clone(),
+        }
+    }
+}
+
+/// Which calendar event a timer was generated from, so syncs can tell
+/// their own timers apart and replace them when the event changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimerSource {
+    subscription_id: String,
+    /// The event's UID and fire time; a moved event gets a new key.
+    event_key: String,
+}
+
+/// A timer as returned to frontends, with countdown fields computed at call
+/// time so clients don't each redo the math against RFC3339 strings.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TimerView {
+    #[serde(flatten)]
+    info: TimerInfo,
+    seconds_remaining: i64,
+    remaining_text: String,
+    /// The occurrences after `target_time`, for recurring timers.
+    next_occurrences: Option<Vec<DateTime<Utc>>>,
+}
+
+#[cfg(feature = "app")]
+impl TimerView {
+    fn new(info: TimerInfo, now: DateTime<Utc>) -> Self {
+        let seconds_remaining = (info.target_time - now).num_seconds().max(0);
+        let next_occurrences = info
+            .recurrence
+            .as_ref()
+            .map(|recurrence| runs_after(info.target_time, recurrence, UPCOMING_OCCURRENCES));
+
+        Self {
+            seconds_remaining,
+            remaining_text: humanize_remaining(seconds_remaining),
+            next_occurrences,
+            info,
+        }
+    }
+}
+
+/// The next `count` runs after `current`, stepping with `compute_next_run`.
+#[cfg(feature = "app")]
+fn runs_after(current: DateTime<Utc>, recurrence: &RecurrenceConfig, count: usize) -> Vec<DateTime<Utc>> {
+    let mut runs = Vec::with_capacity(count);
+    let mut current = current;
+    while runs.len() < count {
+        let Some(next) = compute_next_run(current, recurrence) else {
+            break;
+        };
+        runs.push(next);
+        current = next;
+    }
+    runs
+}
+
+#[cfg(feature = "app")]
+fn humanize_remaining(seconds: i64) -> String {
+    if seconds <= 0 {
+        return "due now".to_string();
+    }
+
+    let days = seconds / 86_400;
+    let hours = (seconds % 86_400) / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let secs = seconds % 60;
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else {
+        format!("{hours}h {minutes}m {secs}s")
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ValidationIssue {
+    /// Request field the issue is about, in its camelCase form.
+    field: &'static str,
+    message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TimerValidation {
+    errors: Vec<ValidationIssue>,
+    warnings: Vec<ValidationIssue>,
+}
+
+impl TimerValidation {
+    fn error(&mut self, field: &'static str, message: impl Into<String>) {
+        self.errors.push(ValidationIssue {
+            field,
+            message: message.into(),
+        });
+    }
+
+    fn warning(&mut self, field: &'static str, message: impl Into<String>) {
+        self.warnings.push(ValidationIssue {
+            field,
+            message: message.into(),
+        });
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DashboardTimer {
+    id: String,
+    action: TimerAction,
+    target_time: DateTime<Utc>,
+    seconds_remaining: i64,
+    remaining_text: String,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DashboardState {
+    next_timer: Option<DashboardTimer>,
+    active_count: usize,
+    paused: bool,
+    paused_until: Option<DateTime<Utc>>,
+    active_profile: String,
+    last_execution: Option<HistoryEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTimerRequest {
+    action: TimerAction,
+    /// RFC3339, epoch milliseconds, or a local date-time without an offset;
+    /// see `parse_target_time`.
+    target_time: String,
+    recurrence: Option<RecurrenceConfig>,
+    message: Option<String>,
+    notes: Option<String>,
+    shutdown_options: Option<ShutdownOptions>,
+    hooks: Option<TimerHooks>,
+    condition: Option<String>,
+    trigger: Option<TimerTrigger>,
+    /// Defaults to the active profile.
+    profile: Option<String>,
+    /// Same formats as `target_time`; see `TimerInfo::expires_at`.
+    expires_at: Option<String>,
+    max_runs: Option<u32>,
+    jitter_minutes: Option<u32>,
+    #[serde(default)]
+    warning_minutes: Option<u32>,
+    confirmation: Option<ConfirmationOptions>,
+    escalation: Option<EscalationPolicy>,
+    repeat_until_acknowledged: Option<NagPolicy>,
+    working_hours: Option<WorkingHours>,
+    #[serde(default)]
+    ignore_working_hours: bool,
+    chat_webhook: Option<ChatWebhook>,
+    #[serde(default)]
+    must_run: bool,
+    #[serde(default)]
+    pause_media: bool,
+    eject_disks: Option<EjectDisksOptions>,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Only set internally by calendar syncs.
+    #[serde(skip)]
+    source: Option<TimerSource>,
+    /// Only set internally for managed preferences.
+    #[serde(skip)]
+    managed: Option<ManagedTimerKey>,
+}
+
+/// How a `targetTime` string was read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum TargetTimeFormat {
+    Rfc3339,
+    EpochMillis,
+    /// No offset given; interpreted in the Mac's time zone.
+    LocalDateTime,
+}
+
+/// `create_timer`'s response: the new timer plus how its `targetTime` was
+/// interpreted.
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatedTimerView {
+    #[serde(flatten)]
+    timer: TimerView,
+    target_time_format: TargetTimeFormat,
+}
+
+#[cfg(feature = "app")]
+impl CreatedTimerView {
+    fn new(info: TimerInfo, target_time_format: TargetTimeFormat, now: DateTime<Utc>) -> Self {
+        Self {
+            timer: TimerView::new(info, now),
+            target_time_format,
+        }
+    }
+}
+
+struct TimerEntry {
+    info: TimerInfo,
+    /// `None` while the timer's profile is inactive and nothing is scheduled.
+    cancel_tx: Option<mpsc::Sender<()>>,
+}
+
+/// Where events and the Dock badge go; the Tauri app once it is built, see
+/// `app.rs`. Without one, as in the integration tests, both are dropped.
+trait Frontend: Send + Sync {
+    fn emit(&self, event: &str, payload: serde_json::Value);
+    /// `None` clears the badge; false if it couldn't be set.
+    fn set_badge_count(&self, count: Option<i64>) -> bool;
+}
+
+#[derive(Clone)]
+pub struct TimerStore {
+    inner: Arc<Mutex<HashMap<String, TimerEntry>>>,
+    storage_path: Arc<PathBuf>,
+    settings: SettingsStore,
+    history: HistoryStore,
+    archive: TimerArchive,
+    plugins: PluginRegistry,
+    audit: AuditLog,
+    holidays: HolidayCalendar,
+    /// Set once the Tauri app is built; used for the Dock badge and events.
+    frontend: Arc<Mutex<Option<Arc<dyn Frontend>>>>,
+    /// Last count shown on the Dock badge, so unchanged counts don't touch
+    /// the window.
+    badge_shown: Arc<Mutex<Option<usize>>>,
+    /// Next occurrence of every scheduled timer, served by the thread
+    /// `spawn_scheduler` starts.
+    queue: DeadlineQueue<ScheduledRun>,
+    /// Triggered timers waiting on their condition, sampled by the thread
+    /// `triggers::spawn_monitor` starts.
+    triggers: TriggerMonitor,
+    /// Occurrences currently being fired, for `health_check`.
+    firing: Arc<AtomicUsize>,
+    /// Times `timers` took over the lock from a thread that panicked while
+    /// holding it, for `health_check`.
+    lock_recoveries: Arc<AtomicUsize>,
+    /// Popups and confirmations, shown one at a time.
+    dialogs: DialogQueue,
+    /// The last shutdown, restart or logout that started, for
+    /// `resolve_conflict`.
+    last_session_end: Arc<Mutex<Option<SessionEnd>>>,
+    /// Set from managed preferences and the local policy file at launch.
+    policy: PolicyStore,
+}
+
+#[derive(Clone)]
+struct SessionEnd {
+    timer_id: String,
+    action: TimerAction,
+    started_at: DateTime<Utc>,
+    /// `None` while the action runs.
+    succeeded: Option<bool>,
+}
+
+/// Counts an occurrence as firing until its thread returns or unwinds.
+struct FiringGuard(Arc<AtomicUsize>);
+
+impl FiringGuard {
+    fn enter(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter.clone())
+    }
+}
+
+impl Drop for FiringGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl TimerStore {
+    fn new(
+        storage_path: PathBuf,
+        settings: SettingsStore,
+        history: HistoryStore,
+        archive: TimerArchive,
+        plugins: PluginRegistry,
+        audit: AuditLog,
+        holidays: HolidayCalendar,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(HashMap::new())),
+            triggers: TriggerMonitor::new(storage_path.with_file_name("trigger-baselines.json")),
+            storage_path: Arc::new(storage_path),
+            settings,
+            history,
+            archive,
+            plugins,
+            audit,
+            holidays,
+            frontend: Arc::new(Mutex::new(None)),
+            badge_shown: Arc::new(Mutex::new(None)),
+            queue: DeadlineQueue::new(),
+            firing: Arc::new(AtomicUsize::new(0)),
+            lock_recoveries: Arc::new(AtomicUsize::new(0)),
+            dialogs: DialogQueue::default(),
+            last_session_end: Arc::new(Mutex::new(None)),
+            policy: PolicyStore::default(),
+        }
+    }
+
+    /// The store over LockPilot's state files in `data_dir`, with settings,
+    /// history and archived timers loaded. Timers still need `restore_timers`.
+    pub fn open(data_dir: &Path) -> Self {
+        let settings = SettingsStore::new(data_dir.join("settings.json"));
+        if let Err(err) = settings.load_persisted() {
+            eprintln!("Failed to load settings: {err}");
+        }
+        let history = HistoryStore::new(data_dir.join("history.json"));
+        if let Err(err) = history.load_persisted() {
+            eprintln!("Failed to load history: {err}");
+        }
+        let archive = TimerArchive::new(data_dir.join("archive.json"));
+        if let Err(err) = archive.load_persisted() {
+            eprintln!("Failed to load archived timers: {err}");
+        }
+        let plugins = PluginRegistry::new(data_dir.join("plugins"));
+        if let Err(err) = fs::create_dir_all(plugins.dir()) {
+            eprintln!("Failed to create plugin directory: {err}");
+        }
+        Self::new(
+            data_dir.join("timers.json"),
+            settings,
+            history,
+            archive,
+            plugins,
+            AuditLog::new(data_dir.join("audit.log")),
+            HolidayCalendar::new(data_dir.join("holidays.json")),
+        )
+    }
+
+    /// Locks the timer map. A panic on a thread holding the lock poisons it;
+    /// rather than failing every later command until LockPilot restarts,
+    /// the poison is cleared and the map used as the panicking thread left
+    /// it. At worst one timer keeps a partial update.
+    fn timers(&self) -> MutexGuard<'_, HashMap<String, TimerEntry>> {
+        self.inner.lock().unwrap_or_else(|poisoned| {
+            self.inner.clear_poison();
+            self.lock_recoveries.fetch_add(1, Ordering::SeqCst);
+            eprintln!("Recovered the timer store after a panic while it was locked");
+            poisoned.into_inner()
+        })
+    }
+
+    /// Where `timers.json` lives, alongside LockPilot's other state files.
+    fn data_dir(&self) -> PathBuf {
+        self.storage_path.parent().map(Path::to_path_buf).unwrap_or_default()
+    }
+
+    #[cfg(feature = "app")]
+    fn attach_frontend(&self, frontend: Arc<dyn Frontend>) {
+        if let Ok(mut slot) = self.frontend.lock() {
+            *slot = Some(frontend);
+        }
+        self.refresh_badge();
+    }
+
+    fn frontend(&self) -> Option<Arc<dyn Frontend>> {
+        self.frontend.lock().ok().and_then(|frontend| frontend.clone())
+    }
+
+    /// The holiday `at` falls on, if `recurrence` skips holidays and a
+    /// region is configured.
+    fn holiday_skip(&self, recurrence: &RecurrenceConfig, at: DateTime<Utc>) -> Option<Holiday> {
+        if !recurrence.skip_holidays {
+            return None;
+        }
+        let region = self.settings.snapshot().ok()?.holiday_region?;
+        self.holidays.holiday_on(&region, at.with_timezone(&Local).date_naive())
+    }
+
+    /// Tells the frontend; silently dropped before the app is attached.
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let (Some(frontend), Ok(payload)) = (self.frontend(), serde_json::to_value(payload)) {
+            frontend.emit(event, payload);
+        }
+    }
+
+    /// Sets the Dock badge to the number of scheduled timers, clearing it
+    /// when there are none.
+    fn refresh_badge(&self) {
+        let store = self.timers();
+        let count = store.values().filter(|entry| entry.cancel_tx.is_some()).count();
+        drop(store);
+
+        let Ok(mut shown) = self.badge_shown.lock() else {
+            return;
+        };
+        if *shown == Some(count) {
+            return;
+        }
+        let Some(frontend) = self.frontend() else {
+            return;
+        };
+        if frontend.set_badge_count((count > 0).then_some(count as i64)) {
+            *shown = Some(count);
+        }
+    }
+
+    #[cfg(feature = "app")]
+    fn list_views(&self) -> Result<Vec<TimerView>, String> {
+        let now = Utc::now();
+        Ok(self
+            .list()?
+            .into_iter()
+            .map(|info| TimerView::new(info, now))
+            .collect())
+    }
+
+    /// Compact snapshot for frequent polling; only the next timer is cloned.
+    #[cfg(feature = "app")]
+    fn dashboard_state(&self) -> Result<DashboardState, String> {
+        let now = Utc::now();
+        let settings = self.settings.snapshot()?;
+        let (next_timer, active_count) = {
+            let store = self.timers();
+            let active = store
+                .values()
+                .filter(|entry| settings.schedules(&entry.info) && settings.shows(&entry.info));
+            let active_count = active.clone().count();
+            // Triggered timers have no time to count down to.
+            let next_timer = active
+                .filter(|entry| entry.info.trigger.is_none())
+                .min_by_key(|entry| entry.info.target_time)
+                .map(|entry| {
+                    let seconds_remaining = (entry.info.target_time - now).num_seconds().max(0);
+                    DashboardTimer {
+                        id: entry.info.id.clone(),
+                        action: entry.info.action.clone(),
+                        target_time: entry.info.target_time,
+                        seconds_remaining,
+                        remaining_text: humanize_remaining(seconds_remaining),
+                    }
+                });
+            (next_timer, active_count)
+        };
+
+        let paused_until = settings.active_pause(now);
+        Ok(DashboardState {
+            next_timer,
+            active_count,
+            paused: paused_until.is_some(),
+            paused_until,
+            active_profile: settings.active_profile,
+            last_execution: self.history.latest()?,
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<TimerInfo>, String> {
+        let store = self.timers();
+        Ok(store.get(id).map(|entry| entry.info.clone()))
+    }
+
+    /// Timers of the active profile in the open workspace, soonest first.
+    #[cfg(feature = "app")]
+    fn list(&self) -> Result<Vec<TimerInfo>, String> {
+        let settings = self.settings.snapshot()?;
+        let store = self.timers();
+
+        let mut timers: Vec<TimerInfo> = store
+            .values()
+            .filter(|entry| settings.schedules(&entry.info) && settings.shows(&entry.info))
+            .map(|entry| entry.info.clone())
+            .collect();
+        timers.sort_by_key(|timer| timer.target_time);
+
+        Ok(timers)
+    }
+
+    #[cfg(feature = "app")]
+    fn cancel(&self, id: &str, origin: &AuditOrigin) -> Result<bool, String> {
+        let mut store = self.timers();
+
+        let managed = store.get(id).is_some_and(|entry| entry.info.managed.is_some());
+        if managed && !matches!(origin, AuditOrigin::System) {
+            return Err("This timer is managed by your organization and can't be cancelled".to_string());
+        }
+        if let Some(entry) = store.remove(id) {
+            if let Some(cancel_tx) = entry.cancel_tx.as_ref() {
+                let _ = cancel_tx.send(());
+            }
+            drop(store);
+            self.persist()?;
+            let _ = self.audit.record(
+                origin,
+                AuditOperation::Cancel,
+                Some(id),
+                format!("Cancelled {} timer", action_label(&entry.info.action)),
+            );
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Counts a run of the timer's action. Not persisted here; the
+    /// scheduler persists after moving on to the next occurrence.
+    fn record_run(&self, id: &str, at: DateTime<Utc>) -> Result<(), String> {
+        let mut store = self.timers();
+        if let Some(entry) = store.get_mut(id) {
+            entry.info.run_count += 1;
+            entry.info.last_run_at = Some(at);
+        }
+        Ok(())
+    }
+
+    fn run_limit_reached(&self, id: &str) -> bool {
+        self.timers()
+            .get(id)
+            .is_some_and(|entry| entry.info.max_runs.is_some_and(|max_runs| entry.info.run_count >= max_runs))
+    }
+
+    /// Removes timers past their `expires_at` or `max_runs`, and one-time
+    /// timers whose target passed more than `STALE_TIMER_HOURS` ago (e.g.
+    /// dormant in an inactive profile, or imported already in the past),
+    /// moves them to the archive, then emits `timers-expired` with what was
+    /// removed.
+    fn sweep_expired(&self) -> Result<Vec<ExpiredTimer>, String> {
+        let now = Utc::now();
+        let stale_before = now - ChronoDuration::hours(STALE_TIMER_HOURS);
+        let mut store = self.timers();
+
+        let expired: Vec<ExpiredTimer> = store
+            .values()
+            .filter_map(|entry| {
+                let info = &entry.info;
+                let reason = if info.expires_at.is_some_and(|expires_at| expires_at <= now) {
+                    "Expired"
+                } else if info.max_runs.is_some_and(|max_runs| info.run_count >= max_runs) {
+                    "Reached its maximum number of runs"
+                } else if info.recurrence.is_none() && info.trigger.is_none() && info.target_time < stale_before {
+                    "Target time passed long ago"
+                } else {
+                    return None;
+                };
+                Some(ExpiredTimer {
+                    id: info.id.clone(),
+                    action: action_label(&info.action),
+                    reason: reason.to_string(),
+                })
+            })
+            .collect();
+        let mut archived = Vec::new();
+        for timer in &expired {
+            let Some(entry) = store.remove(&timer.id) else {
+                continue;
+            };
+            if let Some(cancel_tx) = entry.cancel_tx {
+                let _ = cancel_tx.send(());
+            }
+            archived.push(ArchivedTimer {
+                timer: entry.info,
+                archived_at: now,
+                reason: timer.reason.clone(),
+            });
+        }
+        drop(store);
+
+        if expired.is_empty() {
+            return Ok(expired);
+        }
+        self.persist()?;
+        if let Err(err) = self.archive.add(archived) {
+            eprintln!("Failed to archive expired timers: {err}");
+        }
+        for timer in &expired {
+            let _ = self.audit.record(
+                &AuditOrigin::System,
+                AuditOperation::Expire,
+                Some(&timer.id),
+                format!("Removed {} timer: {}", timer.action, timer.reason),
+            );
+        }
+        self.emit("timers-expired", expired.clone());
+        Ok(expired)
+    }
+
+    /// Moves daily and weekday timers so they keep firing at the same local
+    /// wall-clock time after the UTC offset changed from `previous` (travel
+    /// or a DST switch), then emits `timezone-changed` with the timers whose
+    /// next run moved. Interval and one-time timers are left alone.
+    #[cfg(feature = "app")]
+    fn reanchor_local_times(&self, previous: FixedOffset) -> Result<Vec<ShiftedTimer>, String> {
+        let now = Utc::now();
+        let mut shifted = Vec::new();
+        let mut rescheduled = Vec::new();
+        {
+            let mut store = self.timers();
+
+            for entry in store.values_mut() {
+                let Some(recurrence) = entry.info.recurrence.as_ref() else {
+                    continue;
+                };
+                if !matches!(recurrence.preset, RecurrencePreset::Daily | RecurrencePreset::Weekdays) {
+                    continue;
+                }
+
+                let wall_time = entry.info.target_time.with_timezone(&previous).naive_local();
+                let Some(mut next_run) = Local
+                    .from_local_datetime(&wall_time)
+                    .earliest()
+                    .map(|next_run| next_run.with_timezone(&Utc))
+                else {
+                    continue;
+                };
+                if next_run <= now {
+                    let Some(upcoming) = next_run_after(next_run, recurrence, now) else {
+                        continue;
+                    };
+                    next_run = upcoming;
+                }
+                if next_run == entry.info.target_time {
+                    continue;
+                }
+
+                shifted.push(ShiftedTimer {
+                    id: entry.info.id.clone(),
+                    action: action_label(&entry.info.action),
+                    previous_run: entry.info.target_time,
+                    next_run,
+                });
+                entry.info.target_time = next_run;
+                if let Some(old_tx) = entry.cancel_tx.take() {
+                    let _ = old_tx.send(());
+                    let (cancel_tx, cancel_rx) = mpsc::channel();
+                    entry.cancel_tx = Some(cancel_tx);
+                    rescheduled.push((entry.info.clone(), cancel_rx));
+                }
+            }
+        }
+
+        if shifted.is_empty() {
+            return Ok(shifted);
+        }
+        self.persist()?;
+        for (info, cancel_rx) in rescheduled {
+            schedule_timer(self, info, cancel_rx);
+        }
+        for timer in &shifted {
+            let _ = self.audit.record(
+                &AuditOrigin::System,
+                AuditOperation::Update,
+                Some(&timer.id),
+                format!("Moved {} timer to keep its local time after a time zone change", timer.action),
+            );
+        }
+        self.emit("timezone-changed", shifted.clone());
+        Ok(shifted)
+    }
+
+    /// A button pressed on a `LOCKPILOT_TIMER` notification. Returns whether
+    /// the timer still exists afterwards.
+    #[cfg(feature = "app")]
+    fn notification_action(&self, category: &str, action: &str, timer_id: &str) -> CommandResult<bool> {
+        if category != TIMER_NOTIFICATION_CATEGORY {
+            return Err(CommandError::validation(format!("Unknown notification category: {category}")));
+        }
+
+        match action {
+            NOTIFICATION_ACTION_SNOOZE => {
+                self.snooze(timer_id, NOTIFICATION_SNOOZE_MINUTES, &AuditOrigin::Notification)?;
+                Ok(true)
+            }
+            NOTIFICATION_ACTION_CANCEL => {
+                self.cancel(timer_id, &AuditOrigin::Notification)?;
+                Ok(false)
+            }
+            _ => Err(CommandError::validation(format!("Unknown notification action: {action}"))),
+        }
+    }
+
+    /// Pushes the timer's next run back by `minutes`, counted from now if it
+    /// is already due. Recurring timers continue from the snoozed time.
+    /// Managed timers can't be snoozed.
+    #[cfg(feature = "app")]
+    fn snooze(&self, id: &str, minutes: u32, origin: &AuditOrigin) -> Result<TimerInfo, String> {
+        check_snooze_minutes(minutes)?;
+
+        let (info, cancel_rx) = {
+            let mut store = self.timers();
+            let entry = store
+                .get_mut(id)
+                .ok_or_else(|| format!("Timer not found: {id}"))?;
+            if entry.info.managed.is_some() {
+                return Err(MANAGED_TIMER_SNOOZE.to_string());
+            }
+
+            entry.info.target_time = entry.info.target_time.max(Utc::now()) + ChronoDuration::minutes(minutes as i64);
+            entry.info.modified_at = Some(Utc::now());
+            let cancel_rx = match entry.cancel_tx.take() {
+                Some(old_tx) => {
+                    let _ = old_tx.send(());
+                    let (cancel_tx, cancel_rx) = mpsc::channel();
+                    entry.cancel_tx = Some(cancel_tx);
+                    Some(cancel_rx)
+                }
+                None => None,
+            };
+            (entry.info.clone(), cancel_rx)
+        };
+
+        self.persist()?;
+        if let Some(cancel_rx) = cancel_rx {
+            schedule_timer(self, info.clone(), cancel_rx);
+        }
+        let _ = self.audit.record(
+            origin,
+            AuditOperation::Update,
+            Some(id),
+            format!("Snoozed {} timer by {minutes} min", action_label(&info.action)),
+        );
+        Ok(info)
+    }
+
+    /// Moves the next run of every timer matching `filter` by `minutes`.
+    /// All of them are checked before any is moved, so either every timer
+    /// moves or none does, and the store is persisted once.
+    #[cfg(feature = "app")]
+    fn shift(&self, minutes: i64, filter: &ShiftFilter, origin: &AuditOrigin) -> Result<Vec<ShiftedTimer>, String> {
+        if minutes == 0 || minutes.abs() > MAX_SHIFT_MINUTES {
+            return Err(format!(
+                "Shift must be between -{MAX_SHIFT_MINUTES} and {MAX_SHIFT_MINUTES} minutes, and not 0"
+            ));
+        }
+        let offset = ChronoDuration::minutes(minutes);
+        let now = Utc::now();
+
+        let mut shifted = Vec::new();
+        let mut rescheduled = Vec::new();
+        {
+            let mut store = self.timers();
+            // Managed timers keep their time.
+            let mut matching: Vec<&mut TimerEntry> = store
+                .values_mut()
+                .filter(|entry| entry.info.managed.is_none() && filter.matches(&entry.info))
+                .collect();
+            if let Some(entry) = matching.iter().find(|entry| entry.info.target_time + offset <= now) {
+                return Err(format!(
+                    "Would move the {} timer {} into the past",
+                    action_label(&entry.info.action),
+                    entry.info.id
+                ));
+            }
+
+            for entry in matching.iter_mut() {
+                let previous_run = entry.info.target_time;
+                shifted.push(ShiftedTimer {
+                    id: entry.info.id.clone(),
+                    action: action_label(&entry.info.action),
+                    previous_run,
+                    next_run: previous_run + offset,
+                });
+                if entry.info.recurrence.is_some() && entry.info.shifted_from.is_none() {
+                    entry.info.shifted_from = Some(previous_run);
+                }
+                entry.info.target_time = previous_run + offset;
+                entry.info.modified_at = Some(now);
+                if let Some(old_tx) = entry.cancel_tx.take() {
+                    let _ = old_tx.send(());
+                    let (cancel_tx, cancel_rx) = mpsc::channel();
+                    entry.cancel_tx = Some(cancel_tx);
+                    rescheduled.push((entry.info.clone(), cancel_rx));
+                }
+            }
+        }
+
+        if shifted.is_empty() {
+            return Ok(shifted);
+        }
+        self.persist()?;
+        for (info, cancel_rx) in rescheduled {
+            schedule_timer(self, info, cancel_rx);
+        }
+        for timer in &shifted {
+            let _ = self.audit.record(
+                origin,
+                AuditOperation::Update,
+                Some(&timer.id),
+                format!("Shifted {} timer by {minutes} min", timer.action),
+            );
+            self.emit("timer-shifted", timer.clone());
+        }
+        Ok(shifted)
+    }
+
+    /// Runs every check `create` makes and collects the problems instead of
+    /// stopping at the first. Warnings never block creation.
+    fn validate(&self, request: &CreateTimerRequest) -> Result<TimerValidation, String> {
+        self.validate_request(request, false)
+    }
+
+    /// `validate`, but with `allow_past` a target time that has passed is
+    /// fine, as for a recurring timer copied from another Mac that
+    /// `catch_up` moves on.
+    fn validate_request(&self, request: &CreateTimerRequest, allow_past: bool) -> Result<TimerValidation, String> {
+        let mut report = TimerValidation::default();
+        let now = Utc::now();
+
+        let target = match parse_target_time(&request.target_time) {
+            Ok((target, _)) => Some(target),
+            Err(err) => {
+                report.error("targetTime", err);
+                None
+            }
+        };
+        // A past time arms a trigger right away.
+        if !allow_past && target.is_some_and(|target| target <= now) && request.trigger.is_none() {
+            report.error("targetTime", "Selected time must be in the future");
+        }
+        match parse_expires_at(request.expires_at.as_deref()) {
+            Ok(Some(expires_at)) if target.is_some_and(|target| expires_at <= target) => {
+                report.error("expiresAt", "Expiry must be after the first run");
+            }
+            Ok(_) => {}
+            Err(err) => report.error("expiresAt", err),
+        }
+
+        if matches!(request.action, TimerAction::Popup)
+            && request
+                .message
+                .as_ref()
+                .map(|msg| msg.trim().is_empty())
+                .unwrap_or(true)
+        {
+            report.error("message", "Popup timers require a message");
+        }
+
+        if let TimerAction::Plugin { id } = &request.action {
+            if let Err(err) = self.plugins.find(id) {
+                report.error("action", err);
+            }
+        }
+        if let Err(err) = self.policy.check_action(&request.action) {
+            report.error("action", err);
+        }
+        if let Some(Err(err)) = request
+            .escalation
+            .as_ref()
+            .map(|escalation| self.policy.check_action(&escalation.action))
+        {
+            report.error("escalation", err);
+        }
+        if let Err(err) = self.policy.check_hooks(request.hooks.as_ref()) {
+            report.error("hooks", err);
+        }
+
+        if let TimerAction::Shortcut { name, .. } = &request.action {
+            if name.trim().is_empty() {
+                report.error("action", "Shortcut timers require a shortcut name");
+            } else if let Err(err) = shortcuts::check_exists(name) {
+                report.error("action", err);
+            }
+        }
+
+        if let TimerAction::FadeVolume {
+            target_percent,
+            over_minutes,
+        } = &request.action
+        {
+            if *target_percent > 100 {
+                report.error("action", "Target volume must be between 0 and 100");
+            }
+            if !(1..=MAX_FADE_MINUTES).contains(over_minutes) {
+                report.error("action", format!("Fades must last between 1 and {MAX_FADE_MINUTES} minutes"));
+            }
+        }
+
+        if let TimerAction::Alarm {
+            sound,
+            volume_percent,
+            snooze_minutes,
+        } = &request.action
+        {
+            if let Err(err) = alarm::check_sound(sound) {
+                report.error("action", err);
+            }
+            if *volume_percent > 100 {
+                report.error("action", "Alarm volume must be between 0 and 100");
+            }
+            if !(1..=alarm::MAX_SNOOZE_MINUTES).contains(snooze_minutes) {
+                report.error(
+                    "action",
+                    format!("Snooze must be between 1 and {} minutes", alarm::MAX_SNOOZE_MINUTES),
+                );
+            }
+        }
+
+        if let TimerAction::Countdown { sound: Some(sound) } = &request.action {
+            if let Err(err) = alarm::check_sound(sound) {
+                report.error("action", err);
+            }
+        }
+
+        if let TimerAction::NightShift { strength, .. } = &request.action {
+            if strength.is_some_and(|strength| strength > 100) {
+                report.error("action", "Night Shift strength must be between 0 and 100");
+            } else if !system::night_shift_available() {
+                report.warning("action", "Night Shift is not available on this Mac, so the timer will fail");
+            }
+        }
+
+        if let TimerAction::Vpn { service, .. } = &request.action {
+            if service.trim().is_empty() {
+                report.error("action", "VPN timers require a service name");
+            } else if let Err(err) = vpn::check_exists(service) {
+                report.error("action", err);
+            }
+        }
+
+        if let TimerAction::SshCommand { host, user, command } = &request.action {
+            if let Err(err) = ssh::validate(host, user.as_deref(), command) {
+                report.error("action", err);
+            }
+        }
+
+        if let TimerAction::Automator { path } = &request.action {
+            if let Err(err) = automator::validate(path) {
+                report.error("action", err);
+            }
+        }
+
+        if let Some((language, script)) = request.action.user_script() {
+            if script.trim().is_empty() {
+                report.error("action", format!("{language} timers require a script"));
+            } else if let Err(err) = system::compile_script(script, language) {
+                report.error("action", format!("Script does not compile: {err}"));
+            }
+        }
+
+        if let Err(err) = normalize_tags(request.tags.clone()) {
+            report.error("tags", err);
+        }
+        if let Err(err) = validate_recurrence(request.recurrence.as_ref()) {
+            report.error("recurrence", err);
+        }
+        if request.max_runs == Some(0) {
+            report.error("maxRuns", "Maximum runs must be at least 1");
+        }
+        if let Err(err) = validate_jitter(request.jitter_minutes, request.recurrence.as_ref()) {
+            report.error("jitterMinutes", err);
+        }
+        if request.warning_minutes.is_some_and(|minutes| minutes > MAX_WARNING_MINUTES) {
+            report.error(
+                "warningMinutes",
+                format!("Warnings can come at most {} hours ahead", MAX_WARNING_MINUTES / 60),
+            );
+        }
+        if let Some(trigger) = request.trigger.as_ref() {
+            if let Err(err) = trigger.validate() {
+                report.error("trigger", err);
+            } else {
+                match &trigger.condition {
+                    TriggerCondition::LowDiskSpace { volume, .. } if !Path::new(volume).exists() => {
+                        report.warning("trigger", format!("{volume} isn't mounted; the trigger waits until it is"));
+                    }
+                    TriggerCondition::ProcessExit { name } if !system::is_process_running(name) => {
+                        report.warning(
+                            "trigger",
+                            format!("{name} isn't running; the trigger waits for it to start and exit"),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            if request.recurrence.is_some() {
+                report.error("trigger", "Triggered timers don't recur; use the trigger's repeat option instead");
+            }
+            if request.jitter_minutes.is_some_and(|minutes| minutes > 0) {
+                report.error("jitterMinutes", "Jitter doesn't apply to triggered timers");
+            }
+            if request.must_run {
+                report.error("mustRun", "Triggers are only checked while the Mac is awake, so they can't wake it");
+            }
+            if request.warning_minutes.is_some_and(|minutes| minutes > 0) {
+                report.error("warningMinutes", "Triggered timers can't warn ahead; when they fire isn't known");
+            }
+        }
+        if let Err(err) = validate_shutdown_options(&request.action, request.shutdown_options.as_ref()) {
+            report.error("shutdownOptions", err);
+        }
+        if request
+            .confirmation
+            .as_ref()
+            .is_some_and(|confirmation| !(10..=3600).contains(&confirmation.timeout_seconds))
+        {
+            report.error("confirmation", "Confirmation timeout must be between 10 and 3600 seconds");
+        }
+        if let Err(err) = validate_escalation(&request.action, request.escalation.as_ref()) {
+            report.error("escalation", err);
+        }
+        if let Err(err) = validate_nag(&request.action, request.repeat_until_acknowledged.as_ref()) {
+            report.error("repeatUntilAcknowledged", err);
+        }
+        if request.working_hours.as_ref().is_some_and(|hours| hours.start == hours.end) {
+            report.error("workingHours", "Working hours start and end must differ");
+        }
+        if let Some(Err(err)) = request.chat_webhook.as_ref().map(ChatWebhook::validate) {
+            report.error("chatWebhook", err);
+        }
+        if let Some(condition) = request
+            .condition
+            .as_deref()
+            .map(str::trim)
+            .filter(|condition| !condition.is_empty())
+        {
+            if let Err(err) = conditions::validate(condition) {
+                report.error("condition", err);
+            }
+        }
+
+        let settings = self.settings.snapshot()?;
+        let profile = match request.profile.as_deref() {
+            Some(name) => settings.find_profile(name),
+            None => Some(settings.active_profile.clone()),
+        };
+        if profile.is_none() {
+            report.error(
+                "profile",
+                format!("Profile not found: {}", request.profile.as_deref().unwrap_or_default()),
+            );
+        }
+
+        let delivery = match request.action {
+            TimerAction::Push => Some(("Push", "push notifications", settings.push.is_some())),
+            TimerAction::Email => Some(("Email", "email", settings.smtp.is_some())),
+            _ => None,
+        };
+        if let Some((name, setup, configured)) = delivery {
+            if request.message.as_deref().map_or(true, |message| message.trim().is_empty()) {
+                report.error("message", format!("{name} timers require a message"));
+            }
+            if !configured {
+                report.error("action", format!("Set up {setup} first"));
+            }
+        }
+        if let Some(permission) = required_permission(&request.action, request.shutdown_options.as_ref(), &settings) {
+            report.warning("action", permission);
+        }
+        if request.eject_disks.is_some()
+            && !(request.action.ends_session() || matches!(request.action, TimerAction::SystemSleep))
+        {
+            report.error(
+                "ejectDisks",
+                "Ejecting disks first only applies to shutdown, restart, log out and sleep timers",
+            );
+        }
+        if request.pause_media && !request.action.can_pause_media_first() {
+            report.error("pauseMedia", "Pausing media first only applies to lock and sleep timers");
+        }
+        if request.must_run && !helper::is_installed() {
+            report.warning("mustRun", "Install the privileged helper so LockPilot can wake the Mac for this timer");
+        }
+        if request.recurrence.as_ref().is_some_and(|recurrence| recurrence.skip_holidays)
+            && settings.holiday_region.is_none()
+        {
+            report.warning("recurrence", "No holiday region is set, so no holidays will be skipped");
+        }
+
+        // A triggered timer's target time only arms it, so nothing runs then.
+        let Some(target) = target.filter(|_| request.trigger.is_none()) else {
+            return Ok(report);
+        };
+
+        if let Some(paused_until) = settings
+            .active_pause(now)
+            .filter(|until| target < *until && request.managed.is_none())
+        {
+            report.warning(
+                "targetTime",
+                format!("All timers are paused until {}; this run will be skipped", paused_until.to_rfc3339()),
+            );
+        }
+        if let Some(quiet_hours) = settings.quiet_hours.as_ref() {
+            if request.action.is_non_critical() && quiet_hours.contains(target.with_timezone(&Local).time()) {
+                report.warning("targetTime", "Falls within quiet hours");
+            }
+        }
+        if let Some(hours) = settings.working_hours_for(request.working_hours.as_ref(), request.ignore_working_hours) {
+            if !hours.contains(target.with_timezone(&Local).time()) {
+                let effect = match hours.policy {
+                    OutsideHoursPolicy::Skip => "skipped",
+                    OutsideHoursPolicy::Shift => "held until they start",
+                };
+                report.warning("targetTime", format!("Outside working hours; this run will be {effect}"));
+            }
+        }
+
+        let store = self.timers();
+        let conflicts = store.values().filter(|entry| {
+            Some(&entry.info.profile) == profile.as_ref()
+                && entry.info.trigger.is_none()
+                && (entry.info.target_time - target).num_seconds().abs() < CONFLICT_WINDOW_SECONDS
+        });
+        for entry in conflicts {
+            report.warning(
+                "targetTime",
+                format!(
+                    "Another {} timer fires at {}",
+                    action_label(&entry.info.action),
+                    entry.info.target_time.to_rfc3339()
+                ),
+            );
+        }
+
+        Ok(report)
+    }
+
+    pub fn create(&self, request: CreateTimerRequest, origin: &AuditOrigin) -> Result<TimerInfo, String> {
+        let mut created = self.create_many(vec![request], origin)?;
+        Ok(created.remove(0))
+    }
+
+    /// Leaves `request` in `INCOMING_DIR` for the running app to create,
+    /// once it passes the checks `create` makes. Written aside and renamed
+    /// so the app never reads half a request.
+    pub(crate) fn queue_create(&self, request: &CreateTimerRequest) -> Result<(), String> {
+        if let Some(issue) = self.validate(request)?.errors.into_iter().next() {
+            return Err(issue.message);
+        }
+        let dir = self.data_dir().join(INCOMING_DIR);
+        fs::create_dir_all(&dir).map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        let data = serde_json::to_string(request).map_err(|err| format!("Failed to encode timer: {err}"))?;
+        let path = dir.join(format!("{}.json", Uuid::new_v4()));
+        let staged = path.with_extension("tmp");
+        fs::write(&staged, data)
+            .and_then(|_| fs::rename(&staged, &path))
+            .map_err(|err| format!("Failed to queue timer: {err}"))
+    }
+
+    /// Creates the timers `queue_create` left, oldest first, and removes
+    /// their files. One that no longer passes, e.g. because its time went
+    /// by while LockPilot wasn't running, is logged and dropped, as is
+    /// everything in read-only mode.
+    #[cfg(feature = "app")]
+    fn create_queued(&self) {
+        let Ok(entries) = fs::read_dir(self.data_dir().join(INCOMING_DIR)) else {
+            return;
+        };
+        let mut queued: Vec<(SystemTime, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .map(|path| (fs::metadata(&path).and_then(|meta| meta.modified()).unwrap_or(SystemTime::UNIX_EPOCH), path))
+            .collect();
+        queued.sort();
+        for (_, path) in queued {
+            let read_only = self.settings.read_only_launch.load(Ordering::SeqCst)
+                || self.settings.snapshot().is_ok_and(|settings| settings.read_only);
+            let created = if read_only {
+                Err("LockPilot is read-only on this Mac".to_string())
+            } else {
+                fs::read_to_string(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|raw| serde_json::from_str::<CreateTimerRequest>(&raw).map_err(|err| err.to_string()))
+                    .and_then(|request| self.create(request, &AuditOrigin::Cli))
+            };
+            if let Err(err) = created {
+                eprintln!("Dropped the timer queued in {}: {err}", path.display());
+            }
+            if let Err(err) = fs::remove_file(&path) {
+                eprintln!("Failed to remove {}: {err}", path.display());
+            }
+        }
+    }
+
+    /// Creates all of `requests` or none of them: every request is
+    /// validated before anything is inserted, and the store is persisted
+    /// once for the whole batch.
+    fn create_many(&self, requests: Vec<CreateTimerRequest>, origin: &AuditOrigin) -> Result<Vec<TimerInfo>, String> {
+        for request in &requests {
+            if let Some(issue) = self.validate(request)?.errors.into_iter().next() {
+                return Err(issue.message);
+            }
+        }
+
+        let now = Utc::now();
+        let settings = self.settings.snapshot()?;
+        let mut created = Vec::with_capacity(requests.len());
+        for request in requests {
+            let (target, _) = parse_target_time(&request.target_time)?;
+            let hooks = normalize_hooks(request.hooks);
+            let condition = request
+                .condition
+                .map(|condition| condition.trim().to_string())
+                .filter(|condition| !condition.is_empty());
+            let profile = match request.profile {
+                Some(profile) => settings
+                    .find_profile(&profile)
+                    .ok_or_else(|| format!("Profile not found: {profile}"))?,
+                None => settings.active_profile.clone(),
+            };
+
+            created.push(TimerInfo {
+                id: Uuid::new_v4().to_string(),
+                action: request.action,
+                target_time: target,
+                recurrence: request.recurrence,
+                message: request.message.map(|msg| msg.trim().to_string()),
+                notes: request
+                    .notes
+                    .map(|notes| notes.trim().to_string())
+                    .filter(|notes| !notes.is_empty()),
+                shutdown_options: request.shutdown_options,
+                hooks,
+                condition,
+                trigger: request.trigger,
+                profile,
+                // Managed and calendar timers belong to no experiment.
+                workspace: if request.managed.is_some() || request.source.is_some() {
+                    default_workspace()
+                } else {
+                    settings.active_workspace.clone()
+                },
+                created_at: now,
+                expires_at: parse_expires_at(request.expires_at.as_deref())?,
+                max_runs: request.max_runs,
+                run_count: 0,
+                last_run_at: None,
+                jitter_minutes: request.jitter_minutes.filter(|minutes| *minutes > 0),
+                jitter: None,
+                warning_minutes: request.warning_minutes.filter(|minutes| *minutes > 0),
+                shifted_from: None,
+                confirmation: request.confirmation,
+                escalation: request.escalation,
+                repeat_until_acknowledged: request.repeat_until_acknowledged,
+                working_hours: request.working_hours,
+                ignore_working_hours: request.ignore_working_hours,
+                chat_webhook: request.chat_webhook,
+                must_run: request.must_run,
+                pause_media: request.pause_media,
+                eject_disks: request.eject_disks,
+                tags: normalize_tags(request.tags)?,
+                modified_at: None,
+                source: request.source,
+                managed: request.managed,
+            });
+        }
+
+        let mut scheduled = Vec::new();
+        {
+            let mut store = self.timers();
+
+            for info in &created {
+                let is_active = settings.schedules(info);
+                let (cancel_tx, cancel_rx) = mpsc::channel();
+                store.insert(
+                    info.id.clone(),
+                    TimerEntry {
+                        info: info.clone(),
+                        cancel_tx: is_active.then_some(cancel_tx),
+                    },
+                );
+                if is_active {
+                    scheduled.push((info.clone(), cancel_rx));
+                }
+            }
+        }
+
+        self.persist()?;
+        for (info, cancel_rx) in scheduled {
+            schedule_timer(self, info, cancel_rx);
+        }
+        for info in &created {
+            let _ = self.audit.record(
+                origin,
+                AuditOperation::Create,
+                Some(&info.id),
+                format!(
+                    "Created {} timer for {}",
+                    action_label(&info.action),
+                    info.target_time.to_rfc3339()
+                ),
+            );
+        }
+
+        Ok(created)
+    }
+
+    /// Timers of every profile, in no particular order.
+    #[cfg(feature = "app")]
+    fn all(&self) -> Result<Vec<TimerInfo>, String> {
+        Ok(self.timers().values().map(|entry| entry.info.clone()).collect())
+    }
+
+    /// Adds timers received from a sync peer under their own ids, replacing
+    /// any local copy but keeping its run count. They go to the Default
+    /// workspace, and timers of a profile this Mac lacks to the active one; one-time timers already missed are
+    /// dropped, as are ones the policy forbids and, unless the remote API
+    /// allows scripts, ones that run code. Returns the ids kept.
+    #[cfg(feature = "app")]
+    fn merge_synced(&self, incoming: Vec<TimerInfo>, origin: &AuditOrigin) -> Result<Vec<String>, String> {
+        // Checked like new timers, before the map is locked: an invalid
+        // recurrence would otherwise spin `catch_up` with the lock held.
+        let incoming: Vec<TimerInfo> = incoming
+            .into_iter()
+            .filter(|info| match self.validate_request(&info.to_request(), true) {
+                Ok(report) if report.errors.is_empty() => true,
+                Ok(report) => {
+                    let reasons: Vec<_> = report.errors.into_iter().map(|issue| issue.message).collect();
+                    eprintln!("Dropped synced timer {}: {}", info.id, reasons.join("; "));
+                    false
+                }
+                Err(err) => {
+                    eprintln!("Dropped synced timer {}: {err}", info.id);
+                    false
+                }
+            })
+            .collect();
+        let settings = self.settings.snapshot()?;
+        let now = Utc::now();
+        let cutoff = settings.active_pause(now).unwrap_or(now);
+        let mut merged = Vec::new();
+        let mut to_schedule = Vec::new();
+        {
+            let mut store = self.timers();
+
+            for mut info in incoming {
+                if store.get(&info.id).is_some_and(|entry| entry.info.managed.is_some())
+                    || self
+                        .policy
+                        .check_timer(
+                            &info.action,
+                            info.escalation.as_ref().map(|escalation| &escalation.action),
+                            info.hooks.as_ref(),
+                        )
+                        .is_err()
+                    || (!settings.remote_api.allow_scripts
+                        && runs_code(
+                            &info.action,
+                            info.escalation.as_ref().map(|escalation| &escalation.action),
+                            info.hooks.as_ref(),
+                        ))
+                {
+                    continue;
+                }
+                info.managed = None;
+                info.workspace = default_workspace();
+                info.profile = settings
+                    .find_profile(&info.profile)
+                    .unwrap_or_else(|| settings.active_profile.clone());
+                let previous = store.remove(&info.id);
+                if let Some(previous) = &previous {
+                    if let Some(cancel_tx) = previous.cancel_tx.as_ref() {
+                        let _ = cancel_tx.send(());
+                    }
+                    info.run_count = previous.info.run_count;
+                    info.last_run_at = previous.info.last_run_at;
+                }
+
+                let is_active = settings.schedules(&info);
+                // Like `restore_timers`, inactive profiles are caught up
+                // when activated.
+                let caught_up = if is_active { catch_up(info, cutoff) } else { Some(info) };
+                let Some(info) = caught_up else {
+                    continue;
+                };
+                let (cancel_tx, cancel_rx) = mpsc::channel();
+                store.insert(
+                    info.id.clone(),
+                    TimerEntry {
+                        info: info.clone(),
+                        cancel_tx: is_active.then_some(cancel_tx),
+                    },
+                );
+                if is_active {
+                    to_schedule.push((info.clone(), cancel_rx));
+                }
+                merged.push((info, previous.is_some()));
+            }
+        }
+
+        self.persist()?;
+        for (info, cancel_rx) in to_schedule {
+            schedule_timer(self, info, cancel_rx);
+        }
+        for (info, replaced) in &merged {
+            let (operation, verb) = if *replaced {
+                (AuditOperation::Update, "Synced changes to")
+            } else {
+                (AuditOperation::Create, "Synced new")
+            };
+            let _ = self.audit.record(
+                origin,
+                operation,
+                Some(&info.id),
+                format!("{verb} {} timer for {}", action_label(&info.action), info.target_time.to_rfc3339()),
+            );
+        }
+
+        Ok(merged.into_iter().map(|(info, _)| info.id).collect())
+    }
+
+    /// Sets or clears the global pause and records who did it. `until`
+    /// comes from `parse_pause_until`.
+    fn set_pause(&self, until: Option<DateTime<Utc>>, origin: &AuditOrigin) -> Result<PauseState, String> {
+        let settings = self.settings.update(|settings| settings.paused_until = until)?;
+        let state = PauseState::from_settings(&settings);
+        let (operation, summary) = match state.paused_until {
+            Some(until) => (AuditOperation::Pause, format!("Paused all timers until {}", until.to_rfc3339())),
+            None => (AuditOperation::Resume, "Resumed all timers".to_string()),
+        };
+        let _ = self.audit.record(origin, operation, None, summary);
+        self.emit("pause-changed", state.clone());
+        Ok(state)
+    }
+
+    /// Makes `name` the active profile: its timers are caught up and
+    /// scheduled, every other profile's timers go dormant. Managed timers
+    /// stay scheduled in every profile.
+    #[cfg(feature = "app")]
+    fn set_active_profile(&self, name: &str, origin: &AuditOrigin) -> Result<ProfilesOverview, String> {
+        let name = self
+            .settings
+            .snapshot()?
+            .find_profile(name)
+            .ok_or_else(|| format!("Profile not found: {name}"))?;
+        let settings = self.settings.update(|settings| settings.active_profile = name.clone())?;
+
+        let now = Utc::now();
+        let mut to_schedule = Vec::new();
+        {
+            let mut store = self.timers();
+
+            let mut expired = Vec::new();
+            for (id, entry) in store.iter_mut() {
+                if !settings.schedules(&entry.info) {
+                    if let Some(cancel_tx) = entry.cancel_tx.take() {
+                        let _ = cancel_tx.send(());
+                    }
+                    continue;
+                }
+                if entry.cancel_tx.is_some() {
+                    continue;
+                }
+
+                let cutoff = settings.pause_for(&entry.info, now).unwrap_or(now);
+                match catch_up(entry.info.clone(), cutoff) {
+                    Some(info) => {
+                        let (cancel_tx, cancel_rx) = mpsc::channel();
+                        entry.info = info.clone();
+                        entry.cancel_tx = Some(cancel_tx);
+                        to_schedule.push((info, cancel_rx));
+                    }
+                    None => expired.push(id.clone()),
+                }
+            }
+            for id in expired {
+                store.remove(&id);
+            }
+        }
+
+        self.persist()?;
+        for (info, cancel_rx) in to_schedule {
+            schedule_timer(self, info, cancel_rx);
+        }
+        let _ = self.audit.record(
+            origin,
+            AuditOperation::SwitchProfile,
+            None,
+            format!("Switched to profile {name}"),
+        );
+        self.profiles_overview()
+    }
+
+    #[cfg(feature = "app")]
+    fn create_profile(&self, name: &str) -> Result<ProfilesOverview, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name is required".to_string());
+        }
+        if self.settings.snapshot()?.find_profile(name).is_some() {
+            return Err(format!("Profile already exists: {name}"));
+        }
+
+        self.settings.update(|settings| {
+            let mut profiles = settings.profile_names();
+            profiles.push(name.to_string());
+            settings.profiles = profiles;
+        })?;
+        self.profiles_overview()
+    }
+
+    /// Deletes an inactive profile together with its timers.
+    #[cfg(feature = "app")]
+    fn delete_profile(&self, name: &str, origin: &AuditOrigin) -> Result<ProfilesOverview, String> {
+        let settings = self.settings.snapshot()?;
+        let name = settings
+            .find_profile(name)
+            .ok_or_else(|| format!("Profile not found: {name}"))?;
+        if name == settings.active_profile {
+            return Err("The active profile can't be deleted".to_string());
+        }
+        if self.all()?.iter().any(|timer| timer.profile == name && timer.managed.is_some()) {
+            return Err(format!("Profile {name} has timers managed by your organization"));
+        }
+
+        let removed: Vec<String> = {
+            let mut store = self.timers();
+            let ids: Vec<String> = store
+                .values()
+                .filter(|entry| entry.info.profile == name)
+                .map(|entry| entry.info.id.clone())
+                .collect();
+            for id in &ids {
+                store.remove(id);
+            }
+            ids
+        };
+
+        self.settings.update(|settings| {
+            settings.profiles = settings
+                .profile_names()
+                .into_iter()
+                .filter(|profile| *profile != name)
+                .collect();
+        })?;
+        self.persist()?;
+        for id in removed {
+            let _ = self.audit.record(
+                origin,
+                AuditOperation::Cancel,
+                Some(&id),
+                format!("Cancelled with deleted profile {name}"),
+            );
+        }
+        self.profiles_overview()
+    }
+
+    #[cfg(feature = "app")]
+    fn profiles_overview(&self) -> Result<ProfilesOverview, String> {
+        let settings = self.settings.snapshot()?;
+        let store = self.timers();
+
+        let profiles = settings
+            .profile_names()
+            .into_iter()
+            .map(|name| ProfileSummary {
+                timer_count: store
+                    .values()
+                    .filter(|entry| entry.info.profile == name && settings.shows(&entry.info))
+                    .count(),
+                name,
+            })
+            .collect();
+        Ok(ProfilesOverview {
+            active_profile: settings.active_profile,
+            profiles,
+        })
+    }
+
+    /// Opens `name`'s timers in the UI. Only what is listed and edited
+    /// changes: every workspace's timers stay scheduled, so a `Testing`
+    /// workspace never holds up the real schedule.
+    #[cfg(feature = "app")]
+    fn switch_workspace(&self, name: &str, origin: &AuditOrigin) -> Result<WorkspacesOverview, String> {
+        let settings = self.settings.snapshot()?;
+        let name = settings
+            .find_workspace(name)
+            .ok_or_else(|| format!("Workspace not found: {name}"))?;
+        if name == settings.active_workspace {
+            return self.workspaces_overview();
+        }
+
+        self.settings.update(|settings| settings.active_workspace = name.clone())?;
+        let _ = self.audit.record(
+            origin,
+            AuditOperation::SwitchWorkspace,
+            None,
+            format!("Switched to workspace {name}"),
+        );
+        self.workspaces_overview()
+    }
+
+    #[cfg(feature = "app")]
+    fn create_workspace(&self, name: &str) -> Result<WorkspacesOverview, String> {
+        let name = name.trim();
+        if workspace_slug(name).is_empty() {
+            return Err("Workspace name needs a letter or digit".to_string());
+        }
+        let settings = self.settings.snapshot()?;
+        if let Some(existing) = settings
+            .workspace_names()
+            .into_iter()
+            .find(|existing| workspace_slug(existing) == workspace_slug(name))
+        {
+            return Err(format!("Workspace already exists: {existing}"));
+        }
+
+        self.settings.update(|settings| {
+            settings.workspaces = settings.workspace_names();
+            settings.workspaces.push(name.to_string());
+        })?;
+        self.workspaces_overview()
+    }
+
+    /// Deletes an inactive workspace, cancelling its timers, and its timer
+    /// file.
+    #[cfg(feature = "app")]
+    fn delete_workspace(&self, name: &str, origin: &AuditOrigin) -> Result<WorkspacesOverview, String> {
+        let settings = self.settings.snapshot()?;
+        let name = settings
+            .find_workspace(name)
+            .ok_or_else(|| format!("Workspace not found: {name}"))?;
+        if name == settings.active_workspace {
+            return Err("The active workspace can't be deleted".to_string());
+        }
+        if name == DEFAULT_WORKSPACE {
+            return Err("The Default workspace can't be deleted".to_string());
+        }
+
+        let removed: Vec<String> = {
+            let mut store = self.timers();
+            let ids: Vec<String> = store
+                .values()
+                .filter(|entry| entry.info.workspace == name)
+                .map(|entry| entry.info.id.clone())
+                .collect();
+            for id in &ids {
+                if let Some(cancel_tx) = store.remove(id).and_then(|entry| entry.cancel_tx) {
+                    let _ = cancel_tx.send(());
+                }
+            }
+            ids
+        };
+
+        self.settings.update(|settings| {
+            settings.workspaces = settings
+                .workspace_names()
+                .into_iter()
+                .filter(|workspace| *workspace != name)
+                .collect();
+        })?;
+        self.persist()?;
+        let path = workspace_file(&self.storage_path, &name);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|err| format!("Failed to delete {}: {err}", path.display()))?;
+        }
+        for id in removed {
+            let _ = self.audit.record(
+                origin,
+                AuditOperation::Cancel,
+                Some(&id),
+                format!("Cancelled with deleted workspace {name}"),
+            );
+        }
+        self.workspaces_overview()
+    }
+
+    #[cfg(feature = "app")]
+    fn workspaces_overview(&self) -> Result<WorkspacesOverview, String> {
+        let settings = self.settings.snapshot()?;
+        let store = self.timers();
+        let workspaces = settings
+            .workspace_names()
+            .into_iter()
+            .map(|name| WorkspaceSummary {
+                timer_count: store.values().filter(|entry| entry.info.workspace == name).count(),
+                name,
+            })
+            .collect();
+        Ok(WorkspacesOverview {
+            active_workspace: settings.active_workspace,
+            workspaces,
+        })
+    }
+
+    /// Writes each workspace's timers to its own file.
+    fn persist(&self) -> Result<(), String> {
+        let mut names = self.settings.snapshot()?.workspace_names();
+        let locked = self.timers();
+
+        let mut by_workspace: HashMap<String, Vec<TimerInfo>> = HashMap::new();
+        for entry in locked.values() {
+            by_workspace
+                .entry(entry.info.workspace.clone())
+                .or_default()
+                .push(entry.info.clone());
+        }
+        drop(locked);
+
+        // Timers of a workspace deleted meanwhile still get saved.
+        let unlisted: Vec<String> = by_workspace.keys().filter(|name| !names.contains(name)).cloned().collect();
+        names.extend(unlisted);
+        for name in names {
+            let timers = by_workspace.remove(&name).unwrap_or_default();
+            write_timers(&workspace_file(&self.storage_path, &name), timers)?;
+        }
+        self.refresh_badge();
+        Ok(())
+    }
+
+    /// Every workspace's timers, each tagged with the workspace whose file
+    /// it was read from.
+    fn load_persisted_infos(&self) -> Result<Vec<TimerInfo>, String> {
+        let mut infos = Vec::new();
+        for name in self.settings.snapshot()?.workspace_names() {
+            for mut info in read_timers(&workspace_file(&self.storage_path, &name))? {
+                info.workspace = name.clone();
+                infos.push(info);
+            }
+        }
+        Ok(infos)
+    }
+}
+
+fn write_timers(path: &Path, mut timers: Vec<TimerInfo>) -> Result<(), String> {
+    timers.sort_by_key(|timer| timer.target_time);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| format!("Failed to create timer storage directory: {err}"))?;
+    }
+
+    let data = serde_json::to_string_pretty(&PersistedTimers { timers })
+        .map_err(|err| format!("Failed to encode timer data: {err}"))?;
+    fs::write(path, data).map_err(|err| format!("Failed to write timer data: {err}"))
+}
+
+fn read_timers(path: &Path) -> Result<Vec<TimerInfo>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(path).map_err(|err| format!("Failed to read timer data: {err}"))?;
+    let persisted =
+        serde_json::from_str::<PersistedTimers>(&raw).map_err(|err| format!("Failed to parse timer data: {err}"))?;
+    Ok(persisted.timers)
+}
+
+/// `timers.json` for the default workspace; the others live under
+/// `workspaces/`, named after the workspace.
+fn workspace_file(storage_path: &Path, name: &str) -> PathBuf {
+    if name.eq_ignore_ascii_case(DEFAULT_WORKSPACE) {
+        return storage_path.to_path_buf();
+    }
+    let dir = storage_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    dir.join("workspaces").join(format!("{}.json", workspace_slug(name)))
+}
+
+/// `Lab Machines` becomes `lab-machines`.
+fn workspace_slug(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedTimers {
+    timers: Vec<TimerInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum QuietHoursMode {
+    Skip,
+    Queue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+    mode: QuietHoursMode,
+    /// Block the domains from `set_blocked_domains` during quiet hours.
+    #[serde(default)]
+    block_websites: bool,
+}
+
+impl QuietHours {
+    /// Quiet hours are local wall-clock times and may wrap past midnight.
+    fn contains(&self, time: NaiveTime) -> bool {
+        window_contains(self.start, self.end, time)
+    }
+
+    fn next_end_after(&self, now: DateTime<Local>) -> DateTime<Utc> {
+        next_local_time_after(now, self.end)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OutsideHoursPolicy {
+    Skip,
+    /// Hold the action until the window next opens.
+    Shift,
+}
+
+/// The local hours in which actions may run; may wrap past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkingHours {
+    start: NaiveTime,
+    end: NaiveTime,
+    policy: OutsideHoursPolicy,
+}
+
+impl WorkingHours {
+    fn contains(&self, time: NaiveTime) -> bool {
+        window_contains(self.start, self.end, time)
+    }
+
+    fn next_start_after(&self, now: DateTime<Local>) -> DateTime<Utc> {
+        next_local_time_after(now, self.start)
+    }
+}
+
+fn window_contains(start: NaiveTime, end: NaiveTime, time: NaiveTime) -> bool {
+    if start <= end {
+        time >= start && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+/// The next time the local clock reads `time`, today or tomorrow.
+fn next_local_time_after(now: DateTime<Local>, time: NaiveTime) -> DateTime<Utc> {
+    let mut date = now.date_naive();
+    if now.time() >= time {
+        date += ChronoDuration::days(1);
+    }
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|next| next.with_timezone(&Utc))
+        .unwrap_or_else(|| now.with_timezone(&Utc) + ChronoDuration::hours(1))
+}
+
+/// `hour:minute` on `date` in local time; the earlier one when the clock
+/// is turned back over it.
+fn local_at(date: NaiveDate, hour: u32, minute: u32) -> Result<DateTime<Local>, String> {
+    let time = NaiveTime::from_hms_opt(hour, minute, 0).ok_or_else(|| "Invalid time".to_string())?;
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .ok_or_else(|| "Time doesn't exist in the local time zone".to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppSettings {
+    #[serde(default)]
+    paused_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    quiet_hours: Option<QuietHours>,
+    #[serde(default)]
+    remote_api: RemoteApiSettings,
+    #[serde(default = "default_profile")]
+    active_profile: String,
+    #[serde(default)]
+    profiles: Vec<String>,
+    /// Which timer file is loaded and scheduled; see `switch_workspace`.
+    #[serde(default = "default_workspace")]
+    active_workspace: String,
+    #[serde(default)]
+    workspaces: Vec<String>,
+    /// Fall back to the keystroke/screen saver/display sleep chain when
+    /// native locking fails.
+    #[serde(default)]
+    lock_compatibility: bool,
+    #[serde(default)]
+    history_retention: HistoryRetention,
+    /// How long timers stay in the archive; separate from history since
+    /// archived timers are far fewer and each is worth keeping longer.
+    #[serde(default)]
+    archive_retention: HistoryRetention,
+    /// ISO country code whose public holidays recurrences may skip.
+    #[serde(default)]
+    holiday_region: Option<String>,
+    #[serde(default)]
+    working_hours: Option<WorkingHours>,
+    #[serde(default)]
+    push: Option<PushTarget>,
+    #[serde(default)]
+    smtp: Option<SmtpSettings>,
+    /// Add an event to the LockPilot calendar in Calendar.app for every run.
+    #[serde(default)]
+    calendar_log: bool,
+    /// Install an update found during the session when LockPilot quits,
+    /// then relaunch it.
+    #[serde(default)]
+    install_update_on_quit: bool,
+    /// Minutes before a Shutdown, Reboot or plugin timer during which the
+    /// Mac is kept from sleeping; 0 turns this off.
+    #[serde(default = "default_stay_awake_minutes")]
+    stay_awake_minutes: u32,
+    #[serde(default)]
+    conflict_policy: ConflictPolicy,
+    /// Sent nowhere through /etc/hosts during focus sessions and quiet
+    /// hours that block websites.
+    #[serde(default)]
+    blocked_domains: Vec<String>,
+    #[serde(default)]
+    usage_limit: Option<UsageLimit>,
+    #[serde(default)]
+    break_reminders: Option<BreakReminders>,
+    #[serde(default)]
+    sync: SyncSettings,
+    /// Show timers without allowing changes from this Mac, for lab and
+    /// kiosk machines provisioned centrally. Normally locked through
+    /// managed preferences; see `kiosk`.
+    #[serde(default)]
+    read_only: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            paused_until: None,
+            quiet_hours: None,
+            remote_api: RemoteApiSettings::default(),
+            active_profile: default_profile(),
+            profiles: Vec::new(),
+            active_workspace: default_workspace(),
+            workspaces: Vec::new(),
+            lock_compatibility: false,
+            history_retention: HistoryRetention::default(),
+            archive_retention: HistoryRetention::default(),
+            holiday_region: None,
+            working_hours: None,
+            push: None,
+            smtp: None,
+            calendar_log: false,
+            install_update_on_quit: false,
+            stay_awake_minutes: default_stay_awake_minutes(),
+            conflict_policy: ConflictPolicy::default(),
+            blocked_domains: Vec::new(),
+            usage_limit: None,
+            break_reminders: None,
+            sync: SyncSettings::default(),
+            read_only: false,
+        }
+    }
+}
+
+fn default_profile() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+fn default_workspace() -> String {
+    DEFAULT_WORKSPACE.to_string()
+}
+
+fn default_stay_awake_minutes() -> u32 {
+    2
+}
+
+impl AppSettings {
+    /// Known profiles; the active one is always included.
+    fn profile_names(&self) -> Vec<String> {
+        let mut names = self.profiles.clone();
+        if !names.contains(&self.active_profile) {
+            names.insert(0, self.active_profile.clone());
+        }
+        names
+    }
+
+    /// Case-insensitive lookup returning the stored spelling.
+    fn find_profile(&self, name: &str) -> Option<String> {
+        let name = name.trim();
+        self.profile_names()
+            .into_iter()
+            .find(|profile| profile.eq_ignore_ascii_case(name))
+    }
+
+    /// Known workspaces, the default one first.
+    fn workspace_names(&self) -> Vec<String> {
+        let mut names = vec![default_workspace()];
+        for name in self.workspaces.iter().chain([&self.active_workspace]) {
+            if !names.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    #[cfg(feature = "app")]
+    fn find_workspace(&self, name: &str) -> Option<String> {
+        let name = name.trim();
+        self.workspace_names()
+            .into_iter()
+            .find(|workspace| workspace.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the open workspace lists `info`. Managed timers are listed
+    /// in every workspace.
+    #[cfg(feature = "app")]
+    fn shows(&self, info: &TimerInfo) -> bool {
+        info.managed.is_some() || info.workspace == self.active_workspace
+    }
+
+    /// Whether `info` is scheduled: it belongs to the active profile or
+    /// is managed, which keeps it running in every profile.
+    fn schedules(&self, info: &TimerInfo) -> bool {
+        info.managed.is_some() || info.profile == self.active_profile
+    }
+
+    fn active_pause(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.paused_until.filter(|until| *until > now)
+    }
+
+    /// The pause that holds `info` back; managed timers run through it.
+    fn pause_for(&self, info: &TimerInfo, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.active_pause(now).filter(|_| info.managed.is_none())
+    }
+
+    fn active_quiet_hours(&self, now: DateTime<Local>) -> Option<&QuietHours> {
+        self.quiet_hours
+            .as_ref()
+            .filter(|quiet| quiet.contains(now.time()))
+    }
+
+    /// A timer's own window wins over the global one; `ignore` exempts it
+    /// from both.
+    fn working_hours_for<'a>(&'a self, own: Option<&'a WorkingHours>, ignore: bool) -> Option<&'a WorkingHours> {
+        if ignore {
+            None
+        } else {
+            own.or(self.working_hours.as_ref())
+        }
+    }
+}
+
+#[derive(Clone)]
+struct SettingsStore {
+    inner: Arc<Mutex<AppSettings>>,
+    storage_path: Arc<PathBuf>,
+    /// Settings locked by managed preferences, as their serialized values;
+    /// `update` refuses to change them.
+    managed: Arc<Mutex<serde_json::Map<String, serde_json::Value>>>,
+    /// When `storage_path` was last read or written here; a later change
+    /// came from another process, such as `lockpilot --pause`.
+    synced_at: Arc<Mutex<Option<SystemTime>>>,
+    /// Set when the app was launched read-only, which `readOnly` in the
+    /// file doesn't show; either way outside pauses are ignored.
+    read_only_launch: Arc<AtomicBool>,
+}
+
+impl SettingsStore {
+    fn new(storage_path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(AppSettings::default())),
+            storage_path: Arc::new(storage_path),
+            managed: Arc::new(Mutex::new(serde_json::Map::new())),
+            synced_at: Arc::new(Mutex::new(None)),
+            read_only_launch: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn modified_at(&self) -> Option<SystemTime> {
+        fs::metadata(self.storage_path.as_ref()).and_then(|meta| meta.modified()).ok()
+    }
+
+    fn mark_synced(&self) {
+        if let Ok(mut synced_at) = self.synced_at.lock() {
+            *synced_at = self.modified_at();
+        }
+    }
+
+    /// See `read_only_launch`.
+    #[cfg(feature = "app")]
+    fn mark_read_only_launch(&self) {
+        self.read_only_launch.store(true, Ordering::SeqCst);
+    }
+
+    /// Takes over the pause from `settings.json` if another process
+    /// changed it, so `lockpilot --pause` and `--resume` reach a running
+    /// app. Only the pause: the rest may carry managed overrides that
+    /// aren't on disk. Not in read-only mode.
+    fn pick_up_external_pause(&self) {
+        let modified = self.modified_at();
+        let Ok(mut synced_at) = self.synced_at.lock() else {
+            return;
+        };
+        if modified.is_none() || modified == *synced_at {
+            return;
+        }
+        // A file caught mid-write fails to parse; it is retried next time.
+        let Some(persisted) = fs::read_to_string(self.storage_path.as_ref())
+            .ok()
+            .and_then(|raw| serde_json::from_str::<AppSettings>(&raw).ok())
+        else {
+            return;
+        };
+        if let Ok(mut locked) = self.inner.lock() {
+            let mut candidate = locked.clone();
+            candidate.paused_until = persisted.paused_until;
+            if locked.read_only || self.read_only_launch.load(Ordering::SeqCst) {
+                eprintln!("Ignored the pause in settings.json: LockPilot is read-only on this Mac");
+            } else if let Err(err) = self.check_managed(&candidate) {
+                eprintln!("Ignored the pause in settings.json: {err}");
+            } else {
+                locked.paused_until = persisted.paused_until;
+            }
+            *synced_at = modified;
+        }
+    }
+
+    fn load_persisted(&self) -> Result<(), String> {
+        if !self.storage_path.exists() {
+            return Ok(());
+        }
+
+        let raw = fs::read_to_string(self.storage_path.as_ref())
+            .map_err(|err| format!("Failed to read settings: {err}"))?;
+        let persisted = serde_json::from_str::<AppSettings>(&raw)
+            .map_err(|err| format!("Failed to parse settings: {err}"))?;
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock settings".to_string())?;
+        *locked = persisted;
+        drop(locked);
+        self.mark_synced();
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Result<AppSettings, String> {
+        self.pick_up_external_pause();
+        self.inner
+            .lock()
+            .map(|settings| settings.clone())
+            .map_err(|_| "Failed to lock settings".to_string())
+    }
+
+    fn update<F>(&self, apply: F) -> Result<AppSettings, String>
+    where
+        F: FnOnce(&mut AppSettings),
+    {
+        self.pick_up_external_pause();
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock settings".to_string())?;
+        let mut updated = locked.clone();
+        apply(&mut updated);
+        self.check_managed(&updated)?;
+        *locked = updated.clone();
+        drop(locked);
+        self.write(&updated)?;
+        Ok(updated)
+    }
+
+    fn write(&self, updated: &AppSettings) -> Result<(), String> {
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create settings directory: {err}"))?;
+        }
+
+        let data = serde_json::to_string_pretty(updated)
+            .map_err(|err| format!("Failed to encode settings: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data)
+            .map_err(|err| format!("Failed to write settings: {err}"))?;
+        self.mark_synced();
+        Ok(())
+    }
+
+    /// Overrides the settings named in `locked` (keys as in `settings.json`)
+    /// and keeps them that way. Returns the keys applied; unknown ones are
+    /// left out.
+    #[cfg(feature = "app")]
+    fn lock_managed(&self, locked: serde_json::Map<String, serde_json::Value>) -> Result<Vec<String>, String> {
+        let mut current = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock settings".to_string())?;
+        let mut value = serde_json::to_value(&*current).map_err(|err| format!("Failed to encode settings: {err}"))?;
+        let fields = value
+            .as_object_mut()
+            .ok_or_else(|| "Settings are not an object".to_string())?;
+        let mut applied = Vec::new();
+        for (key, locked_value) in locked {
+            if fields.contains_key(&key) {
+                fields.insert(key.clone(), locked_value);
+                applied.push(key);
+            }
+        }
+        let overlaid: AppSettings =
+            serde_json::from_value(value).map_err(|err| format!("Invalid managed settings: {err}"))?;
+
+        // Compared in serialized form, so defaults filled in on the way
+        // through don't count as changes later.
+        let normalized = serde_json::to_value(&overlaid).map_err(|err| format!("Failed to encode settings: {err}"))?;
+        let enforced = applied
+            .iter()
+            .filter_map(|key| Some((key.clone(), normalized.get(key)?.clone())))
+            .collect();
+        *current = overlaid.clone();
+        drop(current);
+        *self.managed.lock().map_err(|_| "Failed to lock settings".to_string())? = enforced;
+        self.write(&overlaid)?;
+        Ok(applied)
+    }
+
+    fn check_managed(&self, settings: &AppSettings) -> Result<(), String> {
+        let managed = self.managed.lock().map_err(|_| "Failed to lock settings".to_string())?;
+        if managed.is_empty() {
+            return Ok(());
+        }
+        let value = serde_json::to_value(settings).map_err(|err| format!("Failed to encode settings: {err}"))?;
+        match managed.iter().find(|(key, locked)| value.get(key.as_str()) != Some(*locked)) {
+            Some((key, _)) => Err(format!("{key} is managed by your organization")),
+            None => Ok(()),
+        }
+    }
+
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileSummary {
+    name: String,
+    timer_count: usize,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilesOverview {
+    active_profile: String,
+    profiles: Vec<ProfileSummary>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceSummary {
+    name: String,
+    timer_count: usize,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspacesOverview {
+    active_workspace: String,
+    workspaces: Vec<WorkspaceSummary>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PushSettings {
+    target: Option<PushTarget>,
+    /// Whether the target's token is in the keychain. The token itself is
+    /// never sent back to the UI.
+    has_secret: bool,
+}
+
+#[cfg(feature = "app")]
+impl PushSettings {
+    fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            has_secret: settings.push.as_ref().is_some_and(PushTarget::has_secret),
+            target: settings.push.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EmailSettings {
+    smtp: Option<SmtpSettings>,
+    /// Whether the SMTP password is in the keychain; it is never sent back
+    /// to the UI.
+    has_password: bool,
+}
+
+#[cfg(feature = "app")]
+impl EmailSettings {
+    fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            has_password: settings.smtp.as_ref().is_some_and(SmtpSettings::has_password),
+            smtp: settings.smtp.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PauseState {
+    paused: bool,
+    paused_until: Option<DateTime<Utc>>,
+}
+
+impl PauseState {
+    fn from_settings(settings: &AppSettings) -> Self {
+        let paused_until = settings.active_pause(Utc::now());
+        Self {
+            paused: paused_until.is_some(),
+            paused_until,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum HistoryOutcome {
+    Executed,
+    Suppressed,
+    Skipped,
+    Aborted,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryEntry {
+    id: String,
+    timer_id: String,
+    action: TimerAction,
+    scheduled_for: DateTime<Utc>,
+    recorded_at: DateTime<Utc>,
+    outcome: HistoryOutcome,
+    detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PersistedHistory {
+    entries: Vec<HistoryEntry>,
+}
+
+/// How much execution history, or how many archived timers, to keep on
+/// top of the hard `MAX_HISTORY_ENTRIES` or `MAX_ARCHIVED_TIMERS` cap.
+/// Enforced by the janitor.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryRetention {
+    max_days: Option<u32>,
+    max_entries: Option<usize>,
+}
+
+impl HistoryRetention {
+    /// Drops entries, oldest first, dated before `max_days` ago and the
+    /// oldest beyond `max_entries`. Returns how many were removed.
+    #[cfg(feature = "app")]
+    fn apply<T>(&self, entries: &mut Vec<T>, dated: impl Fn(&T) -> DateTime<Utc>, now: DateTime<Utc>) -> usize {
+        let before = entries.len();
+        if let Some(days) = self.max_days {
+            let cutoff = now - ChronoDuration::days(days as i64);
+            entries.retain(|entry| dated(entry) >= cutoff);
+        }
+        if let Some(max_entries) = self.max_entries {
+            let overflow = entries.len().saturating_sub(max_entries);
+            entries.drain(..overflow);
+        }
+        before - entries.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AppearanceMode {
+    Dark,
+    Light,
+    /// Whichever of the two is not active.
+    Toggle,
+}
+
+/// What happens when a lock, logout or restart fires within
+/// `ACTION_CONFLICT_WINDOW` of a more final session end; see
+/// `TimerAction::finality`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ConflictPolicy {
+    /// The lesser action waits for the other and is skipped once it ran,
+    /// since the session is ending anyway; see `resolve_conflict`.
+    #[default]
+    DestructiveWins,
+    /// Both run as scheduled.
+    RunBoth,
+}
+
+/// How far back `get_activity_heatmap` looks.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum HeatmapRange {
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+    All,
+}
+
+#[cfg(feature = "app")]
+impl HeatmapRange {
+    fn days(self) -> Option<i64> {
+        match self {
+            HeatmapRange::Week => Some(7),
+            HeatmapRange::Month => Some(30),
+            HeatmapRange::Quarter => Some(91),
+            HeatmapRange::Year => Some(365),
+            HeatmapRange::All => None,
+        }
+    }
+}
+
+/// Runs in one local weekday/hour slot.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HeatmapCell {
+    /// 0 is Monday.
+    weekday: u32,
+    hour: u32,
+    total: usize,
+    /// Keyed by action label, e.g. "lock" or "shutdown".
+    by_action: BTreeMap<&'static str, usize>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityHeatmap {
+    range: HeatmapRange,
+    /// `None` for `all`.
+    since: Option<DateTime<Utc>>,
+    /// All 168 slots, Monday 00:00 first, including empty ones.
+    cells: Vec<HeatmapCell>,
+    /// The busiest slot's total, for scaling colors.
+    max_total: usize,
+}
+
+#[derive(Clone)]
+struct HistoryStore {
+    inner: Arc<Mutex<Vec<HistoryEntry>>>,
+    storage_path: Arc<PathBuf>,
+}
+
+impl HistoryStore {
+    fn new(storage_path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Vec::new())),
+            storage_path: Arc::new(storage_path),
+        }
+    }
+
+    fn load_persisted(&self) -> Result<(), String> {
+        if !self.storage_path.exists() {
+            return Ok(());
+        }
+
+        let raw = fs::read_to_string(self.storage_path.as_ref())
+            .map_err(|err| format!("Failed to read history: {err}"))?;
+        let persisted = serde_json::from_str::<PersistedHistory>(&raw)
+            .map_err(|err| format!("Failed to parse history: {err}"))?;
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock history".to_string())?;
+        *locked = persisted.entries;
+        Ok(())
+    }
+
+    /// Buckets runs that actually happened (executed or failed) by the
+    /// local weekday and hour they were recorded at.
+    #[cfg(feature = "app")]
+    fn heatmap(&self, range: HeatmapRange) -> Result<ActivityHeatmap, String> {
+        let since = range.days().map(|days| Utc::now() - ChronoDuration::days(days));
+        let mut cells: Vec<HeatmapCell> = (0..7 * 24)
+            .map(|slot| HeatmapCell {
+                weekday: slot / 24,
+                hour: slot % 24,
+                ..HeatmapCell::default()
+            })
+            .collect();
+
+        let locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock history".to_string())?;
+        let runs = locked
+            .iter()
+            .filter(|entry| matches!(entry.outcome, HistoryOutcome::Executed | HistoryOutcome::Failed))
+            .filter(|entry| since.map_or(true, |since| entry.recorded_at >= since));
+        for entry in runs {
+            let local = entry.recorded_at.with_timezone(&Local);
+            let cell = &mut cells[(local.weekday().num_days_from_monday() * 24 + local.hour()) as usize];
+            cell.total += 1;
+            *cell.by_action.entry(action_label(&entry.action)).or_default() += 1;
+        }
+
+        Ok(ActivityHeatmap {
+            range,
+            since,
+            max_total: cells.iter().map(|cell| cell.total).max().unwrap_or(0),
+            cells,
+        })
+    }
+
+    #[cfg(feature = "app")]
+    fn latest(&self) -> Result<Option<HistoryEntry>, String> {
+        let locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock history".to_string())?;
+        Ok(locked.last().cloned())
+    }
+
+    fn record(
+        &self,
+        info: &TimerInfo,
+        scheduled_for: DateTime<Utc>,
+        outcome: HistoryOutcome,
+        detail: Option<String>,
+    ) -> Result<(), String> {
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock history".to_string())?;
+        locked.push(HistoryEntry {
+            id: Uuid::new_v4().to_string(),
+            timer_id: info.id.clone(),
+            action: info.action.clone(),
+            scheduled_for,
+            recorded_at: Utc::now(),
+            outcome,
+            detail,
+        });
+        let overflow = locked.len().saturating_sub(MAX_HISTORY_ENTRIES);
+        locked.drain(..overflow);
+        let entries = locked.clone();
+        drop(locked);
+
+        self.write(entries)
+    }
+
+    /// Drops entries older than `max_days` and the oldest beyond
+    /// `max_entries`. Returns how many were removed.
+    #[cfg(feature = "app")]
+    fn prune(&self, retention: &HistoryRetention, now: DateTime<Utc>) -> Result<usize, String> {
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock history".to_string())?;
+        let removed = retention.apply(&mut locked, |entry| entry.recorded_at, now);
+        if removed == 0 {
+            return Ok(0);
+        }
+        let entries = locked.clone();
+        drop(locked);
+
+        self.write(entries)?;
+        Ok(removed)
+    }
+
+    fn write(&self, entries: Vec<HistoryEntry>) -> Result<(), String> {
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create history directory: {err}"))?;
+        }
+
+        let data = serde_json::to_string_pretty(&PersistedHistory { entries })
+            .map_err(|err| format!("Failed to encode history: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data)
+            .map_err(|err| format!("Failed to write history: {err}"))?;
+        Ok(())
+    }
+}
+
+/// A timer the janitor removed, as it was then; see
+/// `TimerStore::sweep_expired`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchivedTimer {
+    timer: TimerInfo,
+    archived_at: DateTime<Utc>,
+    /// Same as in the `timers-expired` event.
+    reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PersistedArchive {
+    timers: Vec<ArchivedTimer>,
+}
+
+/// Timers that expired or ran out of runs, oldest first, so they can still
+/// be looked up after they're gone. Capped at `MAX_ARCHIVED_TIMERS` and
+/// trimmed by the `archive_retention` setting.
+#[derive(Clone)]
+struct TimerArchive {
+    inner: Arc<Mutex<Vec<ArchivedTimer>>>,
+    storage_path: Arc<PathBuf>,
+}
+
+impl TimerArchive {
+    fn new(storage_path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Vec::new())),
+            storage_path: Arc::new(storage_path),
+        }
+    }
+
+    fn load_persisted(&self) -> Result<(), String> {
+        if !self.storage_path.exists() {
+            return Ok(());
+        }
+
+        let raw = fs::read_to_string(self.storage_path.as_ref())
+            .map_err(|err| format!("Failed to read archived timers: {err}"))?;
+        let persisted = serde_json::from_str::<PersistedArchive>(&raw)
+            .map_err(|err| format!("Failed to parse archived timers: {err}"))?;
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock archived timers".to_string())?;
+        *locked = persisted.timers;
+        Ok(())
+    }
+
+    /// Newest first.
+    #[cfg(feature = "app")]
+    fn list(&self) -> Result<Vec<ArchivedTimer>, String> {
+        let locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock archived timers".to_string())?;
+        Ok(locked.iter().rev().cloned().collect())
+    }
+
+    fn add(&self, timers: Vec<ArchivedTimer>) -> Result<(), String> {
+        if timers.is_empty() {
+            return Ok(());
+        }
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock archived timers".to_string())?;
+        locked.extend(timers);
+        let overflow = locked.len().saturating_sub(MAX_ARCHIVED_TIMERS);
+        locked.drain(..overflow);
+        let timers = locked.clone();
+        drop(locked);
+
+        self.write(timers)
+    }
+
+    /// Like `HistoryStore::prune`, by when each timer was archived.
+    #[cfg(feature = "app")]
+    fn prune(&self, retention: &HistoryRetention, now: DateTime<Utc>) -> Result<usize, String> {
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock archived timers".to_string())?;
+        let removed = retention.apply(&mut locked, |archived| archived.archived_at, now);
+        if removed == 0 {
+            return Ok(0);
+        }
+        let timers = locked.clone();
+        drop(locked);
+
+        self.write(timers)?;
+        Ok(removed)
+    }
+
+    fn write(&self, timers: Vec<ArchivedTimer>) -> Result<(), String> {
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create archive directory: {err}"))?;
+        }
+
+        let data = serde_json::to_string_pretty(&PersistedArchive { timers })
+            .map_err(|err| format!("Failed to encode archived timers: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data)
+            .map_err(|err| format!("Failed to write archived timers: {err}"))?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Deserialize, Clone)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    download_count: u64,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Deserialize, Clone)]
+struct GithubRelease {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    draft: bool,
+    prerelease: bool,
+    published_at: Option<String>,
+    assets: Vec<GithubAsset>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseVersion {
+    tag: String,
+    name: String,
+    published_at: Option<String>,
+    assets: Vec<ReleaseAsset>,
+    /// Architectures with a DMG: `aarch64`, `x86_64`, `universal`, or
+    /// `unlabeled` for a DMG whose name names none.
+    dmg_architectures: Vec<&'static str>,
+    /// Whether a DMG built for this Mac (or a universal one) exists;
+    /// installing without one falls back to whichever DMG there is.
+    native_dmg: bool,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseAsset {
+    name: String,
+    size: u64,
+    download_count: u64,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateInfo {
+    tag: String,
+    name: String,
+    notes: Option<String>,
+    published_at: Option<String>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TextTimerResult {
+    /// `None` when the text couldn't be parsed or the timer was rejected.
+    timer: Option<TimerView>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PermissionStatus {
+    automation: PermissionState,
+    accessibility: PermissionState,
+}
+
+/// Payload of the `permission-denied` event, emitted when an action fails
+/// because macOS refused it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PermissionDeniedEvent {
+    timer_id: String,
+    action: &'static str,
+    permission: Permission,
+    message: String,
+}
+
+/// A timer removed by the janitor; the `timers-expired` event carries a list.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExpiredTimer {
+    id: String,
+    action: &'static str,
+    reason: String,
+}
+
+/// A timer whose next run moved: a daily or weekday timer keeping its local
+/// time (the `timezone-changed` event carries a list) or one moved by
+/// `shift_timers` (one `timer-shifted` event each).
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShiftedTimer {
+    id: String,
+    action: &'static str,
+    previous_run: DateTime<Utc>,
+    next_run: DateTime<Utc>,
+}
+
+/// Which timers `shift_timers` moves. Every field that is set must match;
+/// an empty filter matches every timer.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimerFilter {
+    actions: Option<Vec<TimerAction>>,
+    profile: Option<String>,
+    /// Only timers whose next run is before this; same formats as
+    /// `targetTime`. "Tonight's timers" is `before` tomorrow morning.
+    before: Option<String>,
+}
+
+/// `TimerFilter` with `before` parsed.
+#[cfg(feature = "app")]
+struct ShiftFilter {
+    actions: Option<Vec<TimerAction>>,
+    profile: Option<String>,
+    before: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "app")]
+impl ShiftFilter {
+    fn matches(&self, info: &TimerInfo) -> bool {
+        self.actions.as_ref().map_or(true, |actions| actions.contains(&info.action))
+            && self.profile.as_ref().map_or(true, |profile| *profile == info.profile)
+            && self.before.map_or(true, |before| info.target_time < before)
+    }
+}
+
+/// Payload of the `timer-failed` event, emitted whenever a timer's action
+/// fails.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TimerFailedEvent {
+    timer_id: String,
+    action: &'static str,
+    error: String,
+}
+
+/// Payload of the `lock-failed` event, emitted when every lock method failed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LockFailedEvent {
+    timer_id: String,
+    report: LockReport,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SkippedOccurrence {
+    at: DateTime<Utc>,
+    holiday: String,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OccurrencePreview {
+    occurrences: Vec<DateTime<Utc>>,
+    /// How far either side of each of `occurrences` a run may fire.
+    jitter_minutes: Option<u32>,
+    /// The region whose holidays were applied; `None` unless the
+    /// recurrence skips holidays and a region is set.
+    holiday_region: Option<String>,
+    /// Runs dropped for falling on a holiday, interleaved with the
+    /// `occurrences` that were kept.
+    skipped: Vec<SkippedOccurrence>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateFocusSessionRequest {
+    label: Option<String>,
+    /// Defaults to now; same formats as a timer's `target_time`.
+    starts_at: Option<String>,
+    ends_at: String,
+    #[serde(default)]
+    blocked_apps: Vec<String>,
+    #[serde(default)]
+    mode: BlockMode,
+    #[serde(default)]
+    block_websites: bool,
+}
+
+/// Every `JANITOR_INTERVAL` for the life of the app: archives expired
+/// timers, applies the history and archive retention policies, fetches the holidays
+/// `skipHolidays` recurrences will look up and deletes installer DMGs that
+/// are no longer needed.
+#[cfg(feature = "app")]
+fn spawn_janitor(store: TimerStore) {
+    thread::spawn(move || loop {
+        if let Err(err) = store.sweep_expired() {
+            eprintln!("Failed to remove expired timers: {err}");
+        }
+        if let Ok(settings) = store.settings.snapshot() {
+            if let Err(err) = store.history.prune(&settings.history_retention, Utc::now()) {
+                eprintln!("Failed to prune history: {err}");
+            }
+            if let Err(err) = store.archive.prune(&settings.archive_retention, Utc::now()) {
+                eprintln!("Failed to prune archived timers: {err}");
+            }
+        }
+        if let Ok(Some(region)) = store.settings.snapshot().map(|settings| settings.holiday_region) {
+            let year = Local::now().year();
+            store.holidays.prefetch(&region, &[year, year + 1]);
+        }
+        let running = normalize_version(env!("CARGO_PKG_VERSION"));
+        installer::sweep_cache(running.as_ref());
+        thread::sleep(JANITOR_INTERVAL);
+    });
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CountdownTick {
+    id: String,
+    message: Option<String>,
+    seconds_remaining: i64,
+    remaining_text: String,
+}
+
+/// Sends the remaining time of every running countdown to the UI each
+/// second, and one empty tick when the last one finishes.
+#[cfg(feature = "app")]
+fn spawn_countdown_ticker(store: TimerStore) {
+    thread::spawn(move || {
+        let mut ticking = false;
+        loop {
+            thread::sleep(COUNTDOWN_TICK_INTERVAL);
+            let now = Utc::now();
+            let ticks: Vec<CountdownTick> = store
+                .timers()
+                .values()
+                .filter(|entry| entry.cancel_tx.is_some())
+                .filter(|entry| matches!(entry.info.action, TimerAction::Countdown { .. }))
+                .map(|entry| {
+                    let seconds_remaining = (entry.info.target_time - now).num_seconds().max(0);
+                    CountdownTick {
+                        id: entry.info.id.clone(),
+                        message: entry.info.message.clone(),
+                        seconds_remaining,
+                        remaining_text: humanize_remaining(seconds_remaining),
+                    }
+                })
+                .collect();
+            if ticks.is_empty() && !ticking {
+                continue;
+            }
+            ticking = !ticks.is_empty();
+            store.emit("countdown-tick", ticks);
+        }
+    });
+}
+
+/// Every `INCOMING_CHECK_INTERVAL`, creates the timers `lockpilot
+/// --create` queued, starting with those left while LockPilot wasn't
+/// running.
+#[cfg(feature = "app")]
+fn spawn_incoming_watcher(store: TimerStore) {
+    thread::spawn(move || loop {
+        store.create_queued();
+        thread::sleep(INCOMING_CHECK_INTERVAL);
+    });
+}
+
+/// Polls the local UTC offset and re-anchors local-time recurrences when
+/// it changes.
+#[cfg(feature = "app")]
+fn spawn_timezone_watcher(store: TimerStore) {
+    thread::spawn(move || {
+        let mut offset = *Local::now().offset();
+        loop {
+            thread::sleep(TIMEZONE_CHECK_INTERVAL);
+            let current = *Local::now().offset();
+            if current == offset {
+                continue;
+            }
+            if let Err(err) = store.reanchor_local_times(offset) {
+                eprintln!("Failed to reschedule timers after time zone change: {err}");
+            }
+            offset = current;
+        }
+    });
+}
+
+/// The next occurrence of a scheduled timer, waiting in the scheduler
+/// queue.
+struct ScheduledRun {
+    info: TimerInfo,
+    next_run: DateTime<Utc>,
+    shifted_from: Option<DateTime<Utc>>,
+    cancel_rx: mpsc::Receiver<()>,
+    /// Set while queued early to keep the Mac awake: when the entry comes
+    /// due, the assertion is taken and the run re-queued for this time.
+    fire_at: Option<DateTime<Utc>>,
+    /// Set while queued early for the timer's warning: when the entry
+    /// comes due, the warning is posted and the run re-queued for this
+    /// time, which is `fire_at`'s early entry if there is one.
+    after_warning: Option<DateTime<Utc>>,
+    /// Released when the occurrence has run, or the run is dropped.
+    stay_awake: Option<system::StayAwake>,
+    /// For `must_run` timers; cancelled if the run is dropped before it
+    /// comes due.
+    wake: Option<ScheduledWake>,
+}
+
+impl ScheduledRun {
+    /// The timer was cancelled, rescheduled or removed since this run was
+    /// queued.
+    fn is_cancelled(&self) -> bool {
+        !matches!(self.cancel_rx.try_recv(), Err(mpsc::TryRecvError::Empty))
+    }
+}
+
+fn schedule_timer(store: &TimerStore, task_info: TimerInfo, cancel_rx: mpsc::Receiver<()>) {
+    queue_run(
+        store,
+        ScheduledRun {
+            next_run: task_info.target_time,
+            shifted_from: task_info.shifted_from,
+            info: task_info,
+            cancel_rx,
+            fire_at: None,
+            after_warning: None,
+            stay_awake: None,
+            wake: None,
+        },
+    );
+}
+
+fn queue_run(store: &TimerStore, mut run: ScheduledRun) {
+    let due = jittered_due(store, &mut run);
+    let awake_minutes = if run.info.action.needs_mac_awake() && run.info.trigger.is_none() {
+        store
+            .settings
+            .snapshot()
+            .map(|settings| settings.stay_awake_minutes)
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    if run.info.must_run {
+        let wake_at = due - ChronoDuration::minutes(WAKE_LEAD_MINUTES + awake_minutes as i64);
+        if wake_at > Utc::now() {
+            match ScheduledWake::register(wake_at.with_timezone(&Local)) {
+                Ok(wake) => run.wake = Some(wake),
+                Err(err) => eprintln!("Failed to schedule a wake for timer {}: {err}", run.info.id),
+            }
+        }
+    }
+    let mut queued_for = if awake_minutes > 0 {
+        run.fire_at = Some(due);
+        due - ChronoDuration::minutes(awake_minutes as i64)
+    } else {
+        due
+    };
+    let warn_at = run
+        .info
+        .warning_minutes
+        .filter(|_| run.info.trigger.is_none())
+        .map(|minutes| due - ChronoDuration::minutes(minutes.into()))
+        .filter(|warn_at| *warn_at < queued_for && *warn_at > Utc::now());
+    if let Some(warn_at) = warn_at {
+        run.after_warning = Some(queued_for);
+        queued_for = warn_at;
+    }
+    if let Err(err) = store.queue.push(queued_for, run) {
+        eprintln!("Failed to schedule timer: {err}");
+    }
+}
+
+/// Takes the sleep assertion for a run queued early and queues it again
+/// for when it is actually due.
+fn keep_awake_until_due(store: &TimerStore, mut run: ScheduledRun, due: DateTime<Utc>) {
+    let limit = (due - Utc::now()).to_std().unwrap_or_default() + STAY_AWAKE_GRACE;
+    match system::StayAwake::start(limit) {
+        Ok(stay_awake) => run.stay_awake = Some(stay_awake),
+        Err(err) => eprintln!("Failed to keep the Mac awake for timer {}: {err}", run.info.id),
+    }
+    if let Err(err) = store.queue.push(due, run) {
+        eprintln!("Failed to schedule timer: {err}");
+    }
+}
+
+/// Starts the one thread that waits on every timer's next deadline, and
+/// the one sampling the conditions of triggered timers once due. Each due
+/// occurrence is fired on its own thread, since firing can block on a
+/// confirmation dialog, quiet hours or apps quitting.
+pub fn spawn_scheduler(store: TimerStore) {
+    triggers::spawn_monitor(store.clone());
+    let queue = store.queue.clone();
+    queue.run(ScheduledRun::is_cancelled, move |mut run| {
+        if let Some(next) = run.after_warning.take() {
+            post_warning(&run.info, run.fire_at.unwrap_or(next));
+            if let Err(err) = store.queue.push(next, run) {
+                eprintln!("Failed to schedule timer: {err}");
+            }
+            return;
+        }
+        if let Some(due) = run.fire_at.take() {
+            keep_awake_until_due(&store, run, due);
+            return;
+        }
+        let store = store.clone();
+        let firing = FiringGuard::enter(&store.firing);
+        thread::spawn(move || {
+            let _firing = firing;
+            fire_scheduled(&store, run);
+        });
+    });
+}
+
+/// The notification `warning_minutes` ahead of a run, e.g. "Lock in 5
+/// minutes", with the timer's Snooze and Cancel buttons.
+fn post_warning(info: &TimerInfo, due: DateTime<Utc>) {
+    let minutes = ((due - Utc::now()).num_seconds().max(0) + 59) / 60;
+    let label = action_label(&info.action);
+    let mut label_chars = label.chars();
+    let label = label_chars
+        .next()
+        .map(|first| first.to_uppercase().chain(label_chars).collect::<String>())
+        .unwrap_or_default();
+    let title = match minutes {
+        0 | 1 => format!("{label} in 1 minute"),
+        minutes => format!("{label} in {minutes} minutes"),
+    };
+    let body = info
+        .message
+        .clone()
+        .unwrap_or_else(|| format!("Scheduled for {}", due.with_timezone(&Local).format("%H:%M")));
+    if let Err(err) = notifications::post_for_timer(&info.id, &title, &body) {
+        eprintln!("Failed to post the warning for timer {}: {err}", info.id);
+    }
+}
+
+/// Fires one due occurrence and queues the timer's next one.
+fn fire_scheduled(store: &TimerStore, mut run: ScheduledRun) {
+    if let Some(wake) = run.wake.take() {
+        wake.keep();
+    }
+    if run.is_cancelled() {
+        return;
+    }
+    // Its target time only arms a triggered timer; the monitor fires it.
+    if run.info.trigger.is_some() {
+        store.triggers.watch(run, true);
+        return;
+    }
+    let info = &run.info;
+    let next_run = run.next_run;
+
+    if info.expires_at.is_some_and(|expires_at| next_run >= expires_at) {
+        let _ = store.sweep_expired();
+        return;
+    }
+
+    // While a global pause is active nothing but managed timers fires; the
+    // occurrence is skipped the same way a missed occurrence is skipped on
+    // restore.
+    let paused_until = store
+        .settings
+        .snapshot()
+        .ok()
+        .and_then(|settings| settings.pause_for(info, Utc::now()));
+
+    let holiday = info
+        .recurrence
+        .as_ref()
+        .and_then(|recurrence_cfg| store.holiday_skip(recurrence_cfg, next_run));
+
+    if paused_until.is_some() {
+        let _ = store.history.record(
+            info,
+            next_run,
+            HistoryOutcome::Skipped,
+            Some("Global pause active".to_string()),
+        );
+    } else if let Some(holiday) = holiday {
+        let _ = store.history.record(
+            info,
+            next_run,
+            HistoryOutcome::Skipped,
+            Some(format!("Public holiday: {}", holiday.name)),
+        );
+    } else if !fire_occurrence(store, info, next_run, &run.cancel_rx) {
+        return;
+    }
+    run.stay_awake = None;
+
+    if info.recurrence.is_some() && store.run_limit_reached(&info.id) {
+        let _ = store.sweep_expired();
+        return;
+    }
+
+    let computed_next = info.recurrence.as_ref().and_then(|recurrence_cfg| {
+        let cutoff = paused_until.unwrap_or(next_run);
+        next_run_after(run.shifted_from.take().unwrap_or(next_run), recurrence_cfg, cutoff)
+    });
+    {
+        let mut locked = store.timers();
+        // Checked under the store lock, which every cancel and reschedule
+        // holds while signalling, so a shift or cancel that raced the
+        // firing wins.
+        if run.is_cancelled() {
+            return;
+        }
+        let Some(updated_next) = computed_next else {
+            locked.remove(&run.info.id);
+            drop(locked);
+            let _ = store.persist();
+            return;
+        };
+        let Some(entry) = locked.get_mut(&run.info.id) else {
+            return;
+        };
+        entry.info.target_time = updated_next;
+        entry.info.shifted_from = None;
+        run.next_run = updated_next;
+    }
+    let _ = store.persist();
+    queue_run(store, run);
+}
+
+/// Fires a triggered timer whose condition held, then hands a repeating
+/// one back to the monitor or removes a one-time one.
+fn fire_triggered(store: &TimerStore, run: ScheduledRun) {
+    if run.is_cancelled() {
+        return;
+    }
+    let info = &run.info;
+    let now = Utc::now();
+    if info.expires_at.is_some_and(|expires_at| now >= expires_at) {
+        let _ = store.sweep_expired();
+        return;
+    }
+
+    let paused = store
+        .settings
+        .snapshot()
+        .is_ok_and(|settings| settings.pause_for(info, now).is_some());
+    if paused {
+        let _ = store.history.record(
+            info,
+            now,
+            HistoryOutcome::Skipped,
+            Some("Global pause active".to_string()),
+        );
+    } else if !fire_occurrence(store, info, now, &run.cancel_rx) {
+        return;
+    }
+
+    if info.trigger.as_ref().is_some_and(|trigger| trigger.repeat) {
+        if store.run_limit_reached(&info.id) {
+            let _ = store.sweep_expired();
+        } else {
+            let _ = store.persist();
+            store.triggers.watch(run, false);
+        }
+        return;
+    }
+    let mut locked = store.timers();
+    if run.is_cancelled() {
+        return;
+    }
+    locked.remove(&run.info.id);
+    drop(locked);
+    let _ = store.persist();
+}
+
+/// When `run` is due: its scheduled time moved by a random offset of up to
+/// `jitter_minutes` either way. The offset is rolled once per occurrence
+/// and kept on the timer, so queueing the same occurrence again after a
+/// warning, a pause, an edit or a restart doesn't move it again.
+fn jittered_due(store: &TimerStore, run: &mut ScheduledRun) -> DateTime<Utc> {
+    let Some(span) = run.info.jitter_minutes.map(|minutes| minutes as i64 * 60).filter(|span| *span > 0) else {
+        return run.next_run;
+    };
+    let rolled = run
+        .info
+        .jitter
+        .filter(|jitter| jitter.occurrence == run.next_run && jitter.offset_seconds.abs() <= span);
+    let jitter = match rolled {
+        Some(jitter) => jitter,
+        None => {
+            let jitter = JitterRoll {
+                occurrence: run.next_run,
+                offset_seconds: fastrand::i64(-span..=span),
+            };
+            run.info.jitter = Some(jitter);
+            if let Some(entry) = store.timers().get_mut(&run.info.id) {
+                entry.info.jitter = Some(jitter);
+            }
+            if let Err(err) = store.persist() {
+                eprintln!("Failed to save the jitter of timer {}: {err}", run.info.id);
+            }
+            jitter
+        }
+    };
+    run.next_run + ChronoDuration::seconds(jitter.offset_seconds)
+}
+
+/// Sleeps until the wall clock reaches `deadline`. `recv_timeout` counts
+/// monotonic time, which neither follows clock corrections nor advances
+/// while the Mac sleeps, so the wait is split into short slices that are
+/// re-anchored to `Utc::now()`. Returns `true` if the timer was cancelled.
+fn wait_until(deadline: DateTime<Utc>, cancel_rx: &mpsc::Receiver<()>) -> bool {
+    loop {
+        let Ok(remaining) = (deadline - Utc::now()).to_std() else {
+            return false;
+        };
+        match cancel_rx.recv_timeout(remaining.min(CLOCK_CHECK_INTERVAL)) {
+            Ok(()) => return true,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
+
+/// Runs one occurrence, applying quiet hours right before the action.
+/// Returns `false` if the timer was cancelled while a queued firing waited.
+fn fire_occurrence(
+    store: &TimerStore,
+    info: &TimerInfo,
+    scheduled_for: DateTime<Utc>,
+    cancel_rx: &mpsc::Receiver<()>,
+) -> bool {
+    let quiet_hours = store.settings.snapshot().ok().and_then(|settings| {
+        settings
+            .active_quiet_hours(Local::now())
+            .filter(|_| info.action.is_non_critical())
+            .cloned()
+    });
+
+    let outside_hours = store.settings.snapshot().ok().and_then(|settings| {
+        settings
+            .working_hours_for(info.working_hours.as_ref(), info.ignore_working_hours)
+            .filter(|hours| !hours.contains(Local::now().time()))
+            .cloned()
+    });
+
+    let mut detail = info
+        .trigger
+        .as_ref()
+        .map(|trigger| format!("Triggered: {}", trigger.describe()));
+    if let Some(hours) = outside_hours {
+        match hours.policy {
+            OutsideHoursPolicy::Skip => {
+                let _ = store.history.record(
+                    info,
+                    scheduled_for,
+                    HistoryOutcome::Suppressed,
+                    Some("Outside working hours".to_string()),
+                );
+                return true;
+            }
+            OutsideHoursPolicy::Shift => {
+                let start = hours.next_start_after(Local::now());
+                if wait_until(start, cancel_rx) {
+                    return false;
+                }
+                append_detail(&mut detail, format!("Held until working hours started at {}", start.to_rfc3339()));
+            }
+        }
+    }
+
+    if let Some(quiet) = quiet_hours {
+        match quiet.mode {
+            QuietHoursMode::Skip => {
+                let _ = store.history.record(
+                    info,
+                    scheduled_for,
+                    HistoryOutcome::Suppressed,
+                    Some("Quiet hours".to_string()),
+                );
+                return true;
+            }
+            QuietHoursMode::Queue => {
+                let resume_at = quiet.next_end_after(Local::now());
+                if wait_until(resume_at, cancel_rx) {
+                    return false;
+                }
+                append_detail(&mut detail, format!("Held by quiet hours until {}", resume_at.to_rfc3339()));
+            }
+        }
+    }
+
+    if let Some(condition) = info.condition.as_deref() {
+        let skip = match conditions::evaluate(condition) {
+            Ok(true) => None,
+            Ok(false) => Some((HistoryOutcome::Skipped, "Condition not met".to_string())),
+            Err(err) => Some((HistoryOutcome::Failed, err)),
+        };
+        if let Some((outcome, reason)) = skip {
+            let _ = store.history.record(info, scheduled_for, outcome, Some(reason));
+            return true;
+        }
+    }
+
+    match resolve_conflict(store, info, cancel_rx) {
+        Conflict::Run(note) => {
+            if let Some(note) = note {
+                append_detail(&mut detail, note);
+            }
+        }
+        Conflict::Superseded(reason) => {
+            let _ = store
+                .history
+                .record(info, scheduled_for, HistoryOutcome::Suppressed, Some(reason));
+            return true;
+        }
+        Conflict::Cancelled => return false,
+    }
+
+    if let Some(confirmation) = info.confirmation.as_ref() {
+        let declined = match store.dialogs.exclusive(|| ask_confirmation(info, confirmation)) {
+            Ok(Some(true)) => None,
+            Ok(Some(false)) => Some("Declined at confirmation".to_string()),
+            Ok(None) if confirmation.proceed_on_timeout => {
+                append_detail(&mut detail, "Not confirmed in time; proceeded".to_string());
+                None
+            }
+            Ok(None) => Some("Not confirmed in time".to_string()),
+            Err(err) if confirmation.proceed_on_timeout => {
+                append_detail(&mut detail, format!("Confirmation failed ({err}); proceeded"));
+                None
+            }
+            Err(err) => Some(format!("Confirmation failed ({err})")),
+        };
+        if let Some(reason) = declined {
+            let _ = store
+                .history
+                .record(info, scheduled_for, HistoryOutcome::Skipped, Some(reason));
+            return true;
+        }
+    }
+
+    let hooks = info.hooks.as_ref();
+    // Timers created before the policy forbade hooks fail them.
+    let hooks_allowed = store.policy.check_hooks(hooks);
+    if let Some(script) = hooks.and_then(|hooks| hooks.pre_script.as_deref()) {
+        if let Err(err) = hooks_allowed
+            .clone()
+            .and_then(|()| run_hook(script, info, scheduled_for, None))
+        {
+            if hooks.is_some_and(|hooks| hooks.abort_on_pre_failure) {
+                let reason = format!("Scheduled {} cancelled: pre-hook failed", action_label(&info.action));
+                notify_user(&reason);
+                let _ = store.history.record(
+                    info,
+                    scheduled_for,
+                    HistoryOutcome::Aborted,
+                    Some(format!("{reason} ({err})")),
+                );
+                return true;
+            }
+            append_detail(&mut detail, format!("Pre-hook failed ({err})"));
+        }
+    }
+
+    match prepare_action(info) {
+        Ok(Some(note)) => append_detail(&mut detail, note),
+        Ok(None) => {}
+        Err(reason) => {
+            notify_user(&reason);
+            let _ = store
+                .history
+                .record(info, scheduled_for, HistoryOutcome::Aborted, Some(reason));
+            return true;
+        }
+    }
+
+    if info.action.ends_session() {
+        if let Ok(mut last) = store.last_session_end.lock() {
+            *last = Some(SessionEnd {
+                timer_id: info.id.clone(),
+                action: info.action.clone(),
+                started_at: Utc::now(),
+                succeeded: None,
+            });
+        }
+    }
+    // A timed-out action fails this occurrence only; the next one is queued
+    // as usual once this returns.
+    let result = exec::supervised(info.action.process_timeout(), || {
+        if info.pause_media {
+            match media::pause() {
+                Ok(Some(note)) => append_detail(&mut detail, note),
+                Ok(None) => {}
+                Err(err) => append_detail(&mut detail, err),
+            }
+        }
+        run_action(store, info, scheduled_for)
+    })
+    .and_then(|result| result);
+    if info.action.ends_session() {
+        if let Ok(mut last) = store.last_session_end.lock() {
+            if let Some(last) = last.as_mut().filter(|last| last.timer_id == info.id) {
+                last.succeeded = Some(result.is_ok());
+            }
+        }
+    }
+    let _ = store.record_run(&info.id, Utc::now());
+    let (outcome, result_note) = match result {
+        Ok(note) => (HistoryOutcome::Executed, note),
+        Err(err) => {
+            let label = action_label(&info.action);
+            notify_user(&format!("The scheduled {label} did not run: {}", err.trim()));
+            store.emit(
+                "timer-failed",
+                TimerFailedEvent {
+                    timer_id: info.id.clone(),
+                    action: label,
+                    error: err.clone(),
+                },
+            );
+            if let Some(permission) = system::denied_permission(&err) {
+                store.emit(
+                    "permission-denied",
+                    PermissionDeniedEvent {
+                        timer_id: info.id.clone(),
+                        action: action_label(&info.action),
+                        permission,
+                        message: err.clone(),
+                    },
+                );
+            }
+            (HistoryOutcome::Failed, Some(err))
+        }
+    };
+    if let Some(note) = result_note {
+        append_detail(&mut detail, note);
+    }
+
+    if matches!(outcome, HistoryOutcome::Failed) {
+        if let Some(note) = email_failure_notice(store, info, scheduled_for, detail.as_deref()) {
+            append_detail(&mut detail, note);
+        }
+    }
+    if store.settings.snapshot().is_ok_and(|settings| settings.calendar_log) {
+        let failed = matches!(outcome, HistoryOutcome::Failed);
+        if let Err(err) = calendar_log::log_run(info, failed, detail.as_deref()) {
+            append_detail(&mut detail, format!("Calendar entry not added ({err})"));
+        }
+    }
+    if let Some(webhook) = info.chat_webhook.as_ref() {
+        let failed = matches!(outcome, HistoryOutcome::Failed);
+        if failed || !webhook.only_failures {
+            if let Err(err) = chat::notify(webhook, info, failed, detail.as_deref()) {
+                append_detail(&mut detail, format!("Chat post failed ({err})"));
+            }
+        }
+    }
+
+    if let Some(script) = hooks.and_then(|hooks| hooks.post_script.as_deref()) {
+        let outcome_name = match outcome {
+            HistoryOutcome::Failed => "failed",
+            _ => "executed",
+        };
+        if let Err(err) = hooks_allowed.and_then(|()| run_hook(script, info, scheduled_for, Some(outcome_name))) {
+            append_detail(&mut detail, format!("Post-hook failed ({err})"));
+        }
+    }
+
+    let _ = store.history.record(info, scheduled_for, outcome, detail);
+    true
+}
+
+/// How `resolve_conflict` settled an occurrence.
+enum Conflict {
+    /// Go ahead, noting in history why it was held, if it was.
+    Run(Option<String>),
+    /// Skip it as suppressed, for this reason.
+    Superseded(String),
+    /// The timer was cancelled while held.
+    Cancelled,
+}
+
+/// Under `ConflictPolicy::DestructiveWins`, holds a lock, logout or restart
+/// while a more final session end (see `TimerAction::finality`) is due or
+/// running within `ACTION_CONFLICT_WINDOW`, and skips it only once that one
+/// ran. If the other is declined, aborted, held back or fails, this one
+/// runs after all, so a lock is never lost to a shutdown that didn't
+/// happen. Holds for at most `ACTION_CONFLICT_WINDOW`.
+fn resolve_conflict(store: &TimerStore, info: &TimerInfo, cancel_rx: &mpsc::Receiver<()>) -> Conflict {
+    let Some(finality) = info.action.finality() else {
+        return Conflict::Run(None);
+    };
+    let policy = store.settings.snapshot().map(|settings| settings.conflict_policy);
+    if policy != Ok(ConflictPolicy::DestructiveWins) {
+        return Conflict::Run(None);
+    }
+    let outranks = |action: &TimerAction| action.finality().is_some_and(|other| other > finality);
+
+    let fired_at = Utc::now();
+    let deadline = fired_at + ACTION_CONFLICT_WINDOW;
+    let mut held_for = None;
+    loop {
+        let now = Utc::now();
+        let started = store.last_session_end.lock().ok().and_then(|last| {
+            last.as_ref()
+                .filter(|started| started.timer_id != info.id && outranks(&started.action))
+                .filter(|started| fired_at - started.started_at <= ACTION_CONFLICT_WINDOW)
+                .cloned()
+        });
+        let label = match started {
+            Some(started) => {
+                let label = format!("{} timer {}", action_label(&started.action), started.timer_id);
+                match started.succeeded {
+                    Some(true) => {
+                        return Conflict::Superseded(format!(
+                            "Superseded by {label} started at {}",
+                            started.started_at.to_rfc3339()
+                        ))
+                    }
+                    Some(false) => return Conflict::Run(Some(format!("Held for {label}, which failed"))),
+                    None => label,
+                }
+            }
+            None => {
+                let due = store
+                    .timers()
+                    .values()
+                    .filter(|entry| entry.cancel_tx.is_some() && entry.info.id != info.id)
+                    .filter(|entry| entry.info.trigger.is_none() && outranks(&entry.info.action))
+                    .filter(|entry| (entry.info.target_time - now).abs() <= ACTION_CONFLICT_WINDOW)
+                    .min_by_key(|entry| entry.info.target_time)
+                    .map(|entry| format!("{} timer {}", action_label(&entry.info.action), entry.info.id));
+                match due {
+                    Some(label) => label,
+                    None => return Conflict::Run(held_for.map(|label| format!("Held for {label}, which did not run"))),
+                }
+            }
+        };
+        if now >= deadline {
+            return Conflict::Run(Some(format!("Held for {label} until {}; ran anyway", now.to_rfc3339())));
+        }
+        held_for = Some(label);
+        if wait_until((now + CONFLICT_CHECK_INTERVAL).min(deadline), cancel_rx) {
+            return Conflict::Cancelled;
+        }
+    }
+}
+
+/// Mails a failed run to the SMTP recipients when `notifyFailures` is on.
+/// Failed `Email` timers are not reported this way, since the notice would
+/// go through the same server.
+fn email_failure_notice(
+    store: &TimerStore,
+    info: &TimerInfo,
+    scheduled_for: DateTime<Utc>,
+    detail: Option<&str>,
+) -> Option<String> {
+    let smtp = store
+        .settings
+        .snapshot()
+        .ok()?
+        .smtp
+        .filter(|smtp| smtp.notify_failures && !matches!(info.action, TimerAction::Email))?;
+    let label = action_label(&info.action);
+    let subject = format!("LockPilot: scheduled {label} failed");
+    let body = format!(
+        "The {label} timer {} scheduled for {} failed.\n\n{}",
+        info.id,
+        scheduled_for.with_timezone(&Local).to_rfc2822(),
+        detail.unwrap_or("No details were recorded."),
+    );
+    Some(match email::send(&smtp, &subject, &body) {
+        Ok(_) => "Failure emailed".to_string(),
+        Err(err) => format!("Failure email not sent ({err})"),
+    })
+}
+
+fn append_detail(detail: &mut Option<String>, note: String) {
+    *detail = Some(match detail.take() {
+        Some(existing) => format!("{existing}; {note}"),
+        None => note,
+    });
+}
+
+/// Exposes the timer's metadata to hook scripts and plugins.
+fn apply_timer_env(command: &mut Command, info: &TimerInfo, scheduled_for: DateTime<Utc>) {
+    command
+        .env("LOCKPILOT_TIMER_ID", &info.id)
+        .env("LOCKPILOT_ACTION", action_label(&info.action))
+        .env("LOCKPILOT_SCHEDULED_FOR", scheduled_for.to_rfc3339())
+        .env("LOCKPILOT_RECURRING", if info.recurrence.is_some() { "1" } else { "0" })
+        .env("LOCKPILOT_MESSAGE", info.message.as_deref().unwrap_or_default());
+    if let TimerAction::Plugin { id } = &info.action {
+        command.env("LOCKPILOT_PLUGIN_ID", id);
+    }
+}
+
+/// Runs a hook script with the timer's metadata in `LOCKPILOT_*` variables,
+/// killing it after `HOOK_TIMEOUT`.
+fn run_hook(
+    script: &str,
+    info: &TimerInfo,
+    scheduled_for: DateTime<Utc>,
+    outcome: Option<&str>,
+) -> Result<(), String> {
+    let mut command = Command::new("/bin/sh");
+    command.arg("-c").arg(script);
+    apply_timer_env(&mut command, info, scheduled_for);
+    if let Some(outcome) = outcome {
+        command.env("LOCKPILOT_OUTCOME", outcome);
+    }
+
+    let output = exec::run_with_timeout(&mut command, None, HOOK_TIMEOUT)
+        .map_err(|err| format!("Failed to run hook: {err}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("{}: {}", output.status, stderr.trim()))
+    }
+}
+
+/// Pre-flight steps that may veto an action, e.g. apps refusing to quit
+/// before a shutdown. An `Err` carries the user-facing reason; `Ok(Some(_))`
+/// is a note for the history entry.
+fn prepare_action(info: &TimerInfo) -> Result<Option<String>, String> {
+    let mut note = None;
+    if let Some(eject) = info.eject_disks.as_ref() {
+        let label = action_label(&info.action);
+        match exec::supervised(ACTION_PROCESS_TIMEOUT, disks::eject_external).and_then(|report| report) {
+            Ok(report) if !report.busy.is_empty() => {
+                let busy = report.busy_summary();
+                if eject.abort_if_busy {
+                    return Err(format!("Scheduled {label} cancelled: disks still in use: {busy}"));
+                }
+                notify_user(&format!("Proceeding with scheduled {label}; these disks could not be ejected: {busy}"));
+                note = Some(format!("Could not eject {busy}"));
+            }
+            Ok(report) if !report.ejected.is_empty() => {
+                note = Some(format!("Ejected {}", report.ejected.join(", ")));
+            }
+            Ok(_) => {}
+            Err(err) if eject.abort_if_busy => {
+                return Err(format!("Scheduled {label} cancelled: disks could not be ejected ({err})"));
+            }
+            Err(err) => note = Some(format!("Disks not ejected ({err})")),
+        }
+    }
+
+    let Some(options) = info.shutdown_options.as_ref() else {
+        return Ok(note);
+    };
+
+    if let Some(policy) = options.unsaved_work_policy.as_ref() {
+        // If detection itself fails (e.g. no Accessibility access) we can't
+        // prove the work is safe, so the abort policy treats it as unsaved.
+        let unsaved = apps_with_unsaved_work()
+            .unwrap_or_else(|err| vec![format!("unknown apps ({})", err.trim())]);
+        if !unsaved.is_empty() {
+            let apps = unsaved.join(", ");
+            match policy {
+                UnsavedWorkPolicy::Abort => {
+                    return Err(format!(
+                        "Scheduled {} cancelled: unsaved changes in {apps}",
+                        action_label(&info.action)
+                    ));
+                }
+                UnsavedWorkPolicy::Proceed => {
+                    notify_user(&format!(
+                        "Proceeding with scheduled {} despite unsaved changes in {apps}",
+                        action_label(&info.action)
+                    ));
+                    note = Some(format!("Proceeded with unsaved changes in {apps}"));
+                }
+            }
+        }
+    }
+
+    if let Some(timeout) = options.quit_apps_timeout_seconds {
+        let remaining = quit_running_apps(Duration::from_secs(timeout as u64))?;
+        if !remaining.is_empty() && options.abort_if_apps_refuse {
+            return Err(format!(
+                "Scheduled {} cancelled: {} did not quit",
+                action_label(&info.action),
+                remaining.join(", ")
+            ));
+        }
+    }
+
+    if let Some(reopen) = options.reopen_windows {
+        if let Err(err) = system::set_reopen_windows(reopen) {
+            append_detail(&mut note, format!("Reopen windows setting not applied ({err})"));
+        }
+    }
+
+    Ok(note)
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginCatalog {
+    directory: String,
+    plugins: Vec<PluginInfo>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageToday {
+    day: NaiveDate,
+    active_minutes: u64,
+    limit_minutes: Option<u32>,
+    /// Start of the current stretch of use without a 3-minute idle break.
+    active_since: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageReportDay {
+    day: NaiveDate,
+    active_minutes: u64,
+    first_active_at: Option<DateTime<Utc>>,
+    last_active_at: Option<DateTime<Utc>>,
+    /// Times the usage limit scheduled its lock or shutdown that day.
+    limit_triggers: u32,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageReport {
+    /// Oldest first; days the Mac wasn't used at all are missing.
+    days: Vec<UsageReportDay>,
+    /// The current limit, which earlier days may not have had.
+    limit_minutes: Option<u32>,
+    average_active_minutes: u64,
+    days_over_limit: usize,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BreakReport {
+    /// Oldest first; days without reminders are missing.
+    days: Vec<DailyBreaks>,
+    nudged: u32,
+    taken: u32,
+    skipped: u32,
+    postponed: u32,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CrontabImportResult {
+    created: Vec<TimerInfo>,
+    skipped: Vec<SkippedCrontabLine>,
+}
+
+#[cfg(feature = "app")]
+fn profile_exists(state: &TimerStore, name: &str) -> CommandResult<()> {
+    match state.settings.snapshot()?.find_profile(name) {
+        Some(_) => Ok(()),
+        None => Err(CommandError::not_found(format!("Profile not found: {name}")).with_context(name)),
+    }
+}
+
+#[cfg(feature = "app")]
+fn workspace_exists(state: &TimerStore, name: &str) -> CommandResult<()> {
+    match state.settings.snapshot()?.find_workspace(name) {
+        Some(_) => Ok(()),
+        None => Err(CommandError::not_found(format!("Workspace not found: {name}")).with_context(name)),
+    }
+}
+
+/// Runs the timer's action. Plugins report their own result; lock reports
+/// which step of its fallback chain worked; shutdown and restart report
+/// why loginwindow refused the event. Popups are merged with any others
+/// firing at the same time; closing one with Escape is not a failure.
+fn run_action(store: &TimerStore, info: &TimerInfo, scheduled_for: DateTime<Utc>) -> Result<Option<String>, String> {
+    // Timers created before the policy forbade their action.
+    store.policy.check_action(&info.action)?;
+    match &info.action {
+        TimerAction::Popup => {
+            if let Some(msg) = info.message.as_deref() {
+                if info.escalation.is_some() || info.repeat_until_acknowledged.is_some() {
+                    return present_until_acknowledged(store, info, msg, scheduled_for);
+                }
+                store
+                    .dialogs
+                    .show(msg, |text| run_osascript(&popup_script(text)))
+                    .or_else(|err| {
+                        if err.contains(USER_CANCELED_ERROR) {
+                            Ok(())
+                        } else {
+                            Err(format!("Popup could not be shown: {}", err.trim()))
+                        }
+                    })?;
+            }
+        }
+        TimerAction::Lock => {
+            let compatibility = store
+                .settings
+                .snapshot()
+                .map(|settings| settings.lock_compatibility)
+                .unwrap_or(false);
+            let report = lock::lock_screen(compatibility);
+            if report.locked_by.is_some() {
+                return Ok(Some(report.summary()));
+            }
+            let summary = report.summary();
+            store.emit(
+                "lock-failed",
+                LockFailedEvent {
+                    timer_id: info.id.clone(),
+                    report,
+                },
+            );
+            return Err(summary);
+        }
+        TimerAction::Shutdown | TimerAction::Reboot | TimerAction::Logout => {
+            let force = info.shutdown_options.as_ref().is_some_and(|options| options.force);
+            let event = match (&info.action, force) {
+                (TimerAction::Shutdown, true) => PowerEvent::ShutDown,
+                (TimerAction::Shutdown, false) => PowerEvent::ConfirmShutDown,
+                (TimerAction::Reboot, true) => PowerEvent::Restart,
+                (TimerAction::Reboot, false) => PowerEvent::ConfirmRestart,
+                (_, true) => PowerEvent::LogOut,
+                (_, false) => PowerEvent::ConfirmLogOut,
+            };
+            // The helper shuts down as root, so nothing can hold it up; an
+            // unforced request still goes through loginwindow's dialog first.
+            let helper_request = match info.action {
+                TimerAction::Shutdown => Some(HelperRequest::Shutdown),
+                TimerAction::Reboot => Some(HelperRequest::Reboot),
+                _ => None,
+            }
+            .filter(|_| helper::is_installed());
+            if let Some(request) = helper_request.filter(|_| force) {
+                helper::request(request)?;
+                return Ok(Some("Handed to the privileged helper".to_string()));
+            }
+            if let Err(err) = system::send_power_event(event) {
+                let failure = format!("Scheduled {} failed: {err}", action_label(&info.action));
+                let Some(request) = helper_request else {
+                    return Err(failure);
+                };
+                helper::request(request).map_err(|helper_err| format!("{failure}; {helper_err}"))?;
+                return Ok(Some(format!("{failure}; handed to the privileged helper")));
+            }
+        }
+        TimerAction::DisplaySleep => system::display_sleep()?,
+        TimerAction::SystemSleep => {
+            system::send_power_event(PowerEvent::Sleep).map_err(|err| format!("Sleep failed: {err}"))?;
+        }
+        TimerAction::Plugin { id } => return store.plugins.run(id, info, scheduled_for),
+        TimerAction::AppleScript { script } => return run_user_script(script, "AppleScript", &[]),
+        TimerAction::JavaScript { script } => {
+            return run_user_script(script, "JavaScript", &script_arguments(info, Some(scheduled_for)))
+        }
+        TimerAction::Shortcut { name, input } => return shortcuts::run(name, input.as_deref()),
+        TimerAction::Automator { path } => return automator::run(path),
+        TimerAction::PauseMedia => return media::pause(),
+        TimerAction::Vpn { service, state } => return vpn::set(service, *state),
+        TimerAction::NetworkOff => return network::disable_all(&store.data_dir()),
+        TimerAction::NetworkOn => return network::restore(&store.data_dir()),
+        TimerAction::SshCommand { host, user, command } => return ssh::run(host, user.as_deref(), command),
+        TimerAction::EjectDisks => {
+            let report = disks::eject_external()?;
+            if !report.busy.is_empty() {
+                let busy = report.busy_summary();
+                notify_user(&format!("These disks could not be ejected: {busy}"));
+                return Err(format!("Could not eject {busy}"));
+            }
+            return Ok(Some(if report.ejected.is_empty() {
+                "No external disks attached".to_string()
+            } else {
+                format!("Ejected {}", report.ejected.join(", "))
+            }));
+        }
+        TimerAction::FadeVolume {
+            target_percent,
+            over_minutes,
+        } => {
+            let over = Duration::from_secs(u64::from(*over_minutes) * 60);
+            return media::fade_volume(*target_percent, over, || store.get(&info.id).is_ok_and(|timer| timer.is_some()));
+        }
+        TimerAction::Countdown { sound } => {
+            let message = info.message.as_deref().unwrap_or("Countdown finished");
+            run_osascript(&countdown_script(message, sound.as_deref()))
+                .map_err(|err| format!("Notification could not be shown: {}", err.trim()))?;
+        }
+        TimerAction::Alarm {
+            sound,
+            volume_percent,
+            snooze_minutes,
+        } => {
+            return alarm::ring(
+                &store.dialogs,
+                info.message.as_deref(),
+                sound,
+                *volume_percent,
+                *snooze_minutes,
+                || store.get(&info.id).is_ok_and(|timer| timer.is_some()),
+            );
+        }
+        TimerAction::NightShift { enabled, strength } => {
+            system::set_night_shift(*enabled, strength.map(|strength| f32::from(strength) / 100.0))?;
+        }
+        TimerAction::SetAppearance { mode } => {
+            run_osascript(&appearance_script(*mode))
+                .map_err(|err| format!("Appearance change failed: {}", err.trim()))?;
+        }
+        TimerAction::Push => {
+            let target = store
+                .settings
+                .snapshot()?
+                .push
+                .ok_or_else(|| "Push notifications are not configured".to_string())?;
+            return push::send(&target, info.message.as_deref().unwrap_or_default()).map(Some);
+        }
+        TimerAction::Email => {
+            let smtp = store
+                .settings
+                .snapshot()?
+                .smtp
+                .ok_or_else(|| "Email is not configured".to_string())?;
+            let message = info.message.as_deref().unwrap_or_default();
+            let subject = format!("LockPilot: {}", message.lines().next().unwrap_or_default());
+            return email::send(&smtp, &subject, message).map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Runs a user's script action in the OSA `language`, passing `arguments`
+/// to its run handler. Whatever the script returns is kept, shortened, as
+/// the history note.
+fn run_user_script(script: &str, language: &str, arguments: &[String]) -> Result<Option<String>, String> {
+    let output = exec::output(
+        Command::new("/usr/bin/osascript")
+            .args(["-l", language, "-e", script])
+            .args(arguments),
+    )
+    .map_err(|err| format!("Failed to run osascript: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("{language} failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let result: String = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .chars()
+        .take(MAX_SCRIPT_RESULT_CHARS)
+        .collect();
+    Ok(Some(result).filter(|result| !result.is_empty()))
+}
+
+/// `argv` for a JavaScript action: timer id, action, scheduled time
+/// (RFC 3339, empty when unknown) and message.
+fn script_arguments(info: &TimerInfo, scheduled_for: Option<DateTime<Utc>>) -> Vec<String> {
+    vec![
+        info.id.clone(),
+        action_label(&info.action).to_string(),
+        scheduled_for.map(|at| at.to_rfc3339()).unwrap_or_default(),
+        info.message.clone().unwrap_or_default(),
+    ]
+}
+
+/// Shows the popup until someone clicks OK. Unacknowledged popups are
+/// shown again every `repeat_until_acknowledged` interval, each repeat
+/// recorded in history as suppressed, and once the escalation window has passed the
+/// escalation action runs in the popup's place. Stops early if the timer
+/// is cancelled.
+fn present_until_acknowledged(
+    store: &TimerStore,
+    info: &TimerInfo,
+    message: &str,
+    scheduled_for: DateTime<Utc>,
+) -> Result<Option<String>, String> {
+    let nag = info.repeat_until_acknowledged.as_ref();
+    let escalate_at = info
+        .escalation
+        .as_ref()
+        .map(|escalation| Utc::now() + ChronoDuration::minutes(escalation.after_minutes as i64));
+
+    let mut shown = 0;
+    loop {
+        shown += 1;
+        let until_escalation = escalate_at.map(|at| (at - Utc::now()).num_seconds().max(1));
+        let window = match (nag.map(|nag| nag.every_minutes as i64 * 60), until_escalation) {
+            (Some(every), Some(remaining)) => every.min(remaining),
+            (Some(every), None) => every,
+            (None, Some(remaining)) => remaining,
+            (None, None) => {
+                return store
+                    .dialogs
+                    .show(message, |text| run_osascript(&popup_script(text)))
+                    .map(|_| None)
+            }
+        };
+
+        // Up to 240 minutes, past `POPUP_PROCESS_TIMEOUT`; the dialog
+        // gets its own limit from the window.
+        let script = format!("{} giving up after {window}", popup_script(message));
+        let limit = Duration::from_secs(window as u64) + POPUP_GIVE_UP_GRACE;
+        let output = exec::supervised(limit, || store.dialogs.exclusive(|| osascript_output(&script)))??;
+        if !output.contains("gave up:true") {
+            return Ok(Some(match shown {
+                1 => "Acknowledged".to_string(),
+                _ => format!("Acknowledged after {shown} reminders"),
+            }));
+        }
+        if store.get(&info.id)?.is_none() {
+            return Ok(Some(format!("Not acknowledged after {shown} reminder(s); timer cancelled")));
+        }
+
+        if let (Some(escalation), Some(at)) = (info.escalation.as_ref(), escalate_at) {
+            if Utc::now() >= at {
+                return escalate(store, info, escalation, scheduled_for, shown);
+            }
+        }
+        let nags_left = nag.is_some_and(|nag| shown <= nag.max_repeats);
+        if !nags_left && escalate_at.is_none() {
+            return Ok(Some(format!("Not acknowledged after {shown} reminder(s)")));
+        }
+        // Not a run of its own: the occurrence is recorded once, when it
+        // is acknowledged, escalated or given up on.
+        let _ = store.history.record(
+            info,
+            scheduled_for,
+            HistoryOutcome::Suppressed,
+            Some(format!("Reminder {shown} not acknowledged; showing again")),
+        );
+    }
+}
+
+fn escalate(
+    store: &TimerStore,
+    info: &TimerInfo,
+    escalation: &EscalationPolicy,
+    scheduled_for: DateTime<Utc>,
+    shown: u32,
+) -> Result<Option<String>, String> {
+    let minutes = escalation.after_minutes;
+    let label = action_label(&escalation.action);
+    let escalated = TimerInfo {
+        action: escalation.action.clone(),
+        ..info.clone()
+    };
+    let summary = format!("Not acknowledged within {minutes} min ({shown} reminder(s))");
+    let result = exec::supervised(escalated.action.process_timeout(), || {
+        run_action(store, &escalated, scheduled_for)
+    })
+    .and_then(|result| result);
+    match result {
+        Ok(Some(note)) => Ok(Some(format!("{summary}; escalated to {label} ({note})"))),
+        Ok(None) => Ok(Some(format!("{summary}; escalated to {label}"))),
+        Err(err) => Err(format!("{summary}; escalation to {label} failed: {err}")),
+    }
+}
+
+fn action_label(action: &TimerAction) -> &'static str {
+    match action {
+        TimerAction::Popup => "popup",
+        TimerAction::Lock => "lock",
+        TimerAction::Shutdown => "shutdown",
+        TimerAction::Reboot => "restart",
+        TimerAction::Logout => "log out",
+        TimerAction::DisplaySleep => "display sleep",
+        TimerAction::SystemSleep => "sleep",
+        TimerAction::Plugin { .. } => "plugin",
+        TimerAction::AppleScript { .. } => "AppleScript",
+        TimerAction::JavaScript { .. } => "JavaScript",
+        TimerAction::Shortcut { .. } => "shortcut",
+        TimerAction::Automator { .. } => "workflow",
+        TimerAction::SetAppearance { .. } => "appearance",
+        TimerAction::NightShift { .. } => "Night Shift",
+        TimerAction::PauseMedia => "pause media",
+        TimerAction::EjectDisks => "disk eject",
+        TimerAction::Vpn { .. } => "VPN",
+        TimerAction::NetworkOff => "network off",
+        TimerAction::NetworkOn => "network on",
+        TimerAction::SshCommand { .. } => "SSH command",
+        TimerAction::FadeVolume { .. } => "volume fade",
+        TimerAction::Alarm { .. } => "alarm",
+        TimerAction::Countdown { .. } => "countdown",
+        TimerAction::Push => "push",
+        TimerAction::Email => "email",
+    }
+}
+
+/// Apps that must not be asked to quit: Finder relaunches itself and
+/// quitting LockPilot would cancel the pending action.
+const QUIT_EXCLUDED_APPS: &[&str] = &["Finder", "LockPilot", "lockpilot"];
+
+fn running_gui_apps() -> Result<Vec<String>, String> {
+    let output = osascript_output(
+        "tell application \"System Events\" to get name of every application process whose background only is false",
+    )?;
+    Ok(output
+        .split(", ")
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty() && !QUIT_EXCLUDED_APPS.contains(&name.as_str()))
+        .collect())
+}
+
+/// Finds foreground apps with a window that looks modified: either the
+/// accessibility "edited" flag or the "Edited" marker in the title bar.
+fn apps_with_unsaved_work() -> Result<Vec<String>, String> {
+    let output = osascript_output(
+        r#"set dirtyApps to {}
+tell application "System Events"
+    repeat with proc in (every application process whose background only is false)
+        set procName to name of proc
+        repeat with win in (every window of proc)
+            set isDirty to false
+            try
+                if value of attribute "AXEdited" of win is true then set isDirty to true
+            end try
+            try
+                if name of win ends with "Edited" then set isDirty to true
+            end try
+            if isDirty and dirtyApps does not contain procName then set end of dirtyApps to procName
+        end repeat
+    end repeat
+end tell
+set AppleScript's text item delimiters to ", "
+return dirtyApps as text"#,
+    )?;
+    Ok(output
+        .split(", ")
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect())
+}
+
+/// Politely asks every foreground app to quit and waits up to `timeout`.
+/// Returns the apps still running afterwards.
+fn quit_running_apps(timeout: Duration) -> Result<Vec<String>, String> {
+    let apps = running_gui_apps()?;
+    for app in &apps {
+        // Ignore responses so an app blocked on a save dialog can't stall us.
+        let script = format!(
+            "ignoring application responses\ntell application {} to quit\nend ignoring",
+            applescript_string(app)
+        );
+        let _ = run_osascript(&script);
+    }
+
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        let remaining: Vec<String> = running_gui_apps()?
+            .into_iter()
+            .filter(|app| apps.contains(app))
+            .collect();
+        if remaining.is_empty() || std::time::Instant::now() >= deadline {
+            return Ok(remaining);
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+fn notify_user(message: &str) {
+    let script = format!(
+        "display notification {} with title \"LockPilot\"",
+        applescript_string(message)
+    );
+    let _ = run_osascript(&script);
+}
+
+/// Trims and lowercases tags, dropping blanks and duplicates.
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|tag| tag.trim().to_lowercase()).filter(|tag| !tag.is_empty()) {
+        if tag.chars().count() > MAX_TAG_CHARS
+            || !tag.chars().all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | ' '))
+        {
+            return Err(format!(
+                "Tags must be at most {MAX_TAG_CHARS} letters, digits, spaces, - or _: {tag}"
+            ));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("A timer can have at most {MAX_TAGS} tags"));
+    }
+    Ok(normalized)
+}
+
+/// Drops blank hook scripts so an empty field doesn't count as a hook.
+fn normalize_hooks(hooks: Option<TimerHooks>) -> Option<TimerHooks> {
+    let clean = |script: Option<String>| {
+        script
+            .map(|script| script.trim().to_string())
+            .filter(|script| !script.is_empty())
+    };
+
+    hooks
+        .map(|hooks| TimerHooks {
+            pre_script: clean(hooks.pre_script),
+            post_script: clean(hooks.post_script),
+            abort_on_pre_failure: hooks.abort_on_pre_failure,
+        })
+        .filter(|hooks| hooks.pre_script.is_some() || hooks.post_script.is_some())
+}
+
+/// macOS permission the action relies on, phrased for the user.
+fn required_permission(
+    action: &TimerAction,
+    options: Option<&ShutdownOptions>,
+    settings: &AppSettings,
+) -> Option<&'static str> {
+    match action {
+        TimerAction::Lock if settings.lock_compatibility => {
+            Some("The compatibility fallback sends a keystroke through System Events and needs Accessibility access")
+        }
+        TimerAction::Lock => None,
+        TimerAction::Shutdown | TimerAction::Reboot | TimerAction::Logout
+            if options.is_some_and(|options| options.quit_apps_timeout_seconds.is_some()) =>
+        {
+            Some("Quitting apps first needs Automation access to System Events and each running app")
+        }
+        TimerAction::Popup | TimerAction::Alarm { .. } | TimerAction::SetAppearance { .. } => {
+            Some("Needs Automation access to System Events")
+        }
+        TimerAction::Shutdown | TimerAction::Reboot | TimerAction::Logout => None,
+        TimerAction::AppleScript { .. } | TimerAction::JavaScript { .. } => {
+            Some("Scripts that control other apps need Automation access to each of them")
+        }
+        TimerAction::Automator { .. } => Some("Workflows that control other apps need Automation access to each of them"),
+        TimerAction::PauseMedia => Some("Pausing Music or Spotify needs Automation access to them"),
+        TimerAction::Shortcut { .. }
+        | TimerAction::NightShift { .. }
+        | TimerAction::FadeVolume { .. }
+        | TimerAction::EjectDisks
+        | TimerAction::Vpn { .. }
+        | TimerAction::NetworkOff
+        | TimerAction::NetworkOn
+        | TimerAction::SshCommand { .. }
+        | TimerAction::Countdown { .. } => None,
+        TimerAction::DisplaySleep | TimerAction::SystemSleep | TimerAction::Plugin { .. } | TimerAction::Push | TimerAction::Email => None,
+    }
+}
+
+fn validate_shutdown_options(action: &TimerAction, options: Option<&ShutdownOptions>) -> Result<(), String> {
+    let Some(options) = options else {
+        return Ok(());
+    };
+
+    if !action.ends_session() {
+        return Err("Shutdown options only apply to log out, shutdown and restart timers.".to_string());
+    }
+
+    if let Some(timeout) = options.quit_apps_timeout_seconds {
+        if !(1..=600).contains(&timeout) {
+            return Err("App quit timeout must be between 1 and 600 seconds.".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a timer time as RFC3339 (`2025-06-01T22:00:00+02:00`), epoch
+/// milliseconds (`1748808000000`), or a local date-time without an offset
+/// (`2025-06-01T22:00`, `2025-06-01 22:00:00`) in the Mac's time zone.
+fn parse_target_time(value: &str) -> Result<(DateTime<Utc>, TargetTimeFormat), String> {
+    let value = value.trim();
+    if let Ok(target) = DateTime::parse_from_rfc3339(value) {
+        return Ok((target.with_timezone(&Utc), TargetTimeFormat::Rfc3339));
+    }
+
+    if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return value
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_millis)
+            .map(|target| (target, TargetTimeFormat::EpochMillis))
+            .ok_or_else(|| "Epoch milliseconds are out of range".to_string());
+    }
+
+    const LOCAL_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"];
+    let naive = LOCAL_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| "Invalid date/time format".to_string())?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|target| (target.with_timezone(&Utc), TargetTimeFormat::LocalDateTime))
+        .ok_or_else(|| format!("{value} does not exist in the local time zone"))
+}
+
+/// RFC 3339 end of a global pause, which must be in the future; `None`
+/// resumes.
+#[cfg(feature = "app")]
+fn parse_pause_until(until: Option<&str>) -> Result<Option<DateTime<Utc>>, String> {
+    let Some(raw) = until else {
+        return Ok(None);
+    };
+    let until = DateTime::parse_from_rfc3339(raw)
+        .map_err(|_| "Invalid date/time format".to_string())?
+        .with_timezone(&Utc);
+    if until <= Utc::now() {
+        return Err("Pause end must be in the future".to_string());
+    }
+    Ok(Some(until))
+}
+
+#[cfg(feature = "app")]
+fn check_snooze_minutes(minutes: u32) -> Result<(), String> {
+    if !(1..=MAX_SNOOZE_MINUTES).contains(&minutes) {
+        return Err(format!("Snooze must be between 1 and {MAX_SNOOZE_MINUTES} minutes"));
+    }
+    Ok(())
+}
+
+fn parse_expires_at(value: Option<&str>) -> Result<Option<DateTime<Utc>>, String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            parse_target_time(value)
+                .map(|(expires_at, _)| expires_at)
+                .map_err(|err| format!("Invalid expiry: {err}"))
+        })
+        .transpose()
+}
+
+/// Jitter must stay under half the repeat interval so occurrences can't
+/// swap order or overlap.
+fn validate_jitter(jitter_minutes: Option<u32>, recurrence: Option<&RecurrenceConfig>) -> Result<(), String> {
+    let Some(jitter) = jitter_minutes else {
+        return Ok(());
+    };
+    if jitter > MAX_JITTER_MINUTES {
+        return Err(format!("Jitter must be at most {MAX_JITTER_MINUTES} minutes"));
+    }
+
+    let interval_minutes = recurrence.and_then(|recurrence| match recurrence.preset {
+        RecurrencePreset::Daily | RecurrencePreset::Weekdays => Some(24 * 60),
+        RecurrencePreset::EveryNHours => recurrence.interval_hours.map(|hours| hours.saturating_mul(60)),
+        RecurrencePreset::EveryNMinutes => recurrence.interval_minutes,
+    });
+    if interval_minutes.is_some_and(|interval| jitter.saturating_mul(2) >= interval) {
+        return Err("Jitter must be less than half the repeat interval".to_string());
+    }
+    Ok(())
+}
+
+fn validate_escalation(action: &TimerAction, escalation: Option<&EscalationPolicy>) -> Result<(), String> {
+    let Some(escalation) = escalation else {
+        return Ok(());
+    };
+
+    if !matches!(action, TimerAction::Popup) {
+        return Err("Only popup timers can escalate".to_string());
+    }
+    if matches!(
+        escalation.action,
+        TimerAction::Popup
+            | TimerAction::Plugin { .. }
+            | TimerAction::AppleScript { .. }
+            | TimerAction::JavaScript { .. }
+            | TimerAction::Shortcut { .. }
+            | TimerAction::Automator { .. }
+            | TimerAction::SetAppearance { .. }
+            | TimerAction::NightShift { .. }
+            | TimerAction::PauseMedia
+            | TimerAction::FadeVolume { .. }
+            | TimerAction::EjectDisks
+            | TimerAction::Vpn { .. }
+            | TimerAction::NetworkOff
+            | TimerAction::NetworkOn
+            | TimerAction::SshCommand { .. }
+            | TimerAction::Push
+            | TimerAction::Email
+            | TimerAction::Alarm { .. }
+            | TimerAction::Countdown { .. }
+    ) {
+        return Err("Escalation must lock, sleep, log out, restart or shut down".to_string());
+    }
+    if !(1..=240).contains(&escalation.after_minutes) {
+        return Err("Escalation delay must be between 1 and 240 minutes".to_string());
+    }
+    Ok(())
+}
+
+fn validate_nag(action: &TimerAction, nag: Option<&NagPolicy>) -> Result<(), String> {
+    let Some(nag) = nag else {
+        return Ok(());
+    };
+
+    if !matches!(action, TimerAction::Popup) {
+        return Err("Only popup timers can repeat until acknowledged".to_string());
+    }
+    if !(1..=240).contains(&nag.every_minutes) {
+        return Err("Repeat interval must be between 1 and 240 minutes".to_string());
+    }
+    if !(1..=50).contains(&nag.max_repeats) {
+        return Err("Repeats must be between 1 and 50".to_string());
+    }
+    Ok(())
+}
+
+fn validate_recurrence(recurrence: Option<&RecurrenceConfig>) -> Result<(), String> {
+    let Some(recurrence) = recurrence else {
+        return Ok(());
+    };
+
+    match recurrence.preset {
+        RecurrencePreset::Daily | RecurrencePreset::Weekdays => Ok(()),
+        RecurrencePreset::EveryNHours => {
+            let Some(hours) = recurrence.interval_hours else {
+                return Err("Every N Hours requires an interval.".to_string());
+            };
+            if (1..=24).contains(&hours) {
+                Ok(())
+            } else {
+                Err("Interval hours must be between 1 and 24.".to_string())
+            }
+        }
+        RecurrencePreset::EveryNMinutes => {
+            let Some(minutes) = recurrence.interval_minutes else {
+                return Err("Every N Minutes requires an interval.".to_string());
+            };
+            if (1..=1440).contains(&minutes) {
+                Ok(())
+            } else {
+                Err("Interval minutes must be between 1 and 1440.".to_string())
+            }
+        }
+    }
+}
+
+fn compute_next_run(current_target: DateTime<Utc>, recurrence: &RecurrenceConfig) -> Option<DateTime<Utc>> {
+    match recurrence.preset {
+        RecurrencePreset::Daily => {
+            let mut next = current_target + ChronoDuration::days(1);
+            while next <= Utc::now() {
+                next += ChronoDuration::days(1);
+            }
+            Some(next)
+        }
+        RecurrencePreset::EveryNHours => {
+            let interval = recurrence.interval_hours?;
+            let mut next = current_target + ChronoDuration::hours(interval as i64);
+            while next <= Utc::now() {
+                next += ChronoDuration::hours(interval as i64);
+            }
+            Some(next)
+        }
+        RecurrencePreset::EveryNMinutes => {
+            let interval = recurrence.interval_minutes?;
+            let mut next = current_target + ChronoDuration::minutes(interval as i64);
+            while next <= Utc::now() {
+                next += ChronoDuration::minutes(interval as i64);
+            }
+            Some(next)
+        }
+        RecurrencePreset::Weekdays => {
+            let time = current_target.time();
+            let mut date = current_target.date_naive() + ChronoDuration::days(1);
+
+            for _ in 0..14 {
+                let weekday = date.weekday();
+                if weekday != Weekday::Sat && weekday != Weekday::Sun {
+                    let candidate = Utc.from_utc_datetime(&date.and_time(time));
+                    if candidate > Utc::now() {
+                        return Some(candidate);
+                    }
+                }
+                date += ChronoDuration::days(1);
+            }
+            None
+        }
+    }
+}
+
+/// Advances a recurrence until it lands strictly after `cutoff`, or `None`
+/// when the recurrence cannot produce a later occurrence.
+fn next_run_after(
+    current_target: DateTime<Utc>,
+    recurrence: &RecurrenceConfig,
+    cutoff: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let mut next = compute_next_run(current_target, recurrence)?;
+    while next <= cutoff {
+        next = compute_next_run(next, recurrence)?;
+    }
+    Some(next)
+}
+
+fn run_osascript(script: &str) -> Result<(), String> {
+    osascript_output(script).map(|_| ())
+}
+
+fn osascript_output(script: &str) -> Result<String, String> {
+    let output = exec::output(Command::new("/usr/bin/osascript").arg("-e").arg(script))
+        .map_err(|err| format!("Failed to run osascript: {err}"))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}
+
+fn popup_script(message: &str) -> String {
+    format!(
+        "display dialog {} with title \"LockPilot\" buttons {{\"OK\"}} default button \"OK\"",
+        applescript_string(message)
+    )
+}
+
+/// A notification that plays `sound`, one of the system alert sounds.
+fn countdown_script(message: &str, sound: Option<&str>) -> String {
+    let mut script = format!(
+        "display notification {} with title \"LockPilot\"",
+        applescript_string(message)
+    );
+    if let Some(sound) = sound {
+        script.push_str(&format!(" sound name {}", applescript_string(sound)));
+    }
+    script
+}
+
+/// Sets Dark Mode through System Events' appearance preferences.
+fn appearance_script(mode: AppearanceMode) -> String {
+    let value = match mode {
+        AppearanceMode::Dark => "true",
+        AppearanceMode::Light => "false",
+        AppearanceMode::Toggle => "not dark mode",
+    };
+    format!("tell application \"System Events\" to tell appearance preferences to set dark mode to {value}")
+}
+
+/// Shows a Proceed/Skip dialog for the timer's action. `None` means
+/// nobody answered within the timeout.
+fn ask_confirmation(info: &TimerInfo, options: &ConfirmationOptions) -> Result<Option<bool>, String> {
+    let mut prompt = format!("LockPilot is about to run a scheduled {}.", action_label(&info.action));
+    if let Some(message) = info.message.as_deref().filter(|message| !message.is_empty()) {
+        prompt.push_str(&format!("\n\n{message}"));
+    }
+    let script = format!(
+        "display dialog {} with title \"LockPilot\" buttons {{\"Skip\", \"Proceed\"}} default button \"Proceed\" giving up after {}",
+        applescript_string(&prompt),
+        options.timeout_seconds
+    );
+
+    let output = osascript_output(&script)?;
+    if output.contains("gave up:true") {
+        Ok(None)
+    } else {
+        Ok(Some(output.contains("button returned:Proceed")))
+    }
+}
+
+/// Quotes `value` as an AppleScript string literal.
+fn applescript_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Moves a timer that was not scheduled (app closed, profile inactive) past
+/// `cutoff`, skipping missed occurrences. Missed one-time timers yield `None`;
+/// triggered timers stay armed.
+fn catch_up(mut info: TimerInfo, cutoff: DateTime<Utc>) -> Option<TimerInfo> {
+    if info.target_time <= cutoff && info.trigger.is_none() {
+        let recurrence = info.recurrence.as_ref()?;
+        let anchor = info.shifted_from.take().unwrap_or(info.target_time);
+        info.target_time = next_run_after(anchor, recurrence, cutoff)?;
+    }
+    Some(info)
+}
+
+pub fn restore_timers(store: &TimerStore) -> Result<(), String> {
+    let restored = store.load_persisted_infos()?;
+    if restored.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let settings = store.settings.snapshot()?;
+    for info in restored {
+        // Timers of inactive profiles are kept as-is and caught up when
+        // their profile is activated.
+        if !settings.schedules(&info) {
+            let mut locked = store.timers();
+            locked.insert(info.id.clone(), TimerEntry { info, cancel_tx: None });
+            continue;
+        }
+
+        let cutoff = settings.pause_for(&info, now).unwrap_or(now);
+        let Some(info) = catch_up(info, cutoff) else {
+            continue;
+        };
+
+        let (cancel_tx, cancel_rx) = mpsc::channel();
+        {
+            let mut locked = store.timers();
+            locked.insert(
+                info.id.clone(),
+                TimerEntry {
+                    info: info.clone(),
+                    cancel_tx: Some(cancel_tx),
+                },
+            );
+        }
+
+        schedule_timer(store, info, cancel_rx);
+    }
+
+    store.persist()?;
+    Ok(())
+}
+
+#[cfg(feature = "app")]
+fn fetch_releases() -> Result<Vec<GithubRelease>, String> {
+    let client = Client::builder()
+        .user_agent("LockPilot-Updater")
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/releases?per_page=100",
+        GITHUB_OWNER, GITHUB_REPO
+    );
+
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|err| format!("Failed to fetch GitHub releases: {err}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "GitHub releases API returned status {}",
+            response.status()
+        ));
+    }
+
+    response
+        .json::<Vec<GithubRelease>>()
+        .map_err(|err| format!("Failed to parse GitHub releases: {err}"))
+}
+
+#[cfg(feature = "app")]
+fn normalize_version(version: &str) -> Option<Version> {
+    Version::parse(version.trim().trim_start_matches('v')).ok()
+}
+
+/// Newest first by semver precedence: a prerelease sorts below its release
+/// and compares identifier by identifier, numerically where both are
+/// numbers (`-dev.2` < `-dev.12`). Build metadata doesn't count, so builds
+/// differing only in `+...` tie and the later-published one comes first.
+#[cfg(feature = "app")]
+fn release_version_desc(a: &GithubRelease, b: &GithubRelease) -> std::cmp::Ordering {
+    match (normalize_version(&a.tag_name), normalize_version(&b.tag_name)) {
+        (Some(av), Some(bv)) => bv
+            .cmp_precedence(&av)
+            .then_with(|| b.published_at.cmp(&a.published_at)),
+        (av, bv) => bv.cmp(&av),
+    }
+}
+
+#[cfg(feature = "app")]
+fn stable_releases(releases: Vec<GithubRelease>) -> Vec<GithubRelease> {
+    releases
+        .into_iter()
+        .filter(|release| !release.draft && !release.prerelease)
+        .filter(|release| normalize_version(&release.tag_name).is_some())
+        .collect()
+}
+
+#[cfg(feature = "app")]
+fn releases_for_channel(releases: Vec<GithubRelease>, channel: &UpdateChannel) -> Vec<GithubRelease> {
+    let base = releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .filter(|release| normalize_version(&release.tag_name).is_some());
+
+    match channel {
+        UpdateChannel::Main => base.filter(|release| !release.prerelease).collect(),
+        UpdateChannel::Dev => base.filter(|release| release.prerelease).collect(),
+    }
+}
+
+#[cfg(feature = "app")]
+fn channel_name(channel: &UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Main => "main",
+        UpdateChannel::Dev => "dev",
+    }
+}
+
+#[cfg(feature = "app")]
+fn tags_match(a: &str, b: &str) -> bool {
+    a.trim() == b.trim() || a.trim_start_matches('v') == b.trim_start_matches('v')
+}
+
+#[cfg(feature = "app")]
+fn is_dmg(asset: &GithubAsset) -> bool {
+    asset.name.to_lowercase().ends_with(".dmg")
+}
+
+/// The architecture an asset name says it was built for, as
+/// `std::env::consts::ARCH` spells it, or `universal`.
+#[cfg(feature = "app")]
+fn dmg_architecture(name: &str) -> Option<&'static str> {
+    if name.contains("aarch64") || name.contains("arm64") {
+        Some("aarch64")
+    } else if name.contains("x86_64") || name.contains("amd64") {
+        Some("x86_64")
+    } else if name.to_lowercase().contains("universal") {
+        Some("universal")
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "app")]
+fn pick_dmg_asset(assets: &[GithubAsset]) -> Option<GithubAsset> {
+    let arch = std::env::consts::ARCH;
+    let dmg_assets: Vec<GithubAsset> = assets.iter().filter(|asset| is_dmg(asset)).cloned().collect();
+
+    let arch_match = dmg_assets
+        .iter()
+        .find(|asset| dmg_architecture(&asset.name) == Some(arch))
+        .cloned();
+
+    arch_match.or_else(|| dmg_assets.into_iter().next())
+}
+
+#[cfg(feature = "app")]
+fn download_asset_to_temp(url: &str, tag: &str) -> Result<PathBuf, String> {
+    let client = Client::builder()
+        .user_agent("LockPilot-Updater")
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|err| format!("Failed to download release asset: {err}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Release asset download failed with status {}",
+            response.status()
+        ));
+    }
+
+    let bytes = response
+        .bytes()
+        .map_err(|err| format!("Failed to read release asset body: {err}"))?;
+    let path = installer::dmg_path(tag);
+    fs::write(&path, bytes).map_err(|err| format!("Failed to write installer DMG: {err}"))?;
+    Ok(path)
+}
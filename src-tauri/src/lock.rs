@@ -0,0 +1,190 @@
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::{exec, run_osascript, system};
+
+pub const LOCK_SHORTCUT_SCRIPT: &str =
+    "tell application \"System Events\" to keystroke \"q\" using {control down, command down}";
+pub const SCREEN_SAVER_SCRIPT: &str = "tell application \"System Events\" to start current screen saver";
+const CG_SESSION: &str = "/System/Library/CoreServices/Menu Extras/User.menu/Contents/Resources/CGSession";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LockMethod {
+    /// `SACLockScreenImmediate` from the private login framework; the same
+    /// call the Lock Screen menu item makes.
+    Native,
+    /// `CGSession -suspend`, which switches to the login window. Only
+    /// present on older macOS releases.
+    CgSession,
+    /// The Ctrl+Cmd+Q lock shortcut, sent through System Events.
+    Keystroke,
+    ScreenSaver,
+    DisplaySleep,
+}
+
+impl LockMethod {
+    fn label(self) -> &'static str {
+        match self {
+            LockMethod::Native => "native lock",
+            LockMethod::CgSession => "CGSession",
+            LockMethod::Keystroke => "lock shortcut",
+            LockMethod::ScreenSaver => "screen saver",
+            LockMethod::DisplaySleep => "display sleep",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockAttempt {
+    pub method: LockMethod,
+    /// `None` when the method succeeded.
+    pub error: Option<String>,
+}
+
+/// What happened on each step of the fallback chain. Stops at the first
+/// method that succeeds.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockReport {
+    pub locked_by: Option<LockMethod>,
+    pub attempts: Vec<LockAttempt>,
+}
+
+impl LockReport {
+    /// One line for history, e.g. "Locked via screen saver (lock shortcut
+    /// failed: ...)" or "All lock methods failed: ...".
+    pub fn summary(&self) -> String {
+        let failures: Vec<String> = self
+            .attempts
+            .iter()
+            .filter_map(|attempt| {
+                let error = attempt.error.as_deref()?;
+                Some(format!("{} failed: {error}", attempt.method.label()))
+            })
+            .collect();
+
+        match (self.locked_by, failures.is_empty()) {
+            (Some(method), true) => format!("Locked via {}", method.label()),
+            (Some(method), false) => format!("Locked via {} ({})", method.label(), failures.join("; ")),
+            (None, _) => format!("All lock methods failed: {}", failures.join("; ")),
+        }
+    }
+}
+
+/// Locks natively, needing no Accessibility access and working behind
+/// full-screen apps. With `compatibility` on, falls back to the original
+/// chain if that fails:
+/// 1) trigger Ctrl+Cmd+Q lock shortcut
+/// 2) start screen saver
+/// 3) force display sleep
+pub fn lock_screen(compatibility: bool) -> LockReport {
+    let mut report = LockReport {
+        locked_by: None,
+        attempts: Vec::new(),
+    };
+
+    let mut chain = vec![LockMethod::Native, LockMethod::CgSession];
+    if compatibility {
+        chain.extend([LockMethod::Keystroke, LockMethod::ScreenSaver, LockMethod::DisplaySleep]);
+    }
+    for method in chain {
+        let error = attempt(method).err().map(|err| err.trim().to_string());
+        let succeeded = error.is_none();
+        report.attempts.push(LockAttempt { method, error });
+        if succeeded {
+            report.locked_by = Some(method);
+            break;
+        }
+    }
+
+    report
+}
+
+fn attempt(method: LockMethod) -> Result<(), String> {
+    match method {
+        LockMethod::Native => lock_immediately(),
+        LockMethod::CgSession => cg_session_suspend(),
+        LockMethod::Keystroke => run_osascript(LOCK_SHORTCUT_SCRIPT),
+        LockMethod::ScreenSaver => run_osascript(SCREEN_SAVER_SCRIPT),
+        LockMethod::DisplaySleep => system::display_sleep(),
+    }
+}
+
+#[cfg(feature = "fake-system")]
+fn lock_immediately() -> Result<(), String> {
+    exec::fake::call(exec::fake::NATIVE_LOCK, &[]).map(|_| ())
+}
+
+#[cfg(all(target_os = "macos", not(feature = "fake-system")))]
+fn lock_immediately() -> Result<(), String> {
+    let lock = native_lock_fn()?;
+    match lock() {
+        0 => Ok(()),
+        status => Err(format!("SACLockScreenImmediate returned {status}")),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", feature = "fake-system")))]
+fn lock_immediately() -> Result<(), String> {
+    Err("Native locking is only available on macOS".to_string())
+}
+
+/// Resolves the native lock function without calling it, for self-tests.
+#[cfg(feature = "app")]
+pub fn native_lock_available() -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        native_lock_fn().map(|_| ())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err("Native locking is only available on macOS".to_string())
+    }
+}
+
+/// Whether `CGSession -suspend` exists on this macOS release.
+#[cfg(feature = "app")]
+pub fn cg_session_available() -> bool {
+    std::path::Path::new(CG_SESSION).exists()
+}
+
+#[cfg(all(target_os = "macos", any(feature = "app", not(feature = "fake-system"))))]
+fn native_lock_fn() -> Result<extern "C" fn() -> std::ffi::c_int, String> {
+    use std::ffi::{c_char, c_int, c_void};
+
+    const LOGIN_FRAMEWORK: &std::ffi::CStr = c"/System/Library/PrivateFrameworks/login.framework/Versions/Current/login";
+    const RTLD_LAZY: c_int = 0x1;
+
+    extern "C" {
+        fn dlopen(path: *const c_char, mode: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    // SAFETY: both strings are NUL-terminated literals; the handle is never
+    // closed, so the resolved symbol stays valid. SACLockScreenImmediate
+    // takes no arguments and returns an int status.
+    unsafe {
+        let handle = dlopen(LOGIN_FRAMEWORK.as_ptr(), RTLD_LAZY);
+        if handle.is_null() {
+            return Err("login framework not available".to_string());
+        }
+        let symbol = dlsym(handle, c"SACLockScreenImmediate".as_ptr());
+        if symbol.is_null() {
+            return Err("SACLockScreenImmediate not found".to_string());
+        }
+        Ok(std::mem::transmute::<*mut c_void, extern "C" fn() -> c_int>(symbol))
+    }
+}
+
+fn cg_session_suspend() -> Result<(), String> {
+    let status = exec::status(Command::new(CG_SESSION).arg("-suspend"))
+        .map_err(|err| format!("Failed to run CGSession: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("CGSession exited with {status}"))
+    }
+}
@@ -0,0 +1,90 @@
+//! The Objective-C runtime and CoreFoundation calls that `system` and
+//! `notifications` both make.
+
+use std::ffi::{c_char, c_void, CStr};
+
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+pub type Id = *mut c_void;
+pub type Sel = *mut c_void;
+/// A message taking no arguments and returning an object.
+pub type SendId = unsafe extern "C" fn(Id, Sel) -> Id;
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFStringCreateWithCString(allocator: *const c_void, c_str: *const c_char, encoding: u32) -> Id;
+    pub fn CFRelease(cf: *const c_void);
+}
+
+#[link(name = "Foundation", kind = "framework")]
+extern "C" {}
+
+pub fn sel(name: &CStr) -> Sel {
+    // SAFETY: `name` is NUL-terminated.
+    unsafe { sel_registerName(name.as_ptr()) }
+}
+
+/// Nil if no class is called `name`.
+pub fn class(name: &CStr) -> Id {
+    // SAFETY: as above.
+    unsafe { objc_getClass(name.as_ptr()) }
+}
+
+/// `objc_msgSend` as `F`, an `unsafe extern "C" fn` taking the receiver,
+/// the selector and the selector's arguments.
+///
+/// # Safety
+///
+/// Calls through the result must match the signature of the selector sent.
+pub unsafe fn msg_send<F: Copy>() -> F {
+    let send = objc_msgSend as unsafe extern "C" fn();
+    assert_eq!(std::mem::size_of::<F>(), std::mem::size_of_val(&send));
+    std::mem::transmute_copy(&send)
+}
+
+/// A CFString, toll-free bridged to NSString, that the caller releases;
+/// null if it couldn't be created.
+pub fn cf_string(text: &CStr) -> Id {
+    // SAFETY: `text` is NUL-terminated and copied.
+    unsafe { CFStringCreateWithCString(std::ptr::null(), text.as_ptr(), K_CF_STRING_ENCODING_UTF8) }
+}
+
+/// An autoreleased NSString; nil if `text` has a NUL byte.
+///
+/// # Safety
+///
+/// An autorelease pool must be in place on this thread.
+#[cfg(not(feature = "fake-system"))]
+pub unsafe fn ns_string(text: &str) -> Id {
+    let Ok(text) = std::ffi::CString::new(text) else {
+        return std::ptr::null_mut();
+    };
+    let string = cf_string(&text);
+    if string.is_null() {
+        return string;
+    }
+    msg_send::<SendId>()(string, sel(c"autorelease"))
+}
+
+/// The contents of an NSString; `None` for nil.
+///
+/// # Safety
+///
+/// `string` is nil or an NSString.
+#[cfg(not(feature = "fake-system"))]
+pub unsafe fn rust_string(string: Id) -> Option<String> {
+    type SendUtf8 = unsafe extern "C" fn(Id, Sel) -> *const c_char;
+
+    if string.is_null() {
+        return None;
+    }
+    let utf8 = msg_send::<SendUtf8>()(string, sel(c"UTF8String"));
+    (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
@@ -0,0 +1,244 @@
+#[cfg(feature = "app")]
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[cfg(feature = "app")]
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
+use serde_json::{Map, Value};
+#[cfg(feature = "app")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "app")]
+use crate::{
+    next_run_after, parse_target_time,
+    policy::{self, Policy},
+    remote_api, AuditOrigin, CreateTimerRequest, TimerStore,
+};
+
+/// Where MDM configuration profiles install preferences, one plist per
+/// bundle id, with per-user payloads in a subdirectory named after the user.
+#[cfg(feature = "app")]
+const MANAGED_PREFERENCES_DIR: &str = "/Library/Managed Preferences";
+#[cfg(feature = "app")]
+const DOMAIN: &str = "com.maks.lockpilot";
+#[cfg(feature = "app")]
+const PLUTIL: &str = "/usr/bin/plutil";
+
+/// The payload an organization deploys for LockPilot.
+#[cfg(feature = "app")]
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManagedConfig {
+    /// Settings keys as in `settings.json`, applied at launch and then
+    /// refused by every setter.
+    #[serde(default)]
+    locked_settings: Map<String, Value>,
+    /// `create_timer` requests with an `id` of the organization's choosing;
+    /// parsed one by one so a bad entry doesn't drop the rest.
+    #[serde(default)]
+    mandatory_timers: Vec<Value>,
+    #[serde(default)]
+    policy: Option<Policy>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Deserialize)]
+struct MandatoryTimer {
+    id: String,
+    #[serde(flatten)]
+    request: CreateTimerRequest,
+}
+
+/// Which mandatory timer a timer was created from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedTimerKey {
+    pub id: String,
+    /// Hash of the definition; a changed definition replaces the timer.
+    pub revision: String,
+}
+
+/// What the organization manages on this Mac, for the UI.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedSummary {
+    /// The plists that were read.
+    pub sources: Vec<String>,
+    pub locked_settings: Vec<String>,
+    /// Ids of the mandatory timers in place.
+    pub mandatory_timers: Vec<String>,
+    /// The policy in force, including the local policy file's.
+    pub policy: Policy,
+    /// Entries that could not be applied, also printed at launch.
+    pub problems: Vec<String>,
+}
+
+/// The device-wide plist, then the current user's, which overrides it.
+#[cfg(feature = "app")]
+fn preference_files() -> Vec<PathBuf> {
+    let dir = Path::new(MANAGED_PREFERENCES_DIR);
+    let mut files = vec![dir.join(format!("{DOMAIN}.plist"))];
+    if let Ok(user) = env::var("USER") {
+        files.push(dir.join(user).join(format!("{DOMAIN}.plist")));
+    }
+    files
+}
+
+/// Reads a managed plist, binary or XML, through `plutil`.
+#[cfg(feature = "app")]
+fn read(path: &Path) -> Result<Option<ManagedConfig>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let output = Command::new(PLUTIL)
+        .args(["-convert", "json", "-o", "-"])
+        .arg(path)
+        .output()
+        .map_err(|err| format!("Failed to run plutil: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} could not be read: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map(Some)
+        .map_err(|err| format!("Invalid managed preferences in {}: {err}", path.display()))
+}
+
+/// Reads managed preferences at launch: sets the policy, locks the listed
+/// settings and makes sure every mandatory timer exists, replacing changed
+/// ones and cancelling those no longer listed. Without managed preferences
+/// any timers left from earlier ones are cancelled too.
+#[cfg(feature = "app")]
+pub fn apply(store: &TimerStore) -> ManagedSummary {
+    let mut summary = ManagedSummary::default();
+    let mut locked = Map::new();
+    let mut timers = Vec::new();
+    let mut policy = Policy::default();
+    for path in preference_files() {
+        match read(&path) {
+            Ok(Some(config)) => {
+                summary.sources.push(path.display().to_string());
+                locked.extend(config.locked_settings);
+                timers.extend(config.mandatory_timers);
+                policy = policy.combine(config.policy.unwrap_or_default());
+            }
+            Ok(None) => {}
+            Err(err) => summary.problems.push(err),
+        }
+    }
+    match policy::load_local() {
+        Ok(local) => policy = policy.combine(local.unwrap_or_default()),
+        Err(err) => summary.problems.push(err),
+    }
+    store.policy.set(policy.clone());
+    summary.policy = policy;
+
+    if !locked.is_empty() {
+        let requested: Vec<String> = locked.keys().cloned().collect();
+        match store.settings.lock_managed(locked) {
+            Ok(applied) => {
+                summary.problems.extend(
+                    requested
+                        .iter()
+                        .filter(|key| !applied.contains(key))
+                        .map(|key| format!("Unknown managed setting: {key}")),
+                );
+                summary.locked_settings = applied;
+            }
+            Err(err) => summary.problems.push(err),
+        }
+    }
+
+    let mut wanted: Vec<(ManagedTimerKey, CreateTimerRequest)> = Vec::new();
+    for value in timers {
+        let revision = remote_api::hex(&Sha256::digest(value.to_string().as_bytes()));
+        match serde_json::from_value::<MandatoryTimer>(value) {
+            Ok(timer) if !timer.id.trim().is_empty() => {
+                let id = timer.id.trim().to_string();
+                wanted.retain(|(key, _)| key.id != id);
+                wanted.push((ManagedTimerKey { id, revision }, timer.request));
+            }
+            Ok(_) => summary.problems.push("A mandatory timer has no id".to_string()),
+            Err(err) => summary.problems.push(format!("Invalid mandatory timer: {err}")),
+        }
+    }
+    if let Err(err) = reconcile(store, wanted, &mut summary) {
+        summary.problems.push(err);
+    }
+
+    for problem in &summary.problems {
+        eprintln!("Managed preferences: {problem}");
+    }
+    summary
+}
+
+#[cfg(feature = "app")]
+fn reconcile(
+    store: &TimerStore,
+    wanted: Vec<(ManagedTimerKey, CreateTimerRequest)>,
+    summary: &mut ManagedSummary,
+) -> Result<(), String> {
+    let existing = store.all()?;
+    for timer in &existing {
+        let Some(key) = &timer.managed else {
+            continue;
+        };
+        if !wanted.iter().any(|(wanted, _)| wanted == key) {
+            store.cancel(&timer.id, &AuditOrigin::System)?;
+        }
+    }
+
+    for (key, mut request) in wanted {
+        if existing.iter().any(|timer| timer.managed.as_ref() == Some(&key)) {
+            summary.mandatory_timers.push(key.id);
+            continue;
+        }
+        let id = key.id.clone();
+        match roll_forward(&mut request) {
+            Ok(true) => {}
+            // A one-time timer whose time has passed already ran, or was missed.
+            Ok(false) => continue,
+            Err(err) => {
+                summary.problems.push(format!("Mandatory timer {id}: {err}"));
+                continue;
+            }
+        }
+        request.managed = Some(key);
+        match store.create(request, &AuditOrigin::System) {
+            Ok(_) => summary.mandatory_timers.push(id),
+            Err(err) => summary.problems.push(format!("Mandatory timer {id}: {err}")),
+        }
+    }
+    Ok(())
+}
+
+/// Moves a recurring timer's past start to its next run, so a profile can
+/// say "every day at 22:00 from 2025-01-01" and still apply years later.
+/// `false` for a one-time timer in the past.
+#[cfg(feature = "app")]
+fn roll_forward(request: &mut CreateTimerRequest) -> Result<bool, String> {
+    let (target, _) = parse_target_time(&request.target_time)?;
+    let now = Utc::now();
+    if target > now {
+        return Ok(true);
+    }
+    let Some(recurrence) = &request.recurrence else {
+        return Ok(false);
+    };
+    match next_run_after(target, recurrence, now) {
+        Some(next) => {
+            request.target_time = next.to_rfc3339();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,96 @@
+use std::{thread, time::Duration};
+
+use crate::{applescript_string, osascript_output, run_osascript, system};
+
+/// How often a fade moves the volume one step.
+const FADE_STEP: Duration = Duration::from_secs(10);
+
+/// Players asked directly as well, in case the system now-playing session
+/// can't be reached.
+const PLAYERS: &[&str] = &["Music", "Spotify"];
+
+/// Pauses whatever is playing: the system now-playing session (any app,
+/// browsers included), then Music and Spotify if they are running. Players
+/// that aren't running are never launched. Returns a note for history.
+pub fn pause() -> Result<Option<String>, String> {
+    let mut paused = Vec::new();
+    if system::pause_now_playing() {
+        paused.push("now playing".to_string());
+    }
+
+    let mut errors = Vec::new();
+    for player in PLAYERS.iter().filter(|player| system::is_process_running(player)) {
+        match run_osascript(&format!("tell application {} to pause", applescript_string(player))) {
+            Ok(()) => paused.push(player.to_string()),
+            Err(err) => errors.push(format!("{player}: {}", err.trim())),
+        }
+    }
+
+    if paused.is_empty() && !errors.is_empty() {
+        return Err(format!("Media not paused ({})", errors.join("; ")));
+    }
+    Ok(Some(if paused.is_empty() {
+        "No media player found to pause".to_string()
+    } else {
+        format!("Paused {}", paused.join(", "))
+    }))
+}
+
+/// Lowers the output volume to `target` percent in even steps over
+/// `over`. Stops early if someone changes the volume by hand, or once
+/// `still_wanted` returns `false` (the timer was cancelled).
+pub fn fade_volume(target: u8, over: Duration, still_wanted: impl Fn() -> bool) -> Result<Option<String>, String> {
+    let start = output_volume()?;
+    if start <= target {
+        return Ok(Some(format!("Volume already at {start}%; nothing to fade")));
+    }
+
+    let steps = (over.as_secs() / FADE_STEP.as_secs()).max(1) as u32;
+    let interval = over / steps;
+    let mut current = start;
+    for step in 1..=steps {
+        thread::sleep(interval);
+        if !still_wanted() {
+            return Ok(Some(format!("Fade stopped at {current}%: timer cancelled")));
+        }
+        let actual = output_volume()?;
+        if actual.abs_diff(current) > 1 {
+            return Ok(Some(format!("Fade stopped at {actual}%: volume changed by hand")));
+        }
+        let drop = u32::from(start - target) * step / steps;
+        current = start - drop as u8;
+        set_output_volume(current)?;
+    }
+    Ok(Some(format!("Faded volume from {start}% to {target}%")))
+}
+
+/// Unmutes the output and raises its volume to `percent` if lower, for an
+/// alarm. Returns the volume to put back with `restore_volume`.
+pub fn raise_volume(percent: u8) -> Result<u8, String> {
+    let previous = output_volume()?;
+    run_osascript("set volume without output muted")
+        .map_err(|err| format!("Failed to unmute the output: {}", err.trim()))?;
+    if previous < percent {
+        set_output_volume(percent)?;
+    }
+    Ok(previous)
+}
+
+pub fn restore_volume(percent: u8) -> Result<(), String> {
+    set_output_volume(percent)
+}
+
+/// Output volume as a percentage.
+fn output_volume() -> Result<u8, String> {
+    let volume = osascript_output("output volume of (get volume settings)")
+        .map_err(|err| format!("Failed to read the volume: {}", err.trim()))?;
+    // "missing value" when the output device has no volume control.
+    volume
+        .parse::<u8>()
+        .map_err(|_| format!("The current output device has no volume control ({volume})"))
+}
+
+fn set_output_volume(percent: u8) -> Result<(), String> {
+    run_osascript(&format!("set volume output volume {percent}"))
+        .map_err(|err| format!("Failed to set the volume: {}", err.trim()))
+}
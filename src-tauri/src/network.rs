@@ -0,0 +1,126 @@
+use std::{fs, path::Path, process::Command};
+
+use crate::{
+    exec,
+    helper::{self, HelperRequest},
+};
+
+const NETWORKSETUP: &str = "/usr/sbin/networksetup";
+/// Services `disable_all` turned off, so `restore` only turns those back on.
+const DISABLED_FILE: &str = "network_disabled.json";
+
+/// Turns off every enabled network service (Wi-Fi, Ethernet, ...). Goes
+/// through the privileged helper when it is installed, since
+/// `networksetup` may need root; otherwise tries as the user.
+pub fn disable_all(data_dir: &Path) -> Result<Option<String>, String> {
+    if helper::is_installed() {
+        helper::request(HelperRequest::NetworkOff)?;
+        return Ok(Some("Handed to the privileged helper".to_string()));
+    }
+
+    let services = enabled_services()?;
+    if services.is_empty() {
+        return Ok(Some("No network services were enabled".to_string()));
+    }
+    let mut disabled = Vec::new();
+    let mut errors = Vec::new();
+    for service in services {
+        match set_enabled(&service, false) {
+            Ok(()) => disabled.push(service),
+            Err(err) => errors.push(format!("{service}: {err}")),
+        }
+    }
+    remember_disabled(data_dir, &disabled)?;
+
+    if !errors.is_empty() {
+        return Err(format!(
+            "Could not turn off {}; install the privileged helper to cut the network off as root",
+            errors.join(", ")
+        ));
+    }
+    Ok(Some(format!("Turned off {}", disabled.join(", "))))
+}
+
+/// Turns back on the services the last `disable_all` turned off, leaving
+/// ones the user had off alone.
+pub fn restore(data_dir: &Path) -> Result<Option<String>, String> {
+    if helper::is_installed() {
+        helper::request(HelperRequest::NetworkOn)?;
+        return Ok(Some("Handed to the privileged helper".to_string()));
+    }
+
+    let path = data_dir.join(DISABLED_FILE);
+    let services = match fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str::<Vec<String>>(&raw)
+            .map_err(|err| format!("Failed to read the services turned off earlier: {err}"))?,
+        Err(_) => return Ok(Some("No network services were turned off by LockPilot".to_string())),
+    };
+    let mut errors = Vec::new();
+    for service in &services {
+        if let Err(err) = set_enabled(service, true) {
+            errors.push(format!("{service}: {err}"));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!("Could not turn on {}", errors.join(", ")));
+    }
+    let _ = fs::remove_file(&path);
+    Ok(Some(format!("Turned on {}", services.join(", "))))
+}
+
+/// `networksetup -listallnetworkservices` prints a note first, then one
+/// service per line with disabled ones marked `*`.
+fn enabled_services() -> Result<Vec<String>, String> {
+    let output = exec::output(Command::new(NETWORKSETUP).arg("-listallnetworkservices"))
+        .map_err(|err| format!("Failed to run networksetup: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list network services: {}",
+            String::from_utf8_lossy(&output.stdout).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|service| !service.is_empty() && !service.starts_with('*'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn set_enabled(service: &str, enabled: bool) -> Result<(), String> {
+    let output = exec::output(
+        Command::new(NETWORKSETUP)
+            .arg("-setnetworkserviceenabled")
+            .arg(service)
+            .arg(if enabled { "on" } else { "off" }),
+    )
+    .map_err(|err| format!("Failed to run networksetup: {err}"))?;
+    // networksetup reports some errors on stdout with a zero exit status.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() && !stdout.contains("Error") {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("{} {}", stdout.trim(), stderr.trim()).trim().to_string())
+    }
+}
+
+/// Adds to what an earlier `disable_all` recorded, so two cutoffs in a row
+/// don't forget the first one's services.
+fn remember_disabled(data_dir: &Path, services: &[String]) -> Result<(), String> {
+    let path = data_dir.join(DISABLED_FILE);
+    let mut all = fs::read_to_string(&path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+        .unwrap_or_default();
+    for service in services {
+        if !all.contains(service) {
+            all.push(service.clone());
+        }
+    }
+    let data = serde_json::to_string_pretty(&all).map_err(|err| format!("Failed to encode network state: {err}"))?;
+    fs::create_dir_all(data_dir)
+        .and_then(|_| fs::write(&path, data))
+        .map_err(|err| format!("Failed to save network state: {err}"))
+}
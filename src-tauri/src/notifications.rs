@@ -0,0 +1,391 @@
+#[cfg(feature = "app")]
+use std::sync::OnceLock;
+
+use crate::{applescript_string, run_osascript};
+
+/// Called with the category, action and timer id of a button pressed on
+/// one of `post_for_timer`'s notifications.
+#[cfg(feature = "app")]
+type ResponseHandler = Box<dyn Fn(&str, &str, &str) + Send + Sync>;
+
+#[cfg(feature = "app")]
+static HANDLER: OnceLock<ResponseHandler> = OnceLock::new();
+
+/// Registers the timer category's Snooze and Cancel buttons with
+/// Notification Center, asks for permission to notify and routes button
+/// presses to `handler`. Outside an app bundle, as under `cargo tauri dev`,
+/// Notification Center isn't available and notifications go through
+/// AppleScript, which can't carry buttons.
+#[cfg(feature = "app")]
+pub fn register(handler: impl Fn(&str, &str, &str) + Send + Sync + 'static) -> Result<(), String> {
+    if HANDLER.set(Box::new(handler)).is_err() {
+        return Err("Notification actions are already registered".to_string());
+    }
+    #[cfg(all(target_os = "macos", not(feature = "fake-system")))]
+    {
+        if mac::available() {
+            return mac::register();
+        }
+    }
+    Ok(())
+}
+
+/// Posts a notification about a timer with Snooze and Cancel buttons,
+/// replacing the timer's previous one. Falls back to a plain AppleScript
+/// notification when Notification Center isn't available or LockPilot
+/// isn't allowed to use it.
+pub fn post_for_timer(timer_id: &str, title: &str, body: &str) -> Result<(), String> {
+    #[cfg(all(target_os = "macos", not(feature = "fake-system")))]
+    {
+        if mac::available() && mac::post(timer_id, title, body).is_ok() {
+            return Ok(());
+        }
+    }
+    let _ = timer_id;
+    run_osascript(&format!(
+        "display notification {} with title {}",
+        applescript_string(body),
+        applescript_string(title)
+    ))
+}
+
+#[cfg(all(target_os = "macos", not(feature = "fake-system")))]
+mod mac {
+    use std::{
+        ffi::{c_char, c_void, CStr},
+        panic::{self, AssertUnwindSafe},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            OnceLock,
+        },
+    };
+
+    use super::HANDLER;
+    use crate::{
+        macos_ffi::{class, msg_send, ns_string, rust_string, sel, Id, Sel, SendId},
+        NOTIFICATION_ACTION_CANCEL, NOTIFICATION_ACTION_SNOOZE, TIMER_NOTIFICATION_CATEGORY,
+    };
+
+    /// `userInfo` key holding the timer id.
+    const TIMER_ID_KEY: &str = "timerId";
+    const DELEGATE_CLASS: &CStr = c"LockPilotNotificationDelegate";
+    /// Both delegate methods: `void` return, self, _cmd, the center, the
+    /// response or notification, and a block.
+    const DELEGATE_METHOD_TYPES: &CStr = c"v@:@@@?";
+    /// Identifiers of the system's own actions, such as clicking the banner
+    /// or dismissing it, start with this.
+    const SYSTEM_ACTION_PREFIX: &str = "com.apple.";
+    const UN_AUTHORIZATION_OPTION_SOUND: usize = 1 << 1;
+    const UN_AUTHORIZATION_OPTION_ALERT: usize = 1 << 2;
+    const UN_NOTIFICATION_ACTION_OPTION_DESTRUCTIVE: usize = 1 << 1;
+    /// `UNNotificationPresentationOptionSound | List | Banner`, so warnings
+    /// show while LockPilot is frontmost too.
+    const FOREGROUND_PRESENTATION: usize = (1 << 1) | (1 << 3) | (1 << 4);
+    const BLOCK_IS_GLOBAL: i32 = 1 << 28;
+
+    type SendIdId = unsafe extern "C" fn(Id, Sel, Id) -> Id;
+    type SendIdIdId = unsafe extern "C" fn(Id, Sel, Id, Id) -> Id;
+    type SendVoidId = unsafe extern "C" fn(Id, Sel, Id);
+
+    /// Whether LockPilot may post; until the user answers, and if they
+    /// decline, notifications fall back to AppleScript.
+    static AUTHORIZED: AtomicBool = AtomicBool::new(false);
+    /// The delegate class, registered once; as an address since raw
+    /// pointers aren't `Sync`.
+    static DELEGATE: OnceLock<usize> = OnceLock::new();
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getProtocol(name: *const c_char) -> *mut c_void;
+        fn objc_allocateClassPair(superclass: Id, name: *const c_char, extra_bytes: usize) -> Id;
+        fn objc_registerClassPair(class: Id);
+        fn class_addMethod(class: Id, name: Sel, imp: *const c_void, types: *const c_char) -> i8;
+        fn class_addProtocol(class: Id, protocol: *mut c_void) -> i8;
+        fn objc_autoreleasePoolPush() -> *mut c_void;
+        fn objc_autoreleasePoolPop(pool: *mut c_void);
+    }
+
+    #[link(name = "UserNotifications", kind = "framework")]
+    extern "C" {}
+
+    extern "C" {
+        static _NSConcreteGlobalBlock: *const c_void;
+    }
+
+    /// The fixed start of every block: `invoke` is called with the block
+    /// itself followed by the block's arguments.
+    #[repr(C)]
+    struct Block {
+        isa: *const c_void,
+        flags: i32,
+        reserved: i32,
+        invoke: *const c_void,
+        descriptor: *const BlockDescriptor,
+    }
+
+    #[repr(C)]
+    struct BlockDescriptor {
+        reserved: usize,
+        size: usize,
+    }
+
+    /// Runs `f` in its own autorelease pool, since it is called from
+    /// scheduler threads that have none.
+    fn with_pool<T>(f: impl FnOnce() -> T) -> T {
+        // SAFETY: the pool is popped on the thread that pushed it.
+        unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let result = f();
+            objc_autoreleasePoolPop(pool);
+            result
+        }
+    }
+
+    /// `UNUserNotificationCenter` raises an exception outside an app
+    /// bundle, e.g. for a binary run from `target/debug`.
+    pub fn available() -> bool {
+        // SAFETY: objc_msgSend is called through casts matching each
+        // selector's signature; messages to nil return nil.
+        with_pool(|| unsafe {
+            let send_id: SendId = msg_send();
+            let bundle = send_id(class(c"NSBundle"), sel(c"mainBundle"));
+            let identifier = send_id(bundle, sel(c"bundleIdentifier"));
+            let path = rust_string(send_id(bundle, sel(c"bundlePath")));
+            !identifier.is_null() && path.is_some_and(|path| path.ends_with(".app"))
+        })
+    }
+
+    unsafe fn center() -> Id {
+        let send_id: SendId = msg_send();
+        send_id(class(c"UNUserNotificationCenter"), sel(c"currentNotificationCenter"))
+    }
+
+    /// `userNotificationCenter:didReceiveNotificationResponse:withCompletionHandler:`
+    extern "C" fn did_receive_response(_this: Id, _cmd: Sel, _center: Id, response: Id, completion: *mut Block) {
+        // SAFETY: as in `available`; `completion` is the `void (^)(void)`
+        // block the center passes, called exactly once.
+        unsafe {
+            with_pool(|| {
+                let send_id: SendId = msg_send();
+                let send_id_id: SendIdId = msg_send();
+                let action = rust_string(send_id(response, sel(c"actionIdentifier")));
+                let notification = send_id(response, sel(c"notification"));
+                let content = send_id(send_id(notification, sel(c"request")), sel(c"content"));
+                let category = rust_string(send_id(content, sel(c"categoryIdentifier")));
+                let user_info = send_id(content, sel(c"userInfo"));
+                let key = ns_string(TIMER_ID_KEY);
+                let timer_id = rust_string(send_id_id(user_info, sel(c"objectForKey:"), key));
+
+                if let (Some(category), Some(action), Some(timer_id), Some(handler)) =
+                    (category, action, timer_id, HANDLER.get())
+                {
+                    if !action.starts_with(SYSTEM_ACTION_PREFIX) {
+                        // A panic must not unwind into Objective-C.
+                        let _ = panic::catch_unwind(AssertUnwindSafe(|| handler(&category, &action, &timer_id)));
+                    }
+                }
+            });
+            if !completion.is_null() {
+                let invoke: unsafe extern "C" fn(*mut Block) = std::mem::transmute((*completion).invoke);
+                invoke(completion);
+            }
+        }
+    }
+
+    /// `userNotificationCenter:willPresentNotification:withCompletionHandler:`
+    extern "C" fn will_present(_this: Id, _cmd: Sel, _center: Id, _notification: Id, completion: *mut Block) {
+        // SAFETY: `completion` is the center's
+        // `void (^)(UNNotificationPresentationOptions)` block.
+        unsafe {
+            if !completion.is_null() {
+                let invoke: unsafe extern "C" fn(*mut Block, usize) = std::mem::transmute((*completion).invoke);
+                invoke(completion, FOREGROUND_PRESENTATION);
+            }
+        }
+    }
+
+    /// Completion of `requestAuthorizationWithOptions:completionHandler:`.
+    extern "C" fn authorization_answered(_block: *mut Block, granted: i8, _error: Id) {
+        AUTHORIZED.store(granted != 0, Ordering::SeqCst);
+        if granted == 0 {
+            eprintln!("Notifications are turned off for LockPilot; timer warnings won't have buttons");
+        }
+    }
+
+    /// An `NSObject` subclass implementing `UNUserNotificationCenterDelegate`.
+    unsafe fn delegate_class() -> Id {
+        *DELEGATE.get_or_init(|| {
+            let delegate = objc_allocateClassPair(class(c"NSObject"), DELEGATE_CLASS.as_ptr(), 0);
+            if delegate.is_null() {
+                return class(DELEGATE_CLASS) as usize;
+            }
+            class_addMethod(
+                delegate,
+                sel(c"userNotificationCenter:didReceiveNotificationResponse:withCompletionHandler:"),
+                did_receive_response as *const c_void,
+                DELEGATE_METHOD_TYPES.as_ptr(),
+            );
+            class_addMethod(
+                delegate,
+                sel(c"userNotificationCenter:willPresentNotification:withCompletionHandler:"),
+                will_present as *const c_void,
+                DELEGATE_METHOD_TYPES.as_ptr(),
+            );
+            let protocol = objc_getProtocol(c"UNUserNotificationCenterDelegate".as_ptr());
+            if !protocol.is_null() {
+                class_addProtocol(delegate, protocol);
+            }
+            objc_registerClassPair(delegate);
+            delegate as usize
+        }) as Id
+    }
+
+    pub fn register() -> Result<(), String> {
+        type SendAction = unsafe extern "C" fn(Id, Sel, Id, Id, usize) -> Id;
+        type SendCategory = unsafe extern "C" fn(Id, Sel, Id, Id, Id, usize) -> Id;
+        type SendObjects = unsafe extern "C" fn(Id, Sel, *const Id, usize) -> Id;
+        type SendAuthorize = unsafe extern "C" fn(Id, Sel, usize, *const Block);
+
+        // SAFETY: as in `available`. The delegate is retained and never
+        // released, since the center only keeps a weak reference; the
+        // authorization block is global and leaked, so it outlives the call.
+        with_pool(|| unsafe {
+            let send_id: SendId = msg_send();
+            let send_id_id: SendIdId = msg_send();
+            let send_void_id: SendVoidId = msg_send();
+            let send_action: SendAction = msg_send();
+            let send_category: SendCategory = msg_send();
+            let send_objects: SendObjects = msg_send();
+            let send_authorize: SendAuthorize = msg_send();
+
+            let center = center();
+            let delegate_class = delegate_class();
+            if center.is_null() || delegate_class.is_null() {
+                return Err("Notification Center is not available".to_string());
+            }
+            let delegate = send_id(send_id(delegate_class, sel(c"alloc")), sel(c"init"));
+
+            let action_class = class(c"UNNotificationAction");
+            let action_selector = sel(c"actionWithIdentifier:title:options:");
+            let actions = [
+                send_action(
+                    action_class,
+                    action_selector,
+                    ns_string(NOTIFICATION_ACTION_SNOOZE),
+                    ns_string("Snooze 10 min"),
+                    0,
+                ),
+                send_action(
+                    action_class,
+                    action_selector,
+                    ns_string(NOTIFICATION_ACTION_CANCEL),
+                    ns_string("Cancel Timer"),
+                    UN_NOTIFICATION_ACTION_OPTION_DESTRUCTIVE,
+                ),
+            ];
+            if actions.iter().any(|action| action.is_null()) {
+                return Err("Failed to create the notification actions".to_string());
+            }
+            let actions = send_objects(
+                class(c"NSArray"),
+                sel(c"arrayWithObjects:count:"),
+                actions.as_ptr(),
+                actions.len(),
+            );
+            let category = send_category(
+                class(c"UNNotificationCategory"),
+                sel(c"categoryWithIdentifier:actions:intentIdentifiers:options:"),
+                ns_string(TIMER_NOTIFICATION_CATEGORY),
+                actions,
+                send_id(class(c"NSArray"), sel(c"array")),
+                0,
+            );
+            let categories = send_id_id(class(c"NSSet"), sel(c"setWithObject:"), category);
+            if category.is_null() || categories.is_null() {
+                return Err("Failed to create the notification category".to_string());
+            }
+            send_void_id(center, sel(c"setNotificationCategories:"), categories);
+            send_void_id(center, sel(c"setDelegate:"), delegate);
+
+            let descriptor = Box::leak(Box::new(BlockDescriptor {
+                reserved: 0,
+                size: std::mem::size_of::<Block>(),
+            }));
+            let block = Box::leak(Box::new(Block {
+                isa: std::ptr::addr_of!(_NSConcreteGlobalBlock).cast(),
+                flags: BLOCK_IS_GLOBAL,
+                reserved: 0,
+                invoke: authorization_answered as *const c_void,
+                descriptor,
+            }));
+            send_authorize(
+                center,
+                sel(c"requestAuthorizationWithOptions:completionHandler:"),
+                UN_AUTHORIZATION_OPTION_ALERT | UN_AUTHORIZATION_OPTION_SOUND,
+                block,
+            );
+            Ok(())
+        })
+    }
+
+    pub fn post(timer_id: &str, title: &str, body: &str) -> Result<(), String> {
+        type SendRequest = unsafe extern "C" fn(Id, Sel, Id, Id, Id) -> Id;
+        type SendAdd = unsafe extern "C" fn(Id, Sel, Id, *const c_void);
+
+        if !AUTHORIZED.load(Ordering::SeqCst) {
+            return Err("LockPilot isn't allowed to post notifications".to_string());
+        }
+        // SAFETY: as in `available`; the content is created with
+        // alloc/init and autoreleased, everything else is autoreleased.
+        with_pool(|| unsafe {
+            let send_id: SendId = msg_send();
+            let send_id_id_id: SendIdIdId = msg_send();
+            let send_void_id: SendVoidId = msg_send();
+            let send_request: SendRequest = msg_send();
+            let send_add: SendAdd = msg_send();
+
+            let content = send_id(
+                send_id(class(c"UNMutableNotificationContent"), sel(c"alloc")),
+                sel(c"init"),
+            );
+            if content.is_null() {
+                return Err("Failed to create the notification".to_string());
+            }
+            send_id(content, sel(c"autorelease"));
+            send_void_id(content, sel(c"setTitle:"), ns_string(title));
+            send_void_id(content, sel(c"setBody:"), ns_string(body));
+            send_void_id(content, sel(c"setCategoryIdentifier:"), ns_string(TIMER_NOTIFICATION_CATEGORY));
+            let user_info = send_id_id_id(
+                class(c"NSDictionary"),
+                sel(c"dictionaryWithObject:forKey:"),
+                ns_string(timer_id),
+                ns_string(TIMER_ID_KEY),
+            );
+            send_void_id(content, sel(c"setUserInfo:"), user_info);
+            send_void_id(
+                content,
+                sel(c"setSound:"),
+                send_id(class(c"UNNotificationSound"), sel(c"defaultSound")),
+            );
+
+            // One per timer, so a newer warning replaces the last.
+            let request = send_request(
+                class(c"UNNotificationRequest"),
+                sel(c"requestWithIdentifier:content:trigger:"),
+                ns_string(&format!("lockpilot-timer-{timer_id}")),
+                content,
+                std::ptr::null_mut(),
+            );
+            if request.is_null() {
+                return Err("Failed to create the notification request".to_string());
+            }
+            send_add(
+                center(),
+                sel(c"addNotificationRequest:withCompletionHandler:"),
+                request,
+                std::ptr::null(),
+            );
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Component, Path, PathBuf},
+    process::Command,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{exec, TimerInfo};
+
+const MANIFEST_FILE: &str = "plugin.json";
+const DEFAULT_TIMEOUT_SECONDS: u32 = 30;
+const MAX_TIMEOUT_SECONDS: u32 = 300;
+const MAX_OUTPUT_CHARS: usize = 500;
+
+/// `plugin.json` inside a plugin folder. The folder name is the plugin id.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginManifest {
+    name: String,
+    description: Option<String>,
+    executable: String,
+    timeout_seconds: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub timeout_seconds: u32,
+    #[serde(skip)]
+    executable: PathBuf,
+}
+
+impl PluginInfo {
+    #[cfg(feature = "app")]
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+}
+
+/// Payload written to the plugin's stdin as a single JSON document.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginInvocation<'a> {
+    timer: &'a TimerInfo,
+    scheduled_for: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct PluginRegistry {
+    dir: Arc<PathBuf>,
+}
+
+impl PluginRegistry {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir: Arc::new(dir) }
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.dir.as_ref()
+    }
+
+    /// Scans the plugin directory. Folders without a valid manifest or
+    /// executable are skipped so one broken plugin can't hide the rest.
+    #[cfg(feature = "app")]
+    pub fn discover(&self) -> Vec<PluginInfo> {
+        let Ok(entries) = fs::read_dir(self.dir.as_ref()) else {
+            return Vec::new();
+        };
+
+        let mut plugins: Vec<PluginInfo> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| load_plugin(&entry.path()).ok())
+            .collect();
+        plugins.sort_by_key(|plugin| plugin.name.to_lowercase());
+        plugins
+    }
+
+    pub fn find(&self, id: &str) -> Result<PluginInfo, String> {
+        let folder = self.dir.join(id);
+        if id.is_empty() || id.contains('/') || id.starts_with('.') || !folder.is_dir() {
+            return Err(format!("Plugin not found: {id}"));
+        }
+        load_plugin(&folder)
+    }
+
+    /// Runs a plugin for a firing timer. The plugin receives the timer as
+    /// JSON on stdin plus the usual `LOCKPILOT_*` variables, must exit 0
+    /// within its timeout, and may print a short status line on stdout.
+    pub fn run(&self, id: &str, info: &TimerInfo, scheduled_for: DateTime<Utc>) -> Result<Option<String>, String> {
+        let plugin = self.find(id)?;
+        let payload = serde_json::to_vec(&PluginInvocation {
+            timer: info,
+            scheduled_for,
+        })
+        .map_err(|err| format!("Failed to encode plugin input: {err}"))?;
+
+        let mut command = Command::new(&plugin.executable);
+        command.current_dir(self.dir.join(&plugin.id));
+        crate::apply_timer_env(&mut command, info, scheduled_for);
+
+        let output = exec::run_with_timeout(
+            &mut command,
+            Some(&payload),
+            Duration::from_secs(plugin.timeout_seconds as u64),
+        )?;
+        let stdout = truncate(String::from_utf8_lossy(&output.stdout).trim());
+        if output.status.success() {
+            Ok(Some(stdout).filter(|text| !text.is_empty()))
+        } else {
+            let stderr = truncate(String::from_utf8_lossy(&output.stderr).trim());
+            Err(format!("Plugin {} failed with {}: {stderr}", plugin.name, output.status))
+        }
+    }
+}
+
+fn load_plugin(folder: &Path) -> Result<PluginInfo, String> {
+    let id = folder
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| "Invalid plugin folder name".to_string())?
+        .to_string();
+
+    let raw = fs::read_to_string(folder.join(MANIFEST_FILE))
+        .map_err(|err| format!("Failed to read manifest for plugin {id}: {err}"))?;
+    let manifest = serde_json::from_str::<PluginManifest>(&raw)
+        .map_err(|err| format!("Failed to parse manifest for plugin {id}: {err}"))?;
+
+    let stays_in_folder = Path::new(&manifest.executable)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    let executable = folder.join(&manifest.executable);
+    let is_executable = fs::metadata(&executable)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false);
+    if !stays_in_folder || !is_executable {
+        return Err(format!("Plugin {id} has no executable at {}", manifest.executable));
+    }
+
+    Ok(PluginInfo {
+        id,
+        name: manifest.name,
+        description: manifest.description,
+        timeout_seconds: manifest
+            .timeout_seconds
+            .unwrap_or(DEFAULT_TIMEOUT_SECONDS)
+            .clamp(1, MAX_TIMEOUT_SECONDS),
+        executable,
+    })
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_OUTPUT_CHARS).collect()
+}
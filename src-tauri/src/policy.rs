@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "app")]
+use std::{fs, os::unix::fs::MetadataExt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{TimerAction, TimerHooks};
+
+/// A policy an administrator can drop on the Mac without MDM. Only honored
+/// if owned by root, so a standard user can't write or replace it.
+#[cfg(feature = "app")]
+const LOCAL_POLICY_FILE: &str = "/Library/Application Support/LockPilot/policy.json";
+
+/// Restrictions on what timers may do, from managed preferences or the
+/// local policy file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Policy {
+    /// Action types as they appear in timer requests, e.g. `shutdown` or
+    /// `sshCommand`.
+    #[serde(default)]
+    pub forbidden_actions: Vec<String>,
+    /// Refuse remote API requests that change anything; read-only ones
+    /// still work.
+    #[serde(default)]
+    pub forbid_remote_control: bool,
+    /// Refuse timers with pre- or post-run scripts, which run any shell
+    /// command whatever the timer's action.
+    #[serde(default)]
+    pub forbid_hooks: bool,
+}
+
+impl Policy {
+    /// The stricter of the two: forbidden by either is forbidden.
+    #[cfg(feature = "app")]
+    pub fn combine(mut self, other: Policy) -> Self {
+        for action in other.forbidden_actions {
+            if !self.forbidden_actions.contains(&action) {
+                self.forbidden_actions.push(action);
+            }
+        }
+        self.forbid_remote_control |= other.forbid_remote_control;
+        self.forbid_hooks |= other.forbid_hooks;
+        self
+    }
+
+    #[cfg(feature = "app")]
+    fn is_empty(&self) -> bool {
+        self.forbidden_actions.is_empty() && !self.forbid_remote_control && !self.forbid_hooks
+    }
+}
+
+/// The policy in force, shared by every surface that creates or runs
+/// timers.
+#[derive(Clone, Default)]
+pub struct PolicyStore {
+    inner: Arc<Mutex<Policy>>,
+}
+
+impl PolicyStore {
+    #[cfg(feature = "app")]
+    pub fn set(&self, policy: Policy) {
+        if let Ok(mut locked) = self.inner.lock() {
+            *locked = policy;
+        }
+    }
+
+    pub fn snapshot(&self) -> Policy {
+        self.inner.lock().map(|policy| policy.clone()).unwrap_or_default()
+    }
+
+    /// Fails with the reason if the policy forbids `action`.
+    pub fn check_action(&self, action: &TimerAction) -> Result<(), String> {
+        let kind = action_kind(action);
+        if self.snapshot().forbidden_actions.iter().any(|forbidden| forbidden.eq_ignore_ascii_case(&kind)) {
+            return Err(format!("{kind} timers are not allowed by your organization's policy"));
+        }
+        Ok(())
+    }
+
+    /// Fails if `hooks` has a script and the policy forbids hooks.
+    pub fn check_hooks(&self, hooks: Option<&TimerHooks>) -> Result<(), String> {
+        let has_script = hooks.is_some_and(|hooks| hooks.pre_script.is_some() || hooks.post_script.is_some());
+        if has_script && self.snapshot().forbid_hooks {
+            return Err("Pre- and post-run scripts are not allowed by your organization's policy".to_string());
+        }
+        Ok(())
+    }
+
+    /// Everything a timer can run: its action, its escalation's and its
+    /// hooks. For timers that come in without `TimerStore::validate`.
+    #[cfg(feature = "app")]
+    pub fn check_timer(
+        &self,
+        action: &TimerAction,
+        escalation: Option<&TimerAction>,
+        hooks: Option<&TimerHooks>,
+    ) -> Result<(), String> {
+        self.check_action(action)?;
+        if let Some(escalation) = escalation {
+            self.check_action(escalation)?;
+        }
+        self.check_hooks(hooks)
+    }
+
+    #[cfg(feature = "app")]
+    pub fn check_remote_control(&self) -> Result<(), String> {
+        if self.snapshot().forbid_remote_control {
+            return Err("Remote control is not allowed by your organization's policy".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The action's tag as serialized: `"lock"` for unit actions, the single
+/// key for ones with options, e.g. `{ "sshCommand": { ... } }`.
+pub fn action_kind(action: &TimerAction) -> String {
+    match serde_json::to_value(action) {
+        Ok(serde_json::Value::String(kind)) => kind,
+        Ok(serde_json::Value::Object(fields)) => fields.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// The local policy file, if present and owned by root.
+#[cfg(feature = "app")]
+pub fn load_local() -> Result<Option<Policy>, String> {
+    let path = Path::new(LOCAL_POLICY_FILE);
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(None);
+    };
+    if metadata.uid() != 0 {
+        return Err(format!("Ignoring {LOCAL_POLICY_FILE}: it must be owned by root"));
+    }
+    let raw = fs::read_to_string(path).map_err(|err| format!("Failed to read {LOCAL_POLICY_FILE}: {err}"))?;
+    let policy: Policy =
+        serde_json::from_str(&raw).map_err(|err| format!("Invalid policy in {LOCAL_POLICY_FILE}: {err}"))?;
+    Ok((!policy.is_empty()).then_some(policy))
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::system;
+
+const KEYCHAIN_SERVICE: &str = "com.maks.lockpilot.push";
+const PUSHOVER_API: &str = "https://api.pushover.net/1/messages.json";
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+const TITLE: &str = "LockPilot";
+
+/// Where `Push` timers deliver their message. Only the addressing lives in
+/// settings; the access token (ntfy) or application token (Pushover) is
+/// kept in the login keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "service", rename_all = "camelCase")]
+pub enum PushTarget {
+    Ntfy {
+        #[serde(default = "default_ntfy_server")]
+        server: String,
+        topic: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Pushover { user_key: String },
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+impl PushTarget {
+    fn name(&self) -> &'static str {
+        match self {
+            PushTarget::Ntfy { .. } => "ntfy",
+            PushTarget::Pushover { .. } => "pushover",
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            PushTarget::Ntfy { server, topic } => {
+                let url = reqwest::Url::parse(server.trim()).map_err(|err| format!("Invalid ntfy server: {err}"))?;
+                if !matches!(url.scheme(), "https" | "http") {
+                    return Err("ntfy server must be an http(s) URL".to_string());
+                }
+                let topic = topic.trim();
+                if topic.is_empty() || !topic.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_') {
+                    return Err("ntfy topic may only contain letters, digits, - and _".to_string());
+                }
+            }
+            PushTarget::Pushover { user_key } => {
+                if user_key.trim().len() != 30 || !user_key.trim().chars().all(|ch| ch.is_ascii_alphanumeric()) {
+                    return Err("Pushover user key must be 30 letters and digits".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "app")]
+    pub fn has_secret(&self) -> bool {
+        matches!(system::keychain_password(KEYCHAIN_SERVICE, self.name()), Ok(Some(_)))
+    }
+
+    /// Saves `secret` to the keychain, or removes it when empty.
+    #[cfg(feature = "app")]
+    pub fn store_secret(&self, secret: &str) -> Result<(), String> {
+        match secret.trim() {
+            "" => system::delete_keychain_password(KEYCHAIN_SERVICE, self.name()),
+            secret => system::set_keychain_password(KEYCHAIN_SERVICE, self.name(), secret),
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn forget_secret(&self) -> Result<(), String> {
+        system::delete_keychain_password(KEYCHAIN_SERVICE, self.name())
+    }
+}
+
+/// Sends `message` and returns a one-line delivery note for history.
+pub fn send(target: &PushTarget, message: &str) -> Result<String, String> {
+    let secret = system::keychain_password(KEYCHAIN_SERVICE, target.name())?;
+    let client = Client::builder()
+        .user_agent("LockPilot-Push")
+        .timeout(SEND_TIMEOUT)
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
+
+    match target {
+        PushTarget::Ntfy { server, topic } => {
+            let url = format!("{}/{}", server.trim().trim_end_matches('/'), topic.trim());
+            let mut request = client.post(url).header("Title", TITLE).body(message.to_string());
+            if let Some(token) = secret {
+                request = request.bearer_auth(token);
+            }
+            let response = request.send().map_err(|err| format!("Failed to reach ntfy: {err}"))?;
+            if !response.status().is_success() {
+                return Err(format!("ntfy returned status {}", response.status()));
+            }
+            Ok(format!("Pushed to ntfy topic {}", topic.trim()))
+        }
+        PushTarget::Pushover { user_key } => {
+            let token = secret.ok_or_else(|| "No Pushover application token in the keychain".to_string())?;
+            let response = client
+                .post(PUSHOVER_API)
+                .json(&serde_json::json!({
+                    "token": token,
+                    "user": user_key.trim(),
+                    "title": TITLE,
+                    "message": message,
+                }))
+                .send()
+                .map_err(|err| format!("Failed to reach Pushover: {err}"))?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok("Pushed via Pushover".to_string());
+            }
+            // Rejections carry the reasons in an `errors` array.
+            let errors = response
+                .json::<serde_json::Value>()
+                .ok()
+                .and_then(|body| body.get("errors").cloned())
+                .and_then(|errors| serde_json::from_value::<Vec<String>>(errors).ok())
+                .unwrap_or_default();
+            if errors.is_empty() {
+                Err(format!("Pushover returned status {status}"))
+            } else {
+                Err(format!("Pushover rejected the message: {}", errors.join("; ")))
+            }
+        }
+    }
+}
@@ -0,0 +1,536 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, Timelike, Utc, Weekday};
+use serde::Serialize;
+
+use crate::{local_at, CreateTimerRequest, RecurrenceConfig, RecurrencePreset, TimerAction};
+
+/// Words that end a "remind me to ..." message.
+const CLAUSE_WORDS: &[&str] = &["at", "in", "every", "daily", "weekdays", "tomorrow", "today", "with"];
+const FILLER_WORDS: &[&str] = &["and", "then", "please", "the", "me"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem with part of the entered text. `fragment` is the word or
+/// phrase it refers to, when there is one.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub fragment: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, fragment: Option<&str>) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Error,
+            message: message.into(),
+            fragment: fragment.map(str::to_string),
+        }
+    }
+
+    fn warning(message: impl Into<String>, fragment: Option<&str>) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Warning,
+            message: message.into(),
+            fragment: fragment.map(str::to_string),
+        }
+    }
+}
+
+enum When {
+    In(ChronoDuration),
+    At(NaiveTime),
+}
+
+/// Parses phrases like `lock at 10pm every weekday`, `restart in 2 hours`
+/// or `remind me to stretch every 45 minutes`. Returns `None` for the
+/// request when any diagnostic is an error.
+pub fn parse(text: &str, now: DateTime<Local>) -> (Option<CreateTimerRequest>, Vec<Diagnostic>) {
+    let mut diagnostics = Vec::new();
+
+    // A quoted message ("popup \"Stand up\" at 3pm") is lifted out before tokenizing.
+    let (quoted, rest) = extract_quoted(text);
+    let words: Vec<String> = rest
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| matches!(c, ',' | '.' | '!' | '?'))
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    let Some(first) = words.first() else {
+        diagnostics.push(Diagnostic::error("Describe the timer, e.g. \"lock at 10pm\"", None));
+        return (None, diagnostics);
+    };
+
+    let mut index = 1;
+    let mut message = quoted;
+    let action = match *first {
+        "lock" => Some(TimerAction::Lock),
+        "shutdown" => Some(TimerAction::Shutdown),
+        "shut" if words.get(1) == Some(&"down") => {
+            index = 2;
+            Some(TimerAction::Shutdown)
+        }
+        "restart" | "reboot" => Some(TimerAction::Reboot),
+        "logout" => Some(TimerAction::Logout),
+        "log" if words.get(1) == Some(&"out") => {
+            index = 2;
+            Some(TimerAction::Logout)
+        }
+        "sleep" if matches!(words.get(1).copied(), Some("display" | "displays" | "screen" | "screens")) => {
+            index = 2;
+            Some(TimerAction::DisplaySleep)
+        }
+        "sleep" => Some(TimerAction::SystemSleep),
+        "popup" | "remind" | "notify" | "show" => {
+            if words.get(index) == Some(&"me") {
+                index += 1;
+            }
+            if words.get(index) == Some(&"to") {
+                index += 1;
+            }
+            let start = index;
+            while index < words.len() && !CLAUSE_WORDS.contains(&words[index]) {
+                index += 1;
+            }
+            if message.is_none() && index > start {
+                message = Some(capitalize(&words[start..index].join(" ")));
+            }
+            if message.is_none() {
+                diagnostics.push(Diagnostic::error("Popups need a message, e.g. \"remind me to stretch at 3pm\"", Some(first)));
+            }
+            Some(TimerAction::Popup)
+        }
+        other => {
+            diagnostics.push(Diagnostic::error(
+                "Start with an action: lock, shut down, restart, log out, sleep, sleep displays or remind me to ...",
+                Some(other),
+            ));
+            None
+        }
+    };
+
+    let mut when = None;
+    let mut tomorrow = false;
+    let mut recurrence = None;
+    let mut warning_minutes = None;
+    while index < words.len() {
+        let word = words[index];
+        index += 1;
+        match word {
+            "at" => {
+                let fragment = words.get(index).copied();
+                match parse_clock(&words, &mut index) {
+                    Some(time) => when = Some(When::At(time)),
+                    None => diagnostics.push(Diagnostic::error(
+                        "Expected a time after \"at\", e.g. 10pm or 22:30",
+                        fragment,
+                    )),
+                }
+            }
+            "in" => match parse_amount(&words, &mut index) {
+                Some((amount, Unit::Minutes)) => when = Some(When::In(ChronoDuration::minutes(amount as i64))),
+                Some((amount, Unit::Hours)) => when = Some(When::In(ChronoDuration::hours(amount as i64))),
+                None => diagnostics.push(Diagnostic::error(
+                    "Expected a duration after \"in\", e.g. 30 minutes",
+                    words.get(index).copied(),
+                )),
+            },
+            "tomorrow" => tomorrow = true,
+            "today" | "tonight" => {}
+            "daily" => recurrence = Some(preset(RecurrencePreset::Daily, None, None)),
+            "weekdays" => recurrence = Some(preset(RecurrencePreset::Weekdays, None, None)),
+            "every" => match words.get(index).copied() {
+                Some("day" | "night") => {
+                    index += 1;
+                    recurrence = Some(preset(RecurrencePreset::Daily, None, None));
+                }
+                Some("weekday" | "weekdays") => {
+                    index += 1;
+                    recurrence = Some(preset(RecurrencePreset::Weekdays, None, None));
+                }
+                _ => match parse_amount(&words, &mut index) {
+                    Some((hours, Unit::Hours)) => recurrence = Some(preset(RecurrencePreset::EveryNHours, Some(hours), None)),
+                    Some((minutes, Unit::Minutes)) => {
+                        recurrence = Some(preset(RecurrencePreset::EveryNMinutes, None, Some(minutes)))
+                    }
+                    None => diagnostics.push(Diagnostic::error(
+                        "Expected day, weekday, or an interval after \"every\"",
+                        words.get(index).copied(),
+                    )),
+                },
+            },
+            "with" => {
+                let start = index - 1;
+                match parse_warning(&words, &mut index) {
+                    Some(minutes) => warning_minutes = Some(minutes),
+                    None => diagnostics.push(Diagnostic::error(
+                        "Expected a warning, e.g. \"with a 5 minute warning\"",
+                        Some(&words[start..index].join(" ")),
+                    )),
+                }
+            }
+            filler if FILLER_WORDS.contains(&filler) => {}
+            other => diagnostics.push(Diagnostic::error("Didn't understand this word", Some(other))),
+        }
+    }
+
+    let target = match (&when, &recurrence) {
+        (Some(When::In(duration)), _) => Some(now + *duration),
+        (Some(When::At(time)), _) => first_at(now, *time, tomorrow, &recurrence),
+        (None, Some(recurrence)) => interval_of(recurrence).map(|interval| now + interval),
+        (None, None) => None,
+    };
+    if target.is_none() && when.is_none() {
+        diagnostics.push(Diagnostic::error(
+            "Say when, e.g. \"at 10pm\" or \"in 30 minutes\"",
+            None,
+        ));
+    }
+
+    if let (Some(minutes), Some(target)) = (warning_minutes, target) {
+        if target - now < ChronoDuration::minutes(minutes.into()) {
+            diagnostics.push(Diagnostic::warning(
+                format!("Less than {minutes} min away, so the first run comes without a warning"),
+                None,
+            ));
+        }
+    }
+
+    let has_errors = diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error);
+    let (Some(action), Some(target), false) = (action, target, has_errors) else {
+        return (None, diagnostics);
+    };
+
+    let request = CreateTimerRequest {
+        action,
+        target_time: target.with_timezone(&Utc).to_rfc3339(),
+        recurrence,
+        message,
+        notes: None,
+        shutdown_options: None,
+        hooks: None,
+        condition: None,
+        trigger: None,
+        profile: None,
+        expires_at: None,
+        max_runs: None,
+        jitter_minutes: None,
+        warning_minutes,
+        confirmation: None,
+        escalation: None,
+        repeat_until_acknowledged: None,
+        working_hours: None,
+        ignore_working_hours: false,
+        chat_webhook: None,
+        must_run: false,
+        pause_media: false,
+        eject_disks: None,
+        tags: Vec::new(),
+        source: None,
+        managed: None,
+    };
+    (Some(request), diagnostics)
+}
+
+enum Unit {
+    Minutes,
+    Hours,
+}
+
+/// `30 minutes`, `2 hours`, `an hour`, `hour` (as in "every hour").
+fn parse_amount(words: &[&str], index: &mut usize) -> Option<(u32, Unit)> {
+    let first = *words.get(*index)?;
+    let (amount, unit_word) = match first.parse::<u32>() {
+        Ok(amount) => (amount, *words.get(*index + 1)?),
+        Err(_) if matches!(first, "a" | "an") => (1, *words.get(*index + 1)?),
+        Err(_) => (1, first),
+    };
+    let unit = match unit_word {
+        "minute" | "minutes" | "min" | "mins" => Unit::Minutes,
+        "hour" | "hours" | "hr" | "hrs" => Unit::Hours,
+        _ => return None,
+    };
+    *index += if unit_word == first { 1 } else { 2 };
+    Some((amount, unit))
+}
+
+/// `a 5 minute warning`, `a 5-minute warning`, `10 minutes warning` or
+/// `an hour warning`, in minutes. Consumes up to and including `warning`
+/// even when the amount doesn't parse.
+fn parse_warning(words: &[&str], index: &mut usize) -> Option<u32> {
+    let start = *index;
+    let end = words[start..]
+        .iter()
+        .position(|word| word.starts_with("warning"))
+        .map(|offset| start + offset);
+    *index = end.map_or(words.len(), |end| end + 1);
+
+    let mut phrase: Vec<&str> = words[start..end?].iter().flat_map(|word| word.split('-')).collect();
+    if phrase.len() > 1 && matches!(phrase[0], "a" | "an") && phrase[1].parse::<u32>().is_ok() {
+        phrase.remove(0);
+    }
+    let mut at = 0;
+    let minutes = match parse_amount(&phrase, &mut at)? {
+        (minutes, Unit::Minutes) => minutes,
+        (hours, Unit::Hours) => hours.checked_mul(60)?,
+    };
+    (at == phrase.len() && minutes > 0).then_some(minutes)
+}
+
+/// `10pm`, `10:30pm`, `10 pm`, `22:30`, `noon`, `midnight`.
+fn parse_clock(words: &[&str], index: &mut usize) -> Option<NaiveTime> {
+    let word = *words.get(*index)?;
+    *index += 1;
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+
+    let (clock, mut meridiem) = if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (word, None)
+    };
+    if meridiem.is_none() {
+        meridiem = match words.get(*index).copied() {
+            Some("am") => Some(false),
+            Some("pm") => Some(true),
+            _ => None,
+        };
+        if meridiem.is_some() {
+            *index += 1;
+        }
+    }
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(true) => hour % 12 + 12,
+        Some(false) => hour % 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn first_at(
+    now: DateTime<Local>,
+    time: NaiveTime,
+    tomorrow: bool,
+    recurrence: &Option<RecurrenceConfig>,
+) -> Option<DateTime<Local>> {
+    let weekdays_only = matches!(
+        recurrence,
+        Some(RecurrenceConfig {
+            preset: RecurrencePreset::Weekdays,
+            ..
+        })
+    );
+    let mut date = now.date_naive();
+    if tomorrow {
+        date += ChronoDuration::days(1);
+    }
+    for _ in 0..8 {
+        let is_weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        if let Ok(candidate) = local_at(date, time.hour(), time.minute()) {
+            if candidate > now && !(weekdays_only && is_weekend) {
+                return Some(candidate);
+            }
+        }
+        date += ChronoDuration::days(1);
+    }
+    None
+}
+
+fn interval_of(recurrence: &RecurrenceConfig) -> Option<ChronoDuration> {
+    match recurrence.preset {
+        RecurrencePreset::EveryNHours => recurrence.interval_hours.map(|hours| ChronoDuration::hours(hours as i64)),
+        RecurrencePreset::EveryNMinutes => recurrence
+            .interval_minutes
+            .map(|minutes| ChronoDuration::minutes(minutes as i64)),
+        RecurrencePreset::Daily | RecurrencePreset::Weekdays => None,
+    }
+}
+
+fn preset(preset: RecurrencePreset, interval_hours: Option<u32>, interval_minutes: Option<u32>) -> RecurrenceConfig {
+    RecurrenceConfig {
+        preset,
+        interval_hours,
+        interval_minutes,
+        skip_holidays: false,
+    }
+}
+
+fn extract_quoted(text: &str) -> (Option<String>, String) {
+    let Some(start) = text.find('"') else {
+        return (None, text.to_string());
+    };
+    let Some(length) = text[start + 1..].find('"') else {
+        return (None, text.to_string());
+    };
+    let quoted = text[start + 1..start + 1 + length].trim().to_string();
+    let rest = format!("{} {}", &text[..start], &text[start + 2 + length..]);
+    (Some(quoted).filter(|quoted| !quoted.is_empty()), rest)
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// A Thursday.
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 15, 10, 7, 0).unwrap()
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Local.with_ymd_and_hms(2026, 1, day, hour, minute, 0).unwrap().with_timezone(&Utc)
+    }
+
+    fn request(text: &str) -> CreateTimerRequest {
+        match parse(text, now()) {
+            (Some(request), _) => request,
+            (None, diagnostics) => panic!("{text}: {diagnostics:?}"),
+        }
+    }
+
+    fn target(request: &CreateTimerRequest) -> DateTime<Utc> {
+        request.target_time.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_actions_and_times() {
+        let in_minutes = |minutes| (now() + ChronoDuration::minutes(minutes)).with_timezone(&Utc);
+        let cases = [
+            ("lock at 10pm", TimerAction::Lock, at(15, 22, 0)),
+            ("Lock at 10 PM.", TimerAction::Lock, at(15, 22, 0)),
+            ("shut down at 9am", TimerAction::Shutdown, at(16, 9, 0)),
+            ("restart in 2 hours", TimerAction::Reboot, in_minutes(120)),
+            ("log out at 22:30", TimerAction::Logout, at(15, 22, 30)),
+            ("sleep displays in 30 minutes", TimerAction::DisplaySleep, in_minutes(30)),
+            ("sleep in an hour", TimerAction::SystemSleep, in_minutes(60)),
+            ("lock tomorrow at noon", TimerAction::Lock, at(16, 12, 0)),
+            ("lock at midnight", TimerAction::Lock, at(16, 0, 0)),
+        ];
+        for (text, action, expected) in cases {
+            let request = request(text);
+            assert_eq!(request.action, action, "{text}");
+            assert_eq!(target(&request), expected, "{text}");
+            assert!(request.recurrence.is_none(), "{text}");
+        }
+    }
+
+    #[test]
+    fn parses_recurrences() {
+        let cases = [
+            ("lock at 10pm every day", "Daily", None, None, at(15, 22, 0)),
+            ("log out at 7am daily", "Daily", None, None, at(16, 7, 0)),
+            ("shut down at 11:30pm every weekday", "Weekdays", None, None, at(15, 23, 30)),
+            ("remind me to stretch every 45 minutes", "EveryNMinutes", None, Some(45), at(15, 10, 52)),
+            ("remind me to drink water every 2 hours", "EveryNHours", Some(2), None, at(15, 12, 7)),
+        ];
+        for (text, preset, hours, minutes, expected) in cases {
+            let request = request(text);
+            let recurrence = request.recurrence.as_ref().unwrap_or_else(|| panic!("{text}: no recurrence"));
+            assert_eq!(format!("{:?}", recurrence.preset), preset, "{text}");
+            assert_eq!(recurrence.interval_hours, hours, "{text}");
+            assert_eq!(recurrence.interval_minutes, minutes, "{text}");
+            assert_eq!(target(&request), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn takes_popup_messages() {
+        let cases = [
+            ("remind me to stretch at 3pm", "Stretch"),
+            ("popup \"Stand up\" at 3pm", "Stand up"),
+            ("notify me to call mom in 10 minutes", "Call mom"),
+        ];
+        for (text, message) in cases {
+            let request = request(text);
+            assert_eq!(request.action, TimerAction::Popup, "{text}");
+            assert_eq!(request.message.as_deref(), Some(message), "{text}");
+        }
+    }
+
+    #[test]
+    fn parses_warnings() {
+        let cases = [
+            ("lock at 10pm with a 5 minute warning", 5),
+            ("lock at 10pm with a 5-minute warning", 5),
+            ("lock at 10pm with 10 minutes warning", 10),
+            ("lock at 10pm with an hour warning", 60),
+            ("shut down at 11pm every day with a 15 min warning", 15),
+            ("lock with a 5 minute warning at 10pm", 5),
+        ];
+        for (text, minutes) in cases {
+            let request = request(text);
+            assert_eq!(request.warning_minutes, Some(minutes), "{text}");
+            assert_eq!(target(&request).with_timezone(&Local).minute(), 0, "{text}");
+        }
+
+        let (request, diagnostics) = parse("lock in 3 minutes with a 5 minute warning", now());
+        assert_eq!(request.and_then(|request| request.warning_minutes), Some(5));
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == DiagnosticSeverity::Warning));
+    }
+
+    #[test]
+    fn reports_errors() {
+        let cases = [
+            ("", None),
+            ("dance at 5pm", Some("dance")),
+            ("lock", None),
+            ("lock at 25:00", Some("25:00")),
+            ("lock at 13pm", Some("13pm")),
+            ("lock in soon", Some("soon")),
+            ("remind me at 3pm", Some("remind")),
+            ("lock at 10pm every fortnight", Some("fortnight")),
+            ("lock at 10pm with a warning", Some("with a warning")),
+            ("lock at 10pm with a 0 minute warning", Some("with a 0 minute warning")),
+            ("lock at 10pm with a 5 minute", Some("with a 5 minute")),
+            ("lock at 10pm purple", Some("purple")),
+        ];
+        for (text, fragment) in cases {
+            let (request, diagnostics) = parse(text, now());
+            assert!(request.is_none(), "{text} should fail");
+            let errors: Vec<&Diagnostic> = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error)
+                .collect();
+            assert!(!errors.is_empty(), "{text}: no error");
+            if let Some(fragment) = fragment {
+                assert!(
+                    errors.iter().any(|error| error.fragment.as_deref() == Some(fragment)),
+                    "{text}: {errors:?}"
+                );
+            }
+        }
+    }
+}
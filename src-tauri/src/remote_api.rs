@@ -0,0 +1,609 @@
+#[cfg(feature = "app")]
+use std::{
+    fs,
+    io::Read,
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    process::Command,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[cfg(feature = "app")]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "app")]
+use tiny_http::{Header, Method, Request, Response, Server, SslConfig};
+#[cfg(feature = "app")]
+use uuid::Uuid;
+
+#[cfg(feature = "app")]
+use crate::{
+    fleet::Advertisement,
+    parse_pause_until, parse_target_time, runs_code,
+    sync::{self, PairRequest, SyncBundle, SyncState},
+    AuditOrigin, CreateTimerRequest, CreatedTimerView, PauseState, TimerStore,
+};
+
+#[cfg(feature = "app")]
+const CERT_FILE: &str = "api-cert.pem";
+#[cfg(feature = "app")]
+const KEY_FILE: &str = "api-key.pem";
+/// The certificate's SHA-256 fingerprint, handed to sync peers for pinning.
+#[cfg(feature = "app")]
+const FINGERPRINT_FILE: &str = "api-cert.sha256";
+#[cfg(feature = "app")]
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+/// Sync bundles carry whole timers, so they get more room.
+#[cfg(feature = "app")]
+const MAX_SYNC_BODY_BYTES: u64 = 1024 * 1024;
+/// `last_used_at` is kept current in memory but only written to disk this
+/// often, rather than on every request.
+#[cfg(feature = "app")]
+const LAST_USED_PERSIST_INTERVAL_MINUTES: i64 = 60;
+#[cfg(feature = "app")]
+const SCRIPTS_NOT_ALLOWED: &str =
+    "Timers that run scripts can only be created remotely with \"Accept timers with scripts\" on";
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    Read,
+    Control,
+}
+
+#[cfg(feature = "app")]
+impl ApiScope {
+    fn allows(&self, required: &ApiScope) -> bool {
+        matches!((self, required), (ApiScope::Control, _) | (ApiScope::Read, ApiScope::Read))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub tls: bool,
+    /// Announce the API over Bonjour so other LockPilots can find it.
+    #[serde(default = "default_advertise")]
+    pub advertise: bool,
+    /// Accept timers that run code, such as hook scripts or AppleScript
+    /// actions, over the API and from sync peers. Off, any control token
+    /// could run shell commands as this user.
+    #[serde(default)]
+    pub allow_scripts: bool,
+}
+
+fn default_advertise() -> bool {
+    true
+}
+
+impl Default for RemoteApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47800,
+            tls: false,
+            advertise: true,
+            allow_scripts: false,
+        }
+    }
+}
+
+/// Only the SHA-256 of a token is kept; the plaintext is shown once.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredToken {
+    id: String,
+    name: String,
+    scope: ApiScope,
+    token_hash: String,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PersistedTokens {
+    tokens: Vec<StoredToken>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenInfo {
+    pub id: String,
+    name: String,
+    scope: ApiScope,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "app")]
+impl From<&StoredToken> for ApiTokenInfo {
+    fn from(token: &StoredToken) -> Self {
+        Self {
+            id: token.id.clone(),
+            name: token.name.clone(),
+            scope: token.scope.clone(),
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedApiToken {
+    pub token: String,
+    pub info: ApiTokenInfo,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsCertificateInfo {
+    certificate_path: String,
+    fingerprint_sha256: String,
+}
+
+#[cfg(feature = "app")]
+#[derive(Clone)]
+pub struct ApiTokenStore {
+    inner: Arc<Mutex<Vec<StoredToken>>>,
+    storage_path: Arc<PathBuf>,
+}
+
+#[cfg(feature = "app")]
+impl ApiTokenStore {
+    pub fn new(storage_path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Vec::new())),
+            storage_path: Arc::new(storage_path),
+        }
+    }
+
+    pub fn load_persisted(&self) -> Result<(), String> {
+        if !self.storage_path.exists() {
+            return Ok(());
+        }
+
+        let raw = fs::read_to_string(self.storage_path.as_ref())
+            .map_err(|err| format!("Failed to read API tokens: {err}"))?;
+        let persisted = serde_json::from_str::<PersistedTokens>(&raw)
+            .map_err(|err| format!("Failed to parse API tokens: {err}"))?;
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock API tokens".to_string())?;
+        *locked = persisted.tokens;
+        Ok(())
+    }
+
+    fn persist(&self, tokens: Vec<StoredToken>) -> Result<(), String> {
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create API token directory: {err}"))?;
+        }
+
+        let data = serde_json::to_string_pretty(&PersistedTokens { tokens })
+            .map_err(|err| format!("Failed to encode API tokens: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data)
+            .map_err(|err| format!("Failed to write API tokens: {err}"))?;
+        let _ = fs::set_permissions(self.storage_path.as_ref(), fs::Permissions::from_mode(0o600));
+        Ok(())
+    }
+
+    pub fn create(&self, name: &str, scope: ApiScope) -> Result<CreatedApiToken, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Token name is required".to_string());
+        }
+
+        // Two v4 UUIDs give 244 random bits from the OS RNG.
+        let token = format!("lp_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let stored = StoredToken {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            scope,
+            token_hash: hash_token(&token),
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+        let info = ApiTokenInfo::from(&stored);
+
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock API tokens".to_string())?;
+        locked.push(stored);
+        let tokens = locked.clone();
+        drop(locked);
+
+        self.persist(tokens)?;
+        Ok(CreatedApiToken { token, info })
+    }
+
+    pub fn list(&self) -> Result<Vec<ApiTokenInfo>, String> {
+        let locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock API tokens".to_string())?;
+        Ok(locked.iter().map(ApiTokenInfo::from).collect())
+    }
+
+    pub fn revoke(&self, id: &str) -> Result<bool, String> {
+        let mut locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock API tokens".to_string())?;
+        let before = locked.len();
+        locked.retain(|token| token.id != id);
+        if locked.len() == before {
+            return Ok(false);
+        }
+        let tokens = locked.clone();
+        drop(locked);
+
+        self.persist(tokens)?;
+        Ok(true)
+    }
+
+    fn authenticate(&self, presented: &str) -> Option<ApiScope> {
+        let presented_hash = hash_token(presented);
+        let mut locked = self.inner.lock().ok()?;
+        let token = locked
+            .iter_mut()
+            .find(|token| constant_time_eq(token.token_hash.as_bytes(), presented_hash.as_bytes()))?;
+        let now = Utc::now();
+        let stale = token
+            .last_used_at
+            .map_or(true, |last| now - last >= chrono::Duration::minutes(LAST_USED_PERSIST_INTERVAL_MINUTES));
+        token.last_used_at = Some(now);
+        let scope = token.scope.clone();
+        let tokens = locked.clone();
+        drop(locked);
+
+        if stale {
+            let _ = self.persist(tokens);
+        }
+        Some(scope)
+    }
+}
+
+#[cfg(feature = "app")]
+fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(feature = "app")]
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(feature = "app")]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// The opt-in HTTP API. Every endpoint requires `Authorization: Bearer`
+/// with a token whose scope covers the endpoint.
+#[cfg(feature = "app")]
+#[derive(Clone)]
+pub struct RemoteApi {
+    server: Arc<Mutex<Option<Arc<Server>>>>,
+    advertisement: Arc<Mutex<Option<Advertisement>>>,
+    pub tokens: ApiTokenStore,
+    sync: SyncState,
+    cert_dir: Arc<PathBuf>,
+}
+
+#[cfg(feature = "app")]
+impl RemoteApi {
+    pub fn new(tokens: ApiTokenStore, sync: SyncState, cert_dir: PathBuf) -> Self {
+        Self {
+            server: Arc::new(Mutex::new(None)),
+            advertisement: Arc::new(Mutex::new(None)),
+            tokens,
+            sync,
+            cert_dir: Arc::new(cert_dir),
+        }
+    }
+
+    /// Stops any running server and starts a new one if enabled.
+    pub fn apply(&self, settings: &RemoteApiSettings, timers: TimerStore) -> Result<(), String> {
+        let mut locked = self
+            .server
+            .lock()
+            .map_err(|_| "Failed to lock remote API".to_string())?;
+        if let Some(server) = locked.take() {
+            server.unblock();
+        }
+        let mut advertisement = self
+            .advertisement
+            .lock()
+            .map_err(|_| "Failed to lock remote API".to_string())?;
+        *advertisement = None;
+
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        let address = ("0.0.0.0", settings.port);
+        let server = if settings.tls {
+            let certificate = fs::read(self.cert_dir.join(CERT_FILE))
+                .map_err(|_| "TLS is enabled but no certificate exists; generate one first".to_string())?;
+            let private_key = fs::read(self.cert_dir.join(KEY_FILE))
+                .map_err(|err| format!("Failed to read TLS private key: {err}"))?;
+            Server::https(address, SslConfig { certificate, private_key })
+        } else {
+            Server::http(address)
+        }
+        .map_err(|err| format!("Failed to start remote API on port {}: {err}", settings.port))?;
+
+        let server = Arc::new(server);
+        *locked = Some(server.clone());
+        drop(locked);
+
+        if settings.advertise {
+            let name = match local_host_name() {
+                Some(host) => format!("LockPilot on {host}"),
+                None => "LockPilot".to_string(),
+            };
+            // Not fatal: the API works without it, it just can't be found.
+            match Advertisement::start(&name, settings.port, settings.tls) {
+                Ok(started) => *advertisement = Some(started),
+                Err(err) => eprintln!("Failed to advertise the remote API: {err}"),
+            }
+        }
+        drop(advertisement);
+
+        let tokens = self.tokens.clone();
+        let sync = self.sync.clone();
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                handle_request(request, &timers, &tokens, &sync);
+            }
+        });
+        Ok(())
+    }
+
+    /// Writes a fresh self-signed certificate for `localhost` and this
+    /// Mac's Bonjour name, returning its SHA-256 fingerprint for pinning.
+    pub fn generate_certificate(&self) -> Result<TlsCertificateInfo, String> {
+        let mut names = vec!["localhost".to_string()];
+        if let Some(host) = local_host_name() {
+            names.push(format!("{host}.local"));
+        }
+
+        let certified = rcgen::generate_simple_self_signed(names)
+            .map_err(|err| format!("Failed to generate certificate: {err}"))?;
+
+        fs::create_dir_all(self.cert_dir.as_ref())
+            .map_err(|err| format!("Failed to create certificate directory: {err}"))?;
+        let cert_path = self.cert_dir.join(CERT_FILE);
+        let key_path = self.cert_dir.join(KEY_FILE);
+        fs::write(&cert_path, certified.cert.pem())
+            .map_err(|err| format!("Failed to write certificate: {err}"))?;
+        fs::write(&key_path, certified.key_pair.serialize_pem())
+            .map_err(|err| format!("Failed to write private key: {err}"))?;
+        fs::set_permissions(&key_path, fs::Permissions::from_mode(0o600))
+            .map_err(|err| format!("Failed to protect private key: {err}"))?;
+        let fingerprint = hex(&Sha256::digest(certified.cert.der()));
+        fs::write(self.cert_dir.join(FINGERPRINT_FILE), &fingerprint)
+            .map_err(|err| format!("Failed to write certificate fingerprint: {err}"))?;
+
+        Ok(TlsCertificateInfo {
+            certificate_path: cert_path.display().to_string(),
+            fingerprint_sha256: fingerprint,
+        })
+    }
+
+    /// The current certificate's fingerprint; `None` for certificates
+    /// generated before it was saved alongside them.
+    pub fn certificate_fingerprint(&self) -> Option<String> {
+        fs::read_to_string(self.cert_dir.join(FINGERPRINT_FILE))
+            .ok()
+            .map(|fingerprint| fingerprint.trim().to_string())
+            .filter(|fingerprint| !fingerprint.is_empty())
+    }
+}
+
+#[cfg(feature = "app")]
+pub fn local_host_name() -> Option<String> {
+    let output = Command::new("/usr/sbin/scutil")
+        .args(["--get", "LocalHostName"])
+        .output()
+        .ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(name).filter(|name| output.status.success() && !name.is_empty())
+}
+
+#[cfg(feature = "app")]
+enum Endpoint {
+    ListTimers,
+    CreateTimer,
+    CancelTimer(String),
+    Dashboard,
+    GetPause,
+    SetPause,
+    SyncPair,
+    Sync,
+}
+
+#[cfg(feature = "app")]
+impl Endpoint {
+    fn route(method: &Method, url: &str) -> Option<Self> {
+        let path = url.split('?').next().unwrap_or_default().trim_end_matches('/');
+        match (method, path) {
+            (Method::Get, "/api/timers") => Some(Endpoint::ListTimers),
+            (Method::Post, "/api/timers") => Some(Endpoint::CreateTimer),
+            (Method::Delete, _) => path
+                .strip_prefix("/api/timers/")
+                .filter(|id| !id.is_empty() && !id.contains('/'))
+                .map(|id| Endpoint::CancelTimer(id.to_string())),
+            (Method::Get, "/api/dashboard") => Some(Endpoint::Dashboard),
+            (Method::Get, "/api/pause") => Some(Endpoint::GetPause),
+            (Method::Put, "/api/pause") => Some(Endpoint::SetPause),
+            (Method::Post, "/api/sync/pair") => Some(Endpoint::SyncPair),
+            (Method::Post, "/api/sync") => Some(Endpoint::Sync),
+            _ => None,
+        }
+    }
+
+    fn required_scope(&self) -> ApiScope {
+        match self {
+            Endpoint::ListTimers | Endpoint::Dashboard | Endpoint::GetPause => ApiScope::Read,
+            Endpoint::CreateTimer
+            | Endpoint::CancelTimer(_)
+            | Endpoint::SetPause
+            | Endpoint::SyncPair
+            | Endpoint::Sync => ApiScope::Control,
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Deserialize)]
+struct SetPauseBody {
+    until: Option<String>,
+}
+
+#[cfg(feature = "app")]
+fn handle_request(mut request: Request, timers: &TimerStore, tokens: &ApiTokenStore, sync: &SyncState) {
+    let Some(endpoint) = Endpoint::route(request.method(), request.url()) else {
+        respond_error(request, 404, "Not found");
+        return;
+    };
+
+    let presented = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    let Some(scope) = presented.and_then(|token| tokens.authenticate(&token)) else {
+        respond_error(request, 401, "Missing or invalid API token");
+        return;
+    };
+    if !scope.allows(&endpoint.required_scope()) {
+        respond_error(request, 403, "Token scope does not allow this endpoint");
+        return;
+    }
+    if endpoint.required_scope() == ApiScope::Control {
+        if let Err(err) = timers.policy.check_remote_control() {
+            respond_policy_violation(request, &err);
+            return;
+        }
+    }
+
+    let origin = AuditOrigin::Remote {
+        address: request
+            .remote_addr()
+            .map(|address| address.ip().to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+    };
+
+    let limit = match endpoint {
+        Endpoint::Sync => MAX_SYNC_BODY_BYTES,
+        _ => MAX_BODY_BYTES,
+    };
+    let mut body = String::new();
+    if request
+        .as_reader()
+        .take(limit)
+        .read_to_string(&mut body)
+        .is_err()
+    {
+        respond_error(request, 400, "Request body must be UTF-8");
+        return;
+    }
+    if let Endpoint::CreateTimer = endpoint {
+        if let Ok(create) = serde_json::from_str::<CreateTimerRequest>(&body) {
+            let escalation = create.escalation.as_ref().map(|escalation| &escalation.action);
+            if let Err(err) = timers.policy.check_timer(&create.action, escalation, create.hooks.as_ref()) {
+                respond_policy_violation(request, &err);
+                return;
+            }
+            let allow_scripts = timers
+                .settings
+                .snapshot()
+                .is_ok_and(|settings| settings.remote_api.allow_scripts);
+            if !allow_scripts && runs_code(&create.action, escalation, create.hooks.as_ref()) {
+                respond_error(request, 403, SCRIPTS_NOT_ALLOWED);
+                return;
+            }
+        }
+    }
+
+    let result = match endpoint {
+        Endpoint::ListTimers => timers.list_views().and_then(to_json),
+        Endpoint::CreateTimer => serde_json::from_str::<CreateTimerRequest>(&body)
+            .map_err(|err| format!("Invalid timer request: {err}"))
+            .and_then(|create| {
+                let (_, format) = parse_target_time(&create.target_time)?;
+                let info = timers.create(create, &origin)?;
+                Ok(CreatedTimerView::new(info, format, Utc::now()))
+            })
+            .and_then(to_json),
+        Endpoint::CancelTimer(id) => timers.cancel(&id, &origin).and_then(to_json),
+        Endpoint::Dashboard => timers.dashboard_state().and_then(to_json),
+        Endpoint::GetPause => timers
+            .settings
+            .snapshot()
+            .map(|settings| PauseState::from_settings(&settings))
+            .and_then(to_json),
+        Endpoint::SetPause => serde_json::from_str::<SetPauseBody>(&body)
+            .map_err(|err| format!("Invalid pause request: {err}"))
+            .and_then(|pause| parse_pause_until(pause.until.as_deref()))
+            .and_then(|until| timers.set_pause(until, &origin))
+            .and_then(to_json),
+        Endpoint::SyncPair => serde_json::from_str::<PairRequest>(&body)
+            .map_err(|err| format!("Invalid pairing request: {err}"))
+            .and_then(|pair| sync::accept_pairing(sync, pair))
+            .and_then(to_json),
+        Endpoint::Sync => serde_json::from_str::<SyncBundle>(&body)
+            .map_err(|err| format!("Invalid sync request: {err}"))
+            .and_then(|bundle| sync::exchange(sync, timers, bundle, &origin))
+            .and_then(to_json),
+    };
+
+    match result {
+        Ok(json) => respond_json(request, 200, json),
+        Err(err) => respond_error(request, 400, &err),
+    }
+}
+
+#[cfg(feature = "app")]
+fn to_json<T: Serialize>(value: T) -> Result<String, String> {
+    serde_json::to_string(&value).map_err(|err| format!("Failed to encode response: {err}"))
+}
+
+#[cfg(feature = "app")]
+fn respond_error(request: Request, status: u16, message: &str) {
+    let body = serde_json::json!({ "error": message }).to_string();
+    respond_json(request, status, body);
+}
+
+/// 403 with `"code": "policyViolation"`, so clients can tell a policy
+/// refusal from a token scope one.
+#[cfg(feature = "app")]
+fn respond_policy_violation(request: Request, message: &str) {
+    let body = serde_json::json!({ "error": message, "code": "policyViolation" }).to_string();
+    respond_json(request, 403, body);
+}
+
+#[cfg(feature = "app")]
+fn respond_json(request: Request, status: u16, body: String) {
+    let mut response = Response::from_string(body).with_status_code(status);
+    if let Ok(header) = Header::from_bytes("Content-Type", "application/json") {
+        response = response.with_header(header);
+    }
+    let _ = request.respond(response);
+}
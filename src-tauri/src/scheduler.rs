@@ -0,0 +1,172 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::CLOCK_CHECK_INTERVAL;
+
+/// Upcoming deadlines for every scheduled timer, served by one thread
+/// instead of one sleeping thread per timer.
+pub struct DeadlineQueue<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for DeadlineQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<QueueState<T>>,
+    wake: Condvar,
+    running: AtomicBool,
+}
+
+struct QueueState<T> {
+    heap: BinaryHeap<Reverse<Slot<T>>>,
+    next_seq: u64,
+}
+
+/// Ordered by deadline, then by insertion so equal deadlines fire in the
+/// order they were queued.
+struct Slot<T> {
+    due: DateTime<Utc>,
+    seq: u64,
+    job: T,
+}
+
+impl<T> PartialEq for Slot<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.due, self.seq) == (other.due, other.seq)
+    }
+}
+
+impl<T> Eq for Slot<T> {}
+
+impl<T> PartialOrd for Slot<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Slot<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.due, self.seq).cmp(&(other.due, other.seq))
+    }
+}
+
+impl<T: Send + 'static> Default for DeadlineQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + 'static> DeadlineQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(QueueState {
+                    heap: BinaryHeap::new(),
+                    next_seq: 0,
+                }),
+                wake: Condvar::new(),
+                running: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Queues `job` to be handed out once the wall clock reaches `due`.
+    pub fn push(&self, due: DateTime<Utc>, job: T) -> Result<(), String> {
+        let mut state = self
+            .shared
+            .state
+            .lock()
+            .map_err(|_| "Failed to lock scheduler queue".to_string())?;
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.heap.push(Reverse(Slot { due, seq, job }));
+        drop(state);
+        self.shared.wake.notify_one();
+        Ok(())
+    }
+
+    /// Jobs waiting for their deadline.
+    #[cfg(feature = "app")]
+    pub fn queued(&self) -> usize {
+        self.shared.state.lock().map(|state| state.heap.len()).unwrap_or(0)
+    }
+
+    /// Whether the thread started by `run` is still serving the queue.
+    #[cfg(feature = "app")]
+    pub fn is_running(&self) -> bool {
+        self.shared.running.load(AtomicOrdering::SeqCst)
+    }
+
+    /// Starts the thread that hands each due job to `fire`. `fire` runs on
+    /// that thread, so anything slow must be moved elsewhere. Jobs for which
+    /// `is_cancelled` returns `true` are dropped the next time the thread
+    /// wakes, which is at least every `CLOCK_CHECK_INTERVAL`; the wait is
+    /// re-anchored to `Utc::now()` each time, so clock changes and sleep are
+    /// picked up the same way.
+    pub fn run<C, F>(&self, is_cancelled: C, mut fire: F)
+    where
+        C: Fn(&T) -> bool + Send + 'static,
+        F: FnMut(T) + Send + 'static,
+    {
+        let shared = self.shared.clone();
+        shared.running.store(true, AtomicOrdering::SeqCst);
+        thread::spawn(move || {
+            let _running = RunningGuard(shared.clone());
+            let Ok(mut state) = shared.state.lock() else {
+                return;
+            };
+            loop {
+                state.heap.retain(|Reverse(slot)| !is_cancelled(&slot.job));
+
+                let next_due = state.heap.peek().map(|Reverse(slot)| slot.due);
+                let remaining = next_due.map(|due| (due - Utc::now()).to_std());
+                if let Some(Err(_)) | Some(Ok(Duration::ZERO)) = remaining {
+                    let Some(Reverse(slot)) = state.heap.pop() else {
+                        continue;
+                    };
+                    drop(state);
+                    fire(slot.job);
+                    state = match shared.state.lock() {
+                        Ok(state) => state,
+                        Err(_) => return,
+                    };
+                    continue;
+                }
+                let wait = match remaining {
+                    Some(Ok(remaining)) => remaining.min(CLOCK_CHECK_INTERVAL),
+                    _ => CLOCK_CHECK_INTERVAL,
+                };
+
+                state = match shared.wake.wait_timeout(state, wait) {
+                    Ok((state, _)) => state,
+                    Err(_) => return,
+                };
+            }
+        });
+    }
+}
+
+/// Clears `running` when the queue thread returns or unwinds.
+struct RunningGuard<T>(Arc<Shared<T>>);
+
+impl<T> Drop for RunningGuard<T> {
+    fn drop(&mut self) {
+        self.0.running.store(false, AtomicOrdering::SeqCst);
+    }
+}
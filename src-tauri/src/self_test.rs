@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{
+    health::CheckStatus,
+    lock,
+    plugins::PluginRegistry,
+    popup_script,
+    system::{self, PermissionState},
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestStep {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionTest {
+    pub action: String,
+    /// The worst status among `steps`.
+    pub status: CheckStatus,
+    pub steps: Vec<TestStep>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    /// `false` if any action has an error; warnings don't count.
+    pub passed: bool,
+    pub actions: Vec<ActionTest>,
+}
+
+/// Walks through every action type without performing it: scripts are
+/// compiled but not run, binaries and symbols are looked up, and
+/// permissions are read without prompting.
+pub fn run(plugins: &PluginRegistry, lock_compatibility: bool) -> SelfTestReport {
+    let automation = system::automation_permission(false);
+    let mut actions = vec![
+        action("popup", vec![compile_step(&popup_script("LockPilot self-test"))]),
+        action("lock", lock_steps(lock_compatibility)),
+    ];
+    for name in ["shutdown", "restart", "log out"] {
+        actions.push(action(name, session_steps(automation)));
+    }
+    actions.push(action("display sleep", vec![binary_step("/usr/bin/pmset")]));
+    actions.push(action("sleep", vec![login_window_step()]));
+    for plugin in plugins.discover() {
+        let executable = plugin.executable().display().to_string();
+        actions.push(action(
+            &format!("plugin: {}", plugin.name),
+            vec![step("executable", CheckStatus::Ok, format!("{executable} is executable"))],
+        ));
+    }
+
+    SelfTestReport {
+        passed: actions.iter().all(|action| action.status != CheckStatus::Error),
+        actions,
+    }
+}
+
+fn action(name: &str, steps: Vec<TestStep>) -> ActionTest {
+    ActionTest {
+        action: name.to_string(),
+        status: steps.iter().map(|step| step.status).max().unwrap_or(CheckStatus::Ok),
+        steps,
+    }
+}
+
+fn step(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> TestStep {
+    TestStep {
+        name,
+        status,
+        detail: detail.into(),
+    }
+}
+
+fn compile_step(script: &str) -> TestStep {
+    match system::compile_applescript(script) {
+        Ok(()) => step("applescript", CheckStatus::Ok, "Script compiles"),
+        Err(err) => step("applescript", CheckStatus::Error, err),
+    }
+}
+
+fn binary_step(path: &str) -> TestStep {
+    if Path::new(path).exists() {
+        step("binary", CheckStatus::Ok, format!("{path} found"))
+    } else {
+        step("binary", CheckStatus::Error, format!("{path} is missing"))
+    }
+}
+
+fn login_window_step() -> TestStep {
+    if system::is_process_running("loginwindow") {
+        step("loginwindow", CheckStatus::Ok, "loginwindow is running to receive the Apple Event")
+    } else {
+        step("loginwindow", CheckStatus::Error, "loginwindow is not running")
+    }
+}
+
+fn lock_steps(compatibility: bool) -> Vec<TestStep> {
+    let native = match lock::native_lock_available() {
+        Ok(()) => step("native", CheckStatus::Ok, "SACLockScreenImmediate is available"),
+        // Not fatal on its own while a fallback can still lock.
+        Err(err) if compatibility || lock::cg_session_available() => step("native", CheckStatus::Warning, err),
+        Err(err) => step("native", CheckStatus::Error, err),
+    };
+    let mut steps = vec![native];
+    if lock::cg_session_available() {
+        steps.push(step("cgsession", CheckStatus::Ok, "CGSession -suspend is available"));
+    }
+    if compatibility {
+        steps.push(compile_step(lock::LOCK_SHORTCUT_SCRIPT));
+        steps.push(match system::accessibility_permission() {
+            PermissionState::Granted => step("accessibility", CheckStatus::Ok, "Lock shortcut can be sent"),
+            _ => step(
+                "accessibility",
+                CheckStatus::Warning,
+                "The lock shortcut fallback needs Accessibility access",
+            ),
+        });
+        steps.push(binary_step("/usr/bin/pmset"));
+    }
+    steps
+}
+
+fn session_steps(automation: PermissionState) -> Vec<TestStep> {
+    let quit_apps = match automation {
+        PermissionState::Granted => step("automation", CheckStatus::Ok, "Apps can be asked to quit first"),
+        _ => step(
+            "automation",
+            CheckStatus::Warning,
+            "\"Quit apps first\" needs Automation access to System Events",
+        ),
+    };
+    vec![login_window_step(), quit_apps]
+}
@@ -0,0 +1,69 @@
+use std::{fs, process::Command};
+
+use uuid::Uuid;
+
+use crate::exec;
+
+const SHORTCUTS: &str = "/usr/bin/shortcuts";
+/// Longest shortcut output kept in a history entry.
+const MAX_OUTPUT_CHARS: usize = 500;
+
+/// Names of the user's shortcuts in Shortcuts.app.
+pub fn names() -> Result<Vec<String>, String> {
+    let output = exec::output(Command::new(SHORTCUTS).arg("list"))
+        .map_err(|err| format!("Failed to run shortcuts: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list shortcuts: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Fails unless a shortcut called `name` exists.
+pub fn check_exists(name: &str) -> Result<(), String> {
+    if names()?.iter().any(|existing| existing == name) {
+        Ok(())
+    } else {
+        Err(format!("No shortcut named \"{name}\" in Shortcuts"))
+    }
+}
+
+/// Runs the shortcut, handing it `input` as text, and returns its text
+/// output, shortened, if it produced any.
+pub fn run(name: &str, input: Option<&str>) -> Result<Option<String>, String> {
+    let id = Uuid::new_v4();
+    let input_path = std::env::temp_dir().join(format!("lockpilot-shortcut-{id}.txt"));
+    let output_path = std::env::temp_dir().join(format!("lockpilot-shortcut-{id}-output.txt"));
+
+    let mut command = Command::new(SHORTCUTS);
+    command
+        .args(["run", name, "--output-path"])
+        .arg(&output_path)
+        .args(["--output-type", "public.plain-text"]);
+    if let Some(input) = input.filter(|input| !input.is_empty()) {
+        fs::write(&input_path, input).map_err(|err| format!("Failed to prepare shortcut input: {err}"))?;
+        command.arg("--input-path").arg(&input_path);
+    }
+
+    let result = exec::output(&mut command).map_err(|err| format!("Failed to run shortcuts: {err}"));
+    let text = fs::read_to_string(&output_path).unwrap_or_default();
+    let _ = fs::remove_file(&input_path);
+    let _ = fs::remove_file(&output_path);
+
+    let output = result?;
+    if !output.status.success() {
+        return Err(format!(
+            "Shortcut \"{name}\" failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let text: String = text.trim().chars().take(MAX_OUTPUT_CHARS).collect();
+    Ok(Some(text).filter(|text| !text.is_empty()))
+}
@@ -0,0 +1,91 @@
+use std::process::Command;
+
+use crate::exec;
+
+const SSH: &str = "/usr/bin/ssh";
+/// Longest remote output kept in a history entry.
+const MAX_OUTPUT_CHARS: usize = 500;
+const MAX_COMMAND_LENGTH: usize = 4096;
+/// ssh's own exit status when it fails, as opposed to the remote command's.
+const SSH_FAILED: i32 = 255;
+
+/// Options that keep ssh from ever asking anything: keys come from the
+/// agent, `~/.ssh/config` or the login keychain (`UseKeychain yes`), never
+/// from a password LockPilot stores, and a host whose key isn't already in
+/// `known_hosts` fails instead of being trusted.
+const OPTIONS: &[&str] = &[
+    "-o",
+    "BatchMode=yes",
+    "-o",
+    "StrictHostKeyChecking=yes",
+    "-o",
+    "ConnectTimeout=15",
+];
+
+/// Fails unless the host and user can only be read as names, never as ssh
+/// options, and there is a command to run.
+pub fn validate(host: &str, user: Option<&str>, command: &str) -> Result<(), String> {
+    let is_name = |value: &str, extra: &[char]| {
+        !value.is_empty()
+            && !value.starts_with('-')
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') || extra.contains(&c))
+    };
+    // Colons, brackets and `%` for IPv6 addresses and zones.
+    if !is_name(host, &[':', '[', ']', '%']) {
+        return Err(format!("Not a host name or address: {host}"));
+    }
+    if let Some(user) = user {
+        if !is_name(user, &[]) {
+            return Err(format!("Not a user name: {user}"));
+        }
+    }
+    if command.trim().is_empty() {
+        return Err("SSH timers require a command".to_string());
+    }
+    if command.len() > MAX_COMMAND_LENGTH || command.contains('\0') {
+        return Err(format!("The command must be at most {MAX_COMMAND_LENGTH} characters of text"));
+    }
+    Ok(())
+}
+
+/// `ssh` arguments running `command` on `host`; without `user` the one from
+/// `~/.ssh/config`, or the local user, is used.
+pub fn arguments(host: &str, user: Option<&str>, command: &str) -> Vec<String> {
+    let mut arguments: Vec<String> = OPTIONS.iter().map(|option| option.to_string()).collect();
+    if let Some(user) = user {
+        arguments.extend(["-l".to_string(), user.to_string()]);
+    }
+    arguments.extend(["--".to_string(), host.to_string(), command.to_string()]);
+    arguments
+}
+
+/// Runs `command` on `host`. The remote exit status is kept in history
+/// along with the start of the output.
+pub fn run(host: &str, user: Option<&str>, command: &str) -> Result<Option<String>, String> {
+    validate(host, user, command)?;
+    let output = exec::output(Command::new(SSH).args(arguments(host, user, command)))
+        .map_err(|err| format!("Failed to run ssh: {err}"))?;
+    let shorten = |bytes: &[u8]| -> String {
+        String::from_utf8_lossy(bytes)
+            .trim()
+            .chars()
+            .take(MAX_OUTPUT_CHARS)
+            .collect()
+    };
+
+    match output.status.code() {
+        Some(0) => {
+            let text = shorten(&output.stdout);
+            Ok(Some(if text.is_empty() {
+                format!("{host}: exit status 0")
+            } else {
+                format!("{host}: exit status 0: {text}")
+            }))
+        }
+        Some(SSH_FAILED) => Err(format!("ssh to {host} failed: {}", shorten(&output.stderr))),
+        Some(code) => Err(format!("{host}: exit status {code}: {}", shorten(&output.stderr))),
+        None => Err(format!("ssh to {host} was killed")),
+    }
+}
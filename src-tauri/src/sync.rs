@@ -0,0 +1,708 @@
+#[cfg(feature = "app")]
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "app")]
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+#[cfg(feature = "app")]
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
+use uuid::Uuid;
+
+#[cfg(feature = "app")]
+use crate::{
+    fleet::{self, FleetTarget, PeerCredentials},
+    remote_api::{self, ApiScope, RemoteApi, RemoteApiSettings},
+    system, AuditOrigin, TimerInfo, TimerStore, DEFAULT_WORKSPACE,
+};
+
+#[cfg(feature = "app")]
+const KEYCHAIN_SERVICE: &str = "com.maks.lockpilot.sync";
+#[cfg(feature = "app")]
+const SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Deletions older than this are forgotten; a peer that has been away
+/// longer may bring a deleted timer back.
+#[cfg(feature = "app")]
+const TOMBSTONE_DAYS: i64 = 30;
+
+/// Which timers replicate: those with at least one of `tags`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSettings {
+    pub enabled: bool,
+    pub tags: Vec<String>,
+}
+
+/// A Mac this one syncs with. Its token, and certificate fingerprint for
+/// HTTPS, are in the login keychain under `id`.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPeer {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub paired_at: DateTime<Utc>,
+    /// The API token created on this Mac for the peer, if pairing started
+    /// here; revoked on unpairing.
+    #[serde(default)]
+    pub api_token_id: Option<String>,
+    #[serde(default)]
+    pub last_synced_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+#[cfg(feature = "app")]
+impl SyncPeer {
+    fn target(&self) -> FleetTarget {
+        FleetTarget {
+            host: self.host.clone(),
+            port: self.port,
+            tls: self.tls,
+        }
+    }
+}
+
+/// A timer deleted on some Mac, so peers delete their copy instead of
+/// sending it back.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tombstone {
+    pub id: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// What `/api/sync/pair` receives: how to reach the Mac asking to pair.
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairRequest {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub fingerprint_sha256: Option<String>,
+    /// A control token on the asking Mac, for syncs started from this side.
+    pub token: String,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairResponse {
+    pub name: String,
+}
+
+/// One side's synced timers and deletions. Sent to `/api/sync`, which
+/// merges it and answers with its own.
+#[cfg(feature = "app")]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncBundle {
+    /// The sender's sync tags; the answer is limited to them.
+    pub tags: Vec<String>,
+    pub timers: Vec<TimerInfo>,
+    pub tombstones: Vec<Tombstone>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedSync {
+    peers: Vec<SyncPeer>,
+    tombstones: Vec<Tombstone>,
+    /// Ids of synced timers as of the last sync, so a timer missing since
+    /// then is known to be deleted.
+    known_ids: HashSet<String>,
+}
+
+/// Paired peers and sync bookkeeping, saved in `sync.json`.
+#[cfg(feature = "app")]
+#[derive(Clone)]
+pub struct SyncState {
+    inner: Arc<Mutex<PersistedSync>>,
+    storage_path: Arc<PathBuf>,
+    /// Wakes the sync thread early, e.g. for `sync_now`.
+    wake: Arc<Mutex<Option<mpsc::Sender<()>>>>,
+}
+
+#[cfg(feature = "app")]
+impl SyncState {
+    pub fn new(storage_path: PathBuf) -> Self {
+        let persisted = fs::read_to_string(&storage_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<PersistedSync>(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            inner: Arc::new(Mutex::new(persisted)),
+            storage_path: Arc::new(storage_path),
+            wake: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn modify<T, F>(&self, apply: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut PersistedSync) -> T,
+    {
+        let mut locked = self.inner.lock().map_err(|_| "Failed to lock sync state".to_string())?;
+        let result = apply(&mut locked);
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("Failed to create sync directory: {err}"))?;
+        }
+        let data = serde_json::to_string_pretty(&*locked).map_err(|err| format!("Failed to encode sync state: {err}"))?;
+        drop(locked);
+        fs::write(self.storage_path.as_ref(), data).map_err(|err| format!("Failed to write sync state: {err}"))?;
+        Ok(result)
+    }
+
+    pub fn peers(&self) -> Result<Vec<SyncPeer>, String> {
+        self.inner
+            .lock()
+            .map(|state| state.peers.clone())
+            .map_err(|_| "Failed to lock sync state".to_string())
+    }
+
+    /// Saves `peer` and the credentials for reaching it, replacing an
+    /// earlier pairing with the same host and port, which is returned.
+    fn add_peer(&self, peer: SyncPeer, credentials: &PeerCredentials) -> Result<Option<SyncPeer>, String> {
+        let saved = serde_json::to_string(credentials).map_err(|err| format!("Failed to encode credentials: {err}"))?;
+        system::set_keychain_password(KEYCHAIN_SERVICE, &peer.id, &saved)?;
+        let replaced = self.modify(|state| {
+            let index = state
+                .peers
+                .iter()
+                .position(|existing| existing.host.eq_ignore_ascii_case(&peer.host) && existing.port == peer.port);
+            let replaced = index.map(|index| state.peers.remove(index));
+            state.peers.push(peer);
+            replaced
+        })?;
+        if let Some(replaced) = &replaced {
+            let _ = system::delete_keychain_password(KEYCHAIN_SERVICE, &replaced.id);
+        }
+        Ok(replaced)
+    }
+
+    /// Forgets the peer and its credentials. `None` if it wasn't paired.
+    pub fn remove_peer(&self, id: &str) -> Result<Option<SyncPeer>, String> {
+        let removed = self.modify(|state| {
+            let index = state.peers.iter().position(|peer| peer.id == id)?;
+            Some(state.peers.remove(index))
+        })?;
+        if removed.is_some() {
+            system::delete_keychain_password(KEYCHAIN_SERVICE, id)?;
+        }
+        Ok(removed)
+    }
+
+    fn record_result(&self, id: &str, result: &Result<(), String>) {
+        let _ = self.modify(|state| {
+            if let Some(peer) = state.peers.iter_mut().find(|peer| peer.id == id) {
+                match result {
+                    Ok(()) => {
+                        peer.last_synced_at = Some(Utc::now());
+                        peer.last_error = None;
+                    }
+                    Err(err) => peer.last_error = Some(err.clone()),
+                }
+            }
+        });
+    }
+
+    /// This Mac's synced timers and deletions, limited to `tags`. Timers
+    /// synced before but gone now are tombstoned first.
+    fn bundle(&self, store: &TimerStore, own_tags: &[String], tags: &[String]) -> Result<SyncBundle, String> {
+        let timers = default_workspace_timers(store)?;
+        let now = Utc::now();
+        let tombstones = self.modify(|state| {
+            let present: HashSet<&str> = timers.iter().map(|timer| timer.id.as_str()).collect();
+            let deleted: Vec<String> = state
+                .known_ids
+                .iter()
+                .filter(|id| !present.contains(id.as_str()))
+                .cloned()
+                .collect();
+            for id in deleted {
+                state.known_ids.remove(&id);
+                state.tombstones.push(Tombstone { id, deleted_at: now });
+            }
+            state
+                .tombstones
+                .retain(|tombstone| tombstone.deleted_at > now - ChronoDuration::days(TOMBSTONE_DAYS));
+            state
+                .known_ids
+                .extend(timers.iter().filter(|timer| has_tag(timer, own_tags)).map(|timer| timer.id.clone()));
+            state.tombstones.clone()
+        })?;
+        Ok(SyncBundle {
+            tags: own_tags.to_vec(),
+            timers: timers
+                .into_iter()
+                .filter(|timer| has_tag(timer, own_tags) && has_tag(timer, tags))
+                .collect(),
+            tombstones,
+        })
+    }
+
+    /// Applies a peer's bundle: deletions newer than the local copy win,
+    /// and timers with one of `own_tags` are added, or replace the local
+    /// copy if changed more recently.
+    fn merge(
+        &self,
+        store: &TimerStore,
+        bundle: SyncBundle,
+        own_tags: &[String],
+        origin: &AuditOrigin,
+    ) -> Result<(), String> {
+        let local = default_workspace_timers(store)?;
+        let modified = |timer: &TimerInfo| timer.modified_at.unwrap_or(timer.created_at);
+
+        let mut deleted = Vec::new();
+        let tombstones = self.modify(|state| {
+            for tombstone in bundle.tombstones {
+                let newer_copy = local
+                    .iter()
+                    .any(|timer| timer.id == tombstone.id && modified(timer) > tombstone.deleted_at);
+                if newer_copy {
+                    continue;
+                }
+                if local.iter().any(|timer| timer.id == tombstone.id && has_tag(timer, own_tags)) {
+                    deleted.push(tombstone.id.clone());
+                }
+                state.known_ids.remove(&tombstone.id);
+                match state.tombstones.iter_mut().find(|existing| existing.id == tombstone.id) {
+                    Some(existing) => existing.deleted_at = existing.deleted_at.max(tombstone.deleted_at),
+                    None => state.tombstones.push(tombstone),
+                }
+            }
+            state.tombstones.clone()
+        })?;
+        for id in &deleted {
+            store.cancel(id, origin)?;
+        }
+
+        let incoming: Vec<TimerInfo> = bundle
+            .timers
+            .into_iter()
+            .filter(|timer| has_tag(timer, own_tags))
+            .filter(|timer| {
+                !tombstones
+                    .iter()
+                    .any(|tombstone| tombstone.id == timer.id && tombstone.deleted_at >= modified(timer))
+            })
+            .filter(|timer| match local.iter().find(|existing| existing.id == timer.id) {
+                Some(existing) => modified(timer) > modified(existing),
+                None => true,
+            })
+            .collect();
+        if incoming.is_empty() {
+            return Ok(());
+        }
+        let merged = store.merge_synced(incoming, origin)?;
+        self.modify(|state| state.known_ids.extend(merged))
+    }
+
+    /// Wakes the sync thread to sync with every peer now.
+    pub fn sync_soon(&self) {
+        if let Some(wake) = self.wake.lock().ok().and_then(|wake| wake.clone()) {
+            let _ = wake.send(());
+        }
+    }
+}
+
+/// Timers of the Default workspace, the only one that syncs; others are
+/// experiments peers shouldn't see.
+#[cfg(feature = "app")]
+fn default_workspace_timers(store: &TimerStore) -> Result<Vec<TimerInfo>, String> {
+    Ok(store
+        .all()?
+        .into_iter()
+        .filter(|timer| timer.workspace == DEFAULT_WORKSPACE)
+        .collect())
+}
+
+/// Whether `timer` has one of `tags`. Managed timers are never synced.
+#[cfg(feature = "app")]
+fn has_tag(timer: &TimerInfo, tags: &[String]) -> bool {
+    timer.managed.is_none() && timer.tags.iter().any(|tag| tags.contains(tag))
+}
+
+/// Pairs with the LockPilot at `target`: `credentials` are a control token
+/// created there (and its certificate fingerprint for HTTPS). In return a
+/// new token for this Mac is handed over, so either side can start a sync;
+/// it is revoked if pairing fails.
+#[cfg(feature = "app")]
+pub fn pair(
+    state: &SyncState,
+    api: &RemoteApi,
+    settings: &RemoteApiSettings,
+    target: &FleetTarget,
+    credentials: PeerCredentials,
+) -> Result<SyncPeer, String> {
+    if !settings.enabled {
+        return Err("Turn on the remote API first; the peer syncs back through it".to_string());
+    }
+    let host = remote_api::local_host_name().ok_or_else(|| "This Mac has no local host name".to_string())?;
+    let fingerprint_sha256 = if settings.tls {
+        Some(
+            api.certificate_fingerprint()
+                .ok_or_else(|| "Generate the certificate again so peers can pin it".to_string())?,
+        )
+    } else {
+        None
+    };
+    let created = api.tokens.create(&format!("Sync: {}", target.host), ApiScope::Control)?;
+    let own = PairRequest {
+        name: host.clone(),
+        host: format!("{host}.local"),
+        port: settings.port,
+        tls: settings.tls,
+        fingerprint_sha256,
+        token: created.token,
+    };
+    let token_id = created.info.id;
+    let paired = match request_pairing(target, &credentials, &own) {
+        Ok(paired) => paired,
+        Err(err) => {
+            let _ = api.tokens.revoke(&token_id);
+            return Err(err);
+        }
+    };
+
+    let peer = SyncPeer {
+        id: Uuid::new_v4().to_string(),
+        name: paired.name,
+        host: target.host.clone(),
+        port: target.port,
+        tls: target.tls,
+        paired_at: Utc::now(),
+        api_token_id: Some(token_id),
+        last_synced_at: None,
+        last_error: None,
+    };
+    if let Some(replaced) = state.add_peer(peer.clone(), &credentials)? {
+        if let Some(token_id) = &replaced.api_token_id {
+            let _ = api.tokens.revoke(token_id);
+        }
+    }
+    Ok(peer)
+}
+
+#[cfg(feature = "app")]
+fn request_pairing(
+    target: &FleetTarget,
+    credentials: &PeerCredentials,
+    own: &PairRequest,
+) -> Result<PairResponse, String> {
+    let client = fleet::client_for(target, credentials.fingerprint_sha256.as_deref())?;
+    let response = client
+        .post(target.url("/api/sync/pair"))
+        .bearer_auth(credentials.token.trim())
+        .json(own)
+        .send()
+        .map_err(|err| format!("Failed to reach {}: {err}", target.host))?;
+    let status = response.status();
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(format!("{} refused the token", target.host));
+    }
+    if !status.is_success() {
+        let body: serde_json::Value = response.json().unwrap_or_default();
+        let error = body["error"].as_str().unwrap_or("no details");
+        return Err(format!("{} could not pair ({status}): {error}", target.host));
+    }
+    response
+        .json()
+        .map_err(|err| format!("Unexpected answer from {}: {err}", target.host))
+}
+
+/// The other half of `pair`, run by the remote API: remembers the Mac that
+/// asked and how to reach it.
+#[cfg(feature = "app")]
+pub fn accept_pairing(state: &SyncState, request: PairRequest) -> Result<PairResponse, String> {
+    if request.token.trim().is_empty() || request.host.trim().is_empty() {
+        return Err("Pairing needs a host and a token".to_string());
+    }
+    let credentials = PeerCredentials {
+        token: request.token,
+        fingerprint_sha256: request.fingerprint_sha256,
+    };
+    let peer = SyncPeer {
+        id: Uuid::new_v4().to_string(),
+        name: request.name,
+        host: request.host,
+        port: request.port,
+        tls: request.tls,
+        paired_at: Utc::now(),
+        api_token_id: None,
+        last_synced_at: None,
+        last_error: None,
+    };
+    state.add_peer(peer, &credentials)?;
+    Ok(PairResponse {
+        name: remote_api::local_host_name().unwrap_or_else(|| "LockPilot".to_string()),
+    })
+}
+
+/// Unpairs the peer with `id`, revoking the token this Mac made for it.
+/// `None` if it wasn't paired.
+#[cfg(feature = "app")]
+pub fn unpair(state: &SyncState, api: &RemoteApi, id: &str) -> Result<Option<SyncPeer>, String> {
+    let removed = state.remove_peer(id)?;
+    if let Some(token_id) = removed.as_ref().and_then(|peer| peer.api_token_id.as_ref()) {
+        api.tokens.revoke(token_id)?;
+    }
+    Ok(removed)
+}
+
+/// Answers a peer's `/api/sync` request: merges its bundle, then returns
+/// this Mac's timers in the tags both sides sync.
+#[cfg(feature = "app")]
+pub fn exchange(
+    state: &SyncState,
+    store: &TimerStore,
+    bundle: SyncBundle,
+    origin: &AuditOrigin,
+) -> Result<SyncBundle, String> {
+    let settings = store.settings.snapshot()?;
+    if !settings.sync.enabled {
+        return Err("Sync is turned off on this Mac".to_string());
+    }
+    let settings = settings.sync;
+    let peer_tags = bundle.tags.clone();
+    state.merge(store, bundle, &settings.tags, origin)?;
+    let reply = state.bundle(store, &settings.tags, &peer_tags)?;
+    store.emit("sync-finished", ());
+    Ok(reply)
+}
+
+/// Sends this Mac's bundle to `peer` and merges the answer.
+#[cfg(feature = "app")]
+fn sync_with(state: &SyncState, store: &TimerStore, peer: &SyncPeer, tags: &[String]) -> Result<(), String> {
+    let credentials = system::keychain_password(KEYCHAIN_SERVICE, &peer.id)?
+        .and_then(|saved| serde_json::from_str::<PeerCredentials>(&saved).ok())
+        .ok_or_else(|| "No token saved for this peer; pair again".to_string())?;
+    let target = peer.target();
+    let client = fleet::client_for(&target, credentials.fingerprint_sha256.as_deref())?;
+    let bundle = state.bundle(store, tags, tags)?;
+    let response = client
+        .post(target.url("/api/sync"))
+        .bearer_auth(credentials.token.trim())
+        .json(&bundle)
+        .send()
+        .map_err(|err| format!("Failed to reach {}: {err}", peer.host))?;
+    let status = response.status();
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(format!("{} refused the token; pair again", peer.host));
+    }
+    if !status.is_success() {
+        let body: serde_json::Value = response.json().unwrap_or_default();
+        return Err(body["error"].as_str().unwrap_or("Sync failed").to_string());
+    }
+    let reply: SyncBundle = response
+        .json()
+        .map_err(|err| format!("Unexpected answer from {}: {err}", peer.host))?;
+    state.merge(store, reply, tags, &AuditOrigin::Remote { address: peer.host.clone() })
+}
+
+/// Every `SYNC_INTERVAL`, or when `sync_soon` is called, syncs with every
+/// paired peer while sync is on. The frontend hears `sync-finished`.
+#[cfg(feature = "app")]
+pub fn spawn_syncer(state: SyncState, store: TimerStore) {
+    let (wake_tx, wake_rx) = mpsc::channel();
+    if let Ok(mut wake) = state.wake.lock() {
+        *wake = Some(wake_tx);
+    }
+    thread::spawn(move || loop {
+        let _ = wake_rx.recv_timeout(SYNC_INTERVAL);
+        let Ok(settings) = store.settings.snapshot() else {
+            continue;
+        };
+        if !settings.sync.enabled {
+            continue;
+        }
+        let settings = settings.sync;
+        for peer in state.peers().unwrap_or_default() {
+            let result = sync_with(&state, &store, &peer, &settings.tags);
+            if let Err(err) = &result {
+                eprintln!("Failed to sync with {}: {err}", peer.host);
+            }
+            state.record_result(&peer.id, &result);
+        }
+        store.emit("sync-finished", ());
+    });
+}
+
+#[cfg(all(test, feature = "app"))]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use serde_json::json;
+
+    use super::*;
+
+    struct Fixture {
+        dir: PathBuf,
+        store: TimerStore,
+        state: SyncState,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+            let dir = std::env::temp_dir().join(format!("lockpilot-sync-{}-{nanos}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            Self {
+                store: TimerStore::open(&dir),
+                state: SyncState::new(dir.join("sync.json")),
+                dir,
+            }
+        }
+
+        /// A popup an hour from now with `tags`.
+        fn create(&self, message: &str, tags: &[&str]) -> TimerInfo {
+            let request = serde_json::from_value(json!({
+                "action": "popup",
+                "targetTime": (Utc::now() + ChronoDuration::hours(1)).to_rfc3339(),
+                "message": message,
+                "tags": tags,
+            }))
+            .unwrap();
+            self.store.create(request, &AuditOrigin::Ui).unwrap()
+        }
+
+        fn merge(&self, timers: Vec<TimerInfo>, tombstones: Vec<Tombstone>) {
+            let bundle = SyncBundle {
+                tags: tags(),
+                timers,
+                tombstones,
+            };
+            self.state.merge(&self.store, bundle, &tags(), &origin()).unwrap();
+        }
+
+        fn message(&self, id: &str) -> Option<String> {
+            self.store.get(id).unwrap().and_then(|timer| timer.message)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn tags() -> Vec<String> {
+        vec!["home".to_string()]
+    }
+
+    fn origin() -> AuditOrigin {
+        AuditOrigin::Remote {
+            address: "peer.local".to_string(),
+        }
+    }
+
+    /// `timer` as a peer would send it back, with `message`, changed at `at`.
+    fn copy(timer: &TimerInfo, message: &str, at: DateTime<Utc>) -> TimerInfo {
+        let mut copy = timer.clone();
+        copy.message = Some(message.to_string());
+        copy.modified_at = Some(at);
+        copy
+    }
+
+    #[test]
+    fn newer_copy_replaces_and_older_copy_is_ignored() {
+        let fixture = Fixture::new();
+        let local = fixture.create("Local", &["home"]);
+
+        fixture.merge(vec![copy(&local, "Stale", local.created_at - ChronoDuration::minutes(5))], Vec::new());
+        assert_eq!(fixture.message(&local.id).as_deref(), Some("Local"));
+
+        fixture.merge(vec![copy(&local, "Peer", Utc::now() + ChronoDuration::seconds(1))], Vec::new());
+        assert_eq!(fixture.message(&local.id).as_deref(), Some("Peer"));
+    }
+
+    #[test]
+    fn tombstone_deletes_an_older_copy_but_not_a_newer_one() {
+        let fixture = Fixture::new();
+        let older = fixture.create("Older", &["home"]);
+        let newer = fixture.create("Newer", &["home"]);
+
+        fixture.merge(
+            Vec::new(),
+            vec![
+                Tombstone {
+                    id: older.id.clone(),
+                    deleted_at: Utc::now() + ChronoDuration::seconds(1),
+                },
+                Tombstone {
+                    id: newer.id.clone(),
+                    deleted_at: newer.created_at - ChronoDuration::minutes(5),
+                },
+            ],
+        );
+        assert!(fixture.store.get(&older.id).unwrap().is_none());
+        assert_eq!(fixture.message(&newer.id).as_deref(), Some("Newer"));
+    }
+
+    #[test]
+    fn copy_changed_after_a_tombstone_comes_back() {
+        let fixture = Fixture::new();
+        let timer = fixture.create("Deleted", &["home"]);
+        let deleted_at = Utc::now() + ChronoDuration::seconds(1);
+        fixture.merge(
+            Vec::new(),
+            vec![Tombstone {
+                id: timer.id.clone(),
+                deleted_at,
+            }],
+        );
+        assert!(fixture.store.get(&timer.id).unwrap().is_none());
+
+        fixture.merge(vec![copy(&timer, "Too old", deleted_at - ChronoDuration::seconds(1))], Vec::new());
+        assert!(fixture.store.get(&timer.id).unwrap().is_none());
+
+        fixture.merge(vec![copy(&timer, "Back", deleted_at + ChronoDuration::seconds(1))], Vec::new());
+        assert_eq!(fixture.message(&timer.id).as_deref(), Some("Back"));
+    }
+
+    #[test]
+    fn only_shared_tags_are_merged_and_sent() {
+        let fixture = Fixture::new();
+        let home = fixture.create("Home", &["home"]);
+        let work = fixture.create("Work", &["work"]);
+
+        let mut foreign = copy(&home, "Foreign", Utc::now());
+        foreign.id = Uuid::new_v4().to_string();
+        foreign.tags = vec!["work".to_string()];
+        fixture.merge(vec![foreign.clone()], Vec::new());
+        assert!(fixture.store.get(&foreign.id).unwrap().is_none());
+
+        let own = ["home".to_string(), "work".to_string()];
+        let sent = fixture.state.bundle(&fixture.store, &own, &tags()).unwrap();
+        let ids: Vec<_> = sent.timers.iter().map(|timer| timer.id.as_str()).collect();
+        assert_eq!(ids, [home.id.as_str()]);
+        assert!(!ids.contains(&work.id.as_str()));
+    }
+
+    #[test]
+    fn invalid_timers_are_dropped() {
+        let fixture = Fixture::new();
+        let timer = fixture.create("Template", &["home"]);
+        let mut looping = copy(&timer, "Every 0 minutes", Utc::now());
+        looping.id = Uuid::new_v4().to_string();
+        looping.target_time = Utc::now() - ChronoDuration::hours(1);
+        looping.recurrence = serde_json::from_value(json!({ "preset": "every_n_minutes", "intervalMinutes": 0 })).unwrap();
+
+        fixture.merge(vec![looping.clone()], Vec::new());
+        assert!(fixture.store.get(&looping.id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,1045 @@
+use std::{
+    process::{Child, Command},
+    time::Duration,
+};
+
+#[cfg(feature = "app")]
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::exec;
+
+/// AppleScript error for "Not authorized to send Apple events".
+const NOT_AUTHORIZED_ERROR: &str = "-1743";
+/// AppleScript error when System Events is refused assistive access.
+const ASSISTIVE_ACCESS_ERROR: &str = "-1719";
+#[cfg(target_os = "macos")]
+const SYSTEM_EVENTS_BUNDLE_ID: &str = "com.apple.systemevents";
+
+// Only the macOS probes can report a decided state.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    /// The user hasn't been asked yet.
+    #[serde(rename = "notDetermined")]
+    NotDetermined,
+    Unknown,
+}
+
+/// The TCC permissions LockPilot's built-in actions depend on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Sending Apple events to System Events (lock, shut down, restart).
+    Automation,
+    /// Synthesizing the lock keystroke.
+    Accessibility,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerStatus {
+    /// `None` on Macs without an internal battery.
+    pub battery_percent: Option<u8>,
+    /// As pmset names it: "AC Power", "Battery Power" or "UPS Power".
+    pub power_source: Option<String>,
+    pub on_ac_power: bool,
+    /// Also set once charging is complete.
+    pub charging: bool,
+    /// Time to empty while discharging, to full while charging; `None`
+    /// while macOS is still estimating.
+    pub minutes_remaining: Option<u32>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemInfo {
+    /// e.g. "14.5"; `None` if `sw_vers` couldn't be read.
+    pub macos_version: Option<String>,
+    pub macos_build: Option<String>,
+    pub booted_at: Option<DateTime<Utc>>,
+    /// Since `booted_at`, including time asleep.
+    pub uptime_seconds: Option<u64>,
+    pub scheduled_power_events: Vec<ScheduledPowerEvent>,
+}
+
+/// One line of `pmset -g sched`: a one-time event such as a wake registered
+/// for a must-run timer, or a repeating one set in Energy Saver.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledPowerEvent {
+    /// As pmset names it, e.g. "wake", "wakepoweron", "shutdown".
+    pub kind: String,
+    /// `None` for repeating events; `description` says when those run.
+    pub at: Option<DateTime<Utc>>,
+    /// The app or service that scheduled it, e.g. "pmset".
+    pub scheduled_by: Option<String>,
+    pub repeating: bool,
+    /// The line as pmset printed it.
+    pub description: String,
+}
+
+/// Seconds since the last keyboard/mouse input, from the HID system's
+/// `HIDIdleTime` counter (reported in nanoseconds). Read straight from the
+/// IOKit registry, falling back to `ioreg` if that fails.
+pub fn idle_seconds() -> Result<u64, String> {
+    #[cfg(target_os = "macos")]
+    if let Some(nanos) = mac::hid_idle_nanos() {
+        return Ok(nanos / 1_000_000_000);
+    }
+
+    let output = Command::new("/usr/sbin/ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .map_err(|err| format!("Failed to run ioreg: {err}"))?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    text.lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.rsplit('=').next())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|nanos| nanos / 1_000_000_000)
+        .ok_or_else(|| "HIDIdleTime not reported by ioreg".to_string())
+}
+
+/// Apps holding a `PreventUserIdleDisplaySleep` assertion, as Keynote and
+/// video calls do while presenting or sharing the screen. `caffeinate` is
+/// left out: it is what LockPilot itself and scripts use.
+#[cfg(feature = "app")]
+pub fn display_sleep_holders() -> Result<Vec<String>, String> {
+    let output = exec::output(Command::new("/usr/bin/pmset").args(["-g", "assertions"]))
+        .map_err(|err| format!("Failed to run pmset: {err}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    Ok(parse_display_sleep_holders(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Picks process names out of lines such as
+/// `   pid 412(Keynote): [0x0001a2b3] 00:12:04 PreventUserIdleDisplaySleep named: "Playing"`.
+#[cfg(feature = "app")]
+fn parse_display_sleep_holders(text: &str) -> Vec<String> {
+    let mut holders: Vec<String> = text
+        .lines()
+        .filter(|line| line.contains("PreventUserIdleDisplaySleep named"))
+        .filter_map(|line| line.trim_start().strip_prefix("pid "))
+        .filter_map(|rest| rest.split_once('(').and_then(|(_, rest)| rest.split_once("):")))
+        .map(|(name, _)| name.to_string())
+        .filter(|name| name != "caffeinate")
+        .collect();
+    holders.sort();
+    holders.dedup();
+    holders
+}
+
+/// How hard macOS is working to keep the Mac cool, as
+/// `NSProcessInfo.thermalState` reports it; from `Serious` on it throttles
+/// the CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThermalState {
+    Nominal,
+    Fair,
+    Serious,
+    Critical,
+}
+
+pub fn thermal_state() -> Result<ThermalState, String> {
+    #[cfg(target_os = "macos")]
+    {
+        match mac::thermal_state() {
+            Some(0) => Ok(ThermalState::Nominal),
+            Some(1) => Ok(ThermalState::Fair),
+            Some(2) => Ok(ThermalState::Serious),
+            Some(3) => Ok(ThermalState::Critical),
+            state => Err(format!("Unexpected thermal state: {state:?}")),
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err("The thermal state is only available on macOS".to_string())
+    }
+}
+
+/// The 1-minute load average as a percentage of the logical cores: 100
+/// means every core was kept busy. Above 100 when work was queued.
+pub fn cpu_load_percent() -> Result<u32, String> {
+    let text = command_stdout("/usr/sbin/sysctl", &["-n", "vm.loadavg", "hw.logicalcpu"])
+        .ok_or_else(|| "Failed to read the load average with sysctl".to_string())?;
+    parse_cpu_load(&text).ok_or_else(|| format!("Unexpected sysctl output: {}", text.trim()))
+}
+
+/// Parses `sysctl -n vm.loadavg hw.logicalcpu`, e.g. `{ 6.12 4.80 3.95 }`
+/// then `8` on the next line.
+fn parse_cpu_load(text: &str) -> Option<u32> {
+    let mut lines = text.lines();
+    let load = lines
+        .next()?
+        .trim_matches(|c: char| c == '{' || c == '}' || c.is_whitespace())
+        .split_whitespace()
+        .next()?
+        .parse::<f64>()
+        .ok()?;
+    let cores = lines.next()?.trim().parse::<u32>().ok().filter(|cores| *cores > 0)?;
+    Some((load / f64::from(cores) * 100.0).round() as u32)
+}
+
+pub fn power_status() -> Result<PowerStatus, String> {
+    let output = exec::output(Command::new("/usr/bin/pmset").args(["-g", "batt"]))
+        .map_err(|err| format!("Failed to run pmset: {err}"))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    Ok(parse_pmset_batt(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `pmset -g batt`, e.g.
+/// `Now drawing from 'Battery Power'` followed by
+/// ` -InternalBattery-0 (id=123) 85%; discharging; 3:12 remaining present: true`.
+fn parse_pmset_batt(text: &str) -> PowerStatus {
+    let on_ac_power = text.contains("'AC Power'");
+    let power_source = text
+        .split_once("drawing from '")
+        .and_then(|(_, rest)| rest.split_once('\''))
+        .map(|(source, _)| source.to_string());
+    let battery_line = text.lines().find(|line| line.contains("InternalBattery"));
+
+    let Some(line) = battery_line else {
+        return PowerStatus {
+            battery_percent: None,
+            power_source,
+            on_ac_power,
+            charging: false,
+            minutes_remaining: None,
+        };
+    };
+
+    let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+    let battery_percent = fields
+        .first()
+        .and_then(|field| field.split_whitespace().last())
+        .and_then(|value| value.trim_end_matches('%').parse::<u8>().ok());
+    let charging = fields
+        .get(1)
+        .map(|state| matches!(*state, "charging" | "charged" | "finishing charge"))
+        .unwrap_or(false);
+    let minutes_remaining = fields
+        .get(2)
+        .and_then(|field| field.split_whitespace().next())
+        .and_then(|clock| clock.split_once(':'))
+        .and_then(|(hours, minutes)| Some(hours.parse::<u32>().ok()? * 60 + minutes.parse::<u32>().ok()?));
+
+    PowerStatus {
+        battery_percent,
+        power_source,
+        on_ac_power,
+        charging,
+        minutes_remaining,
+    }
+}
+
+/// macOS version, boot time and scheduled power events. Each part is
+/// optional so one failing tool doesn't hide the rest.
+#[cfg(feature = "app")]
+pub fn system_info() -> SystemInfo {
+    let booted_at = command_stdout("/usr/sbin/sysctl", &["-n", "kern.boottime"])
+        .and_then(|text| parse_boot_time(&text));
+    SystemInfo {
+        macos_version: command_stdout("/usr/bin/sw_vers", &["-productVersion"]).map(|text| text.trim().to_string()),
+        macos_build: command_stdout("/usr/bin/sw_vers", &["-buildVersion"]).map(|text| text.trim().to_string()),
+        booted_at,
+        uptime_seconds: booted_at.and_then(|booted_at| (Utc::now() - booted_at).to_std().ok()).map(|up| up.as_secs()),
+        scheduled_power_events: command_stdout("/usr/bin/pmset", &["-g", "sched"])
+            .map(|text| parse_pmset_sched(&text))
+            .unwrap_or_default(),
+    }
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = exec::output(Command::new(program).args(args)).ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parses `sysctl -n kern.boottime`, e.g.
+/// `{ sec = 1718000000, usec = 120000 } Mon Jun 10 08:13:20 2024`.
+#[cfg(feature = "app")]
+fn parse_boot_time(text: &str) -> Option<DateTime<Utc>> {
+    let (_, rest) = text.split_once("sec = ")?;
+    let seconds = rest.split(|c: char| !c.is_ascii_digit()).next()?.parse::<i64>().ok()?;
+    DateTime::from_timestamp(seconds, 0)
+}
+
+/// Parses `pmset -g sched`, e.g.
+/// ```text
+/// Repeating power events:
+///   wakepoweron at 7:00AM weekdays only
+/// Scheduled power events:
+///  [0]  wake at 06/11/2024 06:30:00 by 'pmset'
+/// ```
+#[cfg(feature = "app")]
+fn parse_pmset_sched(text: &str) -> Vec<ScheduledPowerEvent> {
+    let mut repeating = false;
+    let mut events = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with("Repeating power events") {
+            repeating = true;
+            continue;
+        }
+        if line.starts_with("Scheduled power events") {
+            repeating = false;
+            continue;
+        }
+        // One-time events are numbered, e.g. `[0]  wake at ...`.
+        let description = line.split_once(']').map_or(line, |(_, rest)| rest.trim());
+        let Some((kind, rest)) = description.split_once(" at ") else {
+            continue;
+        };
+        let (when, scheduled_by) = match rest.split_once(" by '") {
+            Some((when, owner)) => (when, owner.split_once('\'').map(|(owner, _)| owner.to_string())),
+            None => (rest, None),
+        };
+        let at = if repeating {
+            None
+        } else {
+            ["%m/%d/%Y %H:%M:%S", "%m/%d/%y %H:%M:%S"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(when.trim(), format).ok())
+                .and_then(|at| Local.from_local_datetime(&at).earliest())
+                .map(|at| at.with_timezone(&Utc))
+        };
+        events.push(ScheduledPowerEvent {
+            kind: kind.trim().to_string(),
+            at,
+            scheduled_by,
+            repeating,
+            description: description.to_string(),
+        });
+    }
+    events
+}
+
+/// Whether a process with exactly this name is running. Uses `pgrep`, so it
+/// needs no Automation permission.
+pub fn is_process_running(name: &str) -> bool {
+    Command::new("/usr/bin/pgrep")
+        .args(["-x", name])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Whether LockPilot may send Apple events to System Events, which the
+/// lock, shutdown and restart actions need. With `ask_user` the macOS
+/// consent prompt is shown if the user hasn't decided yet; this blocks
+/// until they answer.
+#[cfg(feature = "app")]
+pub fn automation_permission(ask_user: bool) -> PermissionState {
+    #[cfg(target_os = "macos")]
+    {
+        let mut status = mac::automation_status(SYSTEM_EVENTS_BUNDLE_ID, ask_user);
+        if status == mac::PROC_NOT_FOUND {
+            // The check needs the target running; System Events quits when idle.
+            let _ = exec::status(Command::new("/usr/bin/open").args(["-g", "-b", SYSTEM_EVENTS_BUNDLE_ID]));
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            status = mac::automation_status(SYSTEM_EVENTS_BUNDLE_ID, ask_user);
+        }
+        mac::permission_state(status)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = ask_user;
+        PermissionState::Unknown
+    }
+}
+
+/// Whether LockPilot is trusted for Accessibility, which the lock keystroke
+/// needs. Never prompts.
+#[cfg(feature = "app")]
+pub fn accessibility_permission() -> PermissionState {
+    #[cfg(target_os = "macos")]
+    {
+        if mac::accessibility_trusted() {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        PermissionState::Unknown
+    }
+}
+
+/// Opens the Privacy & Security pane where the user grants `permission`.
+#[cfg(feature = "app")]
+pub fn open_privacy_settings(permission: Permission) -> Result<(), String> {
+    let pane = match permission {
+        Permission::Automation => "Privacy_Automation",
+        Permission::Accessibility => "Privacy_Accessibility",
+    };
+    exec::spawn(
+        Command::new("/usr/bin/open").arg(format!("x-apple.systempreferences:com.apple.preference.security?{pane}")),
+    )
+    .map_err(|err| format!("Failed to open System Settings: {err}"))
+}
+
+/// Which permission an osascript error says was refused, if the failure
+/// was macOS blocking the Apple event or keystroke rather than the script.
+pub fn denied_permission(error: &str) -> Option<Permission> {
+    if error.contains(ASSISTIVE_ACCESS_ERROR)
+        || error.contains("not allowed to send keystrokes")
+        || error.contains("not allowed assistive access")
+    {
+        Some(Permission::Accessibility)
+    } else if error.contains(NOT_AUTHORIZED_ERROR) {
+        Some(Permission::Automation)
+    } else {
+        None
+    }
+}
+
+/// Keeps the Mac from idle and system sleep until dropped, through a
+/// `caffeinate` child. `limit` bounds the assertion in case LockPilot dies
+/// without dropping it.
+pub struct StayAwake(Child);
+
+impl StayAwake {
+    pub fn start(limit: Duration) -> Result<Self, String> {
+        Command::new("/usr/bin/caffeinate")
+            .args(["-i", "-s", "-t", &limit.as_secs().max(1).to_string()])
+            .spawn()
+            .map(Self)
+            .map_err(|err| format!("Failed to run caffeinate: {err}"))
+    }
+
+    /// Also turns the display on, as user activity would, and keeps it on.
+    pub fn with_display_on(limit: Duration) -> Result<Self, String> {
+        Command::new("/usr/bin/caffeinate")
+            .args(["-d", "-i", "-u", "-t", &limit.as_secs().max(1).to_string()])
+            .spawn()
+            .map(Self)
+            .map_err(|err| format!("Failed to run caffeinate: {err}"))
+    }
+}
+
+impl Drop for StayAwake {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Keeps macOS from App Napping LockPilot while its window is hidden, which
+/// otherwise coalesces the scheduler's timers and lets runs fire minutes
+/// late. Holds for the life of the process.
+#[cfg(feature = "app")]
+pub fn prevent_app_nap() -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        if mac::begin_latency_critical_activity(c"LockPilot timers must fire on time") {
+            Ok(())
+        } else {
+            Err("NSProcessInfo did not start the activity".to_string())
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        Ok(())
+    }
+}
+
+/// Turns Night Shift on or off, setting its strength (0.0 to 1.0) first
+/// when given. Goes through CoreBrightness' private `CBBlueLightClient`,
+/// which may be missing on some releases or Macs.
+pub fn set_night_shift(enabled: bool, strength: Option<f32>) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        mac::set_night_shift(enabled, strength)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (enabled, strength);
+        Err("Night Shift is only available on macOS".to_string())
+    }
+}
+
+/// Sends Pause to the system now-playing session through MediaRemote, the
+/// private framework behind the media keys. `false` if it could not be
+/// sent.
+pub fn pause_now_playing() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        mac::send_media_command(mac::K_MR_PAUSE)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        false
+    }
+}
+
+/// Whether this Mac supports Night Shift, without changing it.
+pub fn night_shift_available() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        mac::blue_light_client_class().is_ok()
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        false
+    }
+}
+
+/// Turns the displays off immediately without sleeping the Mac.
+pub fn display_sleep() -> Result<(), String> {
+    let status = exec::status(Command::new("/usr/bin/pmset").arg("displaysleepnow"))
+        .map_err(|err| format!("Failed to run pmset: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("pmset exited with {status}"))
+    }
+}
+
+/// Checks that `script` compiles without running it.
+#[cfg(feature = "app")]
+pub fn compile_applescript(script: &str) -> Result<(), String> {
+    compile_script(script, "AppleScript")
+}
+
+/// Checks that `script`, in an OSA `language` such as `JavaScript`,
+/// compiles without running it.
+pub fn compile_script(script: &str, language: &str) -> Result<(), String> {
+    let output_path = std::env::temp_dir().join(format!("lockpilot-self-test-{}.scpt", std::process::id()));
+    let output = Command::new("/usr/bin/osacompile")
+        .args(["-l", language])
+        .arg("-e")
+        .arg(script)
+        .arg("-o")
+        .arg(&output_path)
+        .output()
+        .map_err(|err| format!("Failed to run osacompile: {err}"))?;
+    let _ = std::fs::remove_file(&output_path);
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Sets loginwindow's `TALLogoutSavesState`, the "Reopen windows when
+/// logging back in" checkbox, for the next logout, shutdown or restart.
+pub fn set_reopen_windows(reopen: bool) -> Result<(), String> {
+    let status = Command::new("/usr/bin/defaults")
+        .args(["write", "com.apple.loginwindow", "TALLogoutSavesState", "-bool"])
+        .arg(if reopen { "true" } else { "false" })
+        .status()
+        .map_err(|err| format!("Failed to run defaults: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("defaults exited with {status}"))
+    }
+}
+
+/// errSecItemNotFound.
+#[cfg(target_os = "macos")]
+const KEYCHAIN_ITEM_NOT_FOUND: i32 = -25300;
+
+/// Reads a generic password from the login keychain; `None` if there is no
+/// such item.
+pub fn keychain_password(service: &str, account: &str) -> Result<Option<String>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        match security_framework::passwords::get_generic_password(service, account) {
+            Ok(password) => String::from_utf8(password)
+                .map(Some)
+                .map_err(|_| "The keychain item is not valid UTF-8".to_string()),
+            Err(err) if err.code() == KEYCHAIN_ITEM_NOT_FOUND => Ok(None),
+            Err(err) => Err(format!("Failed to read keychain item: {err}")),
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (service, account);
+        Err("The keychain is only available on macOS".to_string())
+    }
+}
+
+/// Adds or replaces a generic password in the login keychain. Goes through
+/// Security.framework rather than `security -w`, which would put the secret
+/// on a command line any process can read.
+#[cfg(feature = "app")]
+pub fn set_keychain_password(service: &str, account: &str, password: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        security_framework::passwords::set_generic_password(service, account, password.as_bytes())
+            .map_err(|err| format!("Failed to save keychain item: {err}"))
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (service, account, password);
+        Err("The keychain is only available on macOS".to_string())
+    }
+}
+
+/// Removes a generic password; a missing item is not an error.
+#[cfg(feature = "app")]
+pub fn delete_keychain_password(service: &str, account: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        match security_framework::passwords::delete_generic_password(service, account) {
+            Ok(()) => Ok(()),
+            Err(err) if err.code() == KEYCHAIN_ITEM_NOT_FOUND => Ok(()),
+            Err(err) => Err(format!("Failed to delete keychain item: {err}")),
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (service, account);
+        Err("The keychain is only available on macOS".to_string())
+    }
+}
+
+/// Apple Events loginwindow handles on behalf of the whole session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    ShutDown,
+    Restart,
+    LogOut,
+    Sleep,
+    /// The confirmation dialogs the Apple menu shows, with their countdown.
+    ConfirmShutDown,
+    ConfirmRestart,
+    ConfirmLogOut,
+}
+
+/// Why loginwindow didn't accept a power event.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PowerEventError {
+    /// The user or an MDM profile blocked Apple events to loginwindow.
+    NotPermitted,
+    TimedOut,
+    /// Any other OSStatus from building or sending the event.
+    Os(i32),
+    #[cfg(not(target_os = "macos"))]
+    Unsupported,
+}
+
+impl std::fmt::Display for PowerEventError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PowerEventError::NotPermitted => {
+                write!(f, "Not authorized to send Apple events to loginwindow ({NOT_AUTHORIZED_ERROR})")
+            }
+            PowerEventError::TimedOut => write!(f, "loginwindow did not respond"),
+            PowerEventError::Os(status) => write!(f, "Apple event failed with status {status}"),
+            #[cfg(not(target_os = "macos"))]
+            PowerEventError::Unsupported => write!(f, "Power events are only available on macOS"),
+        }
+    }
+}
+
+/// Sends a power event straight to loginwindow, the same way the Apple menu
+/// does, instead of scripting System Events through osascript.
+pub fn send_power_event(event: PowerEvent) -> Result<(), PowerEventError> {
+    #[cfg(target_os = "macos")]
+    {
+        let event_id = match event {
+            PowerEvent::ShutDown => mac::K_AE_SHUT_DOWN,
+            PowerEvent::Restart => mac::K_AE_RESTART,
+            PowerEvent::LogOut => mac::K_AE_REALLY_LOG_OUT,
+            PowerEvent::Sleep => mac::K_AE_SLEEP,
+            PowerEvent::ConfirmShutDown => mac::K_AE_SHOW_SHUTDOWN_DIALOG,
+            PowerEvent::ConfirmRestart => mac::K_AE_SHOW_RESTART_DIALOG,
+            PowerEvent::ConfirmLogOut => mac::K_AE_LOG_OUT,
+        };
+        mac::power_event_result(mac::send_to_login_window(event_id))
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = event;
+        Err(PowerEventError::Unsupported)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod mac {
+    use std::ffi::{c_char, c_int, c_void, CStr};
+
+    use super::{PermissionState, PowerEventError};
+    use crate::macos_ffi::{cf_string, class, msg_send, sel, CFRelease, Id, Sel, SendId};
+
+    const TYPE_APPLICATION_BUNDLE_ID: u32 = u32::from_be_bytes(*b"bund");
+    const TYPE_PROCESS_SERIAL_NUMBER: u32 = u32::from_be_bytes(*b"psn ");
+    const K_CORE_EVENT_CLASS: u32 = u32::from_be_bytes(*b"aevt");
+    pub const K_AE_SHUT_DOWN: u32 = u32::from_be_bytes(*b"shut");
+    pub const K_AE_RESTART: u32 = u32::from_be_bytes(*b"rest");
+    pub const K_AE_SLEEP: u32 = u32::from_be_bytes(*b"slep");
+    pub const K_AE_REALLY_LOG_OUT: u32 = u32::from_be_bytes(*b"rlgo");
+    pub const K_AE_LOG_OUT: u32 = u32::from_be_bytes(*b"logo");
+    pub const K_AE_SHOW_SHUTDOWN_DIALOG: u32 = u32::from_be_bytes(*b"rsdn");
+    pub const K_AE_SHOW_RESTART_DIALOG: u32 = u32::from_be_bytes(*b"rrst");
+    /// loginwindow's well-known process serial number.
+    const K_SYSTEM_PROCESS: u32 = 1;
+    const K_AUTO_GENERATE_RETURN_ID: i16 = -1;
+    const K_ANY_TRANSACTION_ID: i32 = 0;
+    const K_AE_NO_REPLY: i32 = 0x1;
+    const K_AE_DEFAULT_TIMEOUT: isize = -1;
+    const ERR_AE_TIMEOUT: i32 = -1712;
+    const TYPE_WILD_CARD: u32 = u32::from_be_bytes(*b"****");
+    const NO_ERR: i32 = 0;
+    const ERR_AE_EVENT_NOT_PERMITTED: i32 = -1743;
+    const ERR_AE_EVENT_WOULD_REQUIRE_USER_CONSENT: i32 = -1744;
+    pub const PROC_NOT_FOUND: i32 = -600;
+
+    #[repr(C)]
+    struct AEDesc {
+        descriptor_type: u32,
+        data_handle: *mut c_void,
+    }
+
+    impl AEDesc {
+        fn null() -> Self {
+            Self {
+                descriptor_type: 0,
+                data_handle: std::ptr::null_mut(),
+            }
+        }
+    }
+
+    #[repr(C)]
+    struct ProcessSerialNumber {
+        high_long_of_psn: u32,
+        low_long_of_psn: u32,
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn AECreateDesc(type_code: u32, data_ptr: *const c_void, data_size: isize, result: *mut AEDesc) -> i16;
+        fn AEDisposeDesc(desc: *mut AEDesc) -> i16;
+        fn AECreateAppleEvent(
+            event_class: u32,
+            event_id: u32,
+            target: *const AEDesc,
+            return_id: i16,
+            transaction_id: i32,
+            result: *mut AEDesc,
+        ) -> i16;
+        fn AESendMessage(event: *const AEDesc, reply: *mut AEDesc, send_mode: i32, timeout_in_ticks: isize) -> i32;
+        fn AEDeterminePermissionToAutomateTarget(
+            target: *const AEDesc,
+            event_class: u32,
+            event_id: u32,
+            ask_user_if_needed: u8,
+        ) -> i32;
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> u8;
+    }
+
+    pub fn automation_status(bundle_id: &str, ask_user: bool) -> i32 {
+        let mut desc = AEDesc::null();
+        // SAFETY: `desc` is a valid out-pointer, the bundle id buffer outlives
+        // the call (AECreateDesc copies it), and the descriptor is disposed
+        // exactly once after use.
+        unsafe {
+            let created = AECreateDesc(
+                TYPE_APPLICATION_BUNDLE_ID,
+                bundle_id.as_ptr().cast(),
+                bundle_id.len() as isize,
+                &mut desc,
+            );
+            if created != 0 {
+                return created as i32;
+            }
+            let status = AEDeterminePermissionToAutomateTarget(&desc, TYPE_WILD_CARD, TYPE_WILD_CARD, ask_user as u8);
+            AEDisposeDesc(&mut desc);
+            status
+        }
+    }
+
+    pub fn send_to_login_window(event_id: u32) -> i32 {
+        let psn = ProcessSerialNumber {
+            high_long_of_psn: 0,
+            low_long_of_psn: K_SYSTEM_PROCESS,
+        };
+        let mut target = AEDesc::null();
+        let mut event = AEDesc::null();
+        let mut reply = AEDesc::null();
+        // SAFETY: every descriptor is a valid out-pointer, `psn` outlives
+        // AECreateDesc (which copies it), and each created descriptor is
+        // disposed exactly once.
+        unsafe {
+            let created = AECreateDesc(
+                TYPE_PROCESS_SERIAL_NUMBER,
+                (&psn as *const ProcessSerialNumber).cast(),
+                std::mem::size_of::<ProcessSerialNumber>() as isize,
+                &mut target,
+            );
+            if created != 0 {
+                return created as i32;
+            }
+            let created = AECreateAppleEvent(
+                K_CORE_EVENT_CLASS,
+                event_id,
+                &target,
+                K_AUTO_GENERATE_RETURN_ID,
+                K_ANY_TRANSACTION_ID,
+                &mut event,
+            );
+            AEDisposeDesc(&mut target);
+            if created != 0 {
+                return created as i32;
+            }
+            let status = AESendMessage(&event, &mut reply, K_AE_NO_REPLY, K_AE_DEFAULT_TIMEOUT);
+            AEDisposeDesc(&mut event);
+            AEDisposeDesc(&mut reply);
+            status
+        }
+    }
+
+    pub fn power_event_result(status: i32) -> Result<(), PowerEventError> {
+        match status {
+            NO_ERR => Ok(()),
+            ERR_AE_EVENT_NOT_PERMITTED => Err(PowerEventError::NotPermitted),
+            ERR_AE_TIMEOUT => Err(PowerEventError::TimedOut),
+            status => Err(PowerEventError::Os(status)),
+        }
+    }
+
+    pub fn permission_state(status: i32) -> PermissionState {
+        match status {
+            NO_ERR => PermissionState::Granted,
+            ERR_AE_EVENT_NOT_PERMITTED => PermissionState::Denied,
+            ERR_AE_EVENT_WOULD_REQUIRE_USER_CONSENT => PermissionState::NotDetermined,
+            _ => PermissionState::Unknown,
+        }
+    }
+
+    pub fn accessibility_trusted() -> bool {
+        // SAFETY: no arguments; reads the calling process's TCC state.
+        unsafe { AXIsProcessTrusted() != 0 }
+    }
+
+    /// `NSActivityUserInitiatedAllowingIdleSystemSleep`: a plain background
+    /// activity may still be napped.
+    const NS_ACTIVITY_USER_INITIATED_ALLOWING_IDLE_SYSTEM_SLEEP: u64 = 0x00FF_FFFF;
+    /// `NSActivityLatencyCritical`: no timer coalescing.
+    const NS_ACTIVITY_LATENCY_CRITICAL: u64 = 0xFF_0000_0000;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFGetTypeID(cf: *const c_void) -> usize;
+        fn CFNumberGetTypeID() -> usize;
+        fn CFNumberGetValue(number: *const c_void, number_type: isize, value: *mut c_void) -> u8;
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+        fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+        fn IORegistryEntryCreateCFProperty(
+            entry: u32,
+            key: *const c_void,
+            allocator: *const c_void,
+            options: u32,
+        ) -> *const c_void;
+        fn IOObjectRelease(object: u32) -> c_int;
+    }
+
+    extern "C" {
+        fn dlopen(path: *const c_char, mode: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    const CORE_BRIGHTNESS: &CStr = c"/System/Library/PrivateFrameworks/CoreBrightness.framework/CoreBrightness";
+    const MEDIA_REMOTE: &CStr = c"/System/Library/PrivateFrameworks/MediaRemote.framework/MediaRemote";
+    /// `kMRPause` in MediaRemote's `MRMediaRemoteCommand`.
+    pub const K_MR_PAUSE: u32 = 1;
+    const RTLD_LAZY: c_int = 0x1;
+    /// `kIOMainPortDefault`.
+    const K_IO_MAIN_PORT_DEFAULT: u32 = 0;
+    /// `kCFNumberSInt64Type`.
+    const K_CF_NUMBER_SINT64_TYPE: isize = 4;
+
+    type SendBool = unsafe extern "C" fn(Id, Sel) -> i8;
+
+    /// `[[NSProcessInfo processInfo] beginActivityWithOptions:reason:]`,
+    /// with the returned token retained and never ended.
+    pub fn begin_latency_critical_activity(reason: &CStr) -> bool {
+        type SendBeginActivity = unsafe extern "C" fn(Id, Sel, u64, Id) -> Id;
+
+        // SAFETY: objc_msgSend is called through casts matching each
+        // selector's signature; CFString is toll-free bridged to NSString,
+        // and every object is checked for nil before use.
+        unsafe {
+            let send_id: SendId = msg_send();
+            let send_begin: SendBeginActivity = msg_send();
+
+            let class = class(c"NSProcessInfo");
+            if class.is_null() {
+                return false;
+            }
+            let process_info = send_id(class, sel(c"processInfo"));
+            let reason = cf_string(reason);
+            if process_info.is_null() || reason.is_null() {
+                return false;
+            }
+            let token = send_begin(
+                process_info,
+                sel(c"beginActivityWithOptions:reason:"),
+                NS_ACTIVITY_USER_INITIATED_ALLOWING_IDLE_SYSTEM_SLEEP | NS_ACTIVITY_LATENCY_CRITICAL,
+                reason,
+            );
+            if token.is_null() {
+                return false;
+            }
+            send_id(token, sel(c"retain"));
+            true
+        }
+    }
+
+    /// `[[NSProcessInfo processInfo] thermalState]`: 0 (nominal) to 3
+    /// (critical).
+    pub fn thermal_state() -> Option<isize> {
+        type SendInteger = unsafe extern "C" fn(Id, Sel) -> isize;
+
+        // SAFETY: as above; `thermalState` takes no arguments and returns
+        // an NSInteger.
+        unsafe {
+            let send_id: SendId = msg_send();
+            let send_integer: SendInteger = msg_send();
+
+            let class = class(c"NSProcessInfo");
+            if class.is_null() {
+                return None;
+            }
+            let process_info = send_id(class, sel(c"processInfo"));
+            if process_info.is_null() {
+                return None;
+            }
+            Some(send_integer(process_info, sel(c"thermalState")))
+        }
+    }
+
+    /// Loads CoreBrightness and finds `CBBlueLightClient`, provided the
+    /// hardware supports Night Shift.
+    pub fn blue_light_client_class() -> Result<Id, String> {
+        // SAFETY: the path and class name are NUL-terminated literals; the
+        // framework stays loaded; `supportsBlueLightReduction` is a class
+        // method taking no arguments and returning BOOL.
+        unsafe {
+            if dlopen(CORE_BRIGHTNESS.as_ptr(), RTLD_LAZY).is_null() {
+                return Err("Night Shift is not available on this Mac: CoreBrightness could not be loaded".to_string());
+            }
+            let class = class(c"CBBlueLightClient");
+            if class.is_null() {
+                return Err("Night Shift is not available on this version of macOS".to_string());
+            }
+            let send_bool: SendBool = msg_send();
+            if send_bool(class, sel(c"supportsBlueLightReduction")) == 0 {
+                return Err("This Mac's displays don't support Night Shift".to_string());
+            }
+            Ok(class)
+        }
+    }
+
+    /// `[client setStrength:commit:]` then `[client setEnabled:]` on a new
+    /// `CBBlueLightClient`.
+    pub fn set_night_shift(enabled: bool, strength: Option<f32>) -> Result<(), String> {
+        type SendSetEnabled = unsafe extern "C" fn(Id, Sel, i8) -> i8;
+        type SendSetStrength = unsafe extern "C" fn(Id, Sel, f32, i8) -> i8;
+
+        let class = blue_light_client_class()?;
+        // SAFETY: as above, objc_msgSend is called through casts matching
+        // each selector's signature, and the client is released once done.
+        unsafe {
+            let send_id: SendId = msg_send();
+            let send_enabled: SendSetEnabled = msg_send();
+            let send_strength: SendSetStrength = msg_send();
+
+            let allocated = send_id(class, sel(c"alloc"));
+            let client = send_id(allocated, sel(c"init"));
+            if client.is_null() {
+                return Err("Night Shift could not be reached".to_string());
+            }
+            let set_strength = sel(c"setStrength:commit:");
+            let set_enabled = sel(c"setEnabled:");
+            let result = if strength.is_some_and(|strength| send_strength(client, set_strength, strength, 1) == 0) {
+                Err("Night Shift refused the strength".to_string())
+            } else if send_enabled(client, set_enabled, i8::from(enabled)) == 0 {
+                Err(format!("Night Shift could not be turned {}", if enabled { "on" } else { "off" }))
+            } else {
+                Ok(())
+            };
+            send_id(client, sel(c"release"));
+            result
+        }
+    }
+    /// The `HIDIdleTime` property of the `IOHIDSystem` service.
+    pub fn hid_idle_nanos() -> Option<u64> {
+        // SAFETY: IOServiceMatching's dictionary is consumed by
+        // IOServiceGetMatchingService; the service and the CF objects
+        // created here are released exactly once; the property is only read
+        // as a number after checking its type.
+        unsafe {
+            let matching = IOServiceMatching(c"IOHIDSystem".as_ptr());
+            let service = IOServiceGetMatchingService(K_IO_MAIN_PORT_DEFAULT, matching);
+            if service == 0 {
+                return None;
+            }
+            let key = cf_string(c"HIDIdleTime");
+            let property = if key.is_null() {
+                std::ptr::null()
+            } else {
+                let property = IORegistryEntryCreateCFProperty(service, key, std::ptr::null(), 0);
+                CFRelease(key);
+                property
+            };
+            IOObjectRelease(service);
+            if property.is_null() {
+                return None;
+            }
+            let mut nanos: i64 = 0;
+            let read = CFGetTypeID(property) == CFNumberGetTypeID()
+                && CFNumberGetValue(property, K_CF_NUMBER_SINT64_TYPE, (&mut nanos as *mut i64).cast()) != 0;
+            CFRelease(property);
+            read.then_some(nanos).and_then(|nanos| u64::try_from(nanos).ok())
+        }
+    }
+
+    /// `MRMediaRemoteSendCommand(command, nil)`.
+    pub fn send_media_command(command: u32) -> bool {
+        type SendCommand = unsafe extern "C" fn(u32, *const c_void) -> u8;
+
+        // SAFETY: the path and symbol are NUL-terminated literals; the
+        // framework stays loaded; MRMediaRemoteSendCommand takes the command
+        // and an optional options dictionary and returns a Boolean.
+        unsafe {
+            let handle = dlopen(MEDIA_REMOTE.as_ptr(), RTLD_LAZY);
+            if handle.is_null() {
+                return false;
+            }
+            let symbol = dlsym(handle, c"MRMediaRemoteSendCommand".as_ptr());
+            if symbol.is_null() {
+                return false;
+            }
+            let send = std::mem::transmute::<*mut c_void, SendCommand>(symbol);
+            send(command, std::ptr::null()) != 0
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem},
+    tray::TrayIconBuilder,
+    AppHandle, Manager,
+};
+
+use crate::{kiosk, AuditOrigin, TimerStore};
+
+const SHOW: &str = "show";
+const RESUME: &str = "resume";
+/// Menu id, title and length in minutes of each pause offered.
+const PAUSES: &[(&str, &str, i64)] = &[
+    ("pause-1h", "Pause All for 1 Hour", 60),
+    ("pause-1d", "Pause All for 1 Day", 24 * 60),
+    ("pause-1w", "Pause All for 1 Week", 7 * 24 * 60),
+];
+
+/// Adds the menu bar icon, whose menu pauses and resumes every timer
+/// like `set_global_pause`. Needs `TimerStore` in app state.
+pub fn install(app: &AppHandle) -> tauri::Result<()> {
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(app, SHOW, "Show LockPilot", true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    for (id, title, _) in PAUSES {
+        menu.append(&MenuItem::with_id(app, *id, *title, true, None::<&str>)?)?;
+    }
+    menu.append(&MenuItem::with_id(app, RESUME, "Resume All", true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&PredefinedMenuItem::quit(app, Some("Quit LockPilot"))?)?;
+
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip("LockPilot")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(|app, event| handle(app, event.id.as_ref()));
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
+}
+
+fn handle(app: &AppHandle, id: &str) {
+    if id == SHOW {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        return;
+    }
+    if kiosk::is_read_only(app) {
+        eprintln!("Ignored tray {id}: LockPilot is read-only on this Mac");
+        return;
+    }
+    let until = match PAUSES.iter().find(|(pause, _, _)| *pause == id) {
+        Some((_, _, minutes)) => Some(Utc::now() + ChronoDuration::minutes(*minutes)),
+        None if id == RESUME => None,
+        None => return,
+    };
+    let store = app.state::<TimerStore>();
+    if let Err(err) = store.set_pause(until, &AuditOrigin::Ui) {
+        eprintln!("Failed to change the pause from the tray: {err}");
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    fs, mem,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use notify::{
+    event::{EventKind, ModifyKind},
+    Event, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    disks, fire_triggered,
+    system::{self, ThermalState},
+    FiringGuard, ScheduledRun, TimerStore,
+};
+
+/// How often watched conditions are sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+/// A longer gap between samples means the Mac slept, which breaks any
+/// stretch a condition was holding for.
+const MAX_SAMPLE_GAP_SECONDS: i64 = 60;
+pub const MAX_SUSTAINED_MINUTES: u32 = 24 * 60;
+/// Decimal, as Finder counts.
+const BYTES_PER_GB: f64 = 1_000_000_000.0;
+/// Rewritten by Finder whenever a folder is opened or rearranged.
+const FINDER_METADATA: &str = ".DS_Store";
+
+/// What a triggered timer waits for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TriggerCondition {
+    /// The load average reaches `percent` of the logical cores; see
+    /// `system::cpu_load_percent`.
+    CpuLoad { percent: u32 },
+    /// macOS reports `level` or a hotter thermal state.
+    ThermalPressure { level: ThermalState },
+    /// Free space on the volume holding `volume`, e.g. `/` or
+    /// `/Volumes/Backup`, drops below `below_gb`. Not met while the volume
+    /// isn't mounted.
+    #[serde(rename_all = "camelCase")]
+    LowDiskSpace { volume: String, below_gb: f64 },
+    /// No process called `name` is running any more, matched exactly as
+    /// `pgrep -x` does, e.g. `ffmpeg`.
+    ProcessExit { name: String },
+    /// A file or folder exists at the absolute `path`, such as a flag file
+    /// a backup script leaves behind. One created and removed between two
+    /// samples still counts, once.
+    PathExists { path: String },
+    /// The file at `path` was modified, or a file in the folder at `path`
+    /// was added, removed or modified, since the trigger was armed, even
+    /// while LockPilot wasn't running. Subfolders' contents aren't watched.
+    PathChanged { path: String },
+}
+
+impl TriggerCondition {
+    /// Samples the system now. A reading that fails counts as not met.
+    /// `baseline` is what `PathChanged` compares against; `touched` is
+    /// whether a path trigger's path saw an event since the last sample.
+    fn is_met(&self, baseline: Option<&PathSnapshot>, touched: bool) -> bool {
+        match self {
+            TriggerCondition::CpuLoad { percent } => system::cpu_load_percent().is_ok_and(|load| load >= *percent),
+            TriggerCondition::ThermalPressure { level } => system::thermal_state().is_ok_and(|state| state >= *level),
+            TriggerCondition::LowDiskSpace { volume, below_gb } => {
+                Path::new(volume).exists()
+                    && disks::free_bytes(volume).is_ok_and(|free| (free as f64) < below_gb * BYTES_PER_GB)
+            }
+            TriggerCondition::ProcessExit { name } => !system::is_process_running(name),
+            TriggerCondition::PathExists { path } => touched || Path::new(path).exists(),
+            TriggerCondition::PathChanged { path } => {
+                touched || baseline.is_some_and(|baseline| *baseline != snapshot(path))
+            }
+        }
+    }
+
+    /// Conditions that only count once they were seen unmet: a process
+    /// that isn't running when the trigger is armed hasn't exited yet.
+    fn needs_change(&self) -> bool {
+        matches!(self, TriggerCondition::ProcessExit { .. })
+    }
+}
+
+/// Fires a timer's action once `condition` has held for `minutes` instead
+/// of at its target time, which only arms the trigger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerTrigger {
+    #[serde(flatten)]
+    pub condition: TriggerCondition,
+    /// Without a break; 0 fires on the first sample that meets it.
+    #[serde(default)]
+    pub minutes: u32,
+    /// Keep the timer and fire again each time the condition clears and
+    /// comes back, rather than removing it after the first run.
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+impl TimerTrigger {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.minutes > MAX_SUSTAINED_MINUTES {
+            return Err(format!(
+                "Triggers can wait at most {} hours for a condition",
+                MAX_SUSTAINED_MINUTES / 60
+            ));
+        }
+        match &self.condition {
+            TriggerCondition::CpuLoad { percent } if !(1..=100).contains(percent) => {
+                Err("CPU load must be between 1 and 100 percent".to_string())
+            }
+            TriggerCondition::ThermalPressure {
+                level: ThermalState::Nominal,
+            } => Err("Thermal triggers need a level above nominal".to_string()),
+            TriggerCondition::LowDiskSpace { volume, .. } if !volume.starts_with('/') => {
+                Err("Disk space triggers need the volume's absolute path, e.g. / or /Volumes/Backup".to_string())
+            }
+            TriggerCondition::LowDiskSpace { below_gb, .. } if !below_gb.is_finite() || *below_gb <= 0.0 => {
+                Err("The free space threshold must be more than 0 GB".to_string())
+            }
+            TriggerCondition::ProcessExit { name } if name.trim().is_empty() => {
+                Err("Process triggers need a process name".to_string())
+            }
+            TriggerCondition::PathExists { path } | TriggerCondition::PathChanged { path }
+                if !Path::new(path).is_absolute() =>
+            {
+                Err("Path triggers need an absolute path".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// e.g. "CPU load at 90% or more for 30 min", for history.
+    pub fn describe(&self) -> String {
+        let condition = match &self.condition {
+            TriggerCondition::CpuLoad { percent } => format!("CPU load at {percent}% or more"),
+            TriggerCondition::ThermalPressure { level } => {
+                let level = match level {
+                    ThermalState::Nominal => "nominal",
+                    ThermalState::Fair => "fair",
+                    ThermalState::Serious => "serious",
+                    ThermalState::Critical => "critical",
+                };
+                format!("Thermal pressure {level} or worse")
+            }
+            TriggerCondition::LowDiskSpace { volume, below_gb } => {
+                format!("Free space on {volume} below {below_gb} GB")
+            }
+            TriggerCondition::ProcessExit { name } => format!("{name} exited"),
+            TriggerCondition::PathExists { path } => format!("{path} appeared"),
+            TriggerCondition::PathChanged { path } => format!("{path} changed"),
+        };
+        match self.minutes {
+            0 => condition,
+            minutes => format!("{condition} for {minutes} min"),
+        }
+    }
+}
+
+/// Name, size and modification time of the file at a path, or of each
+/// entry of the folder there; `None` while nothing is there.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct PathSnapshot(Option<Vec<(OsString, u64, Option<SystemTime>)>>);
+
+fn snapshot(path: &str) -> PathSnapshot {
+    let Ok(metadata) = fs::metadata(path) else {
+        return PathSnapshot(None);
+    };
+    if !metadata.is_dir() {
+        return PathSnapshot(Some(vec![(OsString::new(), metadata.len(), metadata.modified().ok())]));
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return PathSnapshot(None);
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != FINDER_METADATA)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((entry.file_name(), metadata.len(), metadata.modified().ok()))
+        })
+        .collect();
+    files.sort();
+    PathSnapshot(Some(files))
+}
+
+/// FSEvents for a path trigger's path, so a change between two samples
+/// isn't missed.
+struct PathEvents {
+    /// Set by the watcher, cleared by `take`.
+    touched: Arc<AtomicBool>,
+    /// Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+}
+
+impl PathEvents {
+    /// `None` for other conditions, or if the path's folder can't be
+    /// watched; sampling alone then decides.
+    fn start(condition: &TriggerCondition) -> Option<Self> {
+        let (path, created_only) = match condition {
+            TriggerCondition::PathExists { path } => (path, true),
+            TriggerCondition::PathChanged { path } => (path, false),
+            _ => return None,
+        };
+        // FSEvents reports resolved paths, e.g. /private/tmp for /tmp.
+        let path = Path::new(path);
+        let parent = fs::canonicalize(path.parent()?).ok()?;
+        let target = parent.join(path.file_name()?);
+
+        let touched = Arc::new(AtomicBool::new(false));
+        let flag = touched.clone();
+        let watched = target.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let counts = match event.kind {
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_)) => true,
+                EventKind::Modify(ModifyKind::Metadata(_)) | EventKind::Access(_) => false,
+                _ => !created_only,
+            };
+            if counts && event.paths.iter().any(|path| touches(path, &watched, !created_only)) {
+                flag.store(true, Ordering::SeqCst);
+            }
+        })
+        .map_err(|err| eprintln!("Failed to watch {}: {err}", target.display()))
+        .ok()?;
+        // The folder holding the path sees it created, removed or replaced.
+        watcher
+            .watch(&parent, RecursiveMode::NonRecursive)
+            .map_err(|err| eprintln!("Failed to watch {}: {err}", parent.display()))
+            .ok()?;
+        if !created_only && target.is_dir() {
+            let _ = watcher.watch(&target, RecursiveMode::NonRecursive);
+        }
+        Some(Self {
+            touched,
+            _watcher: watcher,
+        })
+    }
+
+    fn take(&self) -> bool {
+        self.touched.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Whether an event at `path` concerns `target`, or with `in_folder` an
+/// entry of the folder at `target` other than Finder's metadata.
+fn touches(path: &Path, target: &Path, in_folder: bool) -> bool {
+    path == target
+        || in_folder && path.parent() == Some(target) && path.file_name() != Some(OsStr::new(FINDER_METADATA))
+}
+
+struct Watch {
+    run: ScheduledRun,
+    baseline: Option<PathSnapshot>,
+    events: Option<PathEvents>,
+    /// Start of the current stretch in which the condition held.
+    met_since: Option<DateTime<Utc>>,
+    /// Cleared after a repeating trigger fires, until its condition clears,
+    /// so one long spike fires it once; see also `needs_change`.
+    armed: bool,
+}
+
+/// Triggered timers past their target time, waiting on their condition.
+#[derive(Clone)]
+pub struct TriggerMonitor {
+    watches: Arc<Mutex<Vec<Watch>>>,
+    /// `trigger-baselines.json`: each `PathChanged` timer's path and
+    /// baseline by timer id, so a restart keeps comparing against the
+    /// snapshot taken when it was armed. Locked while read or written.
+    baselines: Arc<Mutex<PathBuf>>,
+}
+
+impl TriggerMonitor {
+    pub fn new(baselines_path: PathBuf) -> Self {
+        Self {
+            watches: Arc::new(Mutex::new(Vec::new())),
+            baselines: Arc::new(Mutex::new(baselines_path)),
+        }
+    }
+
+    /// Hands a triggered timer to the monitor; the scheduler does this
+    /// when its target time comes, `fire_triggered` after a repeat.
+    pub fn watch(&self, run: ScheduledRun, armed: bool) {
+        let condition = run.info.trigger.as_ref().map(|trigger| &trigger.condition);
+        let baseline = match condition {
+            // The scheduler arms again after a restart; a repeat starts over.
+            Some(TriggerCondition::PathChanged { path }) => Some(self.baseline(&run.info.id, path, armed)),
+            _ => None,
+        };
+        let events = condition.and_then(PathEvents::start);
+        let armed = armed && !condition.is_some_and(TriggerCondition::needs_change);
+        match self.watches.lock() {
+            Ok(mut watches) => watches.push(Watch {
+                run,
+                baseline,
+                events,
+                met_since: None,
+                armed,
+            }),
+            Err(_) => eprintln!("Failed to lock trigger watches"),
+        }
+    }
+
+    /// What `is_met` compares `path` against: the saved baseline if
+    /// `reuse` and one was saved for this path, otherwise a new one, saved.
+    fn baseline(&self, timer_id: &str, path: &str, reuse: bool) -> PathSnapshot {
+        let Ok(file) = self.baselines.lock() else {
+            return snapshot(path);
+        };
+        let mut saved = read_baselines(&file);
+        match saved.get(timer_id) {
+            Some((saved_path, baseline)) if reuse && saved_path == path => return baseline.clone(),
+            _ => {}
+        }
+        let baseline = snapshot(path);
+        saved.insert(timer_id.to_string(), (path.to_string(), baseline.clone()));
+        if let Err(err) = write_baselines(&file, &saved) {
+            eprintln!("{err}");
+        }
+        baseline
+    }
+
+    /// Drops a timer's saved baseline once it fired or was cancelled.
+    fn forget(&self, timer_id: &str) {
+        let Ok(file) = self.baselines.lock() else {
+            return;
+        };
+        let mut saved = read_baselines(&file);
+        if saved.remove(timer_id).is_some() {
+            if let Err(err) = write_baselines(&file, &saved) {
+                eprintln!("{err}");
+            }
+        }
+    }
+}
+
+type Baselines = HashMap<String, (String, PathSnapshot)>;
+
+fn read_baselines(path: &Path) -> Baselines {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn write_baselines(path: &Path, baselines: &Baselines) -> Result<(), String> {
+    let data =
+        serde_json::to_string(baselines).map_err(|err| format!("Failed to encode trigger baselines: {err}"))?;
+    fs::write(path, data).map_err(|err| format!("Failed to save trigger baselines: {err}"))
+}
+
+/// Every `SAMPLE_INTERVAL`, samples the condition of each watched timer
+/// and fires the ones that held long enough, each on its own thread as
+/// the scheduler fires due occurrences. Cancelled timers are dropped.
+/// Path triggers also count what FSEvents saw since the last sample.
+pub fn spawn_monitor(store: TimerStore) {
+    thread::spawn(move || {
+        let mut last_sample = Utc::now();
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+            let now = Utc::now();
+            let slept = (now - last_sample).num_seconds() > MAX_SAMPLE_GAP_SECONDS;
+            last_sample = now;
+
+            // Sampled without the lock, so firing threads can hand repeats back.
+            let Ok(watches) = store.triggers.watches.lock().map(|mut watches| mem::take(&mut *watches)) else {
+                continue;
+            };
+            let mut waiting = Vec::with_capacity(watches.len());
+            for mut watch in watches {
+                if watch.run.is_cancelled() {
+                    store.triggers.forget(&watch.run.info.id);
+                    continue;
+                }
+                let Some(trigger) = watch.run.info.trigger.as_ref() else {
+                    continue;
+                };
+                if slept {
+                    watch.met_since = None;
+                }
+                let touched = watch.events.as_ref().is_some_and(PathEvents::take);
+                if !trigger.condition.is_met(watch.baseline.as_ref(), touched) {
+                    watch.met_since = None;
+                    watch.armed = true;
+                    waiting.push(watch);
+                    continue;
+                }
+                let since = *watch.met_since.get_or_insert(now);
+                if !watch.armed || now - since < ChronoDuration::minutes(trigger.minutes.into()) {
+                    waiting.push(watch);
+                    continue;
+                }
+
+                store.triggers.forget(&watch.run.info.id);
+                let store = store.clone();
+                let firing = FiringGuard::enter(&store.firing);
+                thread::spawn(move || {
+                    let _firing = firing;
+                    fire_triggered(&store, watch.run);
+                });
+            }
+            if let Ok(mut watches) = store.triggers.watches.lock() {
+                watches.extend(waiting);
+            }
+        }
+    });
+}
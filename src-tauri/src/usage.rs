@@ -0,0 +1,290 @@
+#[cfg(feature = "app")]
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::NaiveTime;
+#[cfg(feature = "app")]
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::TimerAction;
+#[cfg(feature = "app")]
+use crate::{action_label, notify_user, system, AuditOrigin, CreateTimerRequest, TimerStore};
+
+#[cfg(feature = "app")]
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+/// A minute counts as use if there was keyboard or mouse input this
+/// recently; reading or watching a video without touching anything for
+/// longer doesn't count.
+#[cfg(feature = "app")]
+const IDLE_THRESHOLD_SECONDS: u64 = 180;
+/// Further use after the limit action ran that triggers it again, e.g.
+/// after unlocking the screen.
+#[cfg(feature = "app")]
+const REPEAT_AFTER_SECONDS: u64 = 10 * 60;
+#[cfg(feature = "app")]
+pub const MAX_WARN_MINUTES: u32 = 60;
+/// Days of usage kept for `get_usage_report`.
+#[cfg(feature = "app")]
+const MAX_DAYS: usize = 400;
+
+/// "After this much use today, warn, then run `action`."
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageLimit {
+    pub limit_minutes: u32,
+    /// Notice given before `action` runs; the action is scheduled as a
+    /// one-time timer this far ahead.
+    #[serde(default = "default_warn_minutes")]
+    pub warn_minutes: u32,
+    pub action: TimerAction,
+    /// When a new usage day begins, in local time; 04:00 counts a late
+    /// night as part of the day before.
+    #[serde(default = "default_day_starts_at")]
+    pub day_starts_at: NaiveTime,
+}
+
+fn default_warn_minutes() -> u32 {
+    10
+}
+
+pub fn default_day_starts_at() -> NaiveTime {
+    NaiveTime::from_hms_opt(4, 0, 0).expect("valid time")
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    pub day: NaiveDate,
+    pub active_seconds: u64,
+    pub first_active_at: Option<DateTime<Utc>>,
+    pub last_active_at: Option<DateTime<Utc>>,
+    /// `active_seconds` when the limit action was last scheduled.
+    #[serde(default)]
+    pub enforced_at: Option<u64>,
+    /// Times the limit scheduled its action this day.
+    #[serde(default)]
+    pub limit_triggers: u32,
+}
+
+#[cfg(feature = "app")]
+impl DailyUsage {
+    fn new(day: NaiveDate) -> Self {
+        Self {
+            day,
+            active_seconds: 0,
+            first_active_at: None,
+            last_active_at: None,
+            enforced_at: None,
+            limit_triggers: 0,
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedUsage {
+    days: Vec<DailyUsage>,
+}
+
+/// Active time per usage day, oldest first, saved in `usage.json` after
+/// every sample so a restart doesn't reset the count.
+#[cfg(feature = "app")]
+#[derive(Clone)]
+pub struct UsageTracker {
+    inner: Arc<Mutex<Vec<DailyUsage>>>,
+    storage_path: Arc<PathBuf>,
+    /// Start of the current stretch of use without an idle break; not
+    /// persisted, since a restart is itself a break.
+    active_since: Arc<Mutex<Option<DateTime<Utc>>>>,
+}
+
+#[cfg(feature = "app")]
+impl UsageTracker {
+    pub fn new(storage_path: PathBuf) -> Self {
+        let days = fs::read_to_string(&storage_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<PersistedUsage>(&raw).ok())
+            .map(|persisted| persisted.days)
+            .unwrap_or_default();
+        Self {
+            inner: Arc::new(Mutex::new(days)),
+            storage_path: Arc::new(storage_path),
+            active_since: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn active_since(&self) -> Option<DateTime<Utc>> {
+        self.active_since.lock().ok().and_then(|since| *since)
+    }
+
+    /// Usage so far on `day`; zero if nothing was recorded for it.
+    pub fn on(&self, day: NaiveDate) -> Result<DailyUsage, String> {
+        Ok(self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock usage".to_string())?
+            .iter()
+            .find(|usage| usage.day == day)
+            .cloned()
+            .unwrap_or_else(|| DailyUsage::new(day)))
+    }
+
+    /// Recorded days from `since` on, oldest first.
+    pub fn days_since(&self, since: Option<NaiveDate>) -> Result<Vec<DailyUsage>, String> {
+        Ok(self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock usage".to_string())?
+            .iter()
+            .filter(|usage| since.map_or(true, |since| usage.day >= since))
+            .cloned()
+            .collect())
+    }
+
+    /// Applies `apply` to `day`, starting a new day if it isn't the latest
+    /// one recorded.
+    fn modify<F>(&self, day: NaiveDate, apply: F) -> Result<DailyUsage, String>
+    where
+        F: FnOnce(&mut DailyUsage),
+    {
+        let mut locked = self.inner.lock().map_err(|_| "Failed to lock usage".to_string())?;
+        if locked.last().map(|usage| usage.day) != Some(day) {
+            locked.push(DailyUsage::new(day));
+            let excess = locked.len().saturating_sub(MAX_DAYS);
+            locked.drain(..excess);
+        }
+        let current = locked.last_mut().expect("a day was just ensured");
+        apply(current);
+        let usage = current.clone();
+        let days = locked.clone();
+        drop(locked);
+
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("Failed to create usage directory: {err}"))?;
+        }
+        let data = serde_json::to_string_pretty(&PersistedUsage { days })
+            .map_err(|err| format!("Failed to encode usage: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data).map_err(|err| format!("Failed to write usage: {err}"))?;
+        Ok(usage)
+    }
+}
+
+/// The usage day `at` belongs to: days run from `day_starts_at` to
+/// `day_starts_at`, so with 04:00 a 1am session counts for the day before.
+#[cfg(feature = "app")]
+pub fn usage_day(at: DateTime<Local>, day_starts_at: NaiveTime) -> NaiveDate {
+    (at.naive_local() - (day_starts_at - NaiveTime::MIN)).date()
+}
+
+/// Every `SAMPLE_INTERVAL` for the life of the app: adds the interval to
+/// today's usage if the user was active, and once a usage limit is
+/// reached warns and schedules its action.
+#[cfg(feature = "app")]
+pub fn spawn_tracker(tracker: UsageTracker, store: TimerStore) {
+    thread::spawn(move || {
+        let mut last_sample = Instant::now();
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+            // Time asleep isn't use; cap what one sample can add.
+            let elapsed = last_sample.elapsed().min(SAMPLE_INTERVAL).as_secs();
+            last_sample = Instant::now();
+
+            let limit = store.settings.snapshot().ok().and_then(|settings| settings.usage_limit);
+            let day_starts_at = limit.as_ref().map_or_else(default_day_starts_at, |limit| limit.day_starts_at);
+            let day = usage_day(Local::now(), day_starts_at);
+            let active = system::idle_seconds().is_ok_and(|idle| idle < IDLE_THRESHOLD_SECONDS);
+            if let Ok(mut since) = tracker.active_since.lock() {
+                *since = active.then(|| since.unwrap_or_else(|| Utc::now() - ChronoDuration::seconds(elapsed as i64)));
+            }
+            let usage = match tracker.modify(day, |usage| {
+                if active {
+                    let now = Utc::now();
+                    usage.active_seconds += elapsed;
+                    usage.first_active_at.get_or_insert(now);
+                    usage.last_active_at = Some(now);
+                }
+            }) {
+                Ok(usage) => usage,
+                Err(err) => {
+                    eprintln!("Failed to record usage: {err}");
+                    continue;
+                }
+            };
+
+            if let Some(limit) = limit {
+                if let Err(err) = enforce(&tracker, &store, &limit, &usage) {
+                    eprintln!("Failed to enforce the usage limit: {err}");
+                }
+            }
+        }
+    });
+}
+
+/// Schedules the limit's action `warn_minutes` ahead once usage reaches
+/// the limit minus the warning, and again after each further
+/// `REPEAT_AFTER_SECONDS` of use.
+#[cfg(feature = "app")]
+fn enforce(tracker: &UsageTracker, store: &TimerStore, limit: &UsageLimit, usage: &DailyUsage) -> Result<(), String> {
+    let limit_seconds = u64::from(limit.limit_minutes) * 60;
+    let warn_seconds = u64::from(limit.warn_minutes) * 60;
+    let due = match usage.enforced_at {
+        None => limit_seconds.saturating_sub(warn_seconds),
+        Some(at) => at + warn_seconds + REPEAT_AFTER_SECONDS,
+    };
+    if usage.active_seconds < due {
+        return Ok(());
+    }
+
+    let fire_at = Utc::now() + ChronoDuration::minutes(limit.warn_minutes.into()).max(ChronoDuration::seconds(5));
+    let hours = limit.limit_minutes / 60;
+    let minutes = limit.limit_minutes % 60;
+    store.create(
+        CreateTimerRequest {
+            action: limit.action.clone(),
+            target_time: fire_at.to_rfc3339(),
+            recurrence: None,
+            message: None,
+            notes: Some(format!("Daily usage limit of {hours}h {minutes:02}m")),
+            shutdown_options: None,
+            hooks: None,
+            condition: None,
+            trigger: None,
+            profile: None,
+            expires_at: None,
+            max_runs: None,
+            jitter_minutes: None,
+            warning_minutes: None,
+            confirmation: None,
+            escalation: None,
+            repeat_until_acknowledged: None,
+            working_hours: None,
+            ignore_working_hours: true,
+            chat_webhook: None,
+            must_run: false,
+            pause_media: false,
+            eject_disks: None,
+            tags: Vec::new(),
+            source: None,
+            managed: None,
+        },
+        &AuditOrigin::System,
+    )?;
+    notify_user(&format!(
+        "Daily usage limit of {hours}h {minutes:02}m: {} at {}",
+        action_label(&limit.action),
+        fire_at.with_timezone(&Local).format("%H:%M")
+    ));
+    let enforced_at = usage.active_seconds;
+    tracker.modify(usage.day, |usage| {
+        usage.enforced_at = Some(enforced_at);
+        usage.limit_triggers += 1;
+    })?;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::{
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::exec;
+
+const SCUTIL: &str = "/usr/sbin/scutil";
+/// How long a connection may take before the run is reported as failed.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VpnState {
+    Connect,
+    Disconnect,
+}
+
+/// VPN services configured in System Settings > VPN. `scutil --nc list`
+/// prints one per line with the name in double quotes.
+pub fn services() -> Result<Vec<String>, String> {
+    let output = exec::output(Command::new(SCUTIL).args(["--nc", "list"]))
+        .map_err(|err| format!("Failed to run scutil: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list VPN services: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let start = line.find('"')? + 1;
+            let end = start + line[start..].find('"')?;
+            Some(line[start..end].to_string())
+        })
+        .collect())
+}
+
+/// Fails unless a VPN service called `name` exists.
+pub fn check_exists(name: &str) -> Result<(), String> {
+    if services()?.iter().any(|service| service == name) {
+        Ok(())
+    } else {
+        Err(format!("No VPN service named \"{name}\""))
+    }
+}
+
+/// Connects or disconnects the service and waits for it to get there.
+pub fn set(name: &str, state: VpnState) -> Result<Option<String>, String> {
+    let (verb, wanted) = match state {
+        VpnState::Connect => ("start", "Connected"),
+        VpnState::Disconnect => ("stop", "Disconnected"),
+    };
+    if status(name)? == wanted {
+        return Ok(Some(format!("{name} was already {}", wanted.to_lowercase())));
+    }
+
+    let output = exec::output(Command::new(SCUTIL).args(["--nc", verb, name]))
+        .map_err(|err| format!("Failed to run scutil: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "VPN {name} could not {verb}: {}",
+            String::from_utf8_lossy(&output.stdout).trim()
+        ));
+    }
+
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    loop {
+        let current = status(name)?;
+        if current == wanted {
+            return Ok(Some(format!("{name} {}", wanted.to_lowercase())));
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "VPN {name} still {} after {}s",
+                current.to_lowercase(),
+                CONNECT_TIMEOUT.as_secs()
+            ));
+        }
+        thread::sleep(STATUS_POLL_INTERVAL);
+    }
+}
+
+/// First line of `scutil --nc status`, e.g. `Connected` or `Disconnected`.
+fn status(name: &str) -> Result<String, String> {
+    let output = exec::output(Command::new(SCUTIL).args(["--nc", "status", name]))
+        .map_err(|err| format!("Failed to run scutil: {err}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first = stdout.lines().next().unwrap_or_default().trim();
+    if !output.status.success() || first.is_empty() {
+        return Err(format!("No VPN service named \"{name}\""));
+    }
+    Ok(first.to_string())
+}
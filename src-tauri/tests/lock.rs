@@ -0,0 +1,51 @@
+//! A lock timer driven headless like `scheduler.rs`: the native lock is
+//! recorded by `fake` rather than called. A separate binary, since `fake`
+//! records globally.
+#![cfg(feature = "fake-system")]
+
+use std::{
+    fs, thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::Utc;
+use lockpilot_lib::{fake, spawn_scheduler, AuditOrigin, CreateTimerRequest, TimerStore};
+use serde_json::json;
+
+const WAIT_LIMIT: Duration = Duration::from_secs(20);
+const CG_SESSION: &str = "/System/Library/CoreServices/Menu Extras/User.menu/Contents/Resources/CGSession";
+
+#[test]
+fn lock_timer_locks_natively_without_the_helper() {
+    fake::reset();
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    let dir = std::env::temp_dir().join(format!("lockpilot-lock-{}-{nanos}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let store = TimerStore::open(&dir);
+    spawn_scheduler(store.clone());
+
+    let request: CreateTimerRequest = serde_json::from_value(json!({
+        "action": "lock",
+        "targetTime": (Utc::now() + chrono::Duration::seconds(1)).to_rfc3339(),
+    }))
+    .unwrap();
+    store.create(request, &AuditOrigin::Ui).unwrap();
+
+    let start = Instant::now();
+    while !fake::invocations()
+        .iter()
+        .any(|invocation| invocation.program == fake::NATIVE_LOCK)
+    {
+        assert!(start.elapsed() < WAIT_LIMIT, "never locked: {:?}", fake::invocations());
+        thread::sleep(Duration::from_millis(100));
+    }
+    // The first method worked, so nothing else was tried, and the helper
+    // isn't installed until stubbed.
+    let programs: Vec<String> = fake::invocations()
+        .into_iter()
+        .map(|invocation| invocation.program)
+        .collect();
+    assert!(!programs.iter().any(|program| program == CG_SESSION || program == fake::HELPER), "{programs:?}");
+
+    let _ = fs::remove_dir_all(dir);
+}
@@ -0,0 +1,65 @@
+//! Popup timers that wait long for an answer, driven headless like
+//! `scheduler.rs`. A separate binary, since `fake` records globally.
+#![cfg(feature = "fake-system")]
+
+use std::{
+    fs, thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::Utc;
+use lockpilot_lib::{fake, spawn_scheduler, AuditOrigin, CreateTimerRequest, TimerStore};
+use serde_json::json;
+
+const WAIT_LIMIT: Duration = Duration::from_secs(20);
+const OSASCRIPT: &str = "/usr/bin/osascript";
+
+#[test]
+fn escalating_popup_dialog_may_stay_up_past_an_hour() {
+    fake::reset();
+    fake::stub(
+        OSASCRIPT,
+        fake::Stub {
+            stdout: "button returned:OK, gave up:false".to_string(),
+            ..Default::default()
+        },
+    );
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    let dir = std::env::temp_dir().join(format!("lockpilot-popups-{}-{nanos}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let store = TimerStore::open(&dir);
+    spawn_scheduler(store.clone());
+
+    let request: CreateTimerRequest = serde_json::from_value(json!({
+        "action": "popup",
+        "targetTime": (Utc::now() + chrono::Duration::seconds(1)).to_rfc3339(),
+        "message": "Time to stop",
+        "escalation": { "afterMinutes": 90, "action": "lock" },
+    }))
+    .unwrap();
+    store.create(request, &AuditOrigin::Ui).unwrap();
+
+    // Shown once, with 90 minutes to answer before escalating.
+    let start = Instant::now();
+    let dialog = loop {
+        let shown = fake::invocations()
+            .into_iter()
+            .find(|invocation| invocation.program == OSASCRIPT && invocation.args.concat().contains("Time to stop"));
+        if let Some(shown) = shown {
+            break shown;
+        }
+        assert!(start.elapsed() < WAIT_LIMIT, "the popup was never shown");
+        thread::sleep(Duration::from_millis(100));
+    };
+    let script = dialog.args.concat();
+    let window: u64 = script
+        .rsplit("giving up after ")
+        .next()
+        .and_then(|seconds| seconds.trim().parse().ok())
+        .expect("the dialog gives up on its own");
+    assert!(window > 60 * 60, "expected a window past an hour, got {window}s");
+    let limit = dialog.limit.expect("the dialog runs supervised");
+    assert!(limit.as_secs() > window, "the dialog would be killed after {limit:?}, before its {window}s window");
+
+    let _ = fs::remove_dir_all(dir);
+}
@@ -0,0 +1,178 @@
+//! Drives the scheduler headless: `cargo test --no-default-features
+//! --features fake-system`. Actions are recorded by `fake` instead of
+//! showing dialogs or locking the Mac.
+#![cfg(feature = "fake-system")]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+use lockpilot_lib::{fake, restore_timers, spawn_scheduler, AuditOrigin, CreateTimerRequest, TimerStore};
+use serde_json::{json, Value};
+
+const WAIT_LIMIT: Duration = Duration::from_secs(20);
+
+fn data_dir() -> PathBuf {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    let dir = std::env::temp_dir().join(format!("lockpilot-test-{}-{nanos}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The timer as `timers.json` or the store has it, as JSON.
+fn timer(store: &TimerStore, id: &str) -> Value {
+    serde_json::to_value(store.get(id).unwrap().expect("timer exists")).unwrap()
+}
+
+/// `timers.json`, as `{ "timers": [...] }`.
+fn persisted(dir: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(dir.join("timers.json")).unwrap()).unwrap()
+}
+
+fn target_time(timer: &Value) -> DateTime<Utc> {
+    timer["targetTime"].as_str().unwrap().parse().unwrap()
+}
+
+fn wait_for_runs(store: &TimerStore, id: &str, runs: u64) -> Value {
+    let start = Instant::now();
+    loop {
+        let timer = timer(store, id);
+        if timer["runCount"].as_u64() == Some(runs) {
+            return timer;
+        }
+        assert!(start.elapsed() < WAIT_LIMIT, "timer never reached {runs} runs: {timer}");
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// `timers.json` once it records `runs`; the store counts a run before
+/// the scheduler gets to persist it.
+fn wait_for_persisted_runs(dir: &Path, runs: u64) -> Value {
+    let start = Instant::now();
+    loop {
+        let on_disk = persisted(dir);
+        if on_disk["timers"][0]["runCount"].as_u64() == Some(runs) {
+            return on_disk;
+        }
+        assert!(start.elapsed() < WAIT_LIMIT, "timers.json never reached {runs} runs: {on_disk}");
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn recurring_timer_fires_recurs_persists_and_restores() {
+    fake::reset();
+    let dir = data_dir();
+    let store = TimerStore::open(&dir);
+    spawn_scheduler(store.clone());
+
+    let target = Utc::now() + chrono::Duration::seconds(2);
+    let request: CreateTimerRequest = serde_json::from_value(json!({
+        "action": "popup",
+        "targetTime": target.to_rfc3339(),
+        "message": "Stretch",
+        "recurrence": { "preset": "every_n_minutes", "intervalMinutes": 1 },
+    }))
+    .unwrap();
+    let created = serde_json::to_value(store.create(request, &AuditOrigin::Ui).unwrap()).unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    // Fires once, then is re-queued a minute on.
+    let fired = wait_for_runs(&store, &id, 1);
+    assert_eq!(target_time(&fired), target + chrono::Duration::minutes(1));
+    assert!(!fake::invocations().is_empty(), "the popup was never shown");
+
+    // The advanced occurrence is on disk.
+    let on_disk = wait_for_persisted_runs(&dir, 1);
+    assert_eq!(on_disk["timers"].as_array().unwrap().len(), 1);
+    assert_eq!(target_time(&on_disk["timers"][0]), target_time(&fired));
+
+    // A restart picks the timer back up and keeps it recurring: bring
+    // its next occurrence close so the restored scheduler fires it.
+    let mut edited = on_disk;
+    let soon = Utc::now() + chrono::Duration::seconds(1);
+    edited["timers"][0]["targetTime"] = json!(soon.to_rfc3339());
+    fs::write(dir.join("timers.json"), serde_json::to_string(&edited).unwrap()).unwrap();
+
+    let restarted = TimerStore::open(&dir);
+    restore_timers(&restarted).unwrap();
+    spawn_scheduler(restarted.clone());
+    let refired = wait_for_runs(&restarted, &id, 2);
+    assert_eq!(target_time(&refired), soon + chrono::Duration::minutes(1));
+    let on_disk = wait_for_persisted_runs(&dir, 2);
+    assert_eq!(target_time(&on_disk["timers"][0]), target_time(&refired));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn jitter_is_rolled_once_per_occurrence() {
+    let dir = data_dir();
+    let store = TimerStore::open(&dir);
+    spawn_scheduler(store.clone());
+
+    let request: CreateTimerRequest = serde_json::from_value(json!({
+        "action": "popup",
+        "targetTime": (Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+        "message": "Stretch",
+        "recurrence": { "preset": "every_n_hours", "intervalHours": 2 },
+        "jitterMinutes": 30,
+    }))
+    .unwrap();
+    let created = serde_json::to_value(store.create(request, &AuditOrigin::Ui).unwrap()).unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let on_disk = persisted(&dir);
+    let rolled = on_disk["timers"][0]["jitter"].clone();
+    let occurrence: DateTime<Utc> = rolled["occurrence"].as_str().unwrap().parse().unwrap();
+    assert_eq!(occurrence, target_time(&created));
+    assert!(rolled["offsetSeconds"].as_i64().unwrap().abs() <= 30 * 60);
+
+    // Queued again on restart, the occurrence keeps its offset.
+    let restarted = TimerStore::open(&dir);
+    restore_timers(&restarted).unwrap();
+    assert_eq!(timer(&restarted, &id)["jitter"], rolled);
+    assert_eq!(persisted(&dir)["timers"][0]["jitter"], rolled);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn timer_out_of_runs_is_archived() {
+    let dir = data_dir();
+    let store = TimerStore::open(&dir);
+    spawn_scheduler(store.clone());
+
+    let request: CreateTimerRequest = serde_json::from_value(json!({
+        "action": "popup",
+        "targetTime": (Utc::now() + chrono::Duration::seconds(1)).to_rfc3339(),
+        "message": "Stretch",
+        "recurrence": { "preset": "every_n_minutes", "intervalMinutes": 1 },
+        "maxRuns": 1,
+    }))
+    .unwrap();
+    let created = serde_json::to_value(store.create(request, &AuditOrigin::Ui).unwrap()).unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let start = Instant::now();
+    while store.get(&id).unwrap().is_some() {
+        assert!(start.elapsed() < WAIT_LIMIT, "the timer was never removed");
+        thread::sleep(Duration::from_millis(100));
+    }
+    let archive: Value = serde_json::from_str(&fs::read_to_string(dir.join("archive.json")).unwrap()).unwrap();
+    let archived = &archive["timers"][0];
+    assert_eq!(archived["timer"]["id"], json!(id));
+    assert_eq!(archived["timer"]["runCount"], json!(1));
+    assert_eq!(archived["reason"], json!("Reached its maximum number of runs"));
+
+    // Gone from timers.json too, so a restart doesn't bring it back.
+    let restarted = TimerStore::open(&dir);
+    restore_timers(&restarted).unwrap();
+    assert!(restarted.get(&id).unwrap().is_none());
+
+    let _ = fs::remove_dir_all(dir);
+}
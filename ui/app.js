@@ -7,118 +7,2401 @@ const targetTimeInput = document.getElementById("target-time");
 const recurrencePresetInput = document.getElementById("recurrence-preset");
 const intervalWrap = document.getElementById("interval-wrap");
 const intervalHoursInput = document.getElementById("interval-hours");
+const recurrencePreviewEl = document.getElementById("recurrence-preview");
+const expiresAtInput = document.getElementById("expires-at");
+const maxRunsInput = document.getElementById("max-runs");
+const requiresConfirmationInput = document.getElementById("requires-confirmation");
+const chatServiceSelect = document.getElementById("chat-service");
+const chatUrlInput = document.getElementById("chat-url");
+const chatTemplateInput = document.getElementById("chat-template");
+const chatOnlyFailuresInput = document.getElementById("chat-only-failures");
+const confirmationTimeoutInput = document.getElementById("confirmation-timeout");
+const proceedOnTimeoutInput = document.getElementById("proceed-on-timeout");
+const jitterMinutesInput = document.getElementById("jitter-minutes");
+const warningMinutesInput = document.getElementById("warning-minutes");
 const messageWrap = document.getElementById("message-wrap");
 const messageInput = document.getElementById("message");
+const scriptWrap = document.getElementById("script-wrap");
+const scriptInput = document.getElementById("script");
+const scriptLabelEl = document.getElementById("script-label");
+const SCRIPT_ACTIONS = ["appleScript", "javaScript"];
+const shortcutWrap = document.getElementById("shortcut-wrap");
+const workflowWrap = document.getElementById("workflow-wrap");
+const sshWrap = document.getElementById("ssh-wrap");
+const sshHostInput = document.getElementById("ssh-host");
+const sshUserInput = document.getElementById("ssh-user");
+const sshCommandInput = document.getElementById("ssh-command");
+const vpnWrap = document.getElementById("vpn-wrap");
+const vpnServiceInput = document.getElementById("vpn-service");
+const vpnServicesList = document.getElementById("vpn-services");
+const nightShiftWrap = document.getElementById("night-shift-wrap");
+const fadeVolumeWrap = document.getElementById("fade-volume-wrap");
+const fadeTargetInput = document.getElementById("fade-target");
+const fadeMinutesInput = document.getElementById("fade-minutes");
+const nightShiftStrengthInput = document.getElementById("night-shift-strength");
+const countdownMinutesInput = document.getElementById("countdown-minutes");
+const countdownLabelInput = document.getElementById("countdown-label");
+const startCountdownBtn = document.getElementById("start-countdown");
+const countdownsEl = document.getElementById("countdowns");
+const alarmWrap = document.getElementById("alarm-wrap");
+const alarmSoundSelect = document.getElementById("alarm-sound");
+const alarmVolumeInput = document.getElementById("alarm-volume");
+const alarmSnoozeInput = document.getElementById("alarm-snooze");
+const workflowPathInput = document.getElementById("workflow-path");
+const shortcutNameInput = document.getElementById("shortcut-name");
+const shortcutNamesList = document.getElementById("shortcut-names");
+const shortcutInput = document.getElementById("shortcut-input");
+const escalationWrap = document.getElementById("escalation-wrap");
+const escalationActionInput = document.getElementById("escalation-action");
+const escalationMinutesInput = document.getElementById("escalation-minutes");
+const repeatUntilAcknowledgedInput = document.getElementById("repeat-until-acknowledged");
+const nagEveryMinutesInput = document.getElementById("nag-every-minutes");
+const nagMaxRepeatsInput = document.getElementById("nag-max-repeats");
+const notesInput = document.getElementById("notes");
+const tagsInput = document.getElementById("tags");
+const shutdownOptionsWrap = document.getElementById("shutdown-options-wrap");
+const guardUnsavedWorkInput = document.getElementById("guard-unsaved-work");
+const quitAppsFirstInput = document.getElementById("quit-apps-first");
+const quitAppsTimeoutWrap = document.getElementById("quit-apps-timeout-wrap");
+const quitAppsTimeoutInput = document.getElementById("quit-apps-timeout");
+const abortIfAppsRefuseWrap = document.getElementById("abort-if-apps-refuse-wrap");
+const abortIfAppsRefuseInput = document.getElementById("abort-if-apps-refuse");
+const forceSessionEndInput = document.getElementById("force-session-end");
+const reopenWindowsSelect = document.getElementById("reopen-windows");
+const conditionInput = document.getElementById("condition");
+const triggerKindSelect = document.getElementById("trigger-kind");
+const triggerPercentWrap = document.getElementById("trigger-percent-wrap");
+const triggerPercentInput = document.getElementById("trigger-percent");
+const triggerLevelWrap = document.getElementById("trigger-level-wrap");
+const triggerLevelSelect = document.getElementById("trigger-level");
+const triggerDiskWrap = document.getElementById("trigger-disk-wrap");
+const triggerVolumeInput = document.getElementById("trigger-volume");
+const triggerBelowGbInput = document.getElementById("trigger-below-gb");
+const triggerProcessWrap = document.getElementById("trigger-process-wrap");
+const triggerProcessInput = document.getElementById("trigger-process");
+const triggerPathWrap = document.getElementById("trigger-path-wrap");
+const triggerPathInput = document.getElementById("trigger-path");
+const triggerMinutesWrap = document.getElementById("trigger-minutes-wrap");
+const triggerMinutesInput = document.getElementById("trigger-minutes");
+const triggerRepeatWrap = document.getElementById("trigger-repeat-wrap");
+const triggerRepeatInput = document.getElementById("trigger-repeat");
+const preHookInput = document.getElementById("pre-hook");
+const abortOnPreHookFailureInput = document.getElementById("abort-on-pre-hook-failure");
+const postHookInput = document.getElementById("post-hook");
+const quickEntryInput = document.getElementById("quick-entry");
+const quickEntryBtn = document.getElementById("quick-entry-add");
+const quickEntryStatusEl = document.getElementById("quick-entry-status");
 const timersEl = document.getElementById("timers");
 const statusEl = document.getElementById("status");
 const refreshBtn = document.getElementById("refresh");
+const profileSelect = document.getElementById("profile-select");
+const profileNameInput = document.getElementById("profile-name");
+const createProfileBtn = document.getElementById("create-profile");
+const deleteProfileBtn = document.getElementById("delete-profile");
+const workspaceSelect = document.getElementById("workspace-select");
+const workspaceNameInput = document.getElementById("workspace-name");
+const createWorkspaceBtn = document.getElementById("create-workspace");
+const deleteWorkspaceBtn = document.getElementById("delete-workspace");
+const pauseUntilInput = document.getElementById("pause-until");
+const pauseToggleBtn = document.getElementById("pause-toggle");
+const pauseStatusEl = document.getElementById("pause-status");
+const shiftMinutesInput = document.getElementById("shift-minutes");
+const shiftBeforeInput = document.getElementById("shift-before");
+const shiftTimersBtn = document.getElementById("shift-timers");
+const auditLogEl = document.getElementById("audit-log");
+const heatmapRangeSelect = document.getElementById("heatmap-range");
+const heatmapEl = document.getElementById("heatmap");
+const refreshAuditBtn = document.getElementById("refresh-audit");
+const crashPromptEl = document.getElementById("crash-prompt");
+const crashSummaryEl = document.getElementById("crash-summary");
+const sendCrashReportBtn = document.getElementById("send-crash-report");
+const dismissCrashReportBtn = document.getElementById("dismiss-crash-report");
+const permissionStatusEl = document.getElementById("permission-status");
+const requestPermissionsBtn = document.getElementById("request-permissions");
+const lockCompatibilityInput = document.getElementById("lock-compatibility");
+const healthChecksEl = document.getElementById("health-checks");
+const runHealthCheckBtn = document.getElementById("run-health-check");
+const runSelfTestBtn = document.getElementById("run-self-test");
+const crontabTextInput = document.getElementById("crontab-text");
+const importCrontabBtn = document.getElementById("import-crontab");
+const crontabStatusEl = document.getElementById("crontab-status");
+const quietEnabledInput = document.getElementById("quiet-enabled");
+const quietStartInput = document.getElementById("quiet-start");
+const quietEndInput = document.getElementById("quiet-end");
+const quietModeSelect = document.getElementById("quiet-mode");
+const quietBlockWebsitesInput = document.getElementById("quiet-block-websites");
+const quietStatusEl = document.getElementById("quiet-status");
+const workingEnabledInput = document.getElementById("working-enabled");
+const workingStartInput = document.getElementById("working-start");
+const workingEndInput = document.getElementById("working-end");
+const workingPolicySelect = document.getElementById("working-policy");
+const workingStatusEl = document.getElementById("working-status");
+const saveWorkingHoursBtn = document.getElementById("save-working-hours");
+const usageEnabledInput = document.getElementById("usage-enabled");
+const breakEnabledInput = document.getElementById("break-enabled");
+const breakPresetSelect = document.getElementById("break-preset");
+const breakWorkMinutesInput = document.getElementById("break-work-minutes");
+const breakSecondsInput = document.getElementById("break-seconds");
+const breakMessageInput = document.getElementById("break-message");
+const breakSuppressInput = document.getElementById("break-suppress");
+const breakStatsEl = document.getElementById("break-stats");
+const breakStatusEl = document.getElementById("break-status");
+const saveBreakRemindersBtn = document.getElementById("save-break-reminders");
+const usageHoursInput = document.getElementById("usage-hours");
+const usageWarnInput = document.getElementById("usage-warn");
+const usageActionSelect = document.getElementById("usage-action");
+const usageDayStartInput = document.getElementById("usage-day-start");
+const usageTodayEl = document.getElementById("usage-today");
+const activeForEl = document.getElementById("active-for");
+const usageStatusEl = document.getElementById("usage-status");
+const saveUsageLimitBtn = document.getElementById("save-usage-limit");
+const usageReportRangeSelect = document.getElementById("usage-report-range");
+const usageReportSummaryEl = document.getElementById("usage-report-summary");
+const usageReportEl = document.getElementById("usage-report");
+const ignoreWorkingHoursInput = document.getElementById("ignore-working-hours");
+const mustRunInput = document.getElementById("must-run");
+const pauseMediaWrap = document.getElementById("pause-media-wrap");
+const pauseMediaInput = document.getElementById("pause-media");
+const PAUSE_MEDIA_FIRST_ACTIONS = ["lock", "displaySleep", "systemSleep"];
+const ejectDisksWrap = document.getElementById("eject-disks-wrap");
+const ejectDisksInput = document.getElementById("eject-disks");
+const ejectAbortIfBusyInput = document.getElementById("eject-abort-if-busy");
+const EJECT_FIRST_ACTIONS = ["shutdown", "reboot", "logout", "systemSleep"];
+const saveQuietHoursBtn = document.getElementById("save-quiet-hours");
+const holidayRegionInput = document.getElementById("holiday-region");
+const saveHolidayRegionBtn = document.getElementById("save-holiday-region");
+const holidayStatusEl = document.getElementById("holiday-status");
+const skipHolidaysWrap = document.getElementById("skip-holidays-wrap");
+const skipHolidaysInput = document.getElementById("skip-holidays");
+const pushServiceSelect = document.getElementById("push-service");
+const pushServerWrap = document.getElementById("push-server-wrap");
+const pushServerInput = document.getElementById("push-server");
+const pushAddressLabel = document.getElementById("push-address-label");
+const pushAddressInput = document.getElementById("push-address");
+const pushSecretLabel = document.getElementById("push-secret-label");
+const pushSecretInput = document.getElementById("push-secret");
+const savePushBtn = document.getElementById("save-push");
+const testPushBtn = document.getElementById("test-push");
+const pushStatusEl = document.getElementById("push-status");
+const emailEnabledInput = document.getElementById("email-enabled");
+const smtpHostInput = document.getElementById("smtp-host");
+const smtpPortInput = document.getElementById("smtp-port");
+const smtpSecuritySelect = document.getElementById("smtp-security");
+const smtpUsernameInput = document.getElementById("smtp-username");
+const smtpPasswordInput = document.getElementById("smtp-password");
+const smtpFromInput = document.getElementById("smtp-from");
+const smtpToInput = document.getElementById("smtp-to");
+const smtpNotifyFailuresInput = document.getElementById("smtp-notify-failures");
+const saveEmailBtn = document.getElementById("save-email");
+const testEmailBtn = document.getElementById("test-email");
+const emailStatusEl = document.getElementById("email-status");
+const toggleHelperBtn = document.getElementById("toggle-helper");
+const stayAwakeMinutesInput = document.getElementById("stay-awake-minutes");
+const stayAwakeStatusEl = document.getElementById("stay-awake-status");
+const conflictPolicySelect = document.getElementById("conflict-policy");
+const conflictPolicyStatusEl = document.getElementById("conflict-policy-status");
+const helperStatusEl = document.getElementById("helper-status");
+const calendarLogInput = document.getElementById("calendar-log");
+const calendarLogStatusEl = document.getElementById("calendar-log-status");
+const focusAppsInput = document.getElementById("focus-apps");
+const focusStartInput = document.getElementById("focus-start");
+const focusEndInput = document.getElementById("focus-end");
+const focusLabelInput = document.getElementById("focus-label");
+const focusModeSelect = document.getElementById("focus-mode");
+const focusBlockWebsitesInput = document.getElementById("focus-block-websites");
+const blockedDomainsInput = document.getElementById("blocked-domains");
+const saveBlockedDomainsBtn = document.getElementById("save-blocked-domains");
+const createFocusSessionBtn = document.getElementById("create-focus-session");
+const focusStatusEl = document.getElementById("focus-status");
+const focusSessionsEl = document.getElementById("focus-sessions");
+const icsUrlInput = document.getElementById("ics-url");
+const icsSummaryInput = document.getElementById("ics-summary");
+const icsActionSelect = document.getElementById("ics-action");
+const icsOffsetInput = document.getElementById("ics-offset");
+const icsAnchorSelect = document.getElementById("ics-anchor");
+const subscribeIcsBtn = document.getElementById("subscribe-ics");
+const icsStatusEl = document.getElementById("ics-status");
+const icsSubscriptionsEl = document.getElementById("ics-subscriptions");
+
+const remoteApiEnabledInput = document.getElementById("remote-api-enabled");
+const remoteApiPortInput = document.getElementById("remote-api-port");
+const remoteApiTlsInput = document.getElementById("remote-api-tls");
+const remoteApiAdvertiseInput = document.getElementById("remote-api-advertise");
+const remoteApiAllowScriptsInput = document.getElementById("remote-api-allow-scripts");
+const discoverPeersBtn = document.getElementById("discover-peers");
+const fleetPeersEl = document.getElementById("fleet-peers");
+const pushToAllBtn = document.getElementById("push-to-all");
+const fleetStatusEl = document.getElementById("fleet-status");
+const managedBannerEl = document.getElementById("managed-banner");
+const syncNowBtn = document.getElementById("sync-now");
+const syncEnabledInput = document.getElementById("sync-enabled");
+const syncTagsInput = document.getElementById("sync-tags");
+const saveSyncBtn = document.getElementById("save-sync");
+const syncPeersEl = document.getElementById("sync-peers");
+const syncStatusEl = document.getElementById("sync-status");
+const saveRemoteApiBtn = document.getElementById("save-remote-api");
+const generateCertificateBtn = document.getElementById("generate-certificate");
+const remoteApiStatusEl = document.getElementById("remote-api-status");
+const tokenNameInput = document.getElementById("token-name");
+const tokenScopeSelect = document.getElementById("token-scope");
+const createTokenBtn = document.getElementById("create-token");
+const createdTokenEl = document.getElementById("created-token");
+const tokensEl = document.getElementById("tokens");
 
 const checkUpdatesBtn = document.getElementById("check-updates");
 const autoCheckUpdatesInput = document.getElementById("auto-check-updates");
+const installOnQuitInput = document.getElementById("install-on-quit");
 const updateChannelSelect = document.getElementById("update-channel");
 const currentVersionEl = document.getElementById("current-version");
+const appInfoEl = document.getElementById("app-info");
+const systemInfoEl = document.getElementById("system-info");
+const scheduleRestartBtn = document.getElementById("schedule-restart");
 const updateStatusEl = document.getElementById("update-status");
 const updateResultEl = document.getElementById("update-result");
 const latestVersionEl = document.getElementById("latest-version");
 const latestNotesEl = document.getElementById("latest-notes");
 const installLatestBtn = document.getElementById("install-latest");
 const rollbackVersionSelect = document.getElementById("rollback-version");
+const rollbackAssetsEl = document.getElementById("rollback-assets");
 const rollbackInstallBtn = document.getElementById("rollback-install");
+const clearUpdateCacheBtn = document.getElementById("clear-update-cache");
 
 const AUTO_UPDATE_KEY = "lockpilot.autoCheckUpdates";
 const UPDATE_CHANNEL_KEY = "lockpilot.updateChannel";
 const LAUNCH_TIME = new Date();
 let currentVersion = "";
 let latestUpdate = null;
+let pauseState = { paused: false, pausedUntil: null };
+
+// Commands reject with { code, message, context }; anything else (e.g. IPC
+// failures) is shown as-is.
+const errorMessage = (err) => (err && typeof err === "object" && "message" in err ? err.message : String(err));
 
 const showStatus = (text, isError = false) => {
   statusEl.textContent = text;
   statusEl.style.color = isError ? "#c30e2e" : "#4f7480";
 };
 
-const showUpdateStatus = (text, isError = false) => {
-  updateStatusEl.textContent = text;
-  updateStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+const showUpdateStatus = (text, isError = false) => {
+  updateStatusEl.textContent = text;
+  updateStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const selectedChannel = () => updateChannelSelect.value;
+
+const toLocalDateTimeValue = (date) => {
+  const pad = (n) => String(n).padStart(2, "0");
+  return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}T${pad(date.getHours())}:${pad(date.getMinutes())}:${pad(date.getSeconds())}`;
+};
+
+const toggleMessage = () => {
+  const isPopup = actionInput.value === "popup";
+  const hasMessage = ["popup", "push", "email"].includes(actionInput.value);
+  messageWrap.style.display = hasMessage || ["alarm", "countdown"].includes(actionInput.value) ? "grid" : "none";
+  messageInput.required = hasMessage;
+  ejectDisksWrap.style.display = EJECT_FIRST_ACTIONS.includes(actionInput.value) ? "grid" : "none";
+  pauseMediaWrap.style.display = PAUSE_MEDIA_FIRST_ACTIONS.includes(actionInput.value) ? "flex" : "none";
+  fadeVolumeWrap.style.display = actionInput.value === "fadeVolume" ? "flex" : "none";
+  alarmWrap.style.display = actionInput.value === "alarm" ? "flex" : "none";
+  if (actionInput.value === "alarm" && !alarmSoundsLoaded) {
+    loadAlarmSounds();
+  }
+  nightShiftWrap.style.display = actionInput.value === "nightShiftOn" ? "grid" : "none";
+  const isVpn = actionInput.value.startsWith(VPN_ACTION_PREFIX);
+  vpnWrap.style.display = isVpn ? "grid" : "none";
+  vpnServiceInput.required = isVpn;
+  if (isVpn && !vpnServicesLoaded) {
+    loadVpnServices();
+  }
+  const isSsh = actionInput.value === "sshCommand";
+  sshWrap.style.display = isSsh ? "grid" : "none";
+  sshHostInput.required = isSsh;
+  sshCommandInput.required = isSsh;
+  const isWorkflow = actionInput.value === "automator";
+  workflowWrap.style.display = isWorkflow ? "grid" : "none";
+  workflowPathInput.required = isWorkflow;
+  const isShortcut = actionInput.value === "shortcut";
+  shortcutWrap.style.display = isShortcut ? "grid" : "none";
+  shortcutNameInput.required = isShortcut;
+  if (isShortcut && !shortcutsLoaded) {
+    loadShortcuts();
+  }
+  const isScript = SCRIPT_ACTIONS.includes(actionInput.value);
+  scriptWrap.style.display = isScript ? "grid" : "none";
+  scriptInput.required = isScript;
+  if (actionInput.value === "javaScript") {
+    scriptLabelEl.textContent = "JavaScript (arguments: timer id, action, scheduled time, message)";
+    scriptInput.placeholder = 'function run(argv) { Application("Music").pause(); return argv[0]; }';
+  } else {
+    scriptLabelEl.textContent = "AppleScript";
+    scriptInput.placeholder = 'tell application "Music" to pause';
+  }
+  escalationWrap.style.display = isPopup ? "grid" : "none";
+};
+
+const buildNag = () => {
+  if (actionInput.value !== "popup" || !repeatUntilAcknowledgedInput.checked) {
+    return null;
+  }
+
+  return {
+    everyMinutes: Number(nagEveryMinutesInput.value || 0),
+    maxRepeats: Number(nagMaxRepeatsInput.value || 0),
+  };
+};
+
+const buildEscalation = () => {
+  if (actionInput.value !== "popup" || !escalationActionInput.value) {
+    return null;
+  }
+
+  return {
+    afterMinutes: Number(escalationMinutesInput.value || 0),
+    action: escalationActionInput.value,
+  };
+};
+
+const isPowerOffAction = () => ["shutdown", "reboot", "logout"].includes(actionInput.value);
+
+const toggleShutdownOptions = () => {
+  shutdownOptionsWrap.classList.toggle("hidden", !isPowerOffAction());
+  quitAppsTimeoutWrap.classList.toggle("hidden", !quitAppsFirstInput.checked);
+  abortIfAppsRefuseWrap.classList.toggle("hidden", !quitAppsFirstInput.checked);
+};
+
+const buildShutdownOptions = () => {
+  if (
+    !isPowerOffAction() ||
+    (!quitAppsFirstInput.checked &&
+      !guardUnsavedWorkInput.checked &&
+      !forceSessionEndInput.checked &&
+      !reopenWindowsSelect.value)
+  ) {
+    return null;
+  }
+
+  return {
+    quitAppsTimeoutSeconds: quitAppsFirstInput.checked ? Number(quitAppsTimeoutInput.value || 0) : null,
+    abortIfAppsRefuse: quitAppsFirstInput.checked && abortIfAppsRefuseInput.checked,
+    unsavedWorkPolicy: guardUnsavedWorkInput.checked ? "abort" : null,
+    force: forceSessionEndInput.checked,
+    reopenWindows: reopenWindowsSelect.value ? reopenWindowsSelect.value === "true" : null,
+  };
+};
+
+const buildHooks = () => {
+  const preScript = preHookInput.value.trim();
+  const postScript = postHookInput.value.trim();
+  if (!preScript && !postScript) {
+    return null;
+  }
+
+  return {
+    preScript: preScript || null,
+    postScript: postScript || null,
+    abortOnPreFailure: abortOnPreHookFailureInput.checked,
+  };
+};
+
+const toggleTrigger = () => {
+  const kind = triggerKindSelect.value;
+  triggerPercentWrap.classList.toggle("hidden", kind !== "cpuLoad");
+  triggerLevelWrap.classList.toggle("hidden", kind !== "thermalPressure");
+  triggerDiskWrap.classList.toggle("hidden", kind !== "lowDiskSpace");
+  triggerProcessWrap.classList.toggle("hidden", kind !== "processExit");
+  triggerPathWrap.classList.toggle("hidden", !["pathExists", "pathChanged"].includes(kind));
+  triggerMinutesWrap.classList.toggle("hidden", !kind);
+  triggerRepeatWrap.classList.toggle("hidden", !kind);
+};
+
+const buildTrigger = () => {
+  const kind = triggerKindSelect.value;
+  if (!kind) {
+    return null;
+  }
+
+  const trigger = { kind, minutes: Number(triggerMinutesInput.value || 0), repeat: triggerRepeatInput.checked };
+  if (kind === "cpuLoad") {
+    trigger.percent = Number(triggerPercentInput.value || 0);
+  } else if (kind === "thermalPressure") {
+    trigger.level = triggerLevelSelect.value;
+  } else if (kind === "lowDiskSpace") {
+    trigger.volume = triggerVolumeInput.value.trim();
+    trigger.belowGb = Number(triggerBelowGbInput.value || 0);
+  } else if (kind === "processExit") {
+    trigger.name = triggerProcessInput.value.trim();
+  } else if (kind === "pathExists" || kind === "pathChanged") {
+    trigger.path = triggerPathInput.value.trim();
+  }
+  return trigger;
+};
+
+const TRIGGER_CONDITIONS = {
+  cpuLoad: (trigger) => `CPU load reaches ${trigger.percent}%`,
+  thermalPressure: (trigger) => `thermal pressure reaches ${trigger.level}`,
+  lowDiskSpace: (trigger) => `${trigger.volume} has less than ${trigger.belowGb} GB free`,
+  processExit: (trigger) => `${trigger.name} exits`,
+  pathExists: (trigger) => `${trigger.path} appears`,
+  pathChanged: (trigger) => `${trigger.path} changes`,
+};
+
+const triggerLabel = (trigger) => {
+  const condition = TRIGGER_CONDITIONS[trigger.kind]?.(trigger) ?? trigger.kind;
+  const sustained = trigger.minutes ? ` for ${trigger.minutes} min` : "";
+  return `Fires when ${condition}${sustained}${trigger.repeat ? ", every time" : ""}`;
+};
+
+const buildConfirmation = () => {
+  if (!requiresConfirmationInput.checked) {
+    return null;
+  }
+
+  return {
+    timeoutSeconds: Number(confirmationTimeoutInput.value || 0),
+    proceedOnTimeout: proceedOnTimeoutInput.checked,
+  };
+};
+
+const buildChatWebhook = () => {
+  if (!chatServiceSelect.value) {
+    return null;
+  }
+
+  return {
+    service: chatServiceSelect.value,
+    url: chatUrlInput.value.trim(),
+    template: chatTemplateInput.value.trim() || null,
+    onlyFailures: chatOnlyFailuresInput.checked,
+  };
+};
+
+const toggleRecurrence = () => {
+  const recurring = recurrencePresetInput.value !== "none";
+  const needsInterval =
+    recurrencePresetInput.value === "every_n_hours" ||
+    recurrencePresetInput.value === "every_n_minutes";
+  intervalWrap.classList.toggle("hidden", !needsInterval);
+  intervalHoursInput.required = needsInterval;
+  intervalHoursInput.max = recurrencePresetInput.value === "every_n_minutes" ? "1440" : "24";
+
+  skipHolidaysWrap.classList.toggle("hidden", !recurring);
+
+  if (!recurring) {
+    intervalWrap.classList.add("hidden");
+  }
+};
+
+const buildRecurrence = () => {
+  const recurrencePreset = recurrencePresetInput.value;
+  if (recurrencePreset === "none") {
+    return null;
+  }
+
+  return {
+    preset: recurrencePreset,
+    intervalHours: recurrencePreset === "every_n_hours" ? Number(intervalHoursInput.value || 0) : null,
+    intervalMinutes: recurrencePreset === "every_n_minutes" ? Number(intervalHoursInput.value || 0) : null,
+    skipHolidays: skipHolidaysInput.checked,
+  };
+};
+
+const PREVIEW_OCCURRENCES = 5;
+
+const updateRecurrencePreview = async () => {
+  const recurrence = buildRecurrence();
+  const start = new Date(targetTimeInput.value);
+  if (!recurrence || Number.isNaN(start.getTime())) {
+    recurrencePreviewEl.classList.add("hidden");
+    return;
+  }
+
+  try {
+    const preview = await invoke("preview_occurrences", {
+      recurrence,
+      start: start.toISOString(),
+      count: PREVIEW_OCCURRENCES,
+      jitterMinutes: jitterMinutesInput.value ? Number(jitterMinutesInput.value) : null,
+    });
+    recurrencePreviewEl.textContent = `Will fire ${preview.occurrences.map(fmtDate).join(", ")}, ...`;
+    if (preview.jitterMinutes) {
+      recurrencePreviewEl.textContent += ` (each ± ${preview.jitterMinutes} min)`;
+    }
+    if (preview.skipped.length) {
+      const skipped = preview.skipped.map((run) => `${fmtDate(run.at)} (${run.holiday})`).join(", ");
+      recurrencePreviewEl.textContent += ` Skipping ${preview.holidayRegion} holidays: ${skipped}`;
+    }
+  } catch (err) {
+    recurrencePreviewEl.textContent = errorMessage(err);
+  }
+  recurrencePreviewEl.classList.remove("hidden");
+};
+
+const PLUGIN_ACTION_PREFIX = "plugin:";
+const APPEARANCE_ACTION_PREFIX = "appearance:";
+const VPN_ACTION_PREFIX = "vpn:";
+let pluginNames = {};
+
+const buildAction = () => {
+  const value = actionInput.value;
+  if (value.startsWith(APPEARANCE_ACTION_PREFIX)) {
+    return { setAppearance: { mode: value.slice(APPEARANCE_ACTION_PREFIX.length) } };
+  }
+  if (value.startsWith(VPN_ACTION_PREFIX)) {
+    return { vpn: { service: vpnServiceInput.value.trim(), state: value.slice(VPN_ACTION_PREFIX.length) } };
+  }
+  if (value === "fadeVolume") {
+    return {
+      fadeVolume: { targetPercent: Number(fadeTargetInput.value) || 0, overMinutes: Number(fadeMinutesInput.value) || 0 },
+    };
+  }
+  if (value === "alarm") {
+    return {
+      alarm: {
+        sound: alarmSoundSelect.value,
+        volumePercent: Number(alarmVolumeInput.value) || 0,
+        snoozeMinutes: Number(alarmSnoozeInput.value) || 0,
+      },
+    };
+  }
+  if (value === "countdown") {
+    return { countdown: { sound: "Glass" } };
+  }
+  if (value === "nightShiftOn" || value === "nightShiftOff") {
+    const strength = nightShiftStrengthInput.value === "" ? null : Number(nightShiftStrengthInput.value);
+    return { nightShift: { enabled: value === "nightShiftOn", strength: value === "nightShiftOn" ? strength : null } };
+  }
+  if (value === "sshCommand") {
+    const user = sshUserInput.value.trim() || null;
+    return { sshCommand: { host: sshHostInput.value.trim(), user, command: sshCommandInput.value } };
+  }
+  if (value === "automator") {
+    return { automator: { path: workflowPathInput.value.trim() } };
+  }
+  if (value === "shortcut") {
+    return { shortcut: { name: shortcutNameInput.value.trim(), input: shortcutInput.value || null } };
+  }
+  if (SCRIPT_ACTIONS.includes(value)) {
+    return { [value]: { script: scriptInput.value } };
+  }
+  if (value.startsWith(PLUGIN_ACTION_PREFIX)) {
+    return { plugin: { id: value.slice(PLUGIN_ACTION_PREFIX.length) } };
+  }
+  return value;
+};
+
+const actionTitle = (action) => {
+  if (typeof action === "string") {
+    return action.replace(/([A-Z])/g, " $1").toUpperCase();
+  }
+
+  if (action.plugin) {
+    return (pluginNames[action.plugin.id] ?? action.plugin.id).toUpperCase();
+  }
+
+  if (action.vpn) {
+    return `${action.vpn.state.toUpperCase()} ${action.vpn.service}`;
+  }
+
+  if (action.sshCommand) {
+    const { host, user } = action.sshCommand;
+    return `SSH ${user ? `${user}@` : ""}${host}`;
+  }
+
+  if (action.fadeVolume) {
+    return `FADE VOLUME TO ${action.fadeVolume.targetPercent}%`;
+  }
+
+  if (action.alarm) {
+    return `ALARM (${action.alarm.sound.toUpperCase()})`;
+  }
+
+  if (action.nightShift) {
+    return `NIGHT SHIFT ${action.nightShift.enabled ? "ON" : "OFF"}`;
+  }
+
+  if (action.setAppearance) {
+    const { mode } = action.setAppearance;
+    return mode === "toggle" ? "TOGGLE APPEARANCE" : `${mode.toUpperCase()} MODE`;
+  }
+
+  return Object.keys(action)[0].toUpperCase();
+};
+
+let shortcutsLoaded = false;
+
+// Only listed once the Shortcut action is picked, since `shortcuts list`
+// can take a moment.
+const loadShortcuts = async () => {
+  shortcutsLoaded = true;
+  try {
+    const names = await invoke("list_shortcuts");
+    shortcutNamesList.replaceChildren(
+      ...names.map((name) => {
+        const option = document.createElement("option");
+        option.value = name;
+        return option;
+      })
+    );
+  } catch (err) {
+    shortcutsLoaded = false;
+    showStatus(`Could not list shortcuts: ${errorMessage(err)}`, true);
+  }
+};
+
+const startCountdown = async () => {
+  const minutes = Number(countdownMinutesInput.value);
+  if (!minutes || minutes < 1) {
+    showStatus("Enter the countdown length in minutes.", true);
+    return;
+  }
+
+  const request = {
+    action: { countdown: { sound: "Glass" } },
+    targetTime: new Date(Date.now() + minutes * 60000).toISOString(),
+    message: countdownLabelInput.value.trim() || null,
+  };
+  try {
+    await invoke("create_timer", { request });
+    countdownLabelInput.value = "";
+    showStatus(`Countdown of ${minutes} min started.`);
+    await loadTimers();
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const renderCountdowns = (ticks) => {
+  countdownsEl.replaceChildren(
+    ...ticks.map((tick) => {
+      const item = document.createElement("li");
+      const label = document.createElement("span");
+      label.textContent = tick.message || "Countdown";
+      const remaining = document.createElement("span");
+      remaining.textContent = tick.remainingText;
+      item.append(label, remaining);
+      return item;
+    })
+  );
+};
+
+let alarmSoundsLoaded = false;
+
+const loadAlarmSounds = async () => {
+  alarmSoundsLoaded = true;
+  try {
+    const names = await invoke("list_alarm_sounds");
+    alarmSoundSelect.replaceChildren(
+      ...names.map((name) => {
+        const option = document.createElement("option");
+        option.value = name;
+        option.textContent = name;
+        option.selected = name === "Glass";
+        return option;
+      })
+    );
+  } catch (err) {
+    alarmSoundsLoaded = false;
+    showStatus(`Could not list alarm sounds: ${errorMessage(err)}`, true);
+  }
+};
+
+let vpnServicesLoaded = false;
+
+const loadVpnServices = async () => {
+  vpnServicesLoaded = true;
+  try {
+    const names = await invoke("list_vpn_services");
+    vpnServicesList.replaceChildren(
+      ...names.map((name) => {
+        const option = document.createElement("option");
+        option.value = name;
+        return option;
+      })
+    );
+  } catch (err) {
+    vpnServicesLoaded = false;
+    showStatus(`Could not list VPN services: ${errorMessage(err)}`, true);
+  }
+};
+
+const loadPlugins = async () => {
+  try {
+    const catalog = await invoke("list_plugins");
+    actionInput.querySelectorAll("option[data-plugin]").forEach((option) => option.remove());
+    pluginNames = {};
+    catalog.plugins.forEach((plugin) => {
+      pluginNames[plugin.id] = plugin.name;
+      const option = document.createElement("option");
+      option.value = `${PLUGIN_ACTION_PREFIX}${plugin.id}`;
+      option.textContent = `Plugin: ${plugin.name}`;
+      option.dataset.plugin = "1";
+      if (plugin.description) {
+        option.title = plugin.description;
+      }
+      actionInput.appendChild(option);
+    });
+  } catch (err) {
+    showStatus(`Could not load plugins: ${errorMessage(err)}`, true);
+  }
+};
+
+const fmtDate = (iso) => new Date(iso).toLocaleString();
+const fmtMinutes = (minutes) => `${Math.floor(minutes / 60)}h ${String(minutes % 60).padStart(2, "0")}m`;
+
+const recurrenceLabel = (recurrence) => {
+  if (!recurrence) {
+    return "One-time";
+  }
+
+  if (recurrence.preset === "daily") {
+    return "Repeats daily";
+  }
+
+  if (recurrence.preset === "weekdays") {
+    return "Repeats weekdays";
+  }
+
+  if (recurrence.preset === "every_n_hours") {
+    return `Repeats every ${recurrence.intervalHours ?? "?"} hour(s)`;
+  }
+
+  if (recurrence.preset === "every_n_minutes") {
+    return `Repeats every ${recurrence.intervalMinutes ?? "?"} minute(s)`;
+  }
+
+  return "Recurring";
+};
+
+const renderTimers = (timers) => {
+  timersEl.innerHTML = "";
+
+  if (!timers.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No active timers.";
+    timersEl.appendChild(empty);
+    return;
+  }
+
+  for (const timer of timers) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const top = document.createElement("div");
+    top.className = "timer-top";
+
+    const title = document.createElement("strong");
+    title.textContent = actionTitle(timer.action);
+
+    const cancelBtn = document.createElement("button");
+    cancelBtn.className = "danger";
+    cancelBtn.textContent = "Cancel";
+    cancelBtn.addEventListener("click", async () => {
+      try {
+        await invoke("cancel_timer", { id: timer.id });
+        await loadTimers();
+        showStatus("Timer canceled.");
+      } catch (err) {
+        showStatus(errorMessage(err), true);
+      }
+    });
+
+    const snoozeBtn = document.createElement("button");
+    snoozeBtn.className = "secondary";
+    snoozeBtn.textContent = "Snooze 10m";
+    snoozeBtn.addEventListener("click", async () => {
+      try {
+        await invoke("snooze_timer", { id: timer.id, minutes: 10 });
+        await loadTimers();
+        showStatus("Timer snoozed for 10 minutes.");
+      } catch (err) {
+        showStatus(errorMessage(err), true);
+      }
+    });
+
+    const buttons = document.createElement("div");
+    buttons.className = "timer-buttons";
+    buttons.append(snoozeBtn);
+    if (!timer.managed) {
+      buttons.append(cancelBtn);
+    }
+
+    top.append(title, buttons);
+
+    const when = document.createElement("div");
+    when.className = "timer-meta";
+    when.textContent = `Runs at ${fmtDate(timer.targetTime)} (${timer.remainingText})`;
+    if (timer.trigger) {
+      when.textContent = `${triggerLabel(timer.trigger)} · armed from ${fmtDate(timer.targetTime)}`;
+    }
+    if (timer.jitterMinutes) {
+      when.textContent += ` ± ${timer.jitterMinutes} min`;
+    }
+    if (timer.warningMinutes) {
+      when.textContent += ` · warns ${timer.warningMinutes} min before`;
+    }
+
+    const recurrence = document.createElement("div");
+    recurrence.className = "timer-meta";
+    recurrence.textContent = recurrenceLabel(timer.recurrence);
+    if (timer.nextOccurrences?.length) {
+      recurrence.textContent += ` · then ${timer.nextOccurrences.map(fmtDate).join(", ")}`;
+    }
+
+    item.append(top, when, recurrence);
+
+    if (timer.runCount > 0 || timer.maxRuns) {
+      const runs = document.createElement("div");
+      runs.className = "timer-meta";
+      runs.textContent = `Has run ${timer.runCount} time(s)`;
+      if (timer.maxRuns) {
+        runs.textContent += ` of ${timer.maxRuns}`;
+      }
+      if (timer.lastRunAt) {
+        runs.textContent += ` · last at ${fmtDate(timer.lastRunAt)}`;
+      }
+      item.append(runs);
+    }
+
+    if (timer.message && ["popup", "push", "email"].includes(timer.action)) {
+      const msg = document.createElement("div");
+      msg.className = "timer-meta";
+      msg.textContent = `Message: ${timer.message}`;
+      item.append(msg);
+    }
+
+    if (timer.condition) {
+      const condition = document.createElement("div");
+      condition.className = "timer-meta";
+      condition.textContent = `Only if ${timer.condition}`;
+      if (powerStatus && POWER_CONDITION.test(timer.condition)) {
+        condition.textContent += ` · now ${describePower(powerStatus)}`;
+      }
+      item.append(condition);
+    }
+
+    if (timer.notes) {
+      const notes = document.createElement("div");
+      notes.className = "timer-meta timer-notes";
+      notes.textContent = timer.notes;
+      item.append(notes);
+    }
+
+    if (timer.managed) {
+      const managed = document.createElement("div");
+      managed.className = "timer-meta";
+      managed.textContent = "Required by your organization";
+      item.append(managed);
+    }
+
+    if (timer.tags?.length) {
+      const tags = document.createElement("div");
+      tags.className = "timer-meta";
+      tags.textContent = `Tags: ${timer.tags.join(", ")}`;
+      item.append(tags);
+    }
+
+    timersEl.append(item);
+  }
+};
+
+const POWER_CONDITION = /battery_percent|on_ac_power|is_charging/;
+let powerStatus = null;
+
+const describePower = (status) => {
+  const parts = [];
+  if (status.batteryPercent !== null) {
+    parts.push(`${status.batteryPercent}%`);
+    parts.push(status.charging ? "charging" : "on battery");
+    if (status.minutesRemaining !== null) {
+      parts.push(`${fmtMinutes(status.minutesRemaining)} ${status.charging ? "to full" : "left"}`);
+    }
+  }
+  if (status.powerSource) {
+    parts.push(status.powerSource);
+  }
+  return parts.join(", ");
+};
+
+// Shown next to timers whose condition depends on the battery.
+const loadPowerStatus = async () => {
+  try {
+    powerStatus = await invoke("get_power_status");
+  } catch (err) {
+    powerStatus = null;
+  }
+};
+
+const loadTimers = async () => {
+  try {
+    const timers = await invoke("list_timers");
+    renderTimers(timers);
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const showQuickEntryStatus = (text, isError = false) => {
+  quickEntryStatusEl.textContent = text;
+  quickEntryStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const describeDiagnostic = (diagnostic) =>
+  diagnostic.fragment ? `${diagnostic.message} ("${diagnostic.fragment}")` : diagnostic.message;
+
+const createFromText = async () => {
+  const text = quickEntryInput.value.trim();
+  if (!text) {
+    return;
+  }
+
+  try {
+    const result = await invoke("create_timer_from_text", { text });
+    const notes = result.diagnostics.map(describeDiagnostic);
+    if (!result.timer) {
+      showQuickEntryStatus(notes.join(" "), true);
+      return;
+    }
+
+    quickEntryInput.value = "";
+    showQuickEntryStatus(
+      [`${actionTitle(result.timer.action)} scheduled for ${fmtDate(result.timer.targetTime)}.`, ...notes].join(" "),
+    );
+    await loadTimers();
+    await loadProfiles();
+  } catch (err) {
+    showQuickEntryStatus(errorMessage(err), true);
+  }
+};
+
+const renderProfiles = (overview) => {
+  profileSelect.innerHTML = "";
+  for (const profile of overview.profiles) {
+    const option = document.createElement("option");
+    option.value = profile.name;
+    option.textContent = `${profile.name} (${profile.timerCount})`;
+    option.selected = profile.name === overview.activeProfile;
+    profileSelect.appendChild(option);
+  }
+  deleteProfileBtn.disabled = overview.profiles.length < 2;
+};
+
+const loadProfiles = async () => {
+  try {
+    renderProfiles(await invoke("list_profiles"));
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const switchProfile = async () => {
+  try {
+    renderProfiles(await invoke("set_active_profile", { name: profileSelect.value }));
+    showStatus(`Switched to profile ${profileSelect.value}.`);
+    await loadTimers();
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+    await loadProfiles();
+  }
+};
+
+const createProfile = async () => {
+  const name = profileNameInput.value.trim();
+  if (!name) {
+    showStatus("Enter a profile name.", true);
+    return;
+  }
+
+  try {
+    renderProfiles(await invoke("create_profile", { name }));
+    profileNameInput.value = "";
+    showStatus(`Profile ${name} created.`);
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const deleteProfile = async () => {
+  const name = profileNameInput.value.trim();
+  if (!name) {
+    showStatus("Enter the name of the profile to delete.", true);
+    return;
+  }
+
+  try {
+    renderProfiles(await invoke("delete_profile", { name }));
+    profileNameInput.value = "";
+    showStatus(`Profile ${name} and its timers deleted.`);
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const renderWorkspaces = (overview) => {
+  workspaceSelect.innerHTML = "";
+  for (const workspace of overview.workspaces) {
+    const option = document.createElement("option");
+    option.value = workspace.name;
+    option.textContent = `${workspace.name} (${workspace.timerCount})`;
+    option.selected = workspace.name === overview.activeWorkspace;
+    workspaceSelect.appendChild(option);
+  }
+  deleteWorkspaceBtn.disabled = overview.workspaces.length < 2;
+};
+
+const loadWorkspaces = async () => {
+  try {
+    renderWorkspaces(await invoke("list_workspaces"));
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const switchWorkspace = async () => {
+  try {
+    renderWorkspaces(await invoke("switch_workspace", { name: workspaceSelect.value }));
+    showStatus(`Switched to workspace ${workspaceSelect.value}.`);
+    await loadProfiles();
+    await loadTimers();
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+    await loadWorkspaces();
+  }
+};
+
+const createWorkspace = async () => {
+  const name = workspaceNameInput.value.trim();
+  if (!name) {
+    showStatus("Enter a workspace name.", true);
+    return;
+  }
+
+  try {
+    renderWorkspaces(await invoke("create_workspace", { name }));
+    workspaceNameInput.value = "";
+    showStatus(`Workspace ${name} created.`);
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const deleteWorkspace = async () => {
+  const name = workspaceNameInput.value.trim();
+  if (!name) {
+    showStatus("Enter the name of the workspace to delete.", true);
+    return;
+  }
+
+  try {
+    renderWorkspaces(await invoke("delete_workspace", { name }));
+    workspaceNameInput.value = "";
+    showStatus(`Workspace ${name} and its timers deleted.`);
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const renderPauseState = (state) => {
+  pauseState = state;
+  pauseToggleBtn.textContent = state.paused ? "Resume" : "Pause All";
+  pauseUntilInput.disabled = state.paused;
+  pauseStatusEl.textContent = state.paused
+    ? `All timers paused until ${fmtDate(state.pausedUntil)}.`
+    : "";
+};
+
+const loadPauseState = async () => {
+  try {
+    renderPauseState(await invoke("get_global_pause"));
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const togglePause = async () => {
+  let until = null;
+  if (!pauseState.paused) {
+    if (!pauseUntilInput.value) {
+      showStatus("Choose when timers should resume.", true);
+      return;
+    }
+    until = new Date(pauseUntilInput.value).toISOString();
+  }
+
+  try {
+    renderPauseState(await invoke("set_global_pause", { until }));
+    showStatus(pauseState.paused ? "All timers paused." : "Timers resumed.");
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const shiftTimers = async () => {
+  const minutes = Number(shiftMinutesInput.value || 0);
+  const filter = {
+    profile: profileSelect.value || null,
+    before: shiftBeforeInput.value ? new Date(shiftBeforeInput.value).toISOString() : null,
+  };
+
+  shiftTimersBtn.disabled = true;
+  try {
+    const shifted = await invoke("shift_timers", { minutes, filter });
+    showStatus(
+      shifted.length
+        ? `Moved ${shifted.length} timer(s) ${minutes > 0 ? "later" : "earlier"} by ${Math.abs(minutes)} min.`
+        : "No timers matched.",
+    );
+    await loadTimers();
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  } finally {
+    shiftTimersBtn.disabled = false;
+  }
+};
+
+const WEEKDAY_LABELS = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+const renderHeatmap = (heatmap) => {
+  heatmapEl.innerHTML = "";
+  heatmapEl.append(document.createElement("span"));
+  for (let hour = 0; hour < 24; hour += 1) {
+    const label = document.createElement("span");
+    label.textContent = hour % 6 === 0 ? String(hour) : "";
+    heatmapEl.append(label);
+  }
+
+  for (const cell of heatmap.cells) {
+    if (cell.hour === 0) {
+      const label = document.createElement("span");
+      label.textContent = WEEKDAY_LABELS[cell.weekday];
+      heatmapEl.append(label);
+    }
+    const square = document.createElement("span");
+    square.className = "heatmap-cell";
+    if (cell.total) {
+      const strength = 0.25 + (0.75 * cell.total) / heatmap.maxTotal;
+      square.style.background = `rgba(31, 140, 178, ${strength.toFixed(2)})`;
+    }
+    const actions = Object.entries(cell.byAction)
+      .map(([action, count]) => `${action}: ${count}`)
+      .join(", ");
+    square.title = `${WEEKDAY_LABELS[cell.weekday]} ${String(cell.hour).padStart(2, "0")}:00 · ${cell.total} run(s)${actions ? ` (${actions})` : ""}`;
+    heatmapEl.append(square);
+  }
+};
+
+const loadHeatmap = async () => {
+  try {
+    renderHeatmap(await invoke("get_activity_heatmap", { range: heatmapRangeSelect.value }));
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const AUDIT_LOG_LIMIT = 20;
+
+const auditOriginLabel = (origin) => {
+  if (origin.kind === "remote") {
+    return `remote ${origin.address}`;
+  }
+  return origin.kind;
+};
+
+const loadAuditLog = async () => {
+  try {
+    const entries = await invoke("get_audit_log", { limit: AUDIT_LOG_LIMIT });
+    auditLogEl.innerHTML = "";
+
+    if (!entries.length) {
+      const empty = document.createElement("li");
+      empty.className = "empty";
+      empty.textContent = "No changes recorded yet.";
+      auditLogEl.appendChild(empty);
+      return;
+    }
+
+    for (const entry of entries) {
+      const item = document.createElement("li");
+      item.className = "timer-item";
+      item.textContent = entry.summary;
+
+      const meta = document.createElement("div");
+      meta.className = "timer-meta";
+      meta.textContent = `${fmtDate(entry.timestamp)} · ${auditOriginLabel(entry.origin)}`;
+
+      item.append(meta);
+      auditLogEl.append(item);
+    }
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+let pendingCrashReport = null;
+
+const loadCrashReports = async () => {
+  try {
+    const reports = await invoke("list_crash_reports");
+    pendingCrashReport = reports[0] ?? null;
+  } catch (err) {
+    pendingCrashReport = null;
+  }
+
+  crashPromptEl.classList.toggle("hidden", !pendingCrashReport);
+  if (pendingCrashReport) {
+    crashSummaryEl.textContent = `${fmtDate(pendingCrashReport.timestamp)} · ${pendingCrashReport.message}`;
+  }
+};
+
+const resolveCrashReport = async (send) => {
+  if (!pendingCrashReport) {
+    return;
+  }
+
+  try {
+    await invoke(send ? "send_crash_report" : "delete_crash_report", { id: pendingCrashReport.id });
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+  await loadCrashReports();
+};
+
+const PERMISSION_LABELS = {
+  granted: "granted",
+  denied: "denied",
+  notDetermined: "not requested yet",
+  unknown: "unknown",
+};
+
+const showPermissionStatus = (status) => {
+  const missing = status.automation !== "granted" || status.accessibility !== "granted";
+  permissionStatusEl.textContent =
+    `Automation: ${PERMISSION_LABELS[status.automation]} · Accessibility: ${PERMISSION_LABELS[status.accessibility]}`;
+  permissionStatusEl.style.color = missing ? "#c30e2e" : "#4f7480";
+};
+
+const loadPermissionStatus = async () => {
+  try {
+    showPermissionStatus(await invoke("get_permission_status"));
+  } catch (err) {
+    permissionStatusEl.textContent = errorMessage(err);
+    permissionStatusEl.style.color = "#c30e2e";
+  }
+};
+
+const requestPermissions = async () => {
+  requestPermissionsBtn.disabled = true;
+  try {
+    showPermissionStatus(await invoke("request_permissions"));
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  } finally {
+    requestPermissionsBtn.disabled = false;
+  }
+};
+
+const loadLockCompatibility = async () => {
+  try {
+    lockCompatibilityInput.checked = await invoke("get_lock_compatibility");
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const saveLockCompatibility = async () => {
+  try {
+    lockCompatibilityInput.checked = await invoke("set_lock_compatibility", {
+      enabled: lockCompatibilityInput.checked,
+    });
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const INSTALL_SOURCE_LABELS = {
+  homebrew: "Homebrew",
+  dmg: "DMG",
+  mountedImage: "running from the DMG",
+  development: "development build",
+};
+
+// Past this, the Updates card suggests scheduling a restart.
+const LONG_UPTIME_DAYS = 14;
+
+const loadSystemInfo = async () => {
+  try {
+    const info = await invoke("get_system_info");
+    const parts = [];
+    if (info.macosVersion) {
+      parts.push(info.macosBuild ? `macOS ${info.macosVersion} (${info.macosBuild})` : `macOS ${info.macosVersion}`);
+    }
+    const uptimeDays = info.uptimeSeconds == null ? null : Math.floor(info.uptimeSeconds / 86400);
+    if (uptimeDays != null) {
+      parts.push(uptimeDays === 0 ? "last restarted today" : `last restarted ${uptimeDays} day${uptimeDays === 1 ? "" : "s"} ago`);
+    }
+    const nextEvent = info.scheduledPowerEvents.find((event) => event.at);
+    if (nextEvent) {
+      parts.push(`next scheduled ${nextEvent.kind} ${fmtDate(nextEvent.at)}`);
+    }
+    const longUptime = uptimeDays != null && uptimeDays >= LONG_UPTIME_DAYS;
+    systemInfoEl.textContent = parts.join(" · ") + (longUptime ? " — schedule one?" : "");
+    scheduleRestartBtn.classList.toggle("hidden", !longUptime);
+  } catch (err) {
+    systemInfoEl.textContent = errorMessage(err);
+  }
+};
+
+const loadManagedConfig = async () => {
+  try {
+    const managed = await invoke("get_managed_config");
+    const { policy } = managed;
+    if (!managed.sources.length && !policy.forbiddenActions.length && !policy.forbidRemoteControl && !policy.forbidHooks) {
+      return;
+    }
+    const parts = ["Managed by your organization"];
+    if (managed.lockedSettings.length) {
+      parts.push(`locked settings: ${managed.lockedSettings.join(", ")}`);
+    }
+    if (managed.mandatoryTimers.length) {
+      parts.push(`${managed.mandatoryTimers.length} required timer(s)`);
+    }
+    if (policy.forbiddenActions.length) {
+      parts.push(`not allowed: ${policy.forbiddenActions.join(", ")}`);
+    }
+    if (policy.forbidRemoteControl) {
+      parts.push("remote API is read-only");
+    }
+    if (policy.forbidHooks) {
+      parts.push("no hook scripts");
+    }
+    managedBannerEl.textContent = parts.join(" · ");
+    managedBannerEl.classList.remove("hidden");
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+// Select values whose action type isn't the part before any ":".
+const OPTION_ACTION_KINDS = { appearance: "setAppearance", nightShiftOn: "nightShift", nightShiftOff: "nightShift" };
+
+const loadPolicy = async () => {
+  try {
+    const policy = await invoke("get_policy");
+    const forbidden = policy.forbiddenActions.map((kind) => kind.toLowerCase());
+    for (const option of actionInput.options) {
+      const base = option.value.split(":")[0];
+      option.disabled = forbidden.includes((OPTION_ACTION_KINDS[base] ?? base).toLowerCase());
+    }
+    preHookInput.disabled = policy.forbidHooks;
+    postHookInput.disabled = policy.forbidHooks;
+    if (actionInput.selectedOptions[0]?.disabled) {
+      const allowed = [...actionInput.options].find((option) => !option.disabled);
+      actionInput.value = allowed ? allowed.value : "";
+      actionInput.dispatchEvent(new Event("change"));
+    }
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  }
+};
+
+const prefillRestart = () => {
+  quickEntryInput.value = "restart at 3am";
+  quickEntryInput.scrollIntoView({ behavior: "smooth", block: "center" });
+  quickEntryInput.focus();
+};
+
+const loadAppInfo = async () => {
+  try {
+    const info = await invoke("get_app_info");
+    appInfoEl.textContent =
+      `Build ${info.buildHash} · ${info.architecture} · ${INSTALL_SOURCE_LABELS[info.installSource]} · data in ${info.storageDir}`;
+  } catch (err) {
+    appInfoEl.textContent = errorMessage(err);
+  }
+};
+
+const HEALTH_STATUS_COLORS = { ok: "#4f7480", warning: "#b26a00", error: "#c30e2e" };
+
+const runHealthCheck = async () => {
+  runHealthCheckBtn.disabled = true;
+  healthChecksEl.innerHTML = "";
+
+  try {
+    const report = await invoke("health_check");
+    for (const check of report.checks) {
+      const item = document.createElement("li");
+      item.className = "timer-item";
+      item.textContent = `${check.name}: ${check.status}`;
+      item.style.color = HEALTH_STATUS_COLORS[check.status];
+
+      const meta = document.createElement("div");
+      meta.className = "timer-meta";
+      meta.textContent = check.detail;
+
+      item.append(meta);
+      healthChecksEl.append(item);
+    }
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  } finally {
+    runHealthCheckBtn.disabled = false;
+  }
+};
+
+const runSelfTest = async () => {
+  runSelfTestBtn.disabled = true;
+  healthChecksEl.innerHTML = "";
+
+  try {
+    const report = await invoke("run_self_test");
+    for (const result of report.actions) {
+      const item = document.createElement("li");
+      item.className = "timer-item";
+      item.textContent = `${result.action}: ${result.status}`;
+      item.style.color = HEALTH_STATUS_COLORS[result.status];
+
+      const meta = document.createElement("div");
+      meta.className = "timer-meta";
+      meta.textContent = result.steps.map((step) => `${step.name}: ${step.detail}`).join(" · ");
+
+      item.append(meta);
+      healthChecksEl.append(item);
+    }
+  } catch (err) {
+    showStatus(errorMessage(err), true);
+  } finally {
+    runSelfTestBtn.disabled = false;
+  }
+};
+
+const showCrontabStatus = (text, isError = false) => {
+  crontabStatusEl.textContent = text;
+  crontabStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const importCrontab = async () => {
+  const text = crontabTextInput.value;
+  if (!text.trim()) {
+    showCrontabStatus("Paste at least one crontab line.", true);
+    return;
+  }
+
+  try {
+    const result = await invoke("import_crontab", { text });
+    const skipped = result.skipped.map((line) => `line ${line.line}: ${line.reason}`);
+    showCrontabStatus(
+      [`Imported ${result.created.length} timer(s).`, ...skipped].join(" "),
+      result.skipped.length > 0,
+    );
+    if (result.skipped.length === 0) {
+      crontabTextInput.value = "";
+    }
+    await loadTimers();
+    await loadProfiles();
+  } catch (err) {
+    showCrontabStatus(errorMessage(err), true);
+  }
+};
+
+const showQuietStatus = (text, isError = false) => {
+  quietStatusEl.textContent = text;
+  quietStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const loadQuietHours = async () => {
+  try {
+    const quietHours = await invoke("get_quiet_hours");
+    quietEnabledInput.checked = Boolean(quietHours);
+    if (quietHours) {
+      quietStartInput.value = quietHours.start.slice(0, 5);
+      quietEndInput.value = quietHours.end.slice(0, 5);
+      quietModeSelect.value = quietHours.mode;
+      quietBlockWebsitesInput.checked = quietHours.blockWebsites;
+    }
+  } catch (err) {
+    showQuietStatus(errorMessage(err), true);
+  }
+};
+
+const saveQuietHours = async () => {
+  const quietHours = quietEnabledInput.checked
+    ? {
+        start: `${quietStartInput.value}:00`,
+        end: `${quietEndInput.value}:00`,
+        mode: quietModeSelect.value,
+        blockWebsites: quietBlockWebsitesInput.checked,
+      }
+    : null;
+
+  try {
+    await invoke("set_quiet_hours", { quietHours });
+    showQuietStatus(quietHours ? "Quiet hours saved." : "Quiet hours disabled.");
+  } catch (err) {
+    showQuietStatus(errorMessage(err), true);
+  }
+};
+
+const showWorkingStatus = (text, isError = false) => {
+  workingStatusEl.textContent = text;
+  workingStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const loadWorkingHours = async () => {
+  try {
+    const workingHours = await invoke("get_working_hours");
+    workingEnabledInput.checked = Boolean(workingHours);
+    if (workingHours) {
+      workingStartInput.value = workingHours.start.slice(0, 5);
+      workingEndInput.value = workingHours.end.slice(0, 5);
+      workingPolicySelect.value = workingHours.policy;
+    }
+  } catch (err) {
+    showWorkingStatus(errorMessage(err), true);
+  }
+};
+
+const saveWorkingHours = async () => {
+  const workingHours = workingEnabledInput.checked
+    ? {
+        start: `${workingStartInput.value}:00`,
+        end: `${workingEndInput.value}:00`,
+        policy: workingPolicySelect.value,
+      }
+    : null;
+
+  try {
+    await invoke("set_working_hours", { workingHours });
+    showWorkingStatus(workingHours ? "Working hours saved." : "Working hours disabled.");
+  } catch (err) {
+    showWorkingStatus(errorMessage(err), true);
+  }
+};
+
+const showUsageStatus = (text, isError = false) => {
+  usageStatusEl.textContent = text;
+  usageStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const loadUsageToday = async () => {
+  try {
+    const usage = await invoke("get_usage_today");
+    usageTodayEl.textContent = usage.limitMinutes
+      ? `Used today: ${fmtMinutes(usage.activeMinutes)} of ${fmtMinutes(usage.limitMinutes)}`
+      : `Used today: ${fmtMinutes(usage.activeMinutes)}`;
+  } catch (err) {
+    showUsageStatus(errorMessage(err), true);
+  }
+};
+
+const fmtClock = (iso) => new Date(iso).toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" });
+
+const renderUsageReport = (report) => {
+  usageReportEl.innerHTML = "";
+  const summary = [`Average ${fmtMinutes(report.averageActiveMinutes)} a day`];
+  if (report.limitMinutes) {
+    summary.push(`over the ${fmtMinutes(report.limitMinutes)} limit on ${report.daysOverLimit} day(s)`);
+  }
+  usageReportSummaryEl.textContent = report.days.length ? summary.join(", ") : "";
+
+  if (!report.days.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No screen time recorded yet.";
+    usageReportEl.appendChild(empty);
+    return;
+  }
+
+  for (const day of [...report.days].reverse()) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const top = document.createElement("div");
+    top.className = "timer-top";
+    const title = document.createElement("strong");
+    title.textContent = new Date(`${day.day}T12:00:00`).toLocaleDateString([], {
+      weekday: "short",
+      month: "short",
+      day: "numeric",
+    });
+    const total = document.createElement("span");
+    total.textContent = fmtMinutes(day.activeMinutes);
+    top.append(title, total);
+
+    const meta = document.createElement("div");
+    meta.className = "timer-meta";
+    const parts = [];
+    if (day.firstActiveAt && day.lastActiveAt) {
+      parts.push(`${fmtClock(day.firstActiveAt)} - ${fmtClock(day.lastActiveAt)}`);
+    }
+    if (day.limitTriggers) {
+      parts.push(`limit enforced ${day.limitTriggers} time(s)`);
+    }
+    meta.textContent = parts.join(" | ");
+
+    item.append(top, meta);
+    usageReportEl.append(item);
+  }
+};
+
+const loadUsageReport = async () => {
+  try {
+    renderUsageReport(await invoke("get_usage_report", { range: usageReportRangeSelect.value }));
+  } catch (err) {
+    showUsageStatus(errorMessage(err), true);
+  }
+};
+
+const IDLE_BREAK_SECONDS = 180;
+
+// Shown above the timers so break reminders have context.
+const loadActiveFor = async () => {
+  try {
+    const idleSeconds = await invoke("get_idle_seconds");
+    if (idleSeconds >= IDLE_BREAK_SECONDS) {
+      activeForEl.textContent = `Idle for ${fmtMinutes(Math.floor(idleSeconds / 60))}`;
+      return;
+    }
+    const { activeSince } = await invoke("get_usage_today");
+    const minutes = activeSince ? Math.floor((Date.now() - new Date(activeSince).getTime()) / 60000) : 0;
+    activeForEl.textContent = `You've been active for ${fmtMinutes(minutes)}`;
+  } catch (err) {
+    activeForEl.textContent = "";
+  }
+};
+
+const loadUsageLimit = async () => {
+  try {
+    const usageLimit = await invoke("get_usage_limit");
+    usageEnabledInput.checked = Boolean(usageLimit);
+    if (usageLimit) {
+      usageHoursInput.value = usageLimit.limitMinutes / 60;
+      usageWarnInput.value = usageLimit.warnMinutes;
+      usageActionSelect.value = usageLimit.action;
+      usageDayStartInput.value = usageLimit.dayStartsAt.slice(0, 5);
+    }
+  } catch (err) {
+    showUsageStatus(errorMessage(err), true);
+  }
+  await loadUsageToday();
+};
+
+const saveUsageLimit = async () => {
+  const usageLimit = usageEnabledInput.checked
+    ? {
+        limitMinutes: Math.round(Number(usageHoursInput.value) * 60),
+        warnMinutes: Number(usageWarnInput.value) || 0,
+        action: usageActionSelect.value,
+        dayStartsAt: `${usageDayStartInput.value}:00`,
+      }
+    : null;
+
+  try {
+    await invoke("set_usage_limit", { usageLimit });
+    showUsageStatus(usageLimit ? "Usage limit saved." : "Usage limit disabled.");
+    await loadUsageToday();
+  } catch (err) {
+    showUsageStatus(errorMessage(err), true);
+  }
+};
+
+const showBreakStatus = (text, isError = false) => {
+  breakStatusEl.textContent = text;
+  breakStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const syncBreakPreset = () => {
+  const value = `${breakWorkMinutesInput.value}:${breakSecondsInput.value}`;
+  const preset = [...breakPresetSelect.options].find((option) => option.value === value);
+  breakPresetSelect.value = preset ? preset.value : "custom";
+};
+
+const applyBreakPreset = () => {
+  if (breakPresetSelect.value === "custom") {
+    return;
+  }
+  const [workMinutes, breakSeconds] = breakPresetSelect.value.split(":");
+  breakWorkMinutesInput.value = workMinutes;
+  breakSecondsInput.value = breakSeconds;
+};
+
+const loadBreakStats = async () => {
+  try {
+    const report = await invoke("get_break_stats", { range: "week" });
+    breakStatsEl.textContent = report.nudged
+      ? `Last 7 days: ${report.taken} of ${report.nudged} breaks taken, ${report.skipped} skipped` +
+        (report.postponed ? `, ${report.postponed} held while presenting.` : ".")
+      : "No break reminders in the last 7 days.";
+  } catch (err) {
+    breakStatsEl.textContent = errorMessage(err);
+  }
+};
+
+const loadBreakReminders = async () => {
+  try {
+    const reminders = await invoke("get_break_reminders");
+    breakEnabledInput.checked = Boolean(reminders);
+    if (reminders) {
+      breakWorkMinutesInput.value = reminders.workMinutes;
+      breakSecondsInput.value = reminders.breakSeconds;
+      breakMessageInput.value = reminders.message ?? "";
+      breakSuppressInput.checked = reminders.suppressWhilePresenting;
+      syncBreakPreset();
+    }
+  } catch (err) {
+    showBreakStatus(errorMessage(err), true);
+  }
+  await loadBreakStats();
+};
+
+const saveBreakReminders = async () => {
+  const breakReminders = breakEnabledInput.checked
+    ? {
+        workMinutes: Number(breakWorkMinutesInput.value) || 0,
+        breakSeconds: Number(breakSecondsInput.value) || 0,
+        message: breakMessageInput.value.trim() || null,
+        suppressWhilePresenting: breakSuppressInput.checked,
+      }
+    : null;
+
+  try {
+    await invoke("set_break_reminders", { breakReminders });
+    showBreakStatus(breakReminders ? "Break reminders saved." : "Break reminders turned off.");
+  } catch (err) {
+    showBreakStatus(errorMessage(err), true);
+  }
+};
+
+const showHolidayStatus = (text, isError = false) => {
+  holidayStatusEl.textContent = text;
+  holidayStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const loadHolidayRegion = async () => {
+  try {
+    holidayRegionInput.value = (await invoke("get_holiday_region")) ?? "";
+  } catch (err) {
+    showHolidayStatus(errorMessage(err), true);
+  }
+};
+
+const saveHolidayRegion = async () => {
+  saveHolidayRegionBtn.disabled = true;
+  try {
+    const region = await invoke("set_holiday_region", { region: holidayRegionInput.value || null });
+    holidayRegionInput.value = region ?? "";
+    showHolidayStatus(region ? `Using ${region} public holidays.` : "Holiday skipping disabled.");
+    updateRecurrencePreview();
+  } catch (err) {
+    showHolidayStatus(errorMessage(err), true);
+  } finally {
+    saveHolidayRegionBtn.disabled = false;
+  }
+};
+
+const showPushStatus = (text, isError = false) => {
+  pushStatusEl.textContent = text;
+  pushStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const togglePushFields = () => {
+  const service = pushServiceSelect.value;
+  pushServerWrap.style.display = service === "ntfy" ? "grid" : "none";
+  pushAddressLabel.textContent = service === "pushover" ? "User key" : "Topic";
+  pushSecretLabel.textContent = service === "pushover" ? "Application token" : "Access token (optional)";
+  pushAddressInput.disabled = !service;
+  pushSecretInput.disabled = !service;
+  testPushBtn.disabled = !service;
+};
+
+const renderPushSettings = (push) => {
+  const target = push.target;
+  pushServiceSelect.value = target?.service ?? "";
+  pushServerInput.value = target?.server ?? "https://ntfy.sh";
+  pushAddressInput.value = target?.topic ?? target?.userKey ?? "";
+  pushSecretInput.value = "";
+  pushSecretInput.placeholder = push.hasSecret ? "Saved in keychain" : "";
+  togglePushFields();
+};
+
+const loadPushSettings = async () => {
+  try {
+    renderPushSettings(await invoke("get_push_settings"));
+  } catch (err) {
+    showPushStatus(errorMessage(err), true);
+  }
+};
+
+const savePushSettings = async () => {
+  const service = pushServiceSelect.value;
+  let target = null;
+  if (service === "ntfy") {
+    target = { service, server: pushServerInput.value, topic: pushAddressInput.value };
+  } else if (service === "pushover") {
+    target = { service, userKey: pushAddressInput.value };
+  }
+
+  savePushBtn.disabled = true;
+  try {
+    const push = await invoke("set_push_settings", {
+      target,
+      // An empty field keeps the token already in the keychain.
+      secret: pushSecretInput.value ? pushSecretInput.value : null,
+    });
+    renderPushSettings(push);
+    showPushStatus(push.target ? "Push notifications saved." : "Push notifications turned off.");
+  } catch (err) {
+    showPushStatus(errorMessage(err), true);
+  } finally {
+    savePushBtn.disabled = false;
+  }
+};
+
+const sendTestPush = async () => {
+  testPushBtn.disabled = true;
+  try {
+    showPushStatus(await invoke("send_test_push"));
+  } catch (err) {
+    showPushStatus(errorMessage(err), true);
+  } finally {
+    testPushBtn.disabled = false;
+  }
+};
+
+const showEmailStatus = (text, isError = false) => {
+  emailStatusEl.textContent = text;
+  emailStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const renderEmailSettings = (email) => {
+  const smtp = email.smtp;
+  emailEnabledInput.checked = Boolean(smtp);
+  smtpHostInput.value = smtp?.host ?? "";
+  smtpPortInput.value = smtp?.port ?? 587;
+  smtpSecuritySelect.value = smtp?.security ?? "startTls";
+  smtpUsernameInput.value = smtp?.username ?? "";
+  smtpFromInput.value = smtp?.from ?? "";
+  smtpToInput.value = smtp?.to ?? "";
+  smtpNotifyFailuresInput.checked = Boolean(smtp?.notifyFailures);
+  smtpPasswordInput.value = "";
+  smtpPasswordInput.placeholder = email.hasPassword ? "Saved in keychain" : "";
+  testEmailBtn.disabled = !smtp;
+};
+
+const loadEmailSettings = async () => {
+  try {
+    renderEmailSettings(await invoke("get_email_settings"));
+  } catch (err) {
+    showEmailStatus(errorMessage(err), true);
+  }
+};
+
+const saveEmailSettings = async () => {
+  const smtp = emailEnabledInput.checked
+    ? {
+        host: smtpHostInput.value,
+        port: Number(smtpPortInput.value || 0),
+        security: smtpSecuritySelect.value,
+        username: smtpUsernameInput.value.trim() || null,
+        from: smtpFromInput.value,
+        to: smtpToInput.value,
+        notifyFailures: smtpNotifyFailuresInput.checked,
+      }
+    : null;
+
+  saveEmailBtn.disabled = true;
+  try {
+    const email = await invoke("set_email_settings", {
+      smtp,
+      // An empty field keeps the password already in the keychain.
+      password: smtpPasswordInput.value ? smtpPasswordInput.value : null,
+    });
+    renderEmailSettings(email);
+    showEmailStatus(email.smtp ? "Email settings saved." : "Email turned off.");
+  } catch (err) {
+    showEmailStatus(errorMessage(err), true);
+  } finally {
+    saveEmailBtn.disabled = false;
+  }
+};
+
+const sendTestEmail = async () => {
+  testEmailBtn.disabled = true;
+  try {
+    showEmailStatus(await invoke("send_test_email"));
+  } catch (err) {
+    showEmailStatus(errorMessage(err), true);
+  } finally {
+    testEmailBtn.disabled = false;
+  }
+};
+
+const showCalendarLogStatus = (text, isError = false) => {
+  calendarLogStatusEl.textContent = text;
+  calendarLogStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const showStayAwakeStatus = (text, isError = false) => {
+  stayAwakeStatusEl.textContent = text;
+  stayAwakeStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const loadStayAwake = async () => {
+  try {
+    stayAwakeMinutesInput.value = await invoke("get_stay_awake_minutes");
+  } catch (err) {
+    showStayAwakeStatus(errorMessage(err), true);
+  }
+};
+
+const saveStayAwake = async () => {
+  try {
+    const minutes = await invoke("set_stay_awake_minutes", { minutes: Number(stayAwakeMinutesInput.value) || 0 });
+    stayAwakeMinutesInput.value = minutes;
+    showStayAwakeStatus(minutes ? `The Mac stays awake ${minutes} min before critical timers.` : "Off.");
+  } catch (err) {
+    showStayAwakeStatus(errorMessage(err), true);
+  }
+};
+
+const showConflictPolicyStatus = (text, isError = false) => {
+  conflictPolicyStatusEl.textContent = text;
+  conflictPolicyStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const loadConflictPolicy = async () => {
+  try {
+    conflictPolicySelect.value = await invoke("get_conflict_policy");
+  } catch (err) {
+    showConflictPolicyStatus(errorMessage(err), true);
+  }
+};
+
+const saveConflictPolicy = async () => {
+  try {
+    const policy = await invoke("set_conflict_policy", { policy: conflictPolicySelect.value });
+    conflictPolicySelect.value = policy;
+    showConflictPolicyStatus(
+      policy === "destructiveWins" ? "Locks next to a shutdown, reboot or log out are skipped once it runs." : "Both actions run."
+    );
+  } catch (err) {
+    showConflictPolicyStatus(errorMessage(err), true);
+  }
+};
+
+let helperInstalled = false;
+
+const showHelperStatus = (text, isError = false) => {
+  helperStatusEl.textContent = text;
+  helperStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const renderHelperStatus = (status) => {
+  helperInstalled = status.installed;
+  toggleHelperBtn.textContent = helperInstalled ? "Remove" : "Install";
+  showHelperStatus(helperInstalled ? "Installed. Forced shutdowns and reboots go through the helper." : "Not installed.");
+};
+
+const loadHelperStatus = async () => {
+  try {
+    renderHelperStatus(await invoke("get_helper_status"));
+  } catch (err) {
+    showHelperStatus(errorMessage(err), true);
+  }
+};
+
+const toggleHelper = async () => {
+  toggleHelperBtn.disabled = true;
+  try {
+    renderHelperStatus(await invoke(helperInstalled ? "uninstall_helper" : "install_helper"));
+  } catch (err) {
+    showHelperStatus(errorMessage(err), true);
+  } finally {
+    toggleHelperBtn.disabled = false;
+  }
+};
+
+const loadCalendarLog = async () => {
+  try {
+    calendarLogInput.checked = await invoke("get_calendar_log");
+  } catch (err) {
+    showCalendarLogStatus(errorMessage(err), true);
+  }
+};
+
+const saveCalendarLog = async () => {
+  calendarLogInput.disabled = true;
+  try {
+    calendarLogInput.checked = await invoke("set_calendar_log", { enabled: calendarLogInput.checked });
+    showCalendarLogStatus(calendarLogInput.checked ? "Runs will be added to the LockPilot calendar." : "");
+  } catch (err) {
+    calendarLogInput.checked = false;
+    showCalendarLogStatus(errorMessage(err), true);
+  } finally {
+    calendarLogInput.disabled = false;
+  }
+};
+
+const showFocusStatus = (text, isError = false) => {
+  focusStatusEl.textContent = text;
+  focusStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const renderFocusSessions = (sessions) => {
+  focusSessionsEl.innerHTML = "";
+
+  if (!sessions.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No focus sessions.";
+    focusSessionsEl.appendChild(empty);
+    return;
+  }
+
+  const now = Date.now();
+  for (const session of sessions) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const top = document.createElement("div");
+    top.className = "timer-top";
+
+    const running = new Date(session.startsAt).getTime() <= now;
+    const title = document.createElement("strong");
+    title.textContent = `${session.label ?? "Focus session"}${running ? " (running)" : ""}`;
+
+    const cancelBtn = document.createElement("button");
+    cancelBtn.className = "danger";
+    cancelBtn.textContent = running ? "End" : "Cancel";
+    cancelBtn.addEventListener("click", async () => {
+      try {
+        await invoke("cancel_focus_session", { id: session.id });
+        await loadFocusSessions();
+      } catch (err) {
+        showFocusStatus(errorMessage(err), true);
+      }
+    });
+
+    top.append(title, cancelBtn);
+
+    const meta = document.createElement("div");
+    meta.className = "timer-meta";
+    const verb = session.mode === "hide" ? "Hides" : "Quits";
+    meta.textContent = `${fmtDate(session.startsAt)} - ${fmtDate(session.endsAt)}`;
+    if (session.blockedApps.length) {
+      meta.textContent += ` | ${verb} ${session.blockedApps.join(", ")}`;
+    }
+    if (session.blockWebsites) {
+      meta.textContent += " | Blocks websites";
+    }
+    if (session.violations) {
+      meta.textContent += ` | Blocked ${session.violations} time(s)`;
+    }
+
+    item.append(top, meta);
+    focusSessionsEl.append(item);
+  }
+};
+
+const loadFocusSessions = async () => {
+  try {
+    renderFocusSessions(await invoke("list_focus_sessions"));
+  } catch (err) {
+    showFocusStatus(errorMessage(err), true);
+  }
+};
+
+const createFocusSession = async () => {
+  if (!focusEndInput.value) {
+    showFocusStatus("Choose when the session ends.", true);
+    return;
+  }
+
+  createFocusSessionBtn.disabled = true;
+  try {
+    const session = await invoke("create_focus_session", {
+      request: {
+        label: focusLabelInput.value || null,
+        startsAt: focusStartInput.value ? new Date(focusStartInput.value).toISOString() : null,
+        endsAt: new Date(focusEndInput.value).toISOString(),
+        blockedApps: focusAppsInput.value.split(","),
+        mode: focusModeSelect.value,
+        blockWebsites: focusBlockWebsitesInput.checked,
+      },
+    });
+    focusLabelInput.value = "";
+    focusStartInput.value = "";
+    focusEndInput.value = "";
+    const blocked = [...session.blockedApps, ...(session.blockWebsites ? ["websites"] : [])];
+    showFocusStatus(`Blocking ${blocked.join(", ")} until ${fmtDate(session.endsAt)}.`);
+    await loadFocusSessions();
+  } catch (err) {
+    showFocusStatus(errorMessage(err), true);
+  } finally {
+    createFocusSessionBtn.disabled = false;
+  }
+};
+
+const loadBlockedDomains = async () => {
+  try {
+    blockedDomainsInput.value = (await invoke("list_blocked_domains")).join("\n");
+  } catch (err) {
+    showFocusStatus(errorMessage(err), true);
+  }
+};
+
+const saveBlockedDomains = async () => {
+  try {
+    const domains = await invoke("set_blocked_domains", { domains: blockedDomainsInput.value.split(/[\s,]+/) });
+    blockedDomainsInput.value = domains.join("\n");
+    showFocusStatus(`${domains.length} website(s) saved.`);
+  } catch (err) {
+    showFocusStatus(errorMessage(err), true);
+  }
+};
+
+const showIcsStatus = (text, isError = false) => {
+  icsStatusEl.textContent = text;
+  icsStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
 };
 
-const selectedChannel = () => updateChannelSelect.value;
+const renderIcsSubscriptions = (subscriptions) => {
+  icsSubscriptionsEl.innerHTML = "";
 
-const toLocalDateTimeValue = (date) => {
-  const pad = (n) => String(n).padStart(2, "0");
-  return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}T${pad(date.getHours())}:${pad(date.getMinutes())}:${pad(date.getSeconds())}`;
-};
+  if (!subscriptions.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No calendar subscriptions.";
+    icsSubscriptionsEl.appendChild(empty);
+    return;
+  }
 
-const toggleMessage = () => {
-  const isPopup = actionInput.value === "popup";
-  messageWrap.style.display = isPopup ? "grid" : "none";
-  messageInput.required = isPopup;
+  for (const subscription of subscriptions) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const top = document.createElement("div");
+    top.className = "timer-top";
+
+    const { mapping } = subscription;
+    const title = document.createElement("strong");
+    const matching = mapping.summaryContains ? `"${mapping.summaryContains}"` : "every event";
+    title.textContent = `${mapping.action} ${mapping.offsetMinutes} min after ${mapping.anchor} of ${matching}`;
+
+    const removeBtn = document.createElement("button");
+    removeBtn.className = "danger";
+    removeBtn.textContent = "Unsubscribe";
+    removeBtn.addEventListener("click", async () => {
+      try {
+        await invoke("unsubscribe_ics", { id: subscription.id });
+        await loadIcsSubscriptions();
+        await loadTimers();
+      } catch (err) {
+        showIcsStatus(errorMessage(err), true);
+      }
+    });
+
+    top.append(title, removeBtn);
+
+    const meta = document.createElement("div");
+    meta.className = "timer-meta";
+    const synced = subscription.lastSyncedAt ? `Synced ${fmtDate(subscription.lastSyncedAt)}` : "Not synced yet";
+    meta.textContent = subscription.lastError
+      ? `${subscription.url} | ${synced}: ${subscription.lastError}`
+      : `${subscription.url} | ${synced}`;
+
+    item.append(top, meta);
+    icsSubscriptionsEl.append(item);
+  }
 };
 
-const toggleRecurrence = () => {
-  const recurring = recurrencePresetInput.value !== "none";
-  const needsInterval =
-    recurrencePresetInput.value === "every_n_hours" ||
-    recurrencePresetInput.value === "every_n_minutes";
-  intervalWrap.classList.toggle("hidden", !needsInterval);
-  intervalHoursInput.required = needsInterval;
-  intervalHoursInput.max = recurrencePresetInput.value === "every_n_minutes" ? "1440" : "24";
+const loadIcsSubscriptions = async () => {
+  try {
+    renderIcsSubscriptions(await invoke("list_ics_subscriptions"));
+  } catch (err) {
+    showIcsStatus(errorMessage(err), true);
+  }
+};
 
-  if (!recurring) {
-    intervalWrap.classList.add("hidden");
+const subscribeIcs = async () => {
+  subscribeIcsBtn.disabled = true;
+  try {
+    const subscription = await invoke("subscribe_ics", {
+      url: icsUrlInput.value,
+      mapping: {
+        summaryContains: icsSummaryInput.value.trim(),
+        action: icsActionSelect.value,
+        anchor: icsAnchorSelect.value,
+        offsetMinutes: Number(icsOffsetInput.value) || 0,
+        message: null,
+      },
+    });
+    icsUrlInput.value = "";
+    if (subscription.lastError) {
+      showIcsStatus(`Subscribed, but the feed could not be synced: ${subscription.lastError}`, true);
+    } else {
+      showIcsStatus("Subscribed.");
+    }
+    await loadIcsSubscriptions();
+    await loadTimers();
+  } catch (err) {
+    showIcsStatus(errorMessage(err), true);
+  } finally {
+    subscribeIcsBtn.disabled = false;
   }
 };
 
-const fmtDate = (iso) => new Date(iso).toLocaleString();
+const showRemoteApiStatus = (text, isError = false) => {
+  remoteApiStatusEl.textContent = text;
+  remoteApiStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
 
-const fmtRemaining = (iso) => {
-  const ms = new Date(iso).getTime() - Date.now();
-  if (ms <= 0) {
-    return "due now";
+const loadRemoteApiSettings = async () => {
+  try {
+    const settings = await invoke("get_remote_api_settings");
+    remoteApiEnabledInput.checked = settings.enabled;
+    remoteApiPortInput.value = String(settings.port);
+    remoteApiTlsInput.checked = settings.tls;
+    remoteApiAdvertiseInput.checked = settings.advertise;
+    remoteApiAllowScriptsInput.checked = settings.allowScripts;
+  } catch (err) {
+    showRemoteApiStatus(errorMessage(err), true);
   }
+};
 
-  const total = Math.floor(ms / 1000);
-  const hours = Math.floor(total / 3600);
-  const minutes = Math.floor((total % 3600) / 60);
-  const seconds = total % 60;
-  return `${hours}h ${minutes}m ${seconds}s`;
+const saveRemoteApiSettings = async () => {
+  const remoteApi = {
+    enabled: remoteApiEnabledInput.checked,
+    port: Number(remoteApiPortInput.value || 0),
+    tls: remoteApiTlsInput.checked,
+    advertise: remoteApiAdvertiseInput.checked,
+    allowScripts: remoteApiAllowScriptsInput.checked,
+  };
+
+  try {
+    await invoke("set_remote_api_settings", { remoteApi });
+    showRemoteApiStatus(remoteApi.enabled ? `Remote API listening on port ${remoteApi.port}.` : "Remote API disabled.");
+  } catch (err) {
+    showRemoteApiStatus(errorMessage(err), true);
+  }
 };
 
-const recurrenceLabel = (recurrence) => {
-  if (!recurrence) {
-    return "One-time";
+const generateCertificate = async () => {
+  try {
+    const certificate = await invoke("generate_tls_certificate");
+    showRemoteApiStatus(`Certificate saved. SHA-256 fingerprint: ${certificate.fingerprintSha256}`);
+  } catch (err) {
+    showRemoteApiStatus(errorMessage(err), true);
   }
+};
 
-  if (recurrence.preset === "daily") {
-    return "Repeats daily";
+const renderTokens = (tokens) => {
+  tokensEl.innerHTML = "";
+
+  if (!tokens.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No API tokens.";
+    tokensEl.appendChild(empty);
+    return;
   }
 
-  if (recurrence.preset === "weekdays") {
-    return "Repeats weekdays";
+  for (const token of tokens) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const top = document.createElement("div");
+    top.className = "timer-top";
+
+    const title = document.createElement("strong");
+    title.textContent = `${token.name} (${token.scope})`;
+
+    const revokeBtn = document.createElement("button");
+    revokeBtn.className = "danger";
+    revokeBtn.textContent = "Revoke";
+    revokeBtn.addEventListener("click", async () => {
+      try {
+        await invoke("revoke_api_token", { id: token.id });
+        await loadTokens();
+      } catch (err) {
+        showRemoteApiStatus(errorMessage(err), true);
+      }
+    });
+
+    top.append(title, revokeBtn);
+
+    const meta = document.createElement("div");
+    meta.className = "timer-meta";
+    meta.textContent = token.lastUsedAt ? `Last used ${fmtDate(token.lastUsedAt)}` : "Never used";
+
+    item.append(top, meta);
+    tokensEl.append(item);
   }
+};
 
-  if (recurrence.preset === "every_n_hours") {
-    return `Repeats every ${recurrence.intervalHours ?? "?"} hour(s)`;
+const loadTokens = async () => {
+  try {
+    renderTokens(await invoke("list_api_tokens"));
+  } catch (err) {
+    showRemoteApiStatus(errorMessage(err), true);
   }
+};
 
-  if (recurrence.preset === "every_n_minutes") {
-    return `Repeats every ${recurrence.intervalMinutes ?? "?"} minute(s)`;
+const createToken = async () => {
+  try {
+    const created = await invoke("create_api_token", {
+      name: tokenNameInput.value,
+      scope: tokenScopeSelect.value,
+    });
+    tokenNameInput.value = "";
+    createdTokenEl.textContent = `Copy this token now, it will not be shown again: ${created.token}`;
+    createdTokenEl.classList.remove("hidden");
+    await loadTokens();
+  } catch (err) {
+    showRemoteApiStatus(errorMessage(err), true);
   }
+};
 
-  return "Recurring";
+const showFleetStatus = (text, isError = false) => {
+  fleetStatusEl.textContent = text;
+  fleetStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
 };
 
-const renderTimers = (timers) => {
-  timersEl.innerHTML = "";
+// Peers from the last search, each with its token and fingerprint inputs.
+let fleetPeers = [];
 
-  if (!timers.length) {
+const pairForSync = async ({ peer, tokenInput, fingerprintInput }) => {
+  const credentials = { token: tokenInput.value.trim(), fingerprintSha256: fingerprintInput?.value.trim() || null };
+  const target = { host: peer.host, port: peer.port, tls: peer.tls };
+  const paired = await invoke("pair_sync_peer", { target, credentials });
+  tokenInput.value = "";
+  await loadSyncPeers();
+  await loadTokens();
+  return paired;
+};
+
+const pushToPeer = async ({ peer, tokenInput, fingerprintInput }) => {
+  const token = tokenInput.value.trim();
+  const credentials = token ? { token, fingerprintSha256: fingerprintInput?.value.trim() || null } : null;
+  const target = { host: peer.host, port: peer.port, tls: peer.tls };
+  await invoke("push_timer", { target, request: buildRequest(), credentials });
+  tokenInput.value = "";
+  tokenInput.placeholder = "Saved in the keychain";
+};
+
+const renderFleetPeers = () => {
+  fleetPeersEl.innerHTML = "";
+  pushToAllBtn.classList.toggle("hidden", fleetPeers.length < 2);
+
+  if (!fleetPeers.length) {
     const empty = document.createElement("li");
     empty.className = "empty";
-    empty.textContent = "No active timers.";
-    timersEl.appendChild(empty);
+    empty.textContent = "No other LockPilots found. Their remote API must be on and advertised.";
+    fleetPeersEl.appendChild(empty);
     return;
   }
 
-  for (const timer of timers) {
+  for (const entry of fleetPeers) {
+    const { peer } = entry;
     const item = document.createElement("li");
     item.className = "timer-item";
 
@@ -126,50 +2409,178 @@ const renderTimers = (timers) => {
     top.className = "timer-top";
 
     const title = document.createElement("strong");
-    title.textContent = timer.action.toUpperCase();
+    title.textContent = peer.name;
 
-    const cancelBtn = document.createElement("button");
-    cancelBtn.className = "danger";
-    cancelBtn.textContent = "Cancel";
-    cancelBtn.addEventListener("click", async () => {
+    const pushBtn = document.createElement("button");
+    pushBtn.className = "secondary";
+    pushBtn.textContent = "Push Timer";
+    pushBtn.addEventListener("click", async () => {
       try {
-        await invoke("cancel_timer", { id: timer.id });
-        await loadTimers();
-        showStatus("Timer canceled.");
+        await pushToPeer(entry);
+        showFleetStatus(`Timer created on ${peer.host}.`);
       } catch (err) {
-        showStatus(String(err), true);
+        showFleetStatus(errorMessage(err), true);
       }
     });
 
-    top.append(title, cancelBtn);
+    const pairBtn = document.createElement("button");
+    pairBtn.className = "secondary";
+    pairBtn.textContent = "Pair for Sync";
+    pairBtn.addEventListener("click", async () => {
+      try {
+        const paired = await pairForSync(entry);
+        showFleetStatus(`Paired with ${paired.name} for sync.`);
+      } catch (err) {
+        showFleetStatus(errorMessage(err), true);
+      }
+    });
 
-    const when = document.createElement("div");
-    when.className = "timer-meta";
-    when.textContent = `Runs at ${fmtDate(timer.targetTime)} (${fmtRemaining(timer.targetTime)})`;
+    top.append(title, pushBtn, pairBtn);
 
-    const recurrence = document.createElement("div");
-    recurrence.className = "timer-meta";
-    recurrence.textContent = recurrenceLabel(timer.recurrence);
+    const meta = document.createElement("div");
+    meta.className = "timer-meta";
+    meta.textContent = `${peer.host}:${peer.port}${peer.tls ? " · HTTPS" : ""}`;
 
-    item.append(top, when, recurrence);
+    entry.tokenInput.type = "password";
+    entry.tokenInput.placeholder = peer.hasToken ? "Saved in the keychain" : "Control token from that Mac";
+    item.append(top, meta, entry.tokenInput);
+    if (entry.fingerprintInput) {
+      entry.fingerprintInput.placeholder = peer.hasToken ? "Saved in the keychain" : "Certificate SHA-256 fingerprint";
+      item.append(entry.fingerprintInput);
+    }
+    fleetPeersEl.append(item);
+  }
+};
 
-    if (timer.action === "popup" && timer.message) {
-      const msg = document.createElement("div");
-      msg.className = "timer-meta";
-      msg.textContent = `Message: ${timer.message}`;
-      item.append(msg);
+const discoverPeers = async () => {
+  discoverPeersBtn.disabled = true;
+  showFleetStatus("Looking for other LockPilots...");
+  try {
+    const peers = await invoke("discover_fleet_peers");
+    fleetPeers = peers
+      .filter((peer) => !peer.thisMac)
+      .map((peer) => ({
+        peer,
+        tokenInput: document.createElement("input"),
+        fingerprintInput: peer.tls ? document.createElement("input") : null,
+      }));
+    renderFleetPeers();
+    showFleetStatus(`Found ${fleetPeers.length} Mac${fleetPeers.length === 1 ? "" : "s"}.`);
+  } catch (err) {
+    showFleetStatus(errorMessage(err), true);
+  } finally {
+    discoverPeersBtn.disabled = false;
+  }
+};
+
+const pushToAll = async () => {
+  pushToAllBtn.disabled = true;
+  const failures = [];
+  for (const entry of fleetPeers) {
+    try {
+      await pushToPeer(entry);
+    } catch (err) {
+      failures.push(`${entry.peer.host}: ${errorMessage(err)}`);
     }
+  }
+  pushToAllBtn.disabled = false;
+  const pushed = fleetPeers.length - failures.length;
+  if (failures.length) {
+    showFleetStatus(`Created on ${pushed} of ${fleetPeers.length}. ${failures.join("; ")}`, true);
+  } else {
+    showFleetStatus(`Created on all ${pushed} Macs.`);
+  }
+};
 
-    timersEl.append(item);
+const showSyncStatus = (text, isError = false) => {
+  syncStatusEl.textContent = text;
+  syncStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const loadSyncSettings = async () => {
+  try {
+    const sync = await invoke("get_sync_settings");
+    syncEnabledInput.checked = sync.enabled;
+    syncTagsInput.value = sync.tags.join(", ");
+  } catch (err) {
+    showSyncStatus(errorMessage(err), true);
   }
 };
 
-const loadTimers = async () => {
+const saveSyncSettings = async () => {
   try {
-    const timers = await invoke("list_timers");
-    renderTimers(timers);
+    const sync = await invoke("set_sync_settings", {
+      sync: { enabled: syncEnabledInput.checked, tags: syncTagsInput.value.split(",") },
+    });
+    syncTagsInput.value = sync.tags.join(", ");
+    showSyncStatus(sync.enabled ? `Syncing timers tagged ${sync.tags.join(", ")}.` : "Sync turned off.");
+  } catch (err) {
+    showSyncStatus(errorMessage(err), true);
+  }
+};
+
+const renderSyncPeers = (peers) => {
+  syncPeersEl.innerHTML = "";
+
+  if (!peers.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No paired Macs.";
+    syncPeersEl.appendChild(empty);
+    return;
+  }
+
+  for (const peer of peers) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const top = document.createElement("div");
+    top.className = "timer-top";
+
+    const title = document.createElement("strong");
+    title.textContent = peer.name;
+
+    const unpairBtn = document.createElement("button");
+    unpairBtn.className = "danger";
+    unpairBtn.textContent = "Unpair";
+    unpairBtn.addEventListener("click", async () => {
+      try {
+        await invoke("unpair_sync_peer", { id: peer.id });
+        await loadSyncPeers();
+        await loadTokens();
+      } catch (err) {
+        showSyncStatus(errorMessage(err), true);
+      }
+    });
+
+    top.append(title, unpairBtn);
+
+    const meta = document.createElement("div");
+    meta.className = "timer-meta";
+    meta.textContent = peer.lastSyncedAt ? `Last synced ${fmtDate(peer.lastSyncedAt)}` : "Not synced yet";
+    if (peer.lastError) {
+      meta.textContent += ` · ${peer.lastError}`;
+    }
+
+    item.append(top, meta);
+    syncPeersEl.append(item);
+  }
+};
+
+const loadSyncPeers = async () => {
+  try {
+    renderSyncPeers(await invoke("list_sync_peers"));
+  } catch (err) {
+    showSyncStatus(errorMessage(err), true);
+  }
+};
+
+const syncNow = async () => {
+  try {
+    await invoke("sync_now");
+    showSyncStatus("Syncing...");
   } catch (err) {
-    showStatus(String(err), true);
+    showSyncStatus(errorMessage(err), true);
   }
 };
 
@@ -188,19 +2599,44 @@ const renderUpdateResult = (update) => {
     : "No release notes provided.";
 };
 
+let releaseVersions = [];
+
+const renderRollbackAssets = () => {
+  const version = releaseVersions.find((candidate) => candidate.tag === rollbackVersionSelect.value);
+  if (!version) {
+    rollbackAssetsEl.textContent = "";
+    rollbackAssetsEl.style.color = "";
+    return;
+  }
+
+  const dmgs = version.assets.filter((asset) => asset.name.toLowerCase().endsWith(".dmg"));
+  const summary = dmgs
+    .map((asset) => `${asset.name} (${(asset.size / (1024 * 1024)).toFixed(1)} MB, ${asset.downloadCount} downloads)`)
+    .join(", ");
+  if (!dmgs.length) {
+    rollbackAssetsEl.textContent = "This release has no DMG to install.";
+  } else if (!version.nativeDmg) {
+    rollbackAssetsEl.textContent = `No build for this Mac's architecture; ${summary} would be installed instead.`;
+  } else {
+    rollbackAssetsEl.textContent = summary;
+  }
+  rollbackAssetsEl.style.color = dmgs.length && version.nativeDmg ? "" : "#c30e2e";
+};
+
 const loadRollbackVersions = async () => {
   rollbackVersionSelect.innerHTML = "";
 
   try {
-    const versions = await invoke("list_release_versions");
-    versions.forEach((version) => {
+    releaseVersions = await invoke("list_release_versions");
+    releaseVersions.forEach((version) => {
       const option = document.createElement("option");
       option.value = version.tag;
       option.textContent = `${version.tag}${version.publishedAt ? ` (${new Date(version.publishedAt).toLocaleDateString()})` : ""}`;
       rollbackVersionSelect.appendChild(option);
     });
+    renderRollbackAssets();
   } catch (err) {
-    showUpdateStatus(`Could not load release versions: ${String(err)}`, true);
+    showUpdateStatus(`Could not load release versions: ${errorMessage(err)}`, true);
   }
 };
 
@@ -220,14 +2656,35 @@ const checkForUpdates = async (silentWhenUpToDate = false) => {
     renderUpdateResult(update);
 
     if (update) {
-      showUpdateStatus(`Update available in ${channel}: ${update.tag}`);
+      showUpdateStatus(
+        installOnQuitInput.checked
+          ? `Update available in ${channel}: ${update.tag}. It will be installed when you quit.`
+          : `Update available in ${channel}: ${update.tag}`
+      );
     } else if (!silentWhenUpToDate) {
       showUpdateStatus(`No newer version found in ${channel}.`);
     } else {
       showUpdateStatus("");
     }
   } catch (err) {
-    showUpdateStatus(`Update check failed: ${String(err)}`, true);
+    showUpdateStatus(`Update check failed: ${errorMessage(err)}`, true);
+  }
+};
+
+const loadInstallOnQuit = async () => {
+  try {
+    installOnQuitInput.checked = await invoke("get_install_update_on_quit");
+  } catch (err) {
+    showUpdateStatus(`Could not load update settings: ${errorMessage(err)}`, true);
+  }
+};
+
+const saveInstallOnQuit = async () => {
+  try {
+    installOnQuitInput.checked = await invoke("set_install_update_on_quit", { enabled: installOnQuitInput.checked });
+  } catch (err) {
+    installOnQuitInput.checked = !installOnQuitInput.checked;
+    showUpdateStatus(`Could not save update settings: ${errorMessage(err)}`, true);
   }
 };
 
@@ -238,7 +2695,7 @@ const installChannelUpdate = async () => {
     const result = await invoke("install_channel_update", { channel });
     showUpdateStatus(`${result}. Complete install from the opened DMG.`);
   } catch (err) {
-    showUpdateStatus(`Install failed: ${String(err)}`, true);
+    showUpdateStatus(`Install failed: ${errorMessage(err)}`, true);
   }
 };
 
@@ -247,10 +2704,75 @@ const installTag = async (tag) => {
     const result = await invoke("install_release", { tag });
     showUpdateStatus(`${result}. Complete install from the opened DMG.`);
   } catch (err) {
-    showUpdateStatus(`Install failed: ${String(err)}`, true);
+    showUpdateStatus(`Install failed: ${errorMessage(err)}`, true);
+  }
+};
+
+const clearUpdateCache = async () => {
+  try {
+    const report = await invoke("clear_update_cache");
+    const megabytes = (report.bytes / (1024 * 1024)).toFixed(1);
+    showUpdateStatus(
+      report.files ? `Removed ${report.files} installer(s), ${megabytes} MB freed.` : "No downloaded installers to remove."
+    );
+  } catch (err) {
+    showUpdateStatus(`Could not clear installers: ${errorMessage(err)}`, true);
   }
 };
 
+const buildRequest = () => ({
+  action: buildAction(),
+  targetTime: new Date(targetTimeInput.value).toISOString(),
+  recurrence: buildRecurrence(),
+  message: ["popup", "push", "email"].includes(actionInput.value) ? messageInput.value : null,
+  notes: notesInput.value.trim() || null,
+  tags: tagsInput.value.split(","),
+  shutdownOptions: buildShutdownOptions(),
+  hooks: buildHooks(),
+  confirmation: buildConfirmation(),
+  escalation: buildEscalation(),
+  repeatUntilAcknowledged: buildNag(),
+  chatWebhook: buildChatWebhook(),
+  ignoreWorkingHours: ignoreWorkingHoursInput.checked,
+  mustRun: mustRunInput.checked,
+  pauseMedia: PAUSE_MEDIA_FIRST_ACTIONS.includes(actionInput.value) && pauseMediaInput.checked,
+  ejectDisks:
+    EJECT_FIRST_ACTIONS.includes(actionInput.value) && ejectDisksInput.checked
+      ? { abortIfBusy: ejectAbortIfBusyInput.checked }
+      : null,
+  condition: conditionInput.value.trim() || null,
+  trigger: buildTrigger(),
+  expiresAt: expiresAtInput.value ? new Date(expiresAtInput.value).toISOString() : null,
+  maxRuns: maxRunsInput.value ? Number(maxRunsInput.value) : null,
+  jitterMinutes: jitterMinutesInput.value ? Number(jitterMinutesInput.value) : null,
+  warningMinutes: warningMinutesInput.value ? Number(warningMinutesInput.value) : null,
+});
+
+let validateTimeout = null;
+
+const validateForm = () => {
+  clearTimeout(validateTimeout);
+  validateTimeout = setTimeout(async () => {
+    if (!targetTimeInput.value) {
+      return;
+    }
+
+    try {
+      const result = await invoke("validate_timer", { request: buildRequest() });
+      if (result.errors.length) {
+        showStatus(result.errors.map((issue) => issue.message).join(" "), true);
+      } else {
+        showStatus(result.warnings.map((issue) => issue.message).join(" "));
+      }
+    } catch (err) {
+      showStatus(errorMessage(err), true);
+    }
+  }, 300);
+};
+
+form.addEventListener("input", validateForm);
+form.addEventListener("change", validateForm);
+
 form.addEventListener("submit", async (event) => {
   event.preventDefault();
 
@@ -259,45 +2781,99 @@ form.addEventListener("submit", async (event) => {
     return;
   }
 
-  const recurrencePreset = recurrencePresetInput.value;
-  let recurrence = null;
-  if (recurrencePreset !== "none") {
-    recurrence = {
-      preset: recurrencePreset,
-      intervalHours: recurrencePreset === "every_n_hours" ? Number(intervalHoursInput.value || 0) : null,
-      intervalMinutes: recurrencePreset === "every_n_minutes" ? Number(intervalHoursInput.value || 0) : null,
-    };
-  }
-
-  const request = {
-    action: actionInput.value,
-    targetTime: new Date(targetTimeInput.value).toISOString(),
-    recurrence,
-    message: actionInput.value === "popup" ? messageInput.value : null,
-  };
+  const request = buildRequest();
 
   try {
     await invoke("create_timer", { request });
+    clearTimeout(validateTimeout);
+    await loadProfiles();
     form.reset();
     targetTimeInput.value = toLocalDateTimeValue(LAUNCH_TIME);
     recurrencePresetInput.value = "none";
     intervalHoursInput.value = "2";
     toggleMessage();
     toggleRecurrence();
+    toggleShutdownOptions();
+    toggleTrigger();
+    updateRecurrencePreview();
     showStatus("Timer created.");
     await loadTimers();
   } catch (err) {
-    showStatus(String(err), true);
+    showStatus(errorMessage(err), true);
   }
 });
 
 refreshBtn.addEventListener("click", loadTimers);
+quickEntryBtn.addEventListener("click", createFromText);
+scheduleRestartBtn.addEventListener("click", prefillRestart);
+quickEntryInput.addEventListener("keydown", (event) => {
+  if (event.key === "Enter") {
+    event.preventDefault();
+    createFromText();
+  }
+});
+profileSelect.addEventListener("change", switchProfile);
+createProfileBtn.addEventListener("click", createProfile);
+deleteProfileBtn.addEventListener("click", deleteProfile);
+startCountdownBtn.addEventListener("click", startCountdown);
+workspaceSelect.addEventListener("change", switchWorkspace);
+createWorkspaceBtn.addEventListener("click", createWorkspace);
+deleteWorkspaceBtn.addEventListener("click", deleteWorkspace);
+pauseToggleBtn.addEventListener("click", togglePause);
+shiftTimersBtn.addEventListener("click", shiftTimers);
+sendCrashReportBtn.addEventListener("click", () => resolveCrashReport(true));
+dismissCrashReportBtn.addEventListener("click", () => resolveCrashReport(false));
+requestPermissionsBtn.addEventListener("click", requestPermissions);
+lockCompatibilityInput.addEventListener("change", saveLockCompatibility);
+runHealthCheckBtn.addEventListener("click", runHealthCheck);
+runSelfTestBtn.addEventListener("click", runSelfTest);
+importCrontabBtn.addEventListener("click", importCrontab);
+saveQuietHoursBtn.addEventListener("click", saveQuietHours);
+saveHolidayRegionBtn.addEventListener("click", saveHolidayRegion);
+subscribeIcsBtn.addEventListener("click", subscribeIcs);
+createFocusSessionBtn.addEventListener("click", createFocusSession);
+saveBlockedDomainsBtn.addEventListener("click", saveBlockedDomains);
+calendarLogInput.addEventListener("change", saveCalendarLog);
+pushServiceSelect.addEventListener("change", togglePushFields);
+savePushBtn.addEventListener("click", savePushSettings);
+testPushBtn.addEventListener("click", sendTestPush);
+saveEmailBtn.addEventListener("click", saveEmailSettings);
+testEmailBtn.addEventListener("click", sendTestEmail);
+saveWorkingHoursBtn.addEventListener("click", saveWorkingHours);
+saveUsageLimitBtn.addEventListener("click", saveUsageLimit);
+saveBreakRemindersBtn.addEventListener("click", saveBreakReminders);
+breakPresetSelect.addEventListener("change", applyBreakPreset);
+breakWorkMinutesInput.addEventListener("input", syncBreakPreset);
+breakSecondsInput.addEventListener("input", syncBreakPreset);
+usageReportRangeSelect.addEventListener("change", loadUsageReport);
+skipHolidaysInput.addEventListener("change", updateRecurrencePreview);
+refreshAuditBtn.addEventListener("click", loadAuditLog);
+heatmapRangeSelect.addEventListener("change", loadHeatmap);
+saveRemoteApiBtn.addEventListener("click", saveRemoteApiSettings);
+generateCertificateBtn.addEventListener("click", generateCertificate);
+createTokenBtn.addEventListener("click", createToken);
+discoverPeersBtn.addEventListener("click", discoverPeers);
+pushToAllBtn.addEventListener("click", pushToAll);
+saveSyncBtn.addEventListener("click", saveSyncSettings);
+syncNowBtn.addEventListener("click", syncNow);
 actionInput.addEventListener("change", toggleMessage);
+actionInput.addEventListener("change", toggleShutdownOptions);
+quitAppsFirstInput.addEventListener("change", toggleShutdownOptions);
 recurrencePresetInput.addEventListener("change", toggleRecurrence);
+triggerKindSelect.addEventListener("change", toggleTrigger);
+recurrencePresetInput.addEventListener("change", updateRecurrencePreview);
+intervalHoursInput.addEventListener("input", updateRecurrencePreview);
+targetTimeInput.addEventListener("change", updateRecurrencePreview);
+jitterMinutesInput.addEventListener("input", updateRecurrencePreview);
 
+toggleHelperBtn.addEventListener("click", toggleHelper);
+stayAwakeMinutesInput.addEventListener("change", saveStayAwake);
+conflictPolicySelect.addEventListener("change", saveConflictPolicy);
 checkUpdatesBtn.addEventListener("click", () => checkForUpdates(false));
+clearUpdateCacheBtn.addEventListener("click", clearUpdateCache);
 installLatestBtn.addEventListener("click", installChannelUpdate);
 
+rollbackVersionSelect.addEventListener("change", renderRollbackAssets);
 rollbackInstallBtn.addEventListener("click", async () => {
   const selectedTag = rollbackVersionSelect.value;
   if (!selectedTag) {
@@ -308,6 +2884,7 @@ rollbackInstallBtn.addEventListener("click", async () => {
   await installTag(selectedTag);
 });
 
+installOnQuitInput.addEventListener("change", saveInstallOnQuit);
 autoCheckUpdatesInput.addEventListener("change", () => {
   localStorage.setItem(AUTO_UPDATE_KEY, autoCheckUpdatesInput.checked ? "1" : "0");
 });
@@ -317,21 +2894,114 @@ updateChannelSelect.addEventListener("change", () => {
   renderUpdateResult(null);
 });
 
+window.__TAURI__.event.listen("permission-denied", ({ payload }) => {
+  showStatus(
+    `The ${payload.action} timer was blocked: LockPilot needs ${payload.permission} access. Use Grant Access under Permissions.`,
+    true
+  );
+  loadPermissionStatus();
+});
+
+window.__TAURI__.event.listen("timers-expired", ({ payload }) => {
+  const reasons = payload.map((timer) => `${timer.action} (${timer.reason.toLowerCase()})`).join(", ");
+  showStatus(`Removed ${payload.length} expired timer(s): ${reasons}`);
+  loadTimers();
+});
+
+window.__TAURI__.event.listen("timezone-changed", ({ payload }) => {
+  const moved = payload.map((timer) => `${timer.action} now at ${fmtDate(timer.nextRun)}`).join(", ");
+  showStatus(`Time zone changed; moved ${payload.length} timer(s) to keep their local time: ${moved}`);
+  loadTimers();
+});
+
+window.__TAURI__.event.listen("timer-failed", ({ payload }) => {
+  showStatus(`The ${payload.action} timer failed: ${payload.error}`, true);
+});
+
+window.__TAURI__.event.listen("focus-sessions-changed", () => {
+  loadFocusSessions();
+});
+
+window.__TAURI__.event.listen("countdown-tick", ({ payload }) => {
+  renderCountdowns(payload);
+});
+
+window.__TAURI__.event.listen("breaks-changed", () => {
+  loadBreakStats();
+});
+
+window.__TAURI__.event.listen("pause-changed", ({ payload }) => {
+  renderPauseState(payload);
+});
+
+window.__TAURI__.event.listen("sync-finished", () => {
+  loadSyncPeers();
+  loadTimers();
+});
+
+window.__TAURI__.event.listen("lock-failed", ({ payload }) => {
+  const failures = payload.report.attempts.map((attempt) => attempt.error).join("; ");
+  showStatus(`Lock timer ran but the screen did not lock: ${failures}`, true);
+});
+
 const initialize = async () => {
   targetTimeInput.value = toLocalDateTimeValue(LAUNCH_TIME);
   toggleMessage();
   toggleRecurrence();
+  toggleShutdownOptions();
+  if (await invoke("get_read_only").catch(() => false)) {
+    document.body.classList.add("read-only");
+  }
+  await loadManagedConfig();
+  await loadPlugins();
+  await loadPolicy();
+  await loadWorkspaces();
+  await loadProfiles();
+  await loadPowerStatus();
   await loadTimers();
+  await loadPauseState();
+  await loadQuietHours();
+  await loadWorkingHours();
+  await loadUsageLimit();
+  await loadBreakReminders();
+  await loadUsageReport();
+  await loadActiveFor();
+  await loadAuditLog();
+  await loadHeatmap();
+  await loadRemoteApiSettings();
+  await loadTokens();
+  await loadSyncSettings();
+  await loadSyncPeers();
+  await loadPermissionStatus();
+  await loadCrashReports();
+  await loadLockCompatibility();
+  await loadHolidayRegion();
+  await loadPushSettings();
+  await loadEmailSettings();
+  await loadCalendarLog();
+  await loadHelperStatus();
+  await loadStayAwake();
+  await loadConflictPolicy();
+  await loadIcsSubscriptions();
+  await loadFocusSessions();
+  await loadBlockedDomains();
   setInterval(loadTimers, 1000);
+  setInterval(loadPauseState, 15000);
+  setInterval(loadUsageToday, 60000);
+  setInterval(loadActiveFor, 30000);
+  setInterval(loadPowerStatus, 60000);
 
   currentVersion = await getVersion();
   currentVersionEl.textContent = currentVersion;
+  await loadAppInfo();
+  await loadSystemInfo();
 
   const savedChannel = localStorage.getItem(UPDATE_CHANNEL_KEY);
   updateChannelSelect.value = savedChannel === "dev" ? "dev" : "main";
 
   const autoCheckSetting = localStorage.getItem(AUTO_UPDATE_KEY);
   autoCheckUpdatesInput.checked = autoCheckSetting !== "0";
+  await loadInstallOnQuit();
 
   await loadRollbackVersions();
 
@@ -341,5 +3011,5 @@ const initialize = async () => {
 };
 
 initialize().catch((err) => {
-  showStatus(`Initialization failed: ${String(err)}`, true);
+  showStatus(`Initialization failed: ${errorMessage(err)}`, true);
 });